@@ -1,7 +1,11 @@
 use crate::controllers::{
-    auth_controller as auth_routes, calendar_controller as calendar_routes,
+    account_controller as account_routes, auth_controller as auth_routes,
+    calendar_controller as calendar_routes, email_controller as email_routes,
     event_controller as event_routes, friendship_controller as friendship_routes,
-    users_controller as users_routes, wish_place_controller as wish_place_routes,
+    identity_controller as identity_routes, passkey_controller as passkey_routes,
+    password_controller as password_routes, session_controller as session_routes,
+    two_factor_controller as two_factor_routes, users_controller as users_routes,
+    wish_place_controller as wish_place_routes,
 };
 use utoipa::OpenApi;
 
@@ -10,12 +14,43 @@ use utoipa::OpenApi;
     paths(
         auth_routes::register,
         auth_routes::login,
+        auth_routes::login_two_factor,
         auth_routes::refresh,
         auth_routes::logout,
+        auth_routes::jwks,
+        password_routes::change_password,
+        password_routes::request_password_reset,
+        password_routes::confirm_password_reset,
+        two_factor_routes::status,
+        two_factor_routes::start_totp,
+        two_factor_routes::confirm_totp,
+        two_factor_routes::regenerate_recovery_codes,
+        two_factor_routes::disable_totp,
+        identity_routes::oidc_login,
+        identity_routes::get_identities,
+        identity_routes::link_identity,
+        identity_routes::unlink_identity,
+        passkey_routes::registration_options,
+        passkey_routes::register_passkey,
+        passkey_routes::get_passkeys,
+        passkey_routes::rename_passkey,
+        passkey_routes::delete_passkey,
+        passkey_routes::login_options,
+        passkey_routes::passkey_login,
         users_routes::get_me,
         users_routes::update_me,
         users_routes::get_user_by_id,
         users_routes::search_users,
+        account_routes::delete_account,
+        account_routes::undo_account_deletion,
+        account_routes::start_data_export,
+        account_routes::get_data_exports,
+        account_routes::download_data_export,
+        email_routes::resend_verification,
+        email_routes::verify_email,
+        session_routes::get_sessions,
+        session_routes::revoke_session,
+        session_routes::revoke_other_sessions,
         friendship_routes::get_friends,
         friendship_routes::friend_request,
         friendship_routes::get_incoming,
@@ -32,25 +67,71 @@ use utoipa::OpenApi;
         event_routes::finish_event,
         event_routes::cancel_event,
         event_routes::get_event_participants,
+        event_routes::get_event_history,
         event_routes::accept_event,
         event_routes::decline_event,
         wish_place_routes::get_wish_places,
         wish_place_routes::create_wish_place,
         wish_place_routes::update_wish_place,
         wish_place_routes::visit_wish_place,
-        wish_place_routes::delete_wish_place
+        wish_place_routes::delete_wish_place,
+        crate::telemetry::metrics_handler,
+        crate::controllers::health_controller::healthz,
+        crate::controllers::health_controller::readyz
     ),
     components(
         schemas(
+            crate::error::ErrorBody,
+            crate::error::ErrorCode,
+            crate::error::FieldError,
             crate::controllers::models::AuthRequestBody,
             crate::controllers::models::LoginRequestBody,
             crate::controllers::models::LoginResponse,
+            crate::controllers::models::TwoFactorChallengeResponse,
+            crate::controllers::models::TwoFactorLoginBody,
             crate::controllers::models::RefreshTokenRequest,
             crate::controllers::models::RefreshTokenResponse,
+            crate::controllers::models::JwksResponse,
+            crate::controllers::models::JwkResponse,
+            crate::controllers::models::password::ChangePasswordRequestBody,
+            crate::controllers::models::password::PasswordResetRequestBody,
+            crate::controllers::models::password::PasswordResetConfirmBody,
+            crate::controllers::models::two_factor::TwoFactorStatusResponse,
+            crate::controllers::models::two_factor::StartTotpBody,
+            crate::controllers::models::two_factor::TotpEnrollmentResponse,
+            crate::controllers::models::two_factor::TwoFactorCodeBody,
+            crate::controllers::models::two_factor::RecoveryCodesResponse,
+            crate::controllers::models::two_factor::DisableTotpBody,
+            crate::controllers::models::identity::OidcLoginBody,
+            crate::controllers::models::identity::LinkIdentityBody,
+            crate::controllers::models::identity::IdentityResponse,
+            crate::controllers::models::passkey::RegistrationOptionsResponse,
+            crate::controllers::models::passkey::CreationOptions,
+            crate::controllers::models::passkey::RelyingPartyEntity,
+            crate::controllers::models::passkey::UserEntity,
+            crate::controllers::models::passkey::CredentialParameters,
+            crate::controllers::models::passkey::CredentialDescriptor,
+            crate::controllers::models::passkey::AuthenticatorSelection,
+            crate::controllers::models::passkey::RegisterPasskeyBody,
+            crate::controllers::models::passkey::RegistrationCredential,
+            crate::controllers::models::passkey::AttestationResponse,
+            crate::controllers::models::passkey::PasskeyResponse,
+            crate::controllers::models::passkey::PasskeyNameBody,
+            crate::controllers::models::passkey::LoginOptionsResponse,
+            crate::controllers::models::passkey::RequestOptions,
+            crate::controllers::models::passkey::PasskeyLoginBody,
+            crate::controllers::models::passkey::AssertionCredential,
+            crate::controllers::models::passkey::AssertionResponse,
             crate::controllers::models::FriendIdBody,
             crate::controllers::models::UserDTO,
             crate::controllers::models::user_response::UserResponse,
             crate::controllers::models::update_user_request_body::UpdateUserRequestBody,
+            crate::controllers::models::account::DeleteAccountBody,
+            crate::controllers::models::account::AccountDeletionResponse,
+            crate::controllers::models::account::DataExportResponse,
+            crate::controllers::models::email::VerifyEmailBody,
+            crate::controllers::models::session::SessionResponse,
+            crate::controllers::models::session::RevokedSessionsResponse,
             crate::controllers::models::calendar::IsBusyRequest,
             crate::controllers::models::calendar::BusydayResponse,
             crate::controllers::models::calendar::PendingInviteResponse,
@@ -60,20 +141,25 @@ use utoipa::OpenApi;
             crate::controllers::models::events::EventScope,
             crate::controllers::models::events::EventResponse,
             crate::controllers::models::events::ParticipantResponse,
+            crate::controllers::models::events::EventHistoryResponse,
             crate::controllers::models::wish_place::CreateWishPlaceBody,
             crate::controllers::models::wish_place::UpdateWishPlaceBody,
             crate::controllers::models::wish_place::VisitWishPlaceBody,
             crate::controllers::models::wish_place::WishPlaceStatusDto,
-            crate::controllers::models::wish_place::WishPlaceResponse
+            crate::controllers::models::wish_place::WishPlaceResponse,
+            crate::controllers::models::health::HealthResponse,
+            crate::controllers::models::health::ReadinessResponse
         )
     ),
     tags(
         (name = "Auth", description = "Authentication endpoints"),
         (name = "Users", description = "User profile endpoints"),
+        (name = "Sessions", description = "Devices the user is logged in on"),
         (name = "Friends", description = "Friendship endpoints"),
         (name = "Calendar", description = "Calendar endpoints"),
         (name = "Events", description = "Events endpoints"),
-        (name = "WishPlaces", description = "Wish places endpoints")
+        (name = "WishPlaces", description = "Wish places endpoints"),
+        (name = "Monitoring", description = "Operational endpoints")
     )
 )]
 pub struct ApiDoc;
@@ -0,0 +1,60 @@
+use rand::Rng;
+use sea_orm::{ColumnTrait, Condition, ConnectionTrait, DbErr, EntityTrait, QueryFilter};
+use uuid::Uuid;
+
+use crate::entities::{User, UserColumn, user};
+
+const USERNAME_HINT_LEN: usize = 24;
+const USERNAME_ATTEMPTS: usize = 5;
+
+/// The account `login` names: its username, or its email address once
+/// verified. A username wins over another account's address.
+pub async fn find_by_login<C: ConnectionTrait>(
+    db: &C,
+    login: &str,
+) -> Result<Option<user::Model>, DbErr> {
+    let mut condition = Condition::any().add(UserColumn::Username.eq(login));
+    if login.contains('@') {
+        condition = condition.add(
+            Condition::all()
+                .add(UserColumn::Email.eq(normalize_email(login)))
+                .add(UserColumn::EmailVerifiedAt.is_not_null()),
+        );
+    }
+    let mut users = User::find().filter(condition).all(db).await?;
+    users.sort_by_key(|user| user.username != login);
+    Ok(users.into_iter().next())
+}
+
+/// Addresses are stored trimmed and lower-case, so one mailbox is one account.
+pub fn normalize_email(email: &str) -> String {
+    email.trim().to_lowercase()
+}
+
+/// An unused username made from `hint`, e.g. the local part of an address,
+/// with a random number appended when the plain one is taken.
+pub async fn free_username<C: ConnectionTrait>(db: &C, hint: &str) -> Result<String, DbErr> {
+    let mut base: String = hint
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'))
+        .map(|c| c.to_ascii_lowercase())
+        .take(USERNAME_HINT_LEN)
+        .collect();
+    if base.len() < 3 {
+        base = "user".to_string();
+    }
+
+    let mut candidate = base.clone();
+    for _ in 0..USERNAME_ATTEMPTS {
+        let taken = User::find()
+            .filter(UserColumn::Username.eq(&candidate))
+            .one(db)
+            .await?
+            .is_some();
+        if !taken {
+            return Ok(candidate);
+        }
+        candidate = format!("{base}{}", rand::rng().random_range(1000..10000));
+    }
+    Ok(format!("{base}-{}", &Uuid::new_v4().simple().to_string()[..12]))
+}
@@ -0,0 +1,88 @@
+use chrono::{DateTime, Utc};
+use sea_orm::sea_query::Expr;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DbErr, EntityTrait, QueryFilter, Set,
+};
+use uuid::Uuid;
+
+use crate::auth::mail_token;
+use crate::entities::{
+    EmailVerificationToken, EmailVerificationTokenActiveModel, EmailVerificationTokenColumn,
+    email_verification_token,
+};
+
+/// Creates a token that verifies `email` for `user_id` until `expires_at`
+/// and returns it. Earlier unused tokens of the user stop working.
+pub async fn issue<C: ConnectionTrait>(
+    db: &C,
+    user_id: Uuid,
+    email: &str,
+    now: DateTime<Utc>,
+    expires_at: DateTime<Utc>,
+) -> Result<String, DbErr> {
+    discard(db, user_id, now).await?;
+
+    let token = mail_token::generate();
+    EmailVerificationTokenActiveModel {
+        id: Set(Uuid::new_v4()),
+        user_id: Set(user_id),
+        email: Set(email.to_string()),
+        token_hash: Set(mail_token::hash(&token)),
+        expires_at: Set(expires_at.into()),
+        created_at: Set(now.into()),
+        ..Default::default()
+    }
+    .insert(db)
+    .await?;
+    Ok(token)
+}
+
+/// Uses up `token` and returns it, or `None` when it is unknown, expired or
+/// already used. Of concurrent calls with one token at most one gets it.
+pub async fn consume<C: ConnectionTrait>(
+    db: &C,
+    token: &str,
+    now: DateTime<Utc>,
+) -> Result<Option<email_verification_token::Model>, DbErr> {
+    let Some(model) = EmailVerificationToken::find()
+        .filter(EmailVerificationTokenColumn::TokenHash.eq(mail_token::hash(token)))
+        .filter(EmailVerificationTokenColumn::UsedAt.is_null())
+        .filter(EmailVerificationTokenColumn::ExpiresAt.gt(now))
+        .one(db)
+        .await?
+    else {
+        return Ok(None);
+    };
+
+    let result = EmailVerificationToken::update_many()
+        .col_expr(EmailVerificationTokenColumn::UsedAt, Expr::value(now))
+        .filter(EmailVerificationTokenColumn::Id.eq(model.id))
+        .filter(EmailVerificationTokenColumn::UsedAt.is_null())
+        .exec(db)
+        .await?;
+    Ok((result.rows_affected == 1).then_some(model))
+}
+
+/// Invalidates the unused tokens of `user_id`, e.g. after the address changed.
+pub async fn discard<C: ConnectionTrait>(
+    db: &C,
+    user_id: Uuid,
+    now: DateTime<Utc>,
+) -> Result<u64, DbErr> {
+    let result = EmailVerificationToken::update_many()
+        .col_expr(EmailVerificationTokenColumn::UsedAt, Expr::value(now))
+        .filter(EmailVerificationTokenColumn::UserId.eq(user_id))
+        .filter(EmailVerificationTokenColumn::UsedAt.is_null())
+        .exec(db)
+        .await?;
+    Ok(result.rows_affected)
+}
+
+/// Deletes tokens that expired before `now`, used or not.
+pub async fn delete_expired<C: ConnectionTrait>(db: &C, now: DateTime<Utc>) -> Result<u64, DbErr> {
+    let result = EmailVerificationToken::delete_many()
+        .filter(EmailVerificationTokenColumn::ExpiresAt.lt(now))
+        .exec(db)
+        .await?;
+    Ok(result.rows_affected)
+}
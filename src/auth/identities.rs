@@ -0,0 +1,88 @@
+use chrono::{DateTime, Utc};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DbErr, EntityTrait, QueryFilter, QueryOrder,
+    Set,
+};
+use uuid::Uuid;
+
+use crate::entities::{UserIdentity, UserIdentityActiveModel, UserIdentityColumn, user_identity};
+use crate::oidc::IdTokenClaims;
+
+/// Names the unique index on provider and subject: that account at the
+/// provider is linked to another user.
+pub const SUBJECT_UNIQUE_INDEX: &str = "idx_user_identities_provider_subject";
+
+pub async fn find<C: ConnectionTrait>(
+    db: &C,
+    provider: &str,
+    subject: &str,
+) -> Result<Option<user_identity::Model>, DbErr> {
+    UserIdentity::find()
+        .filter(UserIdentityColumn::Provider.eq(provider))
+        .filter(UserIdentityColumn::Subject.eq(subject))
+        .one(db)
+        .await
+}
+
+pub async fn list<C: ConnectionTrait>(
+    db: &C,
+    user_id: Uuid,
+) -> Result<Vec<user_identity::Model>, DbErr> {
+    UserIdentity::find()
+        .filter(UserIdentityColumn::UserId.eq(user_id))
+        .order_by_asc(UserIdentityColumn::Provider)
+        .all(db)
+        .await
+}
+
+/// Links the account behind `claims` at `provider` to `user_id`. Fails with a
+/// unique violation when the user already has an identity at `provider` or
+/// the account is linked elsewhere.
+pub async fn link<C: ConnectionTrait>(
+    db: &C,
+    user_id: Uuid,
+    provider: &str,
+    claims: &IdTokenClaims,
+    now: DateTime<Utc>,
+) -> Result<user_identity::Model, DbErr> {
+    UserIdentityActiveModel {
+        id: Set(Uuid::new_v4()),
+        user_id: Set(user_id),
+        provider: Set(provider.to_string()),
+        subject: Set(claims.subject.clone()),
+        email: Set(claims.email.clone()),
+        created_at: Set(now.into()),
+        last_used_at: Set(Some(now.into())),
+    }
+    .insert(db)
+    .await
+}
+
+/// Records a login through `identity` and the address the provider reported.
+pub async fn touch<C: ConnectionTrait>(
+    db: &C,
+    identity: user_identity::Model,
+    claims: &IdTokenClaims,
+    now: DateTime<Utc>,
+) -> Result<(), DbErr> {
+    let mut active: UserIdentityActiveModel = identity.into();
+    active.email = Set(claims.email.clone());
+    active.last_used_at = Set(Some(now.into()));
+    active.update(db).await?;
+    Ok(())
+}
+
+/// Removes the identity of `user_id` at `provider`, returning whether there
+/// was one.
+pub async fn unlink<C: ConnectionTrait>(
+    db: &C,
+    user_id: Uuid,
+    provider: &str,
+) -> Result<bool, DbErr> {
+    let result = UserIdentity::delete_many()
+        .filter(UserIdentityColumn::UserId.eq(user_id))
+        .filter(UserIdentityColumn::Provider.eq(provider))
+        .exec(db)
+        .await?;
+    Ok(result.rows_affected > 0)
+}
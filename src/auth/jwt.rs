@@ -1,16 +1,20 @@
 use chrono::{DateTime, Duration, Utc};
-use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation, decode, encode};
+use jsonwebtoken::{
+    Algorithm, DecodingKey, EncodingKey, Header, Validation, decode, decode_header, encode,
+};
 use serde::{Deserialize, Serialize};
-use std::env;
 use std::fmt::Display;
 use uuid::Uuid;
 
+use crate::auth::keys::JwtKeys;
+
 const JWT_ISSUER: &str = "friends-server";
 const JWT_AUDIENCE: &str = "friends-api";
 
 enum TokenType {
     Refresh,
     Access,
+    TwoFactor,
 }
 
 impl Display for TokenType {
@@ -18,6 +22,7 @@ impl Display for TokenType {
         match self {
             TokenType::Refresh => f.write_str("refresh"),
             TokenType::Access => f.write_str("access"),
+            TokenType::TwoFactor => f.write_str("two_factor"),
         }
     }
 }
@@ -30,14 +35,23 @@ pub struct Payload {
     pub aud: String,
     pub token_type: String,
     pub jti: Option<String>,
+    /// Session of the login, carried by access tokens.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sid: Option<String>,
 }
 
-pub fn create_access_jwt(user_id: Uuid) -> Result<String, String> {
+pub fn create_access_jwt(
+    keys: &JwtKeys,
+    user_id: Uuid,
+    session_id: Uuid,
+) -> Result<String, String> {
     create_jwt(
+        keys,
         user_id,
         TokenType::Access,
-        Duration::minutes(15),
-        None
+        keys.access_ttl,
+        None,
+        Some(session_id),
     ).map(|issue| issue.token)
 }
 
@@ -47,20 +61,35 @@ pub struct RefreshTokenIssue {
     pub expires_at: DateTime<Utc>,
 }
 
-pub fn create_refresh_jwt(user_id: Uuid) -> Result<RefreshTokenIssue, String> {
+pub fn create_refresh_jwt(keys: &JwtKeys, user_id: Uuid) -> Result<RefreshTokenIssue, String> {
     create_jwt(
+        keys,
         user_id,
         TokenType::Refresh,
-        Duration::days(30),
+        keys.refresh_ttl,
         Some(Uuid::new_v4()),
+        None,
     )
 }
 
+/// A short-lived token that proves the password of `user_id` was right, to be
+/// exchanged for a token pair together with a second-factor code.
+pub fn create_two_factor_jwt(
+    keys: &JwtKeys,
+    user_id: Uuid,
+    ttl: Duration,
+) -> Result<String, String> {
+    create_jwt(keys, user_id, TokenType::TwoFactor, ttl, None, None)
+        .map(|issue| issue.token)
+}
+
 fn create_jwt(
+    keys: &JwtKeys,
     user_id: Uuid,
     token_type: TokenType,
     ttl: Duration,
     jti: Option<Uuid>,
+    sid: Option<Uuid>,
 ) -> Result<RefreshTokenIssue, String> {
     let expires_at = Utc::now()
         .checked_add_signed(ttl)
@@ -74,14 +103,21 @@ fn create_jwt(
         aud: JWT_AUDIENCE.to_string(),
         token_type: token_type.to_string(),
         jti: jti.map(|v| v.to_string()),
+        sid: sid.map(|v| v.to_string()),
     };
 
-    let secret = jwt_secret()?;
-    let token = encode(
-        &Header::default(),
-        &payload,
-        &EncodingKey::from_secret(&secret),
-    )
+    let token = match keys.signing_key() {
+        Some((kid, key)) => {
+            let mut header = Header::new(Algorithm::EdDSA);
+            header.kid = Some(kid);
+            encode(&header, &payload, &key)
+        }
+        None => encode(
+            &Header::default(),
+            &payload,
+            &EncodingKey::from_secret(secret_for(keys, &token_type)),
+        ),
+    }
     .map_err(|e| e.to_string())?;
 
     Ok(RefreshTokenIssue {
@@ -91,16 +127,16 @@ fn create_jwt(
     })
 }
 
-pub fn verify_access_jwt(token: &str) -> Result<Payload, String> {
-    let payload = verify_jwt(token)?;
+pub fn verify_access_jwt(keys: &JwtKeys, token: &str) -> Result<Payload, String> {
+    let payload = verify_jwt(keys, token, &TokenType::Access)?;
     if payload.token_type != TokenType::Access.to_string() {
         return Err("invalid token type for protected endpoint".to_string());
     }
     Ok(payload)
 }
 
-pub fn verify_refresh_jwt(token: &str) -> Result<Payload, String> {
-    let payload = verify_jwt(token)?;
+pub fn verify_refresh_jwt(keys: &JwtKeys, token: &str) -> Result<Payload, String> {
+    let payload = verify_jwt(keys, token, &TokenType::Refresh)?;
     if payload.token_type != TokenType::Refresh.to_string() {
         return Err("invalid token type for refresh endpoint".to_string());
     }
@@ -112,19 +148,41 @@ pub fn verify_refresh_jwt(token: &str) -> Result<Payload, String> {
     Ok(payload)
 }
 
-fn verify_jwt(token: &str) -> Result<Payload, String> {
-    let secret = jwt_secret()?;
-    let mut validation = Validation::new(Algorithm::HS256);
+pub fn verify_two_factor_jwt(keys: &JwtKeys, token: &str) -> Result<Payload, String> {
+    let payload = verify_jwt(keys, token, &TokenType::TwoFactor)?;
+    if payload.token_type != TokenType::TwoFactor.to_string() {
+        return Err("invalid token type for two-factor login".to_string());
+    }
+    Ok(payload)
+}
+
+/// EdDSA tokens are checked against the key named by their `kid`, HS256
+/// tokens against the secret while one is configured.
+fn verify_jwt(keys: &JwtKeys, token: &str, token_type: &TokenType) -> Result<Payload, String> {
+    let header = decode_header(token).map_err(|e| e.to_string())?;
+    let key = match header.alg {
+        Algorithm::EdDSA => {
+            let kid = header.kid.as_deref().ok_or("token missing kid")?;
+            keys.decoding_key(kid).ok_or("unknown signing key")?
+        }
+        Algorithm::HS256 if !secret_for(keys, token_type).is_empty() => {
+            DecodingKey::from_secret(secret_for(keys, token_type))
+        }
+        _ => return Err("unsupported token algorithm".to_string()),
+    };
+
+    let mut validation = Validation::new(header.alg);
     validation.set_issuer(&[JWT_ISSUER]);
     validation.set_audience(&[JWT_AUDIENCE]);
 
-    decode::<Payload>(token, &DecodingKey::from_secret(&secret), &validation)
+    decode::<Payload>(token, &key, &validation)
         .map(|data| data.claims)
         .map_err(|e| e.to_string())
 }
 
-fn jwt_secret() -> Result<Vec<u8>, String> {
-    env::var("JWT_SECRET")
-        .map(|s| s.into_bytes())
-        .map_err(|_| "JWT_SECRET must be set".to_string())
+fn secret_for<'a>(keys: &'a JwtKeys, token_type: &TokenType) -> &'a [u8] {
+    match token_type {
+        TokenType::Access | TokenType::TwoFactor => &keys.access_secret,
+        TokenType::Refresh => &keys.refresh_secret,
+    }
 }
@@ -0,0 +1,237 @@
+//! Ed25519 keys that sign access and refresh tokens. Each key is a PKCS#8 PEM
+//! file `<kid>.pem` in `jwt.key_dir`. The kid starts with the creation time,
+//! so every server agrees on which key is the newest.
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use chrono::{DateTime, NaiveDateTime, TimeDelta, Utc};
+use ed25519_dalek::SigningKey;
+use ed25519_dalek::pkcs8::spki::der::pem::LineEnding;
+use ed25519_dalek::pkcs8::{DecodePrivateKey, EncodePrivateKey};
+use jsonwebtoken::{DecodingKey, EncodingKey};
+use rand::{Rng, RngCore};
+
+use crate::config::JwtConfig;
+use crate::error::BoxError;
+
+const KID_TIME_FORMAT: &str = "%Y%m%dT%H%M%S%.3fZ";
+const EXTENSION: &str = "pem";
+
+pub struct KeyPair {
+    pub kid: String,
+    pub created_at: DateTime<Utc>,
+    pub public_key: [u8; 32],
+    encoding: EncodingKey,
+    decoding: DecodingKey,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyState {
+    /// Published, starts signing once its activation delay has passed.
+    Pending,
+    Signing,
+    /// Replaced, kept to verify tokens it signed until they expire.
+    Retired,
+}
+
+/// A public key as published in the JWKS document.
+pub struct PublicKey {
+    pub kid: String,
+    /// The raw key, base64url encoded.
+    pub x: String,
+}
+
+/// The keys tokens are signed and verified with, loaded once at startup and
+/// reloaded by a background job so rotations reach running servers.
+pub struct JwtKeys {
+    dir: Option<PathBuf>,
+    activation: TimeDelta,
+    pub(crate) access_secret: Vec<u8>,
+    pub(crate) refresh_secret: Vec<u8>,
+    pub(crate) access_ttl: TimeDelta,
+    pub(crate) refresh_ttl: TimeDelta,
+    /// Oldest first.
+    keys: RwLock<Arc<Vec<KeyPair>>>,
+}
+
+impl JwtKeys {
+    pub fn load(config: &JwtConfig) -> Result<Self, BoxError> {
+        let keys = match &config.key_dir {
+            Some(dir) => read_non_empty(dir)?,
+            None => Vec::new(),
+        };
+        Ok(JwtKeys {
+            dir: config.key_dir.clone(),
+            activation: activation(config),
+            access_secret: config.access_secret.as_bytes().to_vec(),
+            refresh_secret: config.refresh_secret.as_bytes().to_vec(),
+            access_ttl: config.access_ttl(),
+            refresh_ttl: config.refresh_ttl(),
+            keys: RwLock::new(Arc::new(keys)),
+        })
+    }
+
+    /// Re-reads the key directory. The loaded keys stay in use when it fails.
+    pub fn reload(&self) -> Result<(), BoxError> {
+        let Some(dir) = &self.dir else {
+            return Ok(());
+        };
+        let keys = read_non_empty(dir)?;
+        *self.keys.write().unwrap() = Arc::new(keys);
+        Ok(())
+    }
+
+    pub fn key_dir(&self) -> Option<&Path> {
+        self.dir.as_deref()
+    }
+
+    /// Kid and key new tokens are signed with, `None` without a key
+    /// directory.
+    pub(crate) fn signing_key(&self) -> Option<(String, EncodingKey)> {
+        let keys = self.keys.read().unwrap().clone();
+        let index = signing_index(&keys, self.activation, Utc::now())?;
+        Some((keys[index].kid.clone(), keys[index].encoding.clone()))
+    }
+
+    pub(crate) fn decoding_key(&self, kid: &str) -> Option<DecodingKey> {
+        let keys = self.keys.read().unwrap();
+        keys.iter()
+            .find(|key| key.kid == kid)
+            .map(|key| key.decoding.clone())
+    }
+
+    pub fn public_keys(&self) -> Vec<PublicKey> {
+        let keys = self.keys.read().unwrap();
+        keys.iter()
+            .map(|key| PublicKey {
+                kid: key.kid.clone(),
+                x: URL_SAFE_NO_PAD.encode(key.public_key),
+            })
+            .collect()
+    }
+}
+
+/// Every key in `dir`, oldest first.
+pub fn read_dir(dir: &Path) -> Result<Vec<KeyPair>, BoxError> {
+    let mut keys = Vec::new();
+    for entry in fs::read_dir(dir).map_err(|err| format!("cannot read {}: {err}", dir.display()))? {
+        let path = entry?.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some(EXTENSION) {
+            continue;
+        }
+        keys.push(read_key(&path)?);
+    }
+    keys.sort_by(|a, b| (a.created_at, &a.kid).cmp(&(b.created_at, &b.kid)));
+    Ok(keys)
+}
+
+/// State of every key in `keys`, which must be sorted oldest first.
+pub fn states(keys: &[KeyPair], config: &JwtConfig, now: DateTime<Utc>) -> Vec<KeyState> {
+    let signing = signing_index(keys, activation(config), now);
+    (0..keys.len())
+        .map(|index| match signing {
+            Some(signing) if index == signing => KeyState::Signing,
+            Some(signing) if index < signing => KeyState::Retired,
+            _ => KeyState::Pending,
+        })
+        .collect()
+}
+
+/// Writes a new key to `dir` and returns its kid. It starts signing after
+/// `jwt.key_activation_secs`, or at once if it is the only key.
+pub fn generate(dir: &Path, now: DateTime<Utc>) -> Result<String, BoxError> {
+    fs::create_dir_all(dir)?;
+    let mut rng = rand::rng();
+    let mut secret = [0u8; 32];
+    rng.fill_bytes(&mut secret);
+    let pem = SigningKey::from_bytes(&secret)
+        .to_pkcs8_pem(LineEnding::LF)
+        .map_err(|err| err.to_string())?;
+
+    let kid = format!(
+        "{}-{:04x}",
+        now.format(KID_TIME_FORMAT),
+        rng.random::<u16>()
+    );
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options.open(dir.join(format!("{kid}.{EXTENSION}")))?;
+    file.write_all(pem.as_bytes())?;
+    Ok(kid)
+}
+
+/// Deletes keys no token signed with them can still be valid for: those
+/// replaced by a key that has been signing for longer than the refresh token
+/// lifetime. Returns their kids.
+pub fn prune(dir: &Path, config: &JwtConfig, now: DateTime<Utc>) -> Result<Vec<String>, BoxError> {
+    let keys = read_dir(dir)?;
+    let mut pruned = Vec::new();
+    for pair in keys.windows(2) {
+        let (old, successor) = (&pair[0], &pair[1]);
+        if successor.created_at + activation(config) + config.refresh_ttl() <= now {
+            fs::remove_file(dir.join(format!("{}.{EXTENSION}", old.kid)))?;
+            pruned.push(old.kid.clone());
+        }
+    }
+    Ok(pruned)
+}
+
+/// The newest key past its activation delay, or the oldest key when none is.
+fn signing_index(keys: &[KeyPair], activation: TimeDelta, now: DateTime<Utc>) -> Option<usize> {
+    keys.iter()
+        .rposition(|key| key.created_at + activation <= now)
+        .or(if keys.is_empty() { None } else { Some(0) })
+}
+
+fn activation(config: &JwtConfig) -> TimeDelta {
+    TimeDelta::seconds(config.key_activation_secs as i64)
+}
+
+fn read_non_empty(dir: &Path) -> Result<Vec<KeyPair>, BoxError> {
+    let keys = read_dir(dir)?;
+    if keys.is_empty() {
+        return Err(format!(
+            "no signing keys in {}, create one with `keys rotate`",
+            dir.display()
+        )
+        .into());
+    }
+    Ok(keys)
+}
+
+fn read_key(path: &Path) -> Result<KeyPair, BoxError> {
+    let invalid = |reason: String| format!("invalid key file {}: {reason}", path.display());
+
+    let kid = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .ok_or_else(|| invalid("file name is not UTF-8".into()))?
+        .to_string();
+    let created_at = kid
+        .split('-')
+        .next()
+        .and_then(|time| NaiveDateTime::parse_from_str(time, KID_TIME_FORMAT).ok())
+        .ok_or_else(|| invalid("name does not start with the creation time".into()))?
+        .and_utc();
+
+    let pem = fs::read_to_string(path)?;
+    let signing = SigningKey::from_pkcs8_pem(&pem).map_err(|err| invalid(err.to_string()))?;
+    let public_key = signing.verifying_key().to_bytes();
+    let encoding =
+        EncodingKey::from_ed_pem(pem.as_bytes()).map_err(|err| invalid(err.to_string()))?;
+
+    Ok(KeyPair {
+        kid,
+        created_at,
+        public_key,
+        encoding,
+        decoding: DecodingKey::from_ed_der(&public_key),
+    })
+}
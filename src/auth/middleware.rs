@@ -1,35 +1,59 @@
+use std::sync::Arc;
+
 use crate::auth::jwt::verify_access_jwt;
+use crate::auth::keys::JwtKeys;
+use crate::auth::session_guard::SessionGuard;
+use crate::error::{AppError, ErrorCode};
+use crate::i18n;
 use axum::{
-    extract::FromRequestParts,
-    http::{StatusCode, request::Parts},
+    extract::{FromRef, FromRequestParts},
+    http::request::Parts,
 };
 use uuid::Uuid;
 
 pub struct AuthUser {
     pub user_id: Uuid,
+    /// Session the access token was issued for. Empty for tokens issued
+    /// before sessions existed.
+    pub session_id: Option<Uuid>,
 }
 
 impl<S> FromRequestParts<S> for AuthUser
 where
     S: Send + Sync,
+    Arc<JwtKeys>: FromRef<S>,
+    Arc<SessionGuard>: FromRef<S>,
 {
-    type Rejection = (StatusCode, &'static str);
+    type Rejection = AppError;
 
-    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let keys = Arc::<JwtKeys>::from_ref(state);
         let auth_header = parts
             .headers
             .get("Authorization")
             .and_then(|value| value.to_str().ok());
-        if let Some(header) = auth_header {
-            if header.starts_with("Bearer ") {
-                let token = header.trim_start_matches("Bearer ");
-                if let Ok(payload) = verify_access_jwt(token) {
-                    if let Ok(user_id) = Uuid::parse_str(&payload.sub) {
-                        return Ok(AuthUser { user_id });
-                    }
+        if let Some(token) = auth_header.and_then(|header| header.strip_prefix("Bearer "))
+            && let Ok(payload) = verify_access_jwt(&keys, token)
+            && let Ok(user_id) = Uuid::parse_str(&payload.sub)
+        {
+            let session_id = payload.sid.as_deref().and_then(|v| Uuid::parse_str(v).ok());
+            if let Some(session_id) = session_id {
+                let status = Arc::<SessionGuard>::from_ref(state)
+                    .check(user_id, session_id)
+                    .await?;
+                if !status.active {
+                    return Err(ErrorCode::SessionExpired.into());
+                }
+                if let Some(locale) = status.locale {
+                    i18n::prefer(locale);
                 }
             }
+            tracing::Span::current().record("user_id", tracing::field::display(user_id));
+            return Ok(AuthUser {
+                user_id,
+                session_id,
+            });
         }
-        Err((StatusCode::UNAUTHORIZED, "Your session has expired. Please log in again"))
+        Err(ErrorCode::SessionExpired.into())
     }
 }
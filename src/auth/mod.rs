@@ -1,2 +1,16 @@
+pub mod accounts;
+pub mod email_verification;
+pub mod identities;
 pub mod jwt;
+pub mod keys;
+pub mod mail_token;
 pub mod middleware;
+pub mod passkeys;
+pub mod password;
+pub mod password_reset;
+pub mod refresh_tokens;
+pub mod security_events;
+pub mod session_guard;
+pub mod sessions;
+pub mod totp;
+pub mod two_factor;
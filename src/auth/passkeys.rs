@@ -0,0 +1,166 @@
+use chrono::{DateTime, Utc};
+use sea_orm::sea_query::Expr;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DbErr, EntityTrait, PaginatorTrait,
+    QueryFilter, QueryOrder, Set,
+};
+use uuid::Uuid;
+
+use crate::entities::webauthn_challenge::CeremonyPurpose;
+use crate::entities::{
+    Passkey, PasskeyActiveModel, PasskeyColumn, WebauthnChallenge, WebauthnChallengeActiveModel,
+    WebauthnChallengeColumn, passkey,
+};
+use crate::webauthn::{self, NewCredential};
+
+/// Names the unique index on credential ids: the authenticator already
+/// registered this passkey.
+pub const CREDENTIAL_UNIQUE_INDEX: &str = "idx_passkeys_credential_id";
+
+/// Starts a ceremony and returns its id with the challenge for the
+/// authenticator. Expired challenges of earlier ceremonies are dropped.
+pub async fn issue_challenge<C: ConnectionTrait>(
+    db: &C,
+    user_id: Option<Uuid>,
+    purpose: CeremonyPurpose,
+    now: DateTime<Utc>,
+    expires_at: DateTime<Utc>,
+) -> Result<(Uuid, String), DbErr> {
+    WebauthnChallenge::delete_many()
+        .filter(WebauthnChallengeColumn::ExpiresAt.lte(now))
+        .exec(db)
+        .await?;
+
+    let id = Uuid::new_v4();
+    let challenge = webauthn::generate_challenge();
+    WebauthnChallengeActiveModel {
+        id: Set(id),
+        user_id: Set(user_id),
+        purpose: Set(purpose),
+        challenge: Set(challenge.clone()),
+        expires_at: Set(expires_at.into()),
+        created_at: Set(now.into()),
+    }
+    .insert(db)
+    .await?;
+    Ok((id, challenge))
+}
+
+/// Ends the ceremony `id` of `user_id` and returns its challenge, or `None`
+/// when it is unknown, expired, for something else or already ended. Of
+/// concurrent calls at most one gets the challenge.
+pub async fn consume_challenge<C: ConnectionTrait>(
+    db: &C,
+    id: Uuid,
+    user_id: Option<Uuid>,
+    purpose: CeremonyPurpose,
+    now: DateTime<Utc>,
+) -> Result<Option<String>, DbErr> {
+    let Some(model) = WebauthnChallenge::find_by_id(id)
+        .filter(WebauthnChallengeColumn::Purpose.eq(purpose))
+        .filter(WebauthnChallengeColumn::ExpiresAt.gt(now))
+        .one(db)
+        .await?
+        .filter(|model| model.user_id == user_id)
+    else {
+        return Ok(None);
+    };
+
+    let result = WebauthnChallenge::delete_by_id(model.id).exec(db).await?;
+    Ok((result.rows_affected > 0).then_some(model.challenge))
+}
+
+pub async fn list<C: ConnectionTrait>(db: &C, user_id: Uuid) -> Result<Vec<passkey::Model>, DbErr> {
+    Passkey::find()
+        .filter(PasskeyColumn::UserId.eq(user_id))
+        .order_by_asc(PasskeyColumn::CreatedAt)
+        .all(db)
+        .await
+}
+
+pub async fn count<C: ConnectionTrait>(db: &C, user_id: Uuid) -> Result<u64, DbErr> {
+    Passkey::find()
+        .filter(PasskeyColumn::UserId.eq(user_id))
+        .count(db)
+        .await
+}
+
+/// The passkey `id` if it belongs to `user_id`.
+pub async fn find<C: ConnectionTrait>(
+    db: &C,
+    user_id: Uuid,
+    id: Uuid,
+) -> Result<Option<passkey::Model>, DbErr> {
+    Passkey::find_by_id(id)
+        .filter(PasskeyColumn::UserId.eq(user_id))
+        .one(db)
+        .await
+}
+
+pub async fn find_by_credential<C: ConnectionTrait>(
+    db: &C,
+    credential_id: &str,
+) -> Result<Option<passkey::Model>, DbErr> {
+    Passkey::find()
+        .filter(PasskeyColumn::CredentialId.eq(credential_id))
+        .one(db)
+        .await
+}
+
+/// Stores a registered credential. Fails with a unique violation when the
+/// credential is registered already.
+pub async fn create<C: ConnectionTrait>(
+    db: &C,
+    user_id: Uuid,
+    credential_id: String,
+    credential: NewCredential,
+    name: String,
+    now: DateTime<Utc>,
+) -> Result<passkey::Model, DbErr> {
+    PasskeyActiveModel {
+        id: Set(Uuid::new_v4()),
+        user_id: Set(user_id),
+        credential_id: Set(credential_id),
+        public_key: Set(credential.public_key),
+        algorithm: Set(credential.algorithm),
+        sign_count: Set(credential.sign_count as i64),
+        name: Set(name),
+        created_at: Set(now.into()),
+        last_used_at: Set(None),
+    }
+    .insert(db)
+    .await
+}
+
+/// Records a login with `passkey` and its new counter. Returns `false` when
+/// a concurrent login used the same counter value first, which is a replay.
+pub async fn record_use<C: ConnectionTrait>(
+    db: &C,
+    passkey: &passkey::Model,
+    sign_count: u32,
+    now: DateTime<Utc>,
+) -> Result<bool, DbErr> {
+    let result = Passkey::update_many()
+        .col_expr(PasskeyColumn::SignCount, Expr::value(sign_count as i64))
+        .col_expr(PasskeyColumn::LastUsedAt, Expr::value(now))
+        .filter(PasskeyColumn::Id.eq(passkey.id))
+        .filter(PasskeyColumn::SignCount.eq(passkey.sign_count))
+        .exec(db)
+        .await?;
+    Ok(result.rows_affected > 0)
+}
+
+pub async fn rename<C: ConnectionTrait>(
+    db: &C,
+    passkey: passkey::Model,
+    name: String,
+) -> Result<passkey::Model, DbErr> {
+    let mut active: PasskeyActiveModel = passkey.into();
+    active.name = Set(name);
+    active.update(db).await
+}
+
+pub async fn delete<C: ConnectionTrait>(db: &C, passkey: passkey::Model) -> Result<(), DbErr> {
+    Passkey::delete_by_id(passkey.id).exec(db).await?;
+    Ok(())
+}
@@ -0,0 +1,26 @@
+use argon2::Argon2;
+use argon2::password_hash::rand_core::OsRng;
+use argon2::password_hash::{self, PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+
+/// Hashes `password` with Argon2 and a random salt, in PHC string format.
+pub fn hash_password(password: &str) -> Result<String, password_hash::Error> {
+    let salt = SaltString::generate(&mut OsRng);
+    Ok(Argon2::default()
+        .hash_password(password.as_bytes(), &salt)?
+        .to_string())
+}
+
+/// Checks `password` against a stored hash. A malformed hash is an error,
+/// a wrong password is `Ok(false)`. An empty hash matches nothing: accounts
+/// created through a sign-in provider have no password until one is reset.
+pub fn verify_password(hash: &str, password: &str) -> Result<bool, password_hash::Error> {
+    if hash.is_empty() {
+        return Ok(false);
+    }
+    let parsed_hash = PasswordHash::new(hash)?;
+    match Argon2::default().verify_password(password.as_bytes(), &parsed_hash) {
+        Ok(()) => Ok(true),
+        Err(password_hash::Error::Password) => Ok(false),
+        Err(err) => Err(err),
+    }
+}
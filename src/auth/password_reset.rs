@@ -0,0 +1,87 @@
+use chrono::{DateTime, Utc};
+use sea_orm::sea_query::Expr;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DbErr, EntityTrait, QueryFilter, Set,
+};
+use uuid::Uuid;
+
+use crate::auth::mail_token;
+use crate::entities::{
+    PasswordResetToken, PasswordResetTokenActiveModel, PasswordResetTokenColumn,
+};
+
+/// Creates a reset token for `user_id` valid until `expires_at` and returns
+/// it. Earlier unused tokens of the user stop working.
+pub async fn issue<C: ConnectionTrait>(
+    db: &C,
+    user_id: Uuid,
+    now: DateTime<Utc>,
+    expires_at: DateTime<Utc>,
+) -> Result<String, DbErr> {
+    discard(db, user_id, now).await?;
+
+    let token = mail_token::generate();
+
+    PasswordResetTokenActiveModel {
+        id: Set(Uuid::new_v4()),
+        user_id: Set(user_id),
+        token_hash: Set(mail_token::hash(&token)),
+        expires_at: Set(expires_at.into()),
+        created_at: Set(now.into()),
+        ..Default::default()
+    }
+    .insert(db)
+    .await?;
+    Ok(token)
+}
+
+/// Uses up `token` and returns its user, or `None` when it is unknown,
+/// expired or already used. Of concurrent calls with one token at most one
+/// gets the user.
+pub async fn consume<C: ConnectionTrait>(
+    db: &C,
+    token: &str,
+    now: DateTime<Utc>,
+) -> Result<Option<Uuid>, DbErr> {
+    let Some(model) = PasswordResetToken::find()
+        .filter(PasswordResetTokenColumn::TokenHash.eq(mail_token::hash(token)))
+        .filter(PasswordResetTokenColumn::UsedAt.is_null())
+        .filter(PasswordResetTokenColumn::ExpiresAt.gt(now))
+        .one(db)
+        .await?
+    else {
+        return Ok(None);
+    };
+
+    let result = PasswordResetToken::update_many()
+        .col_expr(PasswordResetTokenColumn::UsedAt, Expr::value(now))
+        .filter(PasswordResetTokenColumn::Id.eq(model.id))
+        .filter(PasswordResetTokenColumn::UsedAt.is_null())
+        .exec(db)
+        .await?;
+    Ok((result.rows_affected == 1).then_some(model.user_id))
+}
+
+/// Invalidates the unused tokens of `user_id`, e.g. after a password change.
+pub async fn discard<C: ConnectionTrait>(
+    db: &C,
+    user_id: Uuid,
+    now: DateTime<Utc>,
+) -> Result<u64, DbErr> {
+    let result = PasswordResetToken::update_many()
+        .col_expr(PasswordResetTokenColumn::UsedAt, Expr::value(now))
+        .filter(PasswordResetTokenColumn::UserId.eq(user_id))
+        .filter(PasswordResetTokenColumn::UsedAt.is_null())
+        .exec(db)
+        .await?;
+    Ok(result.rows_affected)
+}
+
+/// Deletes tokens that expired before `now`, used or not.
+pub async fn delete_expired<C: ConnectionTrait>(db: &C, now: DateTime<Utc>) -> Result<u64, DbErr> {
+    let result = PasswordResetToken::delete_many()
+        .filter(PasswordResetTokenColumn::ExpiresAt.lt(now))
+        .exec(db)
+        .await?;
+    Ok(result.rows_affected)
+}
@@ -0,0 +1,94 @@
+use chrono::{DateTime, TimeDelta, Utc};
+use sea_orm::sea_query::Expr;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DbErr, EntityTrait, PaginatorTrait,
+    QueryFilter, Set,
+};
+use uuid::Uuid;
+
+use crate::entities::{RefreshToken, RefreshTokenActiveModel, RefreshTokenColumn, refresh_token};
+
+/// How long a rotated token may still come back without counting as reuse,
+/// e.g. from a client retrying a refresh whose response it lost.
+pub const REUSE_GRACE: TimeDelta = TimeDelta::seconds(5);
+
+/// Stores token `id`, issued at login, as the first of a new family.
+pub async fn start_family<C: ConnectionTrait>(
+    db: &C,
+    id: Uuid,
+    user_id: Uuid,
+    expires_at: DateTime<Utc>,
+) -> Result<(), DbErr> {
+    RefreshTokenActiveModel {
+        id: Set(id),
+        user_id: Set(user_id),
+        family_id: Set(id),
+        parent_id: Set(None),
+        expires_at: Set(expires_at.into()),
+        revoked_at: Set(None),
+        ..Default::default()
+    }
+    .insert(db)
+    .await?;
+    Ok(())
+}
+
+/// Revokes `parent` and stores token `id` as its successor. Returns `false`
+/// and stores nothing when `parent` has been revoked in the meantime, e.g. by
+/// a concurrent rotation.
+pub async fn rotate<C: ConnectionTrait>(
+    db: &C,
+    parent: &refresh_token::Model,
+    id: Uuid,
+    expires_at: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> Result<bool, DbErr> {
+    let revoked = RefreshToken::update_many()
+        .col_expr(RefreshTokenColumn::RevokedAt, Expr::value(now))
+        .filter(RefreshTokenColumn::Id.eq(parent.id))
+        .filter(RefreshTokenColumn::RevokedAt.is_null())
+        .exec(db)
+        .await?
+        .rows_affected;
+    if revoked == 0 {
+        return Ok(false);
+    }
+
+    RefreshTokenActiveModel {
+        id: Set(id),
+        user_id: Set(parent.user_id),
+        family_id: Set(parent.family_id),
+        parent_id: Set(Some(parent.id)),
+        expires_at: Set(expires_at.into()),
+        revoked_at: Set(None),
+        ..Default::default()
+    }
+    .insert(db)
+    .await?;
+    Ok(true)
+}
+
+/// Whether token `id` has already been exchanged for a successor. Presenting
+/// such a token again means someone else holds a copy of it.
+pub async fn was_rotated<C: ConnectionTrait>(db: &C, id: Uuid) -> Result<bool, DbErr> {
+    let successors = RefreshToken::find()
+        .filter(RefreshTokenColumn::ParentId.eq(id))
+        .count(db)
+        .await?;
+    Ok(successors > 0)
+}
+
+/// Revokes every active token of the family. Returns how many were revoked.
+pub async fn revoke_family<C: ConnectionTrait>(
+    db: &C,
+    family_id: Uuid,
+    now: DateTime<Utc>,
+) -> Result<u64, DbErr> {
+    let result = RefreshToken::update_many()
+        .col_expr(RefreshTokenColumn::RevokedAt, Expr::value(now))
+        .filter(RefreshTokenColumn::FamilyId.eq(family_id))
+        .filter(RefreshTokenColumn::RevokedAt.is_null())
+        .exec(db)
+        .await?;
+    Ok(result.rows_affected)
+}
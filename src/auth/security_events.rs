@@ -0,0 +1,29 @@
+use std::net::IpAddr;
+
+use sea_orm::{ActiveModelTrait, ConnectionTrait, DbErr, Set};
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::entities::SecurityEventActiveModel;
+use crate::entities::security_event::SecurityEventKind;
+
+/// Appends a security event to the account of `user_id` and logs it.
+pub async fn record<C: ConnectionTrait>(
+    db: &C,
+    user_id: Uuid,
+    kind: SecurityEventKind,
+    ip: Option<IpAddr>,
+    details: Value,
+) -> Result<(), DbErr> {
+    tracing::warn!(%user_id, %kind, ip = ?ip, %details, "security event");
+    SecurityEventActiveModel {
+        user_id: Set(user_id),
+        kind: Set(kind),
+        ip: Set(ip.map(|ip| ip.to_string())),
+        details: Set(Some(details)),
+        ..Default::default()
+    }
+    .insert(db)
+    .await?;
+    Ok(())
+}
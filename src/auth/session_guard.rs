@@ -0,0 +1,97 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use sea_orm::{DatabaseConnection, EntityTrait, QuerySelect};
+use uuid::Uuid;
+
+use crate::auth::sessions;
+use crate::entities::{User, UserColumn};
+use crate::error::AppError;
+use crate::i18n::Locale;
+
+/// Checked sessions are dropped once the map grows past this many.
+const PRUNE_AT: usize = 10_000;
+
+struct Checked {
+    user_id: Uuid,
+    status: SessionStatus,
+    at: Instant,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct SessionStatus {
+    pub active: bool,
+    /// Preferred language of the session's user, read with the session so it
+    /// follows profile changes.
+    pub locale: Option<Locale>,
+}
+
+/// Tells whether the session behind an access token is still active. Answers
+/// are cached for `jwt.session_check_secs`, so a session revoked or a locale
+/// changed through another server takes effect within that time, and at once
+/// on this one.
+pub struct SessionGuard {
+    db: Arc<DatabaseConnection>,
+    ttl: Duration,
+    checked: Mutex<HashMap<Uuid, Checked>>,
+}
+
+impl SessionGuard {
+    pub fn new(db: Arc<DatabaseConnection>, ttl: Duration) -> Self {
+        SessionGuard {
+            db,
+            ttl,
+            checked: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub async fn check(&self, user_id: Uuid, session_id: Uuid) -> Result<SessionStatus, AppError> {
+        if let Some(checked) = self.checked.lock().unwrap().get(&session_id)
+            && checked.user_id == user_id
+            && checked.at.elapsed() < self.ttl
+        {
+            return Ok(checked.status);
+        }
+
+        let active = sessions::is_active(self.db.as_ref(), user_id, session_id).await?;
+        let locale = if active {
+            let locale: Option<Option<String>> = User::find_by_id(user_id)
+                .select_only()
+                .column(UserColumn::Locale)
+                .into_tuple()
+                .one(self.db.as_ref())
+                .await?;
+            locale.flatten().as_deref().and_then(Locale::parse)
+        } else {
+            None
+        };
+        let status = SessionStatus { active, locale };
+        let mut checked = self.checked.lock().unwrap();
+        if checked.len() >= PRUNE_AT {
+            checked.retain(|_, entry| entry.at.elapsed() < self.ttl);
+        }
+        checked.insert(
+            session_id,
+            Checked {
+                user_id,
+                status,
+                at: Instant::now(),
+            },
+        );
+        Ok(status)
+    }
+
+    /// Makes the next request of `session_id` check the database again.
+    pub fn forget(&self, session_id: Uuid) {
+        self.checked.lock().unwrap().remove(&session_id);
+    }
+
+    /// Like [`SessionGuard::forget`] for every session of `user_id`.
+    pub fn forget_user(&self, user_id: Uuid) {
+        self.checked
+            .lock()
+            .unwrap()
+            .retain(|_, entry| entry.user_id != user_id);
+    }
+}
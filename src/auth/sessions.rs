@@ -0,0 +1,153 @@
+use std::net::IpAddr;
+
+use chrono::{DateTime, Utc};
+use sea_orm::sea_query::Expr;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DbErr, EntityTrait, QueryFilter, QueryOrder,
+    Set,
+};
+use uuid::Uuid;
+
+use crate::auth::refresh_tokens;
+use crate::entities::{
+    RefreshToken, RefreshTokenColumn, Session, SessionActiveModel, SessionColumn, session,
+};
+
+const DEVICE_NAME_LEN: usize = 100;
+const PLATFORM_LEN: usize = 32;
+const USER_AGENT_LEN: usize = 512;
+
+/// Where a login came from. Longer values are cut to fit their columns.
+#[derive(Default)]
+pub struct DeviceInfo {
+    pub device_name: Option<String>,
+    pub platform: Option<String>,
+    pub ip: Option<IpAddr>,
+    pub user_agent: Option<String>,
+}
+
+/// Stores session `id` of a login at `now` that issued refresh tokens valid
+/// until `expires_at`.
+pub async fn start<C: ConnectionTrait>(
+    db: &C,
+    id: Uuid,
+    user_id: Uuid,
+    device: DeviceInfo,
+    expires_at: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> Result<(), DbErr> {
+    SessionActiveModel {
+        id: Set(id),
+        user_id: Set(user_id),
+        device_name: Set(truncate(device.device_name, DEVICE_NAME_LEN)),
+        platform: Set(truncate(device.platform, PLATFORM_LEN)),
+        ip: Set(device.ip.map(|ip| ip.to_string())),
+        user_agent: Set(truncate(device.user_agent, USER_AGENT_LEN)),
+        created_at: Set(now.into()),
+        last_used_at: Set(now.into()),
+        expires_at: Set(expires_at.into()),
+        revoked_at: Set(None),
+    }
+    .insert(db)
+    .await?;
+    Ok(())
+}
+
+/// Records that session `id` was used to refresh at `now`, from `ip` when
+/// known.
+pub async fn touch<C: ConnectionTrait>(
+    db: &C,
+    id: Uuid,
+    ip: Option<IpAddr>,
+    expires_at: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> Result<(), DbErr> {
+    let mut update = Session::update_many()
+        .col_expr(SessionColumn::LastUsedAt, Expr::value(now))
+        .col_expr(SessionColumn::ExpiresAt, Expr::value(expires_at))
+        .filter(SessionColumn::Id.eq(id));
+    if let Some(ip) = ip {
+        update = update.col_expr(SessionColumn::Ip, Expr::value(ip.to_string()));
+    }
+    update.exec(db).await?;
+    Ok(())
+}
+
+/// Sessions of `user_id` that are neither revoked nor expired, most recently
+/// used first.
+pub async fn list_active<C: ConnectionTrait>(
+    db: &C,
+    user_id: Uuid,
+    now: DateTime<Utc>,
+) -> Result<Vec<session::Model>, DbErr> {
+    Session::find()
+        .filter(SessionColumn::UserId.eq(user_id))
+        .filter(SessionColumn::RevokedAt.is_null())
+        .filter(SessionColumn::ExpiresAt.gt(now))
+        .order_by_desc(SessionColumn::LastUsedAt)
+        .order_by_desc(SessionColumn::Id)
+        .all(db)
+        .await
+}
+
+/// Whether session `id` of `user_id` has not been revoked.
+pub async fn is_active<C: ConnectionTrait>(db: &C, user_id: Uuid, id: Uuid) -> Result<bool, DbErr> {
+    let session = Session::find_by_id(id)
+        .filter(SessionColumn::UserId.eq(user_id))
+        .filter(SessionColumn::RevokedAt.is_null())
+        .one(db)
+        .await?;
+    Ok(session.is_some())
+}
+
+/// Ends session `id` and revokes its refresh tokens. Returns `false` when it
+/// had already ended.
+pub async fn revoke<C: ConnectionTrait>(
+    db: &C,
+    id: Uuid,
+    now: DateTime<Utc>,
+) -> Result<bool, DbErr> {
+    let revoked = Session::update_many()
+        .col_expr(SessionColumn::RevokedAt, Expr::value(now))
+        .filter(SessionColumn::Id.eq(id))
+        .filter(SessionColumn::RevokedAt.is_null())
+        .exec(db)
+        .await?
+        .rows_affected;
+    refresh_tokens::revoke_family(db, id, now).await?;
+    Ok(revoked > 0)
+}
+
+/// Ends every active session, or only those of `user_id`, except `keep`, and
+/// revokes their refresh tokens. Returns how many sessions ended.
+pub async fn revoke_all<C: ConnectionTrait>(
+    db: &C,
+    user_id: Option<Uuid>,
+    keep: Option<Uuid>,
+    now: DateTime<Utc>,
+) -> Result<u64, DbErr> {
+    let mut sessions = Session::update_many()
+        .col_expr(SessionColumn::RevokedAt, Expr::value(now))
+        .filter(SessionColumn::RevokedAt.is_null());
+    let mut tokens = RefreshToken::update_many()
+        .col_expr(RefreshTokenColumn::RevokedAt, Expr::value(now))
+        .filter(RefreshTokenColumn::RevokedAt.is_null());
+    if let Some(user_id) = user_id {
+        sessions = sessions.filter(SessionColumn::UserId.eq(user_id));
+        tokens = tokens.filter(RefreshTokenColumn::UserId.eq(user_id));
+    }
+    if let Some(keep) = keep {
+        sessions = sessions.filter(SessionColumn::Id.ne(keep));
+        tokens = tokens.filter(RefreshTokenColumn::FamilyId.ne(keep));
+    }
+
+    let revoked = sessions.exec(db).await?.rows_affected;
+    tokens.exec(db).await?;
+    Ok(revoked)
+}
+
+fn truncate(value: Option<String>, max_chars: usize) -> Option<String> {
+    value
+        .map(|value| value.trim().chars().take(max_chars).collect::<String>())
+        .filter(|value| !value.is_empty())
+}
@@ -0,0 +1,78 @@
+//! Time-based one-time passwords (RFC 6238) as authenticator apps expect
+//! them: HMAC-SHA1, six digits, 30 second steps.
+
+use chrono::{DateTime, Utc};
+use rand::RngCore;
+use totp_rs::{Algorithm, Secret, TOTP};
+
+pub const DIGITS: usize = 6;
+pub const STEP_SECS: i64 = 30;
+const SECRET_BYTES: usize = 20;
+/// Codes of one step either side are accepted too, for phones whose clock
+/// is a little off.
+const SKEW_STEPS: i64 = 1;
+
+/// A new random secret, base32 encoded as authenticator apps take it.
+pub fn generate_secret() -> String {
+    let mut secret = [0u8; SECRET_BYTES];
+    rand::rng().fill_bytes(&mut secret);
+    base32_encode(&secret)
+}
+
+pub fn step_at(now: DateTime<Utc>) -> i64 {
+    now.timestamp().div_euclid(STEP_SECS)
+}
+
+/// The code of `step`.
+pub fn code(secret: &[u8], step: i64) -> String {
+    totp(secret.to_vec(), None, String::new()).generate(start_of(step))
+}
+
+/// The step `code` was generated for, if it is valid around `now` and later
+/// than `last_used_step`, so each code works only once.
+pub fn verify(
+    secret: &str,
+    code: &str,
+    now: DateTime<Utc>,
+    last_used_step: Option<i64>,
+) -> Option<i64> {
+    let totp = totp(base32_decode(secret)?, None, String::new());
+    let code = code.trim();
+    let current = step_at(now);
+    (current - SKEW_STEPS..=current + SKEW_STEPS)
+        .filter(|step| last_used_step.is_none_or(|last| *step > last))
+        .find(|step| totp.check(code, start_of(*step)))
+}
+
+/// The `otpauth://` URI authenticator apps read from a QR code.
+pub fn otpauth_uri(issuer: &str, account: &str, secret: &str) -> String {
+    let secret = base32_decode(secret).unwrap_or_default();
+    totp(secret, Some(issuer.to_string()), account.to_string()).get_url()
+}
+
+/// RFC 4648 base32 without padding.
+pub fn base32_encode(bytes: &[u8]) -> String {
+    Secret::Raw(bytes.to_vec()).to_encoded().to_string()
+}
+
+pub fn base32_decode(text: &str) -> Option<Vec<u8>> {
+    let text = text.trim_end_matches('=').to_ascii_uppercase();
+    Secret::Encoded(text).to_bytes().ok()
+}
+
+/// Unchecked, as the checked constructor refuses account names with a colon.
+fn totp(secret: Vec<u8>, issuer: Option<String>, account: String) -> TOTP {
+    TOTP::new_unchecked(
+        Algorithm::SHA1,
+        DIGITS,
+        0,
+        STEP_SECS as u64,
+        secret,
+        issuer,
+        account,
+    )
+}
+
+fn start_of(step: i64) -> u64 {
+    (step * STEP_SECS).max(0) as u64
+}
@@ -0,0 +1,218 @@
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use sea_orm::sea_query::{Expr, OnConflict};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DbErr, EntityTrait, PaginatorTrait,
+    QueryFilter, Set,
+};
+use uuid::Uuid;
+
+use crate::auth::password::{hash_password, verify_password};
+use crate::auth::totp;
+use crate::entities::{
+    RecoveryCode, RecoveryCodeActiveModel, RecoveryCodeColumn, UserTotp, UserTotpActiveModel,
+    UserTotpColumn, user_totp,
+};
+use crate::error::{AppError, ResultExt};
+
+pub const RECOVERY_CODE_COUNT: usize = 10;
+/// No 0/O or 1/I/L, so codes survive being written down.
+const RECOVERY_ALPHABET: &[u8] = b"abcdefghjkmnpqrstuvwxyz23456789";
+const RECOVERY_HALF_LEN: usize = 5;
+
+pub async fn find<C: ConnectionTrait>(
+    db: &C,
+    user_id: Uuid,
+) -> Result<Option<user_totp::Model>, DbErr> {
+    UserTotp::find_by_id(user_id).one(db).await
+}
+
+/// The confirmed TOTP setup of `user_id`, if two-factor login is on.
+pub async fn enabled<C: ConnectionTrait>(
+    db: &C,
+    user_id: Uuid,
+) -> Result<Option<user_totp::Model>, DbErr> {
+    Ok(find(db, user_id)
+        .await?
+        .filter(|totp| totp.confirmed_at.is_some()))
+}
+
+/// Stores a fresh secret for `user_id` and returns it. A pending enrollment
+/// is replaced; callers make sure two-factor login is not on yet.
+pub async fn start_enrollment<C: ConnectionTrait>(
+    db: &C,
+    user_id: Uuid,
+    now: DateTime<Utc>,
+) -> Result<String, DbErr> {
+    let secret = totp::generate_secret();
+    UserTotp::insert(UserTotpActiveModel {
+        user_id: Set(user_id),
+        secret: Set(secret.clone()),
+        confirmed_at: Set(None),
+        last_used_step: Set(None),
+        created_at: Set(now.into()),
+    })
+    .on_conflict(
+        OnConflict::column(UserTotpColumn::UserId)
+            .update_columns([
+                UserTotpColumn::Secret,
+                UserTotpColumn::ConfirmedAt,
+                UserTotpColumn::LastUsedStep,
+                UserTotpColumn::CreatedAt,
+            ])
+            .to_owned(),
+    )
+    .exec(db)
+    .await?;
+    Ok(secret)
+}
+
+/// Turns two-factor login on if `code` matches the pending secret and
+/// returns the first recovery codes, or `None` for a wrong code.
+pub async fn confirm<C: ConnectionTrait>(
+    db: &C,
+    pending: user_totp::Model,
+    code: &str,
+    now: DateTime<Utc>,
+) -> Result<Option<Vec<String>>, AppError> {
+    let Some(step) = totp::verify(&pending.secret, code, now, pending.last_used_step) else {
+        return Ok(None);
+    };
+    let user_id = pending.user_id;
+    let mut active: UserTotpActiveModel = pending.into();
+    active.confirmed_at = Set(Some(now.into()));
+    active.last_used_step = Set(Some(step));
+    active.update(db).await?;
+    replace_recovery_codes(db, user_id, now).await.map(Some)
+}
+
+/// Checks a TOTP code or, failing that, a recovery code of `totp`'s user,
+/// using either up. Of concurrent calls with one code at most one succeeds.
+pub async fn verify_code<C: ConnectionTrait>(
+    db: &C,
+    totp: &user_totp::Model,
+    code: &str,
+    now: DateTime<Utc>,
+) -> Result<bool, AppError> {
+    if let Some(step) = totp::verify(&totp.secret, code, now, totp.last_used_step) {
+        let result = UserTotp::update_many()
+            .col_expr(UserTotpColumn::LastUsedStep, Expr::value(step))
+            .filter(UserTotpColumn::UserId.eq(totp.user_id))
+            .filter(
+                UserTotpColumn::LastUsedStep
+                    .is_null()
+                    .or(UserTotpColumn::LastUsedStep.lt(step)),
+            )
+            .exec(db)
+            .await?;
+        return Ok(result.rows_affected == 1);
+    }
+    use_recovery_code(db, totp.user_id, code, now).await
+}
+
+/// Replaces the recovery codes of `user_id` with new ones and returns them.
+/// They are shown once; only their salted hashes are kept, made like
+/// password hashes.
+pub async fn replace_recovery_codes<C: ConnectionTrait>(
+    db: &C,
+    user_id: Uuid,
+    now: DateTime<Utc>,
+) -> Result<Vec<String>, AppError> {
+    RecoveryCode::delete_many()
+        .filter(RecoveryCodeColumn::UserId.eq(user_id))
+        .exec(db)
+        .await?;
+
+    let codes: Vec<String> = (0..RECOVERY_CODE_COUNT)
+        .map(|_| generate_recovery_code())
+        .collect();
+    let mut models = Vec::with_capacity(codes.len());
+    for code in &codes {
+        models.push(RecoveryCodeActiveModel {
+            id: Set(Uuid::new_v4()),
+            user_id: Set(user_id),
+            code_hash: Set(hash_password(code)
+                .map_err(|e| e.to_string())
+                .or_internal()?),
+            used_at: Set(None),
+            created_at: Set(now.into()),
+        });
+    }
+    RecoveryCode::insert_many(models).exec(db).await?;
+    Ok(codes)
+}
+
+pub async fn recovery_codes_left<C: ConnectionTrait>(db: &C, user_id: Uuid) -> Result<u64, DbErr> {
+    RecoveryCode::find()
+        .filter(RecoveryCodeColumn::UserId.eq(user_id))
+        .filter(RecoveryCodeColumn::UsedAt.is_null())
+        .count(db)
+        .await
+}
+
+/// Turns two-factor login off and forgets the secret and recovery codes.
+pub async fn disable<C: ConnectionTrait>(db: &C, user_id: Uuid) -> Result<(), DbErr> {
+    RecoveryCode::delete_many()
+        .filter(RecoveryCodeColumn::UserId.eq(user_id))
+        .exec(db)
+        .await?;
+    UserTotp::delete_by_id(user_id).exec(db).await?;
+    Ok(())
+}
+
+/// Salted hashes cannot be looked up, so the code is checked against each
+/// unused one of the user.
+async fn use_recovery_code<C: ConnectionTrait>(
+    db: &C,
+    user_id: Uuid,
+    code: &str,
+    now: DateTime<Utc>,
+) -> Result<bool, AppError> {
+    let Some(code) = normalize_recovery_code(code) else {
+        return Ok(false);
+    };
+    let unused = RecoveryCode::find()
+        .filter(RecoveryCodeColumn::UserId.eq(user_id))
+        .filter(RecoveryCodeColumn::UsedAt.is_null())
+        .all(db)
+        .await?;
+    for recovery_code in unused {
+        let matches = verify_password(&recovery_code.code_hash, &code)
+            .map_err(|e| e.to_string())
+            .or_internal()?;
+        if matches {
+            let result = RecoveryCode::update_many()
+                .col_expr(RecoveryCodeColumn::UsedAt, Expr::value(now))
+                .filter(RecoveryCodeColumn::Id.eq(recovery_code.id))
+                .filter(RecoveryCodeColumn::UsedAt.is_null())
+                .exec(db)
+                .await?;
+            return Ok(result.rows_affected == 1);
+        }
+    }
+    Ok(false)
+}
+
+fn generate_recovery_code() -> String {
+    let mut rng = rand::rng();
+    let mut half = || -> String {
+        (0..RECOVERY_HALF_LEN)
+            .map(|_| RECOVERY_ALPHABET[rng.random_range(0..RECOVERY_ALPHABET.len())] as char)
+            .collect()
+    };
+    format!("{}-{}", half(), half())
+}
+
+/// `xxxxx-xxxxx` from what the user typed, ignoring case, spaces and the dash.
+fn normalize_recovery_code(code: &str) -> Option<String> {
+    let chars: String = code
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '-')
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    if chars.len() != RECOVERY_HALF_LEN * 2 || !chars.is_ascii() {
+        return None;
+    }
+    let (first, second) = chars.split_at(RECOVERY_HALF_LEN);
+    Some(format!("{first}-{second}"))
+}
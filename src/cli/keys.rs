@@ -0,0 +1,45 @@
+use chrono::Utc;
+use clap::Subcommand;
+
+use crate::auth::keys::{self, KeyState};
+use crate::config::JwtConfig;
+use crate::error::BoxError;
+
+#[derive(Subcommand)]
+pub enum KeysCommand {
+    /// Add a new signing key to `jwt.key_dir` and delete keys no valid token
+    /// can be signed with any more. Servers publish the new key within a
+    /// minute and sign with it after `jwt.key_activation_secs`.
+    Rotate,
+    /// Show the keys in `jwt.key_dir` and which one signs.
+    List,
+}
+
+pub fn run(config: &JwtConfig, command: KeysCommand) -> Result<(), BoxError> {
+    let dir = config
+        .key_dir
+        .as_deref()
+        .ok_or("jwt.key_dir is not set (env JWT_KEY_DIR)")?;
+    let now = Utc::now();
+    match command {
+        KeysCommand::Rotate => {
+            let kid = keys::generate(dir, now)?;
+            println!("key {kid} created");
+            for kid in keys::prune(dir, config, now)? {
+                println!("key {kid} deleted");
+            }
+        }
+        KeysCommand::List => {
+            let keys = keys::read_dir(dir)?;
+            for (key, state) in keys.iter().zip(keys::states(&keys, config, now)) {
+                let state = match state {
+                    KeyState::Pending => "pending",
+                    KeyState::Signing => "signing",
+                    KeyState::Retired => "retired",
+                };
+                println!("{}  {}  {state}", key.kid, key.created_at.to_rfc3339());
+            }
+        }
+    }
+    Ok(())
+}
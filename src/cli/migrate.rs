@@ -0,0 +1,52 @@
+use clap::Subcommand;
+use sea_orm::DatabaseConnection;
+use sea_orm_migration::{MigrationStatus, MigratorTrait};
+
+use crate::error::BoxError;
+use crate::migration::Migrator;
+
+#[derive(Subcommand)]
+pub enum MigrateCommand {
+    /// Apply pending migrations.
+    Up {
+        /// Apply at most this many migrations.
+        #[arg(short = 'n', long)]
+        steps: Option<u32>,
+    },
+    /// Roll back applied migrations.
+    Down {
+        #[arg(short = 'n', long, default_value_t = 1)]
+        steps: u32,
+    },
+    /// List migrations and whether they are applied.
+    Status,
+    /// Drop every table and apply all migrations from scratch.
+    Fresh {
+        /// Confirm that all data may be deleted.
+        #[arg(long)]
+        yes: bool,
+    },
+}
+
+pub async fn run(db: &DatabaseConnection, command: MigrateCommand) -> Result<(), BoxError> {
+    match command {
+        MigrateCommand::Up { steps } => Migrator::up(db, steps).await?,
+        MigrateCommand::Down { steps } => Migrator::down(db, Some(steps)).await?,
+        MigrateCommand::Status => {
+            for migration in Migrator::get_migration_with_status(db).await? {
+                let status = match migration.status() {
+                    MigrationStatus::Applied => "applied",
+                    MigrationStatus::Pending => "pending",
+                };
+                println!("{status:<8} {}", migration.name());
+            }
+        }
+        MigrateCommand::Fresh { yes } => {
+            if !yes {
+                return Err("`migrate fresh` deletes all data, pass --yes to confirm".into());
+            }
+            Migrator::fresh(db).await?;
+        }
+    }
+    Ok(())
+}
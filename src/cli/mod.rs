@@ -0,0 +1,79 @@
+pub mod keys;
+pub mod migrate;
+pub mod seed;
+pub mod token;
+pub mod user;
+
+use std::io::{self, BufRead, Write};
+use std::sync::Arc;
+
+use clap::{Parser, Subcommand};
+
+use crate::config::Config;
+use crate::error::BoxError;
+use crate::{db, server};
+
+#[derive(Parser)]
+#[command(about = "Friends API server and admin tools")]
+pub struct Cli {
+    /// Defaults to `serve`.
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Run the HTTP server. Does not apply migrations.
+    Serve,
+    /// Apply, roll back or inspect database migrations.
+    #[command(subcommand)]
+    Migrate(migrate::MigrateCommand),
+    /// Fill the database with demo data for local development.
+    Seed(seed::SeedArgs),
+    /// Manage user accounts.
+    #[command(subcommand)]
+    User(user::UserCommand),
+    /// Manage refresh tokens.
+    #[command(subcommand)]
+    Token(token::TokenCommand),
+    /// Manage the keys that sign tokens.
+    #[command(subcommand)]
+    Keys(keys::KeysCommand),
+}
+
+pub async fn run(cli: Cli, config: Arc<Config>) -> Result<(), BoxError> {
+    let command = cli.command.unwrap_or(Command::Serve);
+    let command = match command {
+        Command::Serve => return server::serve(config).await,
+        Command::Keys(command) => return keys::run(&config.jwt, command),
+        command => command,
+    };
+
+    let db = db::init_db(&config.database).await?;
+    let result = match command {
+        Command::Serve | Command::Keys(_) => unreachable!("handled above"),
+        Command::Migrate(command) => migrate::run(&db, command).await,
+        Command::Seed(args) => seed::run(&db, args).await,
+        Command::User(command) => user::run(&db, command).await,
+        Command::Token(command) => token::run(&db, command).await,
+    };
+    db.close().await?;
+    result
+}
+
+/// Uses `value` if given, otherwise reads one line from stdin so the password
+/// does not end up in shell history.
+fn password_or_stdin(value: Option<String>) -> Result<String, BoxError> {
+    if let Some(value) = value {
+        return Ok(value);
+    }
+    eprint!("Password: ");
+    io::stderr().flush()?;
+    let mut line = String::new();
+    io::stdin().lock().read_line(&mut line)?;
+    let password = line.trim_end_matches(['\r', '\n']).to_string();
+    if password.is_empty() {
+        return Err("password must not be empty".into());
+    }
+    Ok(password)
+}
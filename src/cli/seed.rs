@@ -0,0 +1,70 @@
+use chrono::{NaiveDate, Utc};
+use clap::Args;
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, TransactionTrait};
+
+use crate::auth::password::hash_password;
+use crate::entities::{User, UserColumn};
+use crate::error::BoxError;
+use crate::seed::{self, SeedOptions};
+
+#[derive(Args)]
+pub struct SeedArgs {
+    /// Same seed and anchor date give the same data, e.g. for sharing a bug repro.
+    #[arg(long, default_value_t = 42)]
+    pub seed: u64,
+    #[arg(long, default_value_t = 10)]
+    pub users: usize,
+    #[arg(long, default_value_t = 12)]
+    pub events: usize,
+    /// Usernames are `<prefix>01`, `<prefix>02`, ...
+    #[arg(long, default_value = "demo")]
+    pub prefix: String,
+    /// Password of every generated user.
+    #[arg(long, default_value = "password")]
+    pub password: String,
+    /// Day that past and future events are placed around, YYYY-MM-DD. Defaults to today.
+    #[arg(long)]
+    pub anchor_date: Option<NaiveDate>,
+}
+
+pub async fn run(db: &DatabaseConnection, args: SeedArgs) -> Result<(), BoxError> {
+    let taken = User::find()
+        .filter(UserColumn::Username.starts_with(&args.prefix))
+        .one(db)
+        .await?;
+    if let Some(user) = taken {
+        return Err(format!(
+            "user {} already exists, use another --prefix or `migrate fresh --yes`",
+            user.username
+        )
+        .into());
+    }
+
+    let options = SeedOptions {
+        seed: args.seed,
+        users: args.users,
+        events: args.events,
+        prefix: args.prefix,
+        password_hash: hash_password(&args.password).map_err(|e| e.to_string())?,
+        anchor_date: args.anchor_date.unwrap_or_else(|| Utc::now().date_naive()),
+    };
+
+    let tx = db.begin().await?;
+    let summary = seed::run(&tx, &options).await?;
+    tx.commit().await?;
+
+    let [pending, confirmed, completed, canceled] = summary.events;
+    println!(
+        "seed {} around {}: {} users, {} accepted and {} pending friendships, {} wish places, \
+         {} busy days, events: {pending} pending, {confirmed} confirmed, {completed} completed, \
+         {canceled} canceled",
+        options.seed,
+        options.anchor_date,
+        summary.users,
+        summary.friendships_accepted,
+        summary.friendships_pending,
+        summary.wish_places,
+        summary.busy_days,
+    );
+    Ok(())
+}
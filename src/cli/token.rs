@@ -0,0 +1,39 @@
+use chrono::Utc;
+use clap::Subcommand;
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter};
+
+use crate::auth::sessions;
+use crate::entities::{User, UserColumn};
+use crate::error::BoxError;
+
+#[derive(Subcommand)]
+pub enum TokenCommand {
+    /// End sessions so users must log in again. Running servers reject their
+    /// access tokens within `jwt.session_check_secs`.
+    RevokeAll {
+        /// Only revoke this user's tokens.
+        #[arg(long)]
+        username: Option<String>,
+    },
+}
+
+pub async fn run(db: &DatabaseConnection, command: TokenCommand) -> Result<(), BoxError> {
+    match command {
+        TokenCommand::RevokeAll { username } => {
+            let user_id = match &username {
+                Some(username) => Some(
+                    User::find()
+                        .filter(UserColumn::Username.eq(username))
+                        .one(db)
+                        .await?
+                        .ok_or_else(|| format!("user {username} not found"))?
+                        .id,
+                ),
+                None => None,
+            };
+            let revoked = sessions::revoke_all(db, user_id, None, Utc::now()).await?;
+            println!("{revoked} session(s) revoked");
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,111 @@
+use chrono::Utc;
+use clap::Subcommand;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, Set,
+    TransactionTrait,
+};
+
+use crate::auth::password::hash_password;
+use crate::auth::sessions;
+use crate::cli::password_or_stdin;
+use crate::entities::{User, UserActiveModel, UserColumn, user};
+use crate::error::BoxError;
+
+#[derive(Subcommand)]
+pub enum UserCommand {
+    /// Create an account.
+    Create {
+        #[arg(long)]
+        username: String,
+        /// Read from stdin when omitted.
+        #[arg(long)]
+        password: Option<String>,
+        #[arg(long)]
+        bio: Option<String>,
+    },
+    /// Set a new password and sign the user out everywhere.
+    ResetPassword {
+        #[arg(long)]
+        username: String,
+        /// Read from stdin when omitted.
+        #[arg(long)]
+        password: Option<String>,
+    },
+    /// Block logins and sign the user out everywhere.
+    Disable {
+        #[arg(long)]
+        username: String,
+    },
+    /// Allow a disabled user to log in again.
+    Enable {
+        #[arg(long)]
+        username: String,
+    },
+}
+
+pub async fn run(db: &DatabaseConnection, command: UserCommand) -> Result<(), BoxError> {
+    match command {
+        UserCommand::Create {
+            username,
+            password,
+            bio,
+        } => {
+            if username.trim().is_empty() {
+                return Err("username must not be empty".into());
+            }
+            let password = password_or_stdin(password)?;
+            let password_hash = hash_password(&password).map_err(|e| e.to_string())?;
+            let model = UserActiveModel {
+                username: Set(username),
+                password_hash: Set(password_hash),
+                bio: Set(bio),
+                ..Default::default()
+            }
+            .insert(db)
+            .await?;
+            println!("created user {} ({})", model.username, model.id);
+        }
+        UserCommand::ResetPassword { username, password } => {
+            let model = find_user(db, &username).await?;
+            let password = password_or_stdin(password)?;
+            let password_hash = hash_password(&password).map_err(|e| e.to_string())?;
+
+            let tx = db.begin().await?;
+            let user_id = model.id;
+            let mut active: UserActiveModel = model.into();
+            active.password_hash = Set(password_hash);
+            active.update(&tx).await?;
+            let revoked = sessions::revoke_all(&tx, Some(user_id), None, Utc::now()).await?;
+            tx.commit().await?;
+            println!("password of {username} reset, {revoked} session(s) revoked");
+        }
+        UserCommand::Disable { username } => {
+            let model = find_user(db, &username).await?;
+
+            let tx = db.begin().await?;
+            let user_id = model.id;
+            let mut active: UserActiveModel = model.into();
+            active.disabled_at = Set(Some(Utc::now().into()));
+            active.update(&tx).await?;
+            let revoked = sessions::revoke_all(&tx, Some(user_id), None, Utc::now()).await?;
+            tx.commit().await?;
+            println!("user {username} disabled, {revoked} session(s) revoked");
+        }
+        UserCommand::Enable { username } => {
+            let model = find_user(db, &username).await?;
+            let mut active: UserActiveModel = model.into();
+            active.disabled_at = Set(None);
+            active.update(db).await?;
+            println!("user {username} enabled");
+        }
+    }
+    Ok(())
+}
+
+async fn find_user(db: &DatabaseConnection, username: &str) -> Result<user::Model, BoxError> {
+    User::find()
+        .filter(UserColumn::Username.eq(username))
+        .one(db)
+        .await?
+        .ok_or_else(|| format!("user {username} not found").into())
+}
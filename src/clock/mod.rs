@@ -0,0 +1,84 @@
+//! Time as the application sees it. Handlers take "now" from the [`Clock`] in
+//! the app state instead of the system clock, so tests can pin it.
+
+use std::sync::Mutex;
+
+use chrono::{DateTime, NaiveDate, TimeDelta, Utc};
+use chrono_tz::Tz;
+use sea_orm::{ConnectionTrait, EntityTrait, QuerySelect};
+use uuid::Uuid;
+
+use crate::entities::{User, UserColumn};
+use crate::error::AppError;
+
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+
+    /// The calendar date in `tz` right now.
+    fn today(&self, tz: Tz) -> NaiveDate {
+        self.now().with_timezone(&tz).date_naive()
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// A clock that only moves when told to.
+#[derive(Debug)]
+pub struct ManualClock {
+    now: Mutex<DateTime<Utc>>,
+}
+
+impl ManualClock {
+    pub fn new(now: DateTime<Utc>) -> Self {
+        ManualClock {
+            now: Mutex::new(now),
+        }
+    }
+
+    pub fn set(&self, now: DateTime<Utc>) {
+        *self.now.lock().unwrap() = now;
+    }
+
+    pub fn advance(&self, by: TimeDelta) {
+        *self.now.lock().unwrap() += by;
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.now.lock().unwrap()
+    }
+}
+
+/// Parses an IANA zone name such as `Europe/Moscow`.
+pub fn parse_timezone(name: &str) -> Option<Tz> {
+    name.parse().ok()
+}
+
+/// Today in the timezone stored on the user's profile, or in UTC when none is
+/// set.
+pub async fn user_today<C: ConnectionTrait>(
+    db: &C,
+    clock: &dyn Clock,
+    user_id: Uuid,
+) -> Result<NaiveDate, AppError> {
+    let timezone: Option<Option<String>> = User::find_by_id(user_id)
+        .select_only()
+        .column(UserColumn::Timezone)
+        .into_tuple()
+        .one(db)
+        .await?;
+    let tz = timezone
+        .flatten()
+        .as_deref()
+        .and_then(parse_timezone)
+        .unwrap_or(Tz::UTC);
+    Ok(clock.today(tz))
+}
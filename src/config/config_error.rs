@@ -0,0 +1,38 @@
+use std::fmt;
+use std::path::PathBuf;
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Read {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    Parse {
+        path: PathBuf,
+        source: toml::de::Error,
+    },
+    /// Every setting that is missing or out of range, as `(key, problem)`.
+    Invalid(Vec<(&'static str, String)>),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Read { path, source } => {
+                write!(f, "cannot read config file {}: {source}", path.display())
+            }
+            ConfigError::Parse { path, source } => {
+                write!(f, "invalid config file {}: {source}", path.display())
+            }
+            ConfigError::Invalid(problems) => {
+                writeln!(f, "invalid configuration:")?;
+                for (key, problem) in problems {
+                    writeln!(f, "  {key}: {problem}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
@@ -0,0 +1,5 @@
+pub mod config_error;
+pub mod settings;
+
+pub use config_error::ConfigError;
+pub use settings::*;
@@ -0,0 +1,908 @@
+use std::env;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use axum::http::HeaderValue;
+use chrono::Duration;
+use serde::Deserialize;
+
+use crate::config::ConfigError;
+
+/// File read when `CONFIG_FILE` is not set. It is optional.
+const DEFAULT_CONFIG_FILE: &str = "config.toml";
+const MIN_SECRET_LEN: usize = 32;
+
+/// Application settings, loaded once at startup from an optional TOML file
+/// and then overridden by environment variables.
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub server: ServerConfig,
+    pub database: DatabaseConfig,
+    pub jwt: JwtConfig,
+    pub cors: CorsConfig,
+    pub storage: StorageConfig,
+    pub logging: LoggingConfig,
+    pub rate_limit: RateLimitConfig,
+    pub mail: MailConfig,
+    pub two_factor: TwoFactorConfig,
+    pub oidc: OidcConfig,
+    pub webauthn: WebauthnConfig,
+    pub account: AccountConfig,
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    pub listen_addr: SocketAddr,
+    /// How long in-flight requests may take to finish after SIGTERM.
+    pub shutdown_timeout_secs: u64,
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DatabaseConfig {
+    pub url: String,
+    pub max_connections: u32,
+    pub min_connections: u32,
+    pub acquire_timeout_secs: u64,
+    /// Statements slower than this are logged as warnings.
+    pub slow_query_ms: u64,
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct JwtConfig {
+    /// Directory of Ed25519 signing keys, managed with `keys rotate`. When
+    /// set, tokens are signed with EdDSA and published at
+    /// `/.well-known/jwks.json`; otherwise with HS256 and the secrets below.
+    pub key_dir: Option<PathBuf>,
+    /// How long a new key is only published before it starts signing, so
+    /// every server and verifier has picked it up by then.
+    pub key_activation_secs: u64,
+    /// Required without `key_dir`. With it, tokens signed with the secrets
+    /// are still accepted while they are set, so switching logs nobody out.
+    pub access_secret: String,
+    /// Falls back to `access_secret` when empty.
+    pub refresh_secret: String,
+    pub access_ttl_secs: i64,
+    pub refresh_ttl_secs: i64,
+    /// How long a server trusts that the session of an access token is still
+    /// active before checking the database again. Revoking a session on the
+    /// same server takes effect at once.
+    pub session_check_secs: u64,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CorsConfig {
+    /// Origins allowed to call the API from a browser. Empty disables CORS,
+    /// `*` allows any origin.
+    pub allowed_origins: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StorageConfig {
+    /// Directory for files the server writes, such as exports.
+    pub data_dir: PathBuf,
+}
+
+#[derive(Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LogFormat {
+    #[default]
+    Json,
+    Text,
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "json" => Ok(LogFormat::Json),
+            "text" => Ok(LogFormat::Text),
+            _ => Err("expected `json` or `text`".to_string()),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RateLimitBackend {
+    /// Counters live in the process, each instance limits on its own.
+    #[default]
+    Memory,
+    /// Counters live in the `rate_limits` table, shared by every instance.
+    Postgres,
+}
+
+impl FromStr for RateLimitBackend {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "memory" => Ok(RateLimitBackend::Memory),
+            "postgres" => Ok(RateLimitBackend::Postgres),
+            _ => Err("expected `memory` or `postgres`".to_string()),
+        }
+    }
+}
+
+/// At most `requests` within each window of `window_secs`.
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Limit {
+    pub requests: u32,
+    pub window_secs: u64,
+}
+
+/// Limits of one auth route. A missing limit is not enforced.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RouteLimits {
+    pub per_ip: Option<Limit>,
+    pub per_username: Option<Limit>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RateLimitConfig {
+    pub enabled: bool,
+    pub backend: RateLimitBackend,
+    /// Take the client address from the first `X-Forwarded-For` entry. Only
+    /// enable behind a proxy that overwrites the header.
+    pub trust_forwarded_for: bool,
+    /// Failed logins that lock a username, 0 disables the lockout.
+    pub lockout_failures: u32,
+    /// How long failures are remembered, and so how long a lockout lasts.
+    pub lockout_secs: u64,
+    pub login: RouteLimits,
+    pub register: RouteLimits,
+    pub refresh: RouteLimits,
+    pub password_reset: RouteLimits,
+    /// Verification mail, counted per account.
+    pub email_verification: RouteLimits,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MailTransport {
+    /// Mail is only written to the log, for local development.
+    #[default]
+    Log,
+    /// Each mail is written as an `.eml` file to `mail.dir`.
+    File,
+    Smtp,
+}
+
+impl FromStr for MailTransport {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "log" => Ok(MailTransport::Log),
+            "file" => Ok(MailTransport::File),
+            "smtp" => Ok(MailTransport::Smtp),
+            _ => Err("expected `log`, `file` or `smtp`".to_string()),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SmtpTls {
+    /// Plain connection upgraded with `STARTTLS`, usually on port 587.
+    #[default]
+    StartTls,
+    /// TLS from the first byte, usually on port 465.
+    Tls,
+    /// No encryption, only for relays on a trusted network.
+    None,
+}
+
+impl FromStr for SmtpTls {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "start_tls" => Ok(SmtpTls::StartTls),
+            "tls" => Ok(SmtpTls::Tls),
+            "none" => Ok(SmtpTls::None),
+            _ => Err("expected `start_tls`, `tls` or `none`".to_string()),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SmtpConfig {
+    pub host: String,
+    pub port: u16,
+    pub tls: SmtpTls,
+    /// Empty skips authentication.
+    pub username: String,
+    pub password: String,
+    pub timeout_secs: u64,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MailConfig {
+    pub transport: MailTransport,
+    /// `From` header of every mail, e.g. `Friends <no-reply@example.com>`.
+    pub from: String,
+    /// Where the `file` transport writes mail.
+    pub dir: PathBuf,
+    /// Link sent in password reset mail, `{token}` is replaced with the token.
+    pub password_reset_url: String,
+    pub password_reset_ttl_secs: i64,
+    /// Link sent to verify an address, `{token}` is replaced with the token.
+    pub email_verification_url: String,
+    pub email_verification_ttl_secs: i64,
+    pub smtp: SmtpConfig,
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TwoFactorConfig {
+    /// Name authenticator apps show next to the account.
+    pub issuer: String,
+    /// How long the password step of a two-factor login stays valid.
+    pub challenge_ttl_secs: i64,
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OidcConfig {
+    /// Sign-in providers such as Apple or Google. Only set in the file.
+    pub providers: Vec<OidcProviderConfig>,
+    /// How long a fetched key set is used before it is fetched again.
+    pub jwks_cache_secs: u64,
+    pub timeout_secs: u64,
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct OidcProviderConfig {
+    /// Used in URLs, e.g. `/auth/oidc/google`.
+    pub name: String,
+    /// Expected `iss` of ID tokens.
+    pub issuer: String,
+    pub jwks_url: String,
+    /// Accepted `aud` values: the client ids of the apps.
+    pub client_ids: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WebauthnConfig {
+    /// Domain passkeys are bound to; changing it orphans every passkey.
+    pub rp_id: String,
+    /// Name authenticators show when a passkey is created.
+    pub rp_name: String,
+    /// Origins the ceremonies may run on, e.g. `https://friends.example` or
+    /// `android:apk-key-hash:...` for the Android app.
+    pub origins: Vec<String>,
+    /// How long a user has to answer the authenticator prompt.
+    pub challenge_ttl_secs: i64,
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AccountConfig {
+    /// How long a deleted account can still be restored before it is purged.
+    pub deletion_grace_secs: i64,
+    /// Download link of a data export, `{token}` is replaced by its token.
+    pub export_download_url: String,
+    /// How long a data export can be downloaded before it is deleted.
+    pub export_ttl_secs: i64,
+}
+
+/// Log verbosity comes from `RUST_LOG`, only the output format is configured here.
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LoggingConfig {
+    pub format: LogFormat,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            listen_addr: SocketAddr::from(([0, 0, 0, 0], 3000)),
+            shutdown_timeout_secs: 30,
+        }
+    }
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        Self {
+            url: String::new(),
+            max_connections: 10,
+            min_connections: 1,
+            acquire_timeout_secs: 10,
+            slow_query_ms: 500,
+        }
+    }
+}
+
+impl Default for JwtConfig {
+    fn default() -> Self {
+        Self {
+            key_dir: None,
+            key_activation_secs: 5 * 60,
+            access_secret: String::new(),
+            refresh_secret: String::new(),
+            access_ttl_secs: 15 * 60,
+            refresh_ttl_secs: 30 * 24 * 60 * 60,
+            session_check_secs: 15,
+        }
+    }
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        Self {
+            data_dir: PathBuf::from("data"),
+        }
+    }
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            backend: RateLimitBackend::Memory,
+            trust_forwarded_for: false,
+            lockout_failures: 5,
+            lockout_secs: 15 * 60,
+            login: RouteLimits {
+                per_ip: Some(Limit {
+                    requests: 20,
+                    window_secs: 60,
+                }),
+                per_username: Some(Limit {
+                    requests: 10,
+                    window_secs: 60,
+                }),
+            },
+            register: RouteLimits {
+                per_ip: Some(Limit {
+                    requests: 10,
+                    window_secs: 60 * 60,
+                }),
+                per_username: None,
+            },
+            refresh: RouteLimits {
+                per_ip: Some(Limit {
+                    requests: 60,
+                    window_secs: 60,
+                }),
+                per_username: None,
+            },
+            password_reset: RouteLimits {
+                per_ip: Some(Limit {
+                    requests: 10,
+                    window_secs: 60 * 60,
+                }),
+                per_username: Some(Limit {
+                    requests: 3,
+                    window_secs: 60 * 60,
+                }),
+            },
+            email_verification: RouteLimits {
+                per_ip: None,
+                per_username: Some(Limit {
+                    requests: 3,
+                    window_secs: 60 * 60,
+                }),
+            },
+        }
+    }
+}
+
+impl Default for SmtpConfig {
+    fn default() -> Self {
+        Self {
+            host: String::new(),
+            port: 587,
+            tls: SmtpTls::StartTls,
+            username: String::new(),
+            password: String::new(),
+            timeout_secs: 10,
+        }
+    }
+}
+
+impl Default for MailConfig {
+    fn default() -> Self {
+        Self {
+            transport: MailTransport::Log,
+            from: "Friends <no-reply@localhost>".to_string(),
+            dir: PathBuf::from("data/mail"),
+            password_reset_url: "friends://reset-password?token={token}".to_string(),
+            password_reset_ttl_secs: 60 * 60,
+            email_verification_url: "friends://verify-email?token={token}".to_string(),
+            email_verification_ttl_secs: 24 * 60 * 60,
+            smtp: SmtpConfig::default(),
+        }
+    }
+}
+
+impl Default for TwoFactorConfig {
+    fn default() -> Self {
+        Self {
+            issuer: "Friends".to_string(),
+            challenge_ttl_secs: 5 * 60,
+        }
+    }
+}
+
+impl Default for OidcConfig {
+    fn default() -> Self {
+        Self {
+            providers: Vec::new(),
+            jwks_cache_secs: 60 * 60,
+            timeout_secs: 10,
+        }
+    }
+}
+
+impl Default for WebauthnConfig {
+    fn default() -> Self {
+        Self {
+            rp_id: "localhost".to_string(),
+            rp_name: "Friends".to_string(),
+            origins: vec!["http://localhost:3000".to_string()],
+            challenge_ttl_secs: 5 * 60,
+        }
+    }
+}
+
+impl Default for AccountConfig {
+    fn default() -> Self {
+        Self {
+            deletion_grace_secs: 14 * 24 * 60 * 60,
+            export_download_url: "http://localhost:3000/exports/{token}".to_string(),
+            export_ttl_secs: 7 * 24 * 60 * 60,
+        }
+    }
+}
+
+impl TwoFactorConfig {
+    pub fn challenge_ttl(&self) -> Duration {
+        Duration::seconds(self.challenge_ttl_secs)
+    }
+}
+
+impl WebauthnConfig {
+    pub fn challenge_ttl(&self) -> Duration {
+        Duration::seconds(self.challenge_ttl_secs)
+    }
+}
+
+impl AccountConfig {
+    pub fn deletion_grace(&self) -> Duration {
+        Duration::seconds(self.deletion_grace_secs)
+    }
+
+    pub fn export_ttl(&self) -> Duration {
+        Duration::seconds(self.export_ttl_secs)
+    }
+}
+
+impl MailConfig {
+    pub fn password_reset_ttl(&self) -> Duration {
+        Duration::seconds(self.password_reset_ttl_secs)
+    }
+
+    pub fn email_verification_ttl(&self) -> Duration {
+        Duration::seconds(self.email_verification_ttl_secs)
+    }
+}
+
+impl JwtConfig {
+    pub fn access_ttl(&self) -> Duration {
+        Duration::seconds(self.access_ttl_secs)
+    }
+
+    pub fn refresh_ttl(&self) -> Duration {
+        Duration::seconds(self.refresh_ttl_secs)
+    }
+}
+
+impl Config {
+    /// Reads `CONFIG_FILE` (or `config.toml` if present), applies environment
+    /// overrides and validates the result.
+    pub fn load() -> Result<Self, ConfigError> {
+        let mut config = match env::var("CONFIG_FILE") {
+            Ok(path) => Self::from_file(Path::new(&path))?,
+            Err(_) if Path::new(DEFAULT_CONFIG_FILE).exists() => {
+                Self::from_file(Path::new(DEFAULT_CONFIG_FILE))?
+            }
+            Err(_) => Config::default(),
+        };
+
+        let mut problems = Vec::new();
+        config.apply_env(&mut problems);
+        config.validate(&mut problems);
+
+        if problems.is_empty() {
+            Ok(config)
+        } else {
+            Err(ConfigError::Invalid(problems))
+        }
+    }
+
+    fn from_file(path: &Path) -> Result<Self, ConfigError> {
+        let text = std::fs::read_to_string(path).map_err(|source| ConfigError::Read {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        toml::from_str(&text).map_err(|source| ConfigError::Parse {
+            path: path.to_path_buf(),
+            source,
+        })
+    }
+
+    fn apply_env(&mut self, problems: &mut Vec<(&'static str, String)>) {
+        env_override("LISTEN_ADDR", &mut self.server.listen_addr, problems);
+        env_override(
+            "SHUTDOWN_TIMEOUT_SECS",
+            &mut self.server.shutdown_timeout_secs,
+            problems,
+        );
+        env_override("DATABASE_URL", &mut self.database.url, problems);
+        env_override(
+            "DB_MAX_CONNECTIONS",
+            &mut self.database.max_connections,
+            problems,
+        );
+        env_override(
+            "DB_MIN_CONNECTIONS",
+            &mut self.database.min_connections,
+            problems,
+        );
+        env_override(
+            "DB_ACQUIRE_TIMEOUT_SECS",
+            &mut self.database.acquire_timeout_secs,
+            problems,
+        );
+        env_override(
+            "DB_SLOW_QUERY_MS",
+            &mut self.database.slow_query_ms,
+            problems,
+        );
+        if let Ok(dir) = env::var("JWT_KEY_DIR") {
+            self.jwt.key_dir = (!dir.is_empty()).then(|| PathBuf::from(dir));
+        }
+        env_override(
+            "JWT_KEY_ACTIVATION_SECS",
+            &mut self.jwt.key_activation_secs,
+            problems,
+        );
+        env_override("JWT_SECRET", &mut self.jwt.access_secret, problems);
+        env_override("JWT_REFRESH_SECRET", &mut self.jwt.refresh_secret, problems);
+        env_override(
+            "JWT_ACCESS_TTL_SECS",
+            &mut self.jwt.access_ttl_secs,
+            problems,
+        );
+        env_override(
+            "JWT_REFRESH_TTL_SECS",
+            &mut self.jwt.refresh_ttl_secs,
+            problems,
+        );
+        env_override(
+            "JWT_SESSION_CHECK_SECS",
+            &mut self.jwt.session_check_secs,
+            problems,
+        );
+        env_override("DATA_DIR", &mut self.storage.data_dir, problems);
+        env_override("LOG_FORMAT", &mut self.logging.format, problems);
+        env_override("RATE_LIMIT_ENABLED", &mut self.rate_limit.enabled, problems);
+        env_override("RATE_LIMIT_BACKEND", &mut self.rate_limit.backend, problems);
+        env_override(
+            "RATE_LIMIT_TRUST_FORWARDED_FOR",
+            &mut self.rate_limit.trust_forwarded_for,
+            problems,
+        );
+        env_override(
+            "LOGIN_LOCKOUT_FAILURES",
+            &mut self.rate_limit.lockout_failures,
+            problems,
+        );
+        env_override(
+            "LOGIN_LOCKOUT_SECS",
+            &mut self.rate_limit.lockout_secs,
+            problems,
+        );
+        env_override("MAIL_TRANSPORT", &mut self.mail.transport, problems);
+        env_override("MAIL_FROM", &mut self.mail.from, problems);
+        env_override("MAIL_DIR", &mut self.mail.dir, problems);
+        env_override(
+            "PASSWORD_RESET_URL",
+            &mut self.mail.password_reset_url,
+            problems,
+        );
+        env_override(
+            "PASSWORD_RESET_TTL_SECS",
+            &mut self.mail.password_reset_ttl_secs,
+            problems,
+        );
+        env_override(
+            "EMAIL_VERIFICATION_URL",
+            &mut self.mail.email_verification_url,
+            problems,
+        );
+        env_override(
+            "EMAIL_VERIFICATION_TTL_SECS",
+            &mut self.mail.email_verification_ttl_secs,
+            problems,
+        );
+        env_override("SMTP_HOST", &mut self.mail.smtp.host, problems);
+        env_override("SMTP_PORT", &mut self.mail.smtp.port, problems);
+        env_override("SMTP_TLS", &mut self.mail.smtp.tls, problems);
+        env_override("SMTP_USERNAME", &mut self.mail.smtp.username, problems);
+        env_override("SMTP_PASSWORD", &mut self.mail.smtp.password, problems);
+        env_override("TOTP_ISSUER", &mut self.two_factor.issuer, problems);
+        env_override(
+            "TOTP_CHALLENGE_TTL_SECS",
+            &mut self.two_factor.challenge_ttl_secs,
+            problems,
+        );
+        env_override("OIDC_JWKS_CACHE_SECS", &mut self.oidc.jwks_cache_secs, problems);
+        env_override("WEBAUTHN_RP_ID", &mut self.webauthn.rp_id, problems);
+        env_override("WEBAUTHN_RP_NAME", &mut self.webauthn.rp_name, problems);
+        env_override(
+            "ACCOUNT_DELETION_GRACE_SECS",
+            &mut self.account.deletion_grace_secs,
+            problems,
+        );
+        env_override(
+            "EXPORT_DOWNLOAD_URL",
+            &mut self.account.export_download_url,
+            problems,
+        );
+        env_override("EXPORT_TTL_SECS", &mut self.account.export_ttl_secs, problems);
+
+        if let Ok(origins) = env::var("CORS_ALLOWED_ORIGINS") {
+            self.cors.allowed_origins = origins
+                .split(',')
+                .map(str::trim)
+                .filter(|origin| !origin.is_empty())
+                .map(String::from)
+                .collect();
+        }
+        if let Ok(origins) = env::var("WEBAUTHN_ORIGINS") {
+            self.webauthn.origins = origins
+                .split(',')
+                .map(str::trim)
+                .filter(|origin| !origin.is_empty())
+                .map(String::from)
+                .collect();
+        }
+
+        if self.jwt.refresh_secret.is_empty() {
+            self.jwt.refresh_secret = self.jwt.access_secret.clone();
+        }
+    }
+
+    fn validate(&self, problems: &mut Vec<(&'static str, String)>) {
+        let database = &self.database;
+        if !(database.url.starts_with("postgres://") || database.url.starts_with("postgresql://")) {
+            problems.push((
+                "database.url",
+                "must be set to a postgres:// connection string (env DATABASE_URL)".into(),
+            ));
+        }
+        if database.max_connections == 0 {
+            problems.push(("database.max_connections", "must be at least 1".into()));
+        }
+        if database.min_connections > database.max_connections {
+            problems.push((
+                "database.min_connections",
+                "must not exceed database.max_connections".into(),
+            ));
+        }
+        if database.acquire_timeout_secs == 0 {
+            problems.push(("database.acquire_timeout_secs", "must be at least 1".into()));
+        }
+
+        let jwt = &self.jwt;
+        // With a key directory the secrets only verify older tokens and may be empty.
+        let secrets_required = jwt.key_dir.is_none() || !jwt.access_secret.is_empty();
+        if secrets_required && jwt.access_secret.len() < MIN_SECRET_LEN {
+            problems.push((
+                "jwt.access_secret",
+                format!(
+                    "must be at least {MIN_SECRET_LEN} bytes long (env JWT_SECRET), or set jwt.key_dir"
+                ),
+            ));
+        }
+        if secrets_required && jwt.refresh_secret.len() < MIN_SECRET_LEN {
+            problems.push((
+                "jwt.refresh_secret",
+                format!("must be at least {MIN_SECRET_LEN} bytes long (env JWT_REFRESH_SECRET)"),
+            ));
+        }
+        if jwt.access_ttl_secs <= 0 {
+            problems.push(("jwt.access_ttl_secs", "must be positive".into()));
+        }
+        if jwt.refresh_ttl_secs <= jwt.access_ttl_secs {
+            problems.push((
+                "jwt.refresh_ttl_secs",
+                "must be longer than jwt.access_ttl_secs".into(),
+            ));
+        }
+
+        for origin in &self.cors.allowed_origins {
+            let valid = origin == "*"
+                || ((origin.starts_with("http://") || origin.starts_with("https://"))
+                    && HeaderValue::from_str(origin).is_ok());
+            if !valid {
+                problems.push((
+                    "cors.allowed_origins",
+                    format!("`{origin}` is not `*` or an http(s) origin"),
+                ));
+            }
+        }
+
+        if self.storage.data_dir.as_os_str().is_empty() {
+            problems.push(("storage.data_dir", "must not be empty".into()));
+        }
+
+        let rate_limit = &self.rate_limit;
+        for (route, limits) in [
+            ("rate_limit.login", &rate_limit.login),
+            ("rate_limit.register", &rate_limit.register),
+            ("rate_limit.refresh", &rate_limit.refresh),
+            ("rate_limit.password_reset", &rate_limit.password_reset),
+            ("rate_limit.email_verification", &rate_limit.email_verification),
+        ] {
+            for limit in [limits.per_ip, limits.per_username].into_iter().flatten() {
+                if limit.requests == 0 || limit.window_secs == 0 {
+                    problems.push((route, "requests and window_secs must be at least 1".into()));
+                }
+            }
+        }
+        if rate_limit.lockout_failures > 0 && rate_limit.lockout_secs == 0 {
+            problems.push(("rate_limit.lockout_secs", "must be at least 1".into()));
+        }
+
+        let mail = &self.mail;
+        if !mail.from.contains('@') {
+            problems.push(("mail.from", "must contain an address (env MAIL_FROM)".into()));
+        }
+        if !mail.password_reset_url.contains("{token}") {
+            problems.push((
+                "mail.password_reset_url",
+                "must contain `{token}` (env PASSWORD_RESET_URL)".into(),
+            ));
+        }
+        if mail.password_reset_ttl_secs <= 0 {
+            problems.push(("mail.password_reset_ttl_secs", "must be positive".into()));
+        }
+        if !mail.email_verification_url.contains("{token}") {
+            problems.push((
+                "mail.email_verification_url",
+                "must contain `{token}` (env EMAIL_VERIFICATION_URL)".into(),
+            ));
+        }
+        if mail.email_verification_ttl_secs <= 0 {
+            problems.push(("mail.email_verification_ttl_secs", "must be positive".into()));
+        }
+        if mail.transport == MailTransport::File && mail.dir.as_os_str().is_empty() {
+            problems.push(("mail.dir", "must not be empty with the file transport".into()));
+        }
+        if mail.transport == MailTransport::Smtp && mail.smtp.host.is_empty() {
+            problems.push((
+                "mail.smtp.host",
+                "must be set with the smtp transport (env SMTP_HOST)".into(),
+            ));
+        }
+
+        if self.two_factor.issuer.trim().is_empty() || self.two_factor.issuer.contains(':') {
+            problems.push((
+                "two_factor.issuer",
+                "must be non-empty and without `:` (env TOTP_ISSUER)".into(),
+            ));
+        }
+        if self.two_factor.challenge_ttl_secs <= 0 {
+            problems.push(("two_factor.challenge_ttl_secs", "must be positive".into()));
+        }
+
+        let providers = &self.oidc.providers;
+        for (index, provider) in providers.iter().enumerate() {
+            let name = &provider.name;
+            let valid_name = !name.is_empty()
+                && name.len() <= 32
+                && name
+                    .bytes()
+                    .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-');
+            if !valid_name {
+                problems.push((
+                    "oidc.providers.name",
+                    format!("`{name}` must be 1 to 32 of a-z, 0-9 and `-`"),
+                ));
+            }
+            if providers[..index].iter().any(|other| &other.name == name) {
+                problems.push(("oidc.providers.name", format!("`{name}` is configured twice")));
+            }
+            if provider.issuer.is_empty() {
+                problems.push(("oidc.providers.issuer", format!("must be set for `{name}`")));
+            }
+            let jwks_url = &provider.jwks_url;
+            if !jwks_url.starts_with("https://") && !jwks_url.starts_with("http://") {
+                problems.push((
+                    "oidc.providers.jwks_url",
+                    format!("`{jwks_url}` of `{name}` is not an http(s) URL"),
+                ));
+            }
+            if provider.client_ids.is_empty() {
+                problems.push((
+                    "oidc.providers.client_ids",
+                    format!("must name at least one client for `{name}`"),
+                ));
+            }
+        }
+
+        let webauthn = &self.webauthn;
+        let valid_rp_id = !webauthn.rp_id.is_empty()
+            && webauthn
+                .rp_id
+                .bytes()
+                .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-' || b == b'.');
+        if !valid_rp_id {
+            problems.push((
+                "webauthn.rp_id",
+                "must be a lowercase domain without scheme or port (env WEBAUTHN_RP_ID)".into(),
+            ));
+        }
+        if webauthn.rp_name.trim().is_empty() {
+            problems.push(("webauthn.rp_name", "must not be empty".into()));
+        }
+        if webauthn.origins.is_empty() {
+            problems.push((
+                "webauthn.origins",
+                "must name at least one origin (env WEBAUTHN_ORIGINS)".into(),
+            ));
+        }
+        if webauthn.challenge_ttl_secs <= 0 {
+            problems.push(("webauthn.challenge_ttl_secs", "must be positive".into()));
+        }
+
+        if self.account.deletion_grace_secs < 0 {
+            problems.push(("account.deletion_grace_secs", "must not be negative".into()));
+        }
+        if !self.account.export_download_url.contains("{token}") {
+            problems.push((
+                "account.export_download_url",
+                "must contain `{token}` (env EXPORT_DOWNLOAD_URL)".into(),
+            ));
+        }
+        if self.account.export_ttl_secs <= 0 {
+            problems.push(("account.export_ttl_secs", "must be positive".into()));
+        }
+    }
+}
+
+/// Replaces `target` with the parsed value of `key` when the variable is set.
+fn env_override<T: FromStr>(
+    key: &'static str,
+    target: &mut T,
+    problems: &mut Vec<(&'static str, String)>,
+) where
+    T::Err: std::fmt::Display,
+{
+    let Ok(value) = env::var(key) else {
+        return;
+    };
+    match value.parse() {
+        Ok(parsed) => *target = parsed,
+        Err(err) => problems.push((key, format!("cannot parse `{value}`: {err}"))),
+    }
+}
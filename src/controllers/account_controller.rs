@@ -0,0 +1,304 @@
+use std::sync::Arc;
+
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    http::{StatusCode, header},
+    response::IntoResponse,
+    routing::{get, post},
+};
+use sea_orm::{DatabaseConnection, EntityTrait};
+
+use crate::auth::middleware::AuthUser;
+use crate::clock::Clock;
+use crate::config::Config;
+use crate::controllers::models::account::{
+    AccountDeletionResponse, DataExportResponse, DeleteAccountBody,
+};
+use crate::controllers::password_controller::require_password;
+use crate::entities::data_export::{self, DataExportStatus};
+use crate::entities::{User, user};
+use crate::error::{AppError, ErrorCode, ResultExt};
+use crate::i18n::{self, Locale};
+use crate::mail::{self, MailTemplate, Mailer, Message};
+use crate::rate_limit::RateLimiter;
+use crate::services::{account_deletion, data_exports};
+use crate::state::AppState;
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/users/me/deletion",
+            post(delete_account).delete(undo_account_deletion),
+        )
+        .route(
+            "/users/me/exports",
+            post(start_data_export).get(get_data_exports),
+        )
+        .route("/exports/{token}", get(download_data_export))
+}
+
+#[utoipa::path(
+    post,
+    path = "/users/me/deletion",
+    summary = "Delete account",
+    description = "Schedules the account for deletion after checking the password; accounts without a password only need to be logged in. Nothing is deleted during the grace period of `account.deletion_grace_secs`, the user can log in and undo it. Afterwards open events the user organises are canceled and their participants notified, past ones are handed over to a participant, and the account is anonymised with all its data, sessions and sign-in methods removed.",
+    request_body = DeleteAccountBody,
+    responses(
+        (status = 202, description = "Deletion scheduled", body = AccountDeletionResponse),
+        (status = 400, description = "Validation error: the password is wrong"),
+        (status = 401, description = "Unauthorized: invalid or missing authentication token"),
+        (status = 409, description = "Conflict: the deletion is already scheduled"),
+        (status = 429, description = "The account is locked after failed logins, see Retry-After"),
+        (status = 500, description = "Server error: hashing or database error")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Users"
+)]
+pub async fn delete_account(
+    auth: AuthUser,
+    State(db): State<Arc<DatabaseConnection>>,
+    State(config): State<Arc<Config>>,
+    State(limiter): State<Arc<RateLimiter>>,
+    State(clock): State<Arc<dyn Clock>>,
+    State(mailer): State<Arc<dyn Mailer>>,
+    Json(body): Json<DeleteAccountBody>,
+) -> Result<(StatusCode, Json<AccountDeletionResponse>), AppError> {
+    let user = User::find_by_id(auth.user_id)
+        .one(&*db)
+        .await?
+        .ok_or(ErrorCode::ProfileNotFound)?;
+    if user.deletion_scheduled_at.is_some() {
+        return Err(ErrorCode::AccountDeletionPending.into());
+    }
+    if !user.password_hash.is_empty() {
+        let password = body.password.as_deref().unwrap_or_default();
+        require_password(&limiter, &user, password, "password").await?;
+    }
+
+    let at = clock.now() + config.account.deletion_grace();
+    let user = account_deletion::schedule(&*db, user, at).await?;
+    tracing::info!(user_id = %user.id, %at, "account deletion scheduled");
+
+    if let Some(email) = verified_email(&user) {
+        let date = at.format("%Y-%m-%d %H:%M").to_string();
+        let message = Message::localized(
+            email,
+            MailTemplate::AccountDeletionScheduled,
+            locale(&user),
+            &[("username", &user.username), ("date", &date)],
+        );
+        mail::send_later(mailer, message);
+    }
+
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(AccountDeletionResponse {
+            deletion_scheduled_at: at.to_rfc3339(),
+        }),
+    ))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/users/me/deletion",
+    summary = "Undo account deletion",
+    description = "Keeps the account after all, as long as the grace period has not ended.",
+    responses(
+        (status = 204, description = "Deletion undone"),
+        (status = 400, description = "Validation error: no deletion is scheduled"),
+        (status = 401, description = "Unauthorized: invalid or missing authentication token"),
+        (status = 500, description = "Server error: database error")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Users"
+)]
+pub async fn undo_account_deletion(
+    auth: AuthUser,
+    State(db): State<Arc<DatabaseConnection>>,
+) -> Result<StatusCode, AppError> {
+    if !account_deletion::cancel(&*db, auth.user_id).await? {
+        return Err(ErrorCode::AccountDeletionNotScheduled.into());
+    }
+    tracing::info!(user_id = %auth.user_id, "account deletion undone");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    post,
+    path = "/users/me/exports",
+    summary = "Export my data",
+    description = "Starts writing a ZIP archive with the user's profile, friendships, events and RSVPs, busy days, wish places and memory images; the format is described in the README. The response carries the download link, which works once the export is `ready` and until `expires_at` (`account.export_ttl_secs`). A verified address also gets the link by mail when the archive is ready. Only one export can be in progress at a time.",
+    responses(
+        (status = 202, description = "Export started", body = DataExportResponse),
+        (status = 401, description = "Unauthorized: invalid or missing authentication token"),
+        (status = 409, description = "Conflict: an export is already in progress"),
+        (status = 500, description = "Server error: database error")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Users"
+)]
+pub async fn start_data_export(
+    auth: AuthUser,
+    State(db): State<Arc<DatabaseConnection>>,
+    State(config): State<Arc<Config>>,
+    State(clock): State<Arc<dyn Clock>>,
+    State(mailer): State<Arc<dyn Mailer>>,
+) -> Result<(StatusCode, Json<DataExportResponse>), AppError> {
+    let user = User::find_by_id(auth.user_id)
+        .one(&*db)
+        .await?
+        .ok_or(ErrorCode::ProfileNotFound)?;
+    let now = clock.now();
+    if data_exports::is_pending(&*db, user.id, now).await? {
+        return Err(ErrorCode::DataExportPending.into());
+    }
+
+    let expires_at = now + config.account.export_ttl();
+    let (export, token) = data_exports::create(&*db, user.id, now, expires_at).await?;
+    let link = config
+        .account
+        .export_download_url
+        .replace("{token}", &token);
+    tracing::info!(user_id = %user.id, export_id = %export.id, "data export started");
+
+    let mut response = to_response(export.clone());
+    response.download_url = Some(link.clone());
+    tokio::spawn(build_data_export(db, config, mailer, user, export, link));
+    Ok((StatusCode::ACCEPTED, Json(response)))
+}
+
+/// Writes the archive in the background and mails the link once it is ready.
+async fn build_data_export(
+    db: Arc<DatabaseConnection>,
+    config: Arc<Config>,
+    mailer: Arc<dyn Mailer>,
+    user: user::Model,
+    export: data_export::Model,
+    link: String,
+) {
+    let export_id = export.id;
+    let now = export.created_at.to_utc();
+    let export = match data_exports::build(&db, &config.storage.data_dir, export, now).await {
+        Ok(export) => export,
+        Err(err) => {
+            tracing::error!(user_id = %user.id, %export_id, error = %err, "data export failed");
+            return;
+        }
+    };
+    tracing::info!(user_id = %user.id, %export_id, size = export.size_bytes, "data export ready");
+
+    if let Some(email) = verified_email(&user) {
+        let hours = ((config.account.export_ttl_secs + 3599) / 3600).to_string();
+        let message = Message::localized(
+            email,
+            MailTemplate::DataExportReady,
+            locale(&user),
+            &[
+                ("username", &user.username),
+                ("link", &link),
+                ("hours", &hours),
+            ],
+        );
+        if let Err(err) = mailer.send(&message).await {
+            tracing::error!(error = %err, subject = %message.subject, "sending mail failed");
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/users/me/exports",
+    summary = "My data exports",
+    description = "Exports of the user that have not expired yet, newest first. Download links are only shown when an export is started.",
+    responses(
+        (status = 200, description = "Exports", body = [DataExportResponse]),
+        (status = 401, description = "Unauthorized: invalid or missing authentication token"),
+        (status = 500, description = "Server error: database error")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Users"
+)]
+pub async fn get_data_exports(
+    auth: AuthUser,
+    State(db): State<Arc<DatabaseConnection>>,
+    State(clock): State<Arc<dyn Clock>>,
+) -> Result<Json<Vec<DataExportResponse>>, AppError> {
+    let exports = data_exports::list(&*db, auth.user_id, clock.now()).await?;
+    Ok(Json(exports.into_iter().map(to_response).collect()))
+}
+
+#[utoipa::path(
+    get,
+    path = "/exports/{token}",
+    summary = "Download data export",
+    description = "The ZIP archive of a data export. The token in the link is the only credential, so the link works in a browser without logging in.",
+    params(("token" = String, Path, description = "Token from the download link")),
+    responses(
+        (status = 200, description = "The archive", content_type = "application/zip"),
+        (status = 404, description = "The link is unknown or expired, or the export failed"),
+        (status = 409, description = "Conflict: the export is not ready yet"),
+        (status = 500, description = "Server error: database or file error")
+    ),
+    tag = "Users"
+)]
+pub async fn download_data_export(
+    State(db): State<Arc<DatabaseConnection>>,
+    State(config): State<Arc<Config>>,
+    State(clock): State<Arc<dyn Clock>>,
+    Path(token): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let export = data_exports::find_by_token(&*db, &token, clock.now())
+        .await?
+        .ok_or(ErrorCode::DataExportNotFound)?;
+    match export.status {
+        DataExportStatus::Ready => {}
+        DataExportStatus::Pending => return Err(ErrorCode::DataExportNotReady.into()),
+        DataExportStatus::Failed => return Err(ErrorCode::DataExportNotFound.into()),
+    }
+
+    let path = data_exports::archive_path(&config.storage.data_dir, export.id);
+    let archive = match tokio::fs::read(&path).await {
+        Ok(archive) => archive,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            return Err(ErrorCode::DataExportNotFound.into());
+        }
+        Err(err) => return Err(err).or_internal(),
+    };
+    let filename = format!(
+        "attachment; filename=\"friends-export-{}.zip\"",
+        export.created_at.format("%Y-%m-%d")
+    );
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/zip".to_string()),
+            (header::CONTENT_DISPOSITION, filename),
+            (header::CACHE_CONTROL, "no-store".to_string()),
+        ],
+        archive,
+    ))
+}
+
+fn to_response(export: data_export::Model) -> DataExportResponse {
+    DataExportResponse {
+        id: export.id,
+        status: export.status.to_string(),
+        created_at: export.created_at.to_rfc3339(),
+        completed_at: export.completed_at.map(|at| at.to_rfc3339()),
+        size_bytes: export.size_bytes,
+        expires_at: export.expires_at.to_rfc3339(),
+        download_url: None,
+    }
+}
+
+/// The address mail to `user` goes to, once they have verified it.
+fn verified_email(user: &user::Model) -> Option<&str> {
+    user.email_verified_at.and(user.email.as_deref())
+}
+
+fn locale(user: &user::Model) -> Locale {
+    user.locale
+        .as_deref()
+        .and_then(Locale::parse)
+        .unwrap_or_else(i18n::current)
+}
@@ -1,30 +1,52 @@
 use axum::extract::State;
-use axum::http::StatusCode;
-use axum::routing::post;
+use axum::http::{HeaderMap, StatusCode, header};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
 use axum::{Json, Router};
 use chrono::{DateTime, Utc};
-use sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, Set};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, DatabaseTransaction, EntityTrait,
+    QueryFilter, Set, TransactionTrait,
+};
+use serde_json::json;
+use std::net::IpAddr;
+use std::sync::Arc;
 use uuid::Uuid;
 
-use argon2::Argon2;
-use argon2::password_hash::rand_core::OsRng;
-use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
-
-use crate::auth::jwt::{create_access_jwt, create_refresh_jwt, verify_refresh_jwt};
+use crate::auth::jwt::{
+    create_access_jwt, create_refresh_jwt, create_two_factor_jwt, verify_refresh_jwt,
+    verify_two_factor_jwt,
+};
+use crate::auth::password::{hash_password, verify_password};
+use crate::auth::keys::JwtKeys;
+use crate::auth::session_guard::SessionGuard;
+use crate::auth::sessions::{self, DeviceInfo};
+use crate::auth::{accounts, refresh_tokens, security_events, two_factor};
+use crate::clock::Clock;
+use crate::config::Config;
 use crate::controllers::models::user_response::UserResponse;
 use crate::controllers::models::{
-    AuthRequestBody, LoginRequestBody, LoginResponse, RefreshTokenRequest, RefreshTokenResponse,
+    AuthRequestBody, JwkResponse, JwksResponse, LoginRequestBody, LoginResponse, RefreshTokenRequest, RefreshTokenResponse,
+    TwoFactorChallengeResponse, TwoFactorLoginBody,
 };
+use crate::entities::security_event::SecurityEventKind;
 use crate::entities::{
-    RefreshToken, RefreshTokenActiveModel, RefreshTokenColumn, User, UserActiveModel, user,
+    RefreshToken, RefreshTokenColumn, User, UserActiveModel,
+    refresh_token, user,
 };
+use crate::error::{AppError, ErrorCode, ResultExt};
+use crate::rate_limit::{ClientIp, LimitedRoute, RateLimiter};
+use crate::state::AppState;
+use crate::telemetry::counters;
 
-pub fn router() -> Router<DatabaseConnection> {
+pub fn router() -> Router<AppState> {
     Router::new()
         .route("/auth/register", post(register))
         .route("/auth/login", post(login))
+        .route("/auth/login/2fa", post(login_two_factor))
         .route("/auth/refresh", post(refresh))
         .route("/auth/logout", post(logout))
+        .route("/.well-known/jwks.json", get(jwks))
 }
 
 #[utoipa::path(
@@ -35,52 +57,45 @@ pub fn router() -> Router<DatabaseConnection> {
         (status = 201, description = "User created successfully"),
         (status = 400, description = "Validation error: missing username or password"),
         (status = 409, description = "Username already exists: 'You already have an account, please log in'"),
+        (status = 429, description = "Too many registrations from this address, see Retry-After"),
         (status = 500, description = "Server error: hashing or database error")
     )
 )]
 pub async fn register(
-    State(db_connection): State<DatabaseConnection>,
+    State(db_connection): State<Arc<DatabaseConnection>>,
+    State(limiter): State<Arc<RateLimiter>>,
+    ClientIp(ip): ClientIp,
     Json(body): Json<AuthRequestBody>,
-) -> Result<StatusCode, (StatusCode, String)> {
+) -> Result<StatusCode, AppError> {
     if body.username.trim().is_empty() || body.password.trim().is_empty() {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            "Please enter both username and password.".to_string(),
-        ));
+        return Err(ErrorCode::MissingCredentials.into());
     }
+    limiter.check(LimitedRoute::Register, ip, None).await?;
 
     let existing_user = User::find()
         .filter(user::Column::Username.eq(&body.username))
-        .one(&db_connection)
-        .await
-        .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "Failed to register. Please try again.".to_string()))?;
+        .one(&*db_connection)
+        .await?;
 
     if existing_user.is_some() {
-        return Err((
-            StatusCode::CONFLICT,
-            "This username is already taken. Please choose another.".to_string(),
-        ));
+        return Err(AppError::field(ErrorCode::UsernameTaken, "username"));
     }
 
-    let salt = SaltString::generate(&mut OsRng);
-    let password_hash = Argon2::default()
-        .hash_password(body.password.as_bytes(), &salt)
-        .map_err(|_| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "Failed to secure your password. Please try again.".to_string(),
-            )
-        })?
-        .to_string();
+    let password_hash = hash_password(&body.password)
+        .map_err(|e| e.to_string())
+        .or_internal()?;
 
     let active = UserActiveModel {
         username: Set(body.username),
         password_hash: Set(password_hash),
+        avatar_url: Set(body.avatar_url),
+        bio: Set(body.bio),
         ..Default::default()
     };
-    let _ = active.insert(&db_connection).await.map_err(|_| {
-        (StatusCode::INTERNAL_SERVER_ERROR, "Failed to create account. Please try again.".to_string())
-    })?;
+    active
+        .insert(&*db_connection)
+        .await
+        .map_err(|err| AppError::on_unique_violation(err, ErrorCode::UsernameTaken))?;
     Ok(StatusCode::CREATED)
 }
 
@@ -90,58 +105,220 @@ pub async fn register(
     request_body = LoginRequestBody,
     responses(
         (status = 200, description = "Login successful", body = LoginResponse),
+        (status = 202, description = "Password correct, the account wants a second factor: finish at `/auth/login/2fa`", body = TwoFactorChallengeResponse),
         (status = 400, description = "Validation error: missing username or password"),
-        (status = 401, description = "Unauthorized: 'User not found' or 'Invalid password'"),
+        (status = 401, description = "Unauthorized: unknown username or verified email, or wrong password"),
+        (status = 403, description = "Forbidden: the account is disabled"),
+        (status = 429, description = "Too many attempts or the account is locked after failed logins, see Retry-After"),
         (status = 500, description = "Server error: database or hash verification error")
     )
 )]
+#[allow(clippy::too_many_arguments)]
 pub async fn login(
-    State(db_connection): State<DatabaseConnection>,
+    State(db_connection): State<Arc<DatabaseConnection>>,
+    State(keys): State<Arc<JwtKeys>>,
+    State(limiter): State<Arc<RateLimiter>>,
+    ClientIp(ip): ClientIp,
+    State(config): State<Arc<Config>>,
+    State(clock): State<Arc<dyn Clock>>,
+    headers: HeaderMap,
     Json(body): Json<LoginRequestBody>,
-) -> Result<Json<LoginResponse>, (StatusCode, String)> {
+) -> Result<LoginOutcome, AppError> {
     if body.username.trim().is_empty() || body.password.trim().is_empty() {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            "Please enter both username and password.".to_string(),
-        ));
+        return Err(ErrorCode::MissingCredentials.into());
     }
+    limiter
+        .check(LimitedRoute::Login, ip, Some(&body.username))
+        .await?;
+    limiter.ensure_not_locked(&body.username).await?;
 
-    let model = User::find()
-        .filter(user::Column::Username.eq(body.username))
-        .one(&db_connection)
-        .await
-        .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "Login failed. Please try again.".to_string()))?;
+    let model = accounts::find_by_login(&*db_connection, &body.username).await?;
 
     let Some(model) = model else {
-        return Err((StatusCode::UNAUTHORIZED, "Invalid username or password.".to_string()));
+        counters::login_failed();
+        limiter.login_failed(&body.username).await?;
+        return Err(ErrorCode::InvalidCredentials.into());
     };
+    // Logins by address and by username share the lockout of the account.
+    if model.username != body.username {
+        limiter.ensure_not_locked(&model.username).await?;
+    }
 
-    let parsed_hash = PasswordHash::new(&model.password_hash).map_err(|_| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            "Login failed. Please try again.".to_string(),
-        )
-    })?;
+    let password_matches = verify_password(&model.password_hash, &body.password)
+        .map_err(|e| e.to_string())
+        .or_internal()?;
 
-    Argon2::default()
-        .verify_password(body.password.as_bytes(), &parsed_hash)
-        .map_err(|_| (StatusCode::UNAUTHORIZED, "Invalid username or password.".to_string()))?;
+    if !password_matches {
+        counters::login_failed();
+        limiter.login_failed(&model.username).await?;
+        return Err(ErrorCode::InvalidCredentials.into());
+    }
 
-    let access_token = create_access_jwt(model.id)
-        .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "Failed to generate token. Please try again.".to_string()))?;
-    let refresh_issue = create_refresh_jwt(model.id)
-        .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "Failed to generate token. Please try again.".to_string()))?;
+    if model.disabled_at.is_some() {
+        counters::login_failed();
+        return Err(ErrorCode::AccountDisabled.into());
+    }
 
-    persist_refresh_token(
+    let device = device_info(body.device_name, body.platform, ip, &headers);
+    complete_login(
         &db_connection,
-        refresh_issue.jti,
+        &keys,
+        &config,
+        &limiter,
+        model,
+        device,
+        clock.now(),
+    )
+    .await
+}
+
+#[utoipa::path(
+    post,
+    path = "/auth/login/2fa",
+    summary = "Finish two-factor login",
+    description = "Exchanges the challenge token from `/auth/login` and a code from the authenticator app for a token pair. A recovery code works instead of the app code, once. Wrong codes count towards the login lockout.",
+    request_body = TwoFactorLoginBody,
+    responses(
+        (status = 200, description = "Login successful", body = LoginResponse),
+        (status = 400, description = "Validation error: the code is wrong or already used"),
+        (status = 401, description = "Unauthorized: the challenge token is invalid or expired, log in again"),
+        (status = 403, description = "Forbidden: the account is disabled"),
+        (status = 429, description = "Too many attempts or the account is locked after failed logins, see Retry-After"),
+        (status = 500, description = "Server error: database error")
+    ),
+    tag = "Auth"
+)]
+pub async fn login_two_factor(
+    State(db_connection): State<Arc<DatabaseConnection>>,
+    State(keys): State<Arc<JwtKeys>>,
+    State(limiter): State<Arc<RateLimiter>>,
+    State(clock): State<Arc<dyn Clock>>,
+    ClientIp(ip): ClientIp,
+    headers: HeaderMap,
+    Json(body): Json<TwoFactorLoginBody>,
+) -> Result<Json<LoginResponse>, AppError> {
+    limiter.check(LimitedRoute::Login, ip, None).await?;
+    let user_id = verify_two_factor_jwt(&keys, &body.challenge_token)
+        .ok()
+        .and_then(|payload| Uuid::parse_str(&payload.sub).ok())
+        .ok_or(ErrorCode::TwoFactorChallengeExpired)?;
+
+    let model = User::find_by_id(user_id)
+        .one(&*db_connection)
+        .await?
+        .ok_or(ErrorCode::TwoFactorChallengeExpired)?;
+    if model.disabled_at.is_some() {
+        counters::login_failed();
+        return Err(ErrorCode::AccountDisabled.into());
+    }
+    limiter.ensure_not_locked(&model.username).await?;
+
+    // Two-factor login was turned off since the password step: that
+    // challenge no longer leads anywhere.
+    let totp = two_factor::enabled(&*db_connection, model.id)
+        .await?
+        .ok_or(ErrorCode::TwoFactorChallengeExpired)?;
+    if !two_factor::verify_code(&*db_connection, &totp, &body.code, clock.now()).await? {
+        counters::login_failed();
+        limiter.login_failed(&model.username).await?;
+        return Err(AppError::field(ErrorCode::InvalidTwoFactorCode, "code"));
+    }
+
+    let device = device_info(body.device_name, body.platform, ip, &headers);
+    let response = start_session(&db_connection, &keys, model, device, clock.now()).await?;
+    limiter.login_succeeded(&response.user.username).await?;
+    counters::login_succeeded();
+    Ok(Json(response))
+}
+
+/// What a right first factor earns: the token pair, or a challenge when the
+/// account has two-factor login on.
+pub enum LoginOutcome {
+    Tokens(Box<LoginResponse>),
+    Challenge(TwoFactorChallengeResponse),
+}
+
+impl IntoResponse for LoginOutcome {
+    fn into_response(self) -> Response {
+        match self {
+            LoginOutcome::Tokens(response) => Json(response).into_response(),
+            LoginOutcome::Challenge(challenge) => {
+                (StatusCode::ACCEPTED, Json(challenge)).into_response()
+            }
+        }
+    }
+}
+
+/// Finishes a login of `model` whose password or other first factor was right.
+pub(crate) async fn complete_login(
+    db_connection: &DatabaseConnection,
+    keys: &JwtKeys,
+    config: &Config,
+    limiter: &RateLimiter,
+    model: user::Model,
+    device: DeviceInfo,
+    now: DateTime<Utc>,
+) -> Result<LoginOutcome, AppError> {
+    // The lockout is only lifted once the second factor is right too.
+    if two_factor::enabled(db_connection, model.id).await?.is_some() {
+        let ttl = config.two_factor.challenge_ttl();
+        let challenge_token = create_two_factor_jwt(keys, model.id, ttl).or_internal()?;
+        return Ok(LoginOutcome::Challenge(TwoFactorChallengeResponse {
+            challenge_token,
+            expires_in: ttl.num_seconds(),
+        }));
+    }
+
+    let response = start_session(db_connection, keys, model, device, now).await?;
+    limiter.login_succeeded(&response.user.username).await?;
+    counters::login_succeeded();
+    Ok(LoginOutcome::Tokens(Box::new(response)))
+}
+
+pub(crate) fn device_info(
+    device_name: Option<String>,
+    platform: Option<String>,
+    ip: Option<IpAddr>,
+    headers: &HeaderMap,
+) -> DeviceInfo {
+    DeviceInfo {
+        device_name,
+        platform,
+        ip,
+        user_agent: headers
+            .get(header::USER_AGENT)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string),
+    }
+}
+
+/// Issues the token pair of a new login at `now` and records its session.
+pub(crate) async fn start_session(
+    db_connection: &DatabaseConnection,
+    keys: &JwtKeys,
+    model: user::Model,
+    device: DeviceInfo,
+    now: DateTime<Utc>,
+) -> Result<LoginResponse, AppError> {
+    let refresh_issue = create_refresh_jwt(keys, model.id).or_internal()?;
+    let session_id = refresh_issue.jti;
+    let access_token = create_access_jwt(keys, model.id, session_id).or_internal()?;
+
+    let tx = db_connection.begin().await?;
+    sessions::start(
+        &tx,
+        session_id,
         model.id,
+        device,
         refresh_issue.expires_at,
+        now,
     )
-    .await
-    .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "Login failed. Please try again.".to_string()))?;
+    .await?;
+    refresh_tokens::start_family(&tx, refresh_issue.jti, model.id, refresh_issue.expires_at)
+        .await?;
+    tx.commit().await?;
 
-    Ok(Json(LoginResponse {
+    Ok(LoginResponse {
         access_token,
         refresh_token: refresh_issue.token,
         user: UserResponse {
@@ -149,8 +326,13 @@ pub async fn login(
             username: model.username,
             avatar_url: model.avatar_url,
             bio: model.bio,
+            email_verified: Some(model.email_verified_at.is_some()),
+            email: model.email,
+            locale: model.locale,
+            timezone: model.timezone,
+            deletion_scheduled_at: model.deletion_scheduled_at.map(|at| at.to_rfc3339()),
         },
-    }))
+    })
 }
 
 #[utoipa::path(
@@ -159,92 +341,64 @@ pub async fn login(
     request_body = RefreshTokenRequest,
     responses(
         (status = 200, description = "New token pair issued", body = RefreshTokenResponse),
-        (status = 401, description = "Unauthorized: invalid, revoked, or expired refresh token"),
+        (status = 401, description = "Unauthorized: invalid, revoked, or expired refresh token. Replaying a token that was rotated more than a few seconds ago also revokes every token rotated from the same login"),
+        (status = 429, description = "Too many refreshes from this address, see Retry-After"),
         (status = 500, description = "Server error: database error")
     )
 )]
 pub async fn refresh(
-    State(db_connection): State<DatabaseConnection>,
+    State(db_connection): State<Arc<DatabaseConnection>>,
+    State(keys): State<Arc<JwtKeys>>,
+    State(limiter): State<Arc<RateLimiter>>,
+    State(guard): State<Arc<SessionGuard>>,
+    State(clock): State<Arc<dyn Clock>>,
+    ClientIp(ip): ClientIp,
     Json(body): Json<RefreshTokenRequest>,
-) -> Result<Json<RefreshTokenResponse>, (StatusCode, String)> {
-    let payload = verify_refresh_jwt(&body.refresh_token).map_err(|_| {
-        (
-            StatusCode::UNAUTHORIZED,
-            "Your session has expired. Please log in again.".to_string(),
-        )
-    })?;
+) -> Result<Json<RefreshTokenResponse>, AppError> {
+    limiter.check(LimitedRoute::Refresh, ip, None).await?;
+    let now = clock.now();
+    let payload = verify_refresh_jwt(&keys, &body.refresh_token)
+        .map_err(|_| AppError::from(ErrorCode::SessionExpired))?;
 
-    let user_id = Uuid::parse_str(&payload.sub).map_err(|_| {
-        (
-            StatusCode::UNAUTHORIZED,
-            "Your session has expired. Please log in again.".to_string(),
-        )
-    })?;
+    let user_id =
+        Uuid::parse_str(&payload.sub).map_err(|_| AppError::from(ErrorCode::SessionExpired))?;
 
     let jti = payload
         .jti
-        .as_ref()
-        .ok_or((
-            StatusCode::UNAUTHORIZED,
-            "Your session has expired. Please log in again.".to_string(),
-        ))
-        .and_then(|v| {
-            Uuid::parse_str(v).map_err(|_| {
-                (
-                    StatusCode::UNAUTHORIZED,
-                    "Your session has expired. Please log in again.".to_string(),
-                )
-            })
-        })?;
-
-    let user_exists = User::find_by_id(user_id)
-        .one(&db_connection)
-        .await
-        .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "Failed to refresh session. Please try again.".to_string()))?
-        .is_some();
-
-    if !user_exists {
-        return Err((
-            StatusCode::UNAUTHORIZED,
-            "Your session has expired. Please log in again.".to_string(),
-        ));
-    }
-
-    let active_token = RefreshToken::find_by_id(jti)
+        .as_deref()
+        .and_then(|v| Uuid::parse_str(v).ok())
+        .ok_or(ErrorCode::SessionExpired)?;
+
+    User::find_by_id(user_id)
+        .one(&*db_connection)
+        .await?
+        .filter(|user| user.disabled_at.is_none())
+        .ok_or(ErrorCode::SessionExpired)?;
+
+    let tx = db_connection.begin().await?;
+    let token = RefreshToken::find_by_id(jti)
         .filter(RefreshTokenColumn::UserId.eq(user_id))
-        .one(&db_connection)
-        .await
-        .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "Failed to refresh session. Please try again.".to_string()))?
-        .ok_or((
-            StatusCode::UNAUTHORIZED,
-            "Your session has expired. Please log in again.".to_string(),
-        ))?;
-
-    if active_token.revoked_at.is_some() || active_token.expires_at < Utc::now() {
-        return Err((
-            StatusCode::UNAUTHORIZED,
-            "Your session has expired. Please log in again.".to_string(),
-        ));
-    }
-
-    revoke_refresh_token(&db_connection, jti).await
-        .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "Failed to refresh session. Please try again.".to_string()))?;
-
-    let access_token = create_access_jwt(user_id)
-        .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "Failed to refresh session. Please try again.".to_string()))?;
+        .one(&tx)
+        .await?
+        .ok_or(ErrorCode::SessionExpired)?;
 
-    let refresh_issue = create_refresh_jwt(user_id)
-        .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "Failed to refresh session. Please try again.".to_string()))?;
+    if token.revoked_at.is_some() {
+        return Err(reject_revoked(tx, &guard, &token, ip, now).await);
+    }
+    if token.expires_at < now {
+        return Err(ErrorCode::SessionExpired.into());
+    }
 
-    persist_refresh_token(
-        &db_connection,
-        refresh_issue.jti,
-        user_id,
-        refresh_issue.expires_at,
-    )
-    .await
-    .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "Failed to refresh session. Please try again.".to_string()))?;
+    let refresh_issue = create_refresh_jwt(&keys, user_id).or_internal()?;
+    if !refresh_tokens::rotate(&tx, &token, refresh_issue.jti, refresh_issue.expires_at, now).await?
+    {
+        return Err(reject_revoked(tx, &guard, &token, ip, now).await);
+    }
+    sessions::touch(&tx, token.family_id, ip, refresh_issue.expires_at, now).await?;
+    let access_token = create_access_jwt(&keys, user_id, token.family_id).or_internal()?;
+    tx.commit().await?;
 
+    counters::refresh_token_rotated();
     Ok(Json(RefreshTokenResponse {
         access_token,
         refresh_token: refresh_issue.token,
@@ -256,77 +410,104 @@ pub async fn refresh(
     path = "/auth/logout",
     request_body = RefreshTokenRequest,
     responses(
-        (status = 204, description = "Refresh token revoked successfully"),
+        (status = 204, description = "Session ended, its refresh and access tokens no longer work"),
         (status = 401, description = "Unauthorized: invalid or malformed refresh token"),
         (status = 500, description = "Server error: database error")
     )
 )]
 pub async fn logout(
-    State(db_connection): State<DatabaseConnection>,
+    State(db_connection): State<Arc<DatabaseConnection>>,
+    State(keys): State<Arc<JwtKeys>>,
+    State(guard): State<Arc<SessionGuard>>,
+    State(clock): State<Arc<dyn Clock>>,
     Json(body): Json<RefreshTokenRequest>,
-) -> Result<StatusCode, (StatusCode, String)> {
-    let payload = verify_refresh_jwt(&body.refresh_token).map_err(|_| {
-        (
-            StatusCode::UNAUTHORIZED,
-            "Invalid token. Please log in again.".to_string(),
-        )
-    })?;
+) -> Result<StatusCode, AppError> {
+    let payload = verify_refresh_jwt(&keys, &body.refresh_token)
+        .map_err(|_| AppError::from(ErrorCode::InvalidToken))?;
     let jti = payload
         .jti
-        .as_ref()
-        .ok_or((
-            StatusCode::UNAUTHORIZED,
-            "Invalid token. Please log in again.".to_string(),
-        ))
-        .and_then(|v| {
-            Uuid::parse_str(v).map_err(|_| {
-                (
-                    StatusCode::UNAUTHORIZED,
-                    "Invalid token. Please log in again.".to_string(),
-                )
-            })
-        })?;
-
-    revoke_refresh_token(&db_connection, jti).await
-        .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "Failed to logout. Please try again.".to_string()))?;
+        .as_deref()
+        .and_then(|v| Uuid::parse_str(v).ok())
+        .ok_or(ErrorCode::InvalidToken)?;
+
+    if let Some(token) = RefreshToken::find_by_id(jti).one(&*db_connection).await? {
+        let tx = db_connection.begin().await?;
+        sessions::revoke(&tx, token.family_id, clock.now()).await?;
+        tx.commit().await?;
+        guard.forget(token.family_id);
+    }
     Ok(StatusCode::NO_CONTENT)
 }
 
-async fn persist_refresh_token(
-    db_connection: &DatabaseConnection,
-    token_id: Uuid,
-    user_id: Uuid,
-    expires_at: DateTime<Utc>,
-) -> Result<(), (StatusCode, String)> {
-    let active = RefreshTokenActiveModel {
-        id: Set(token_id),
-        user_id: Set(user_id),
-        expires_at: Set(expires_at.into()),
-        revoked_at: Set(None),
-        ..Default::default()
-    };
-    active
-        .insert(db_connection)
-        .await
-        .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "Failed to save session. Please try again.".to_string()))?;
-    Ok(())
+#[utoipa::path(
+    get,
+    path = "/.well-known/jwks.json",
+    summary = "Token signing keys",
+    description = "Public keys that verify access and refresh tokens, matched by the `kid` token header. Empty when the server signs with a shared secret.",
+    responses(
+        (status = 200, description = "JSON Web Key Set", body = JwksResponse)
+    )
+)]
+pub async fn jwks(
+    State(keys): State<Arc<JwtKeys>>,
+) -> ([(header::HeaderName, &'static str); 1], Json<JwksResponse>) {
+    let keys = keys
+        .public_keys()
+        .into_iter()
+        .map(|key| JwkResponse {
+            kty: "OKP",
+            crv: "Ed25519",
+            alg: "EdDSA",
+            key_use: "sig",
+            kid: key.kid,
+            x: key.x,
+        })
+        .collect();
+    // Shorter than the key activation delay, so verifiers see a new key
+    // before tokens signed with it arrive.
+    (
+        [(header::CACHE_CONTROL, "public, max-age=60")],
+        Json(JwksResponse { keys }),
+    )
 }
 
-async fn revoke_refresh_token(
-    db_connection: &DatabaseConnection,
-    token_id: Uuid,
-) -> Result<(), (StatusCode, String)> {
-    if let Some(row) = RefreshToken::find_by_id(token_id)
-        .one(db_connection)
-        .await
-        .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "Failed to revoke token. Please try again.".to_string()))?
-    {
-        let mut active: RefreshTokenActiveModel = row.into();
-        active.revoked_at = Set(Some(Utc::now().into()));
-        active
-            .update(db_connection)
-            .await
-            .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "Failed to revoke token. Please try again.".to_string()))?;
+/// Answers a refresh with a revoked token. When the token had been rotated
+/// longer than [`refresh_tokens::REUSE_GRACE`] ago, someone replayed a copy
+/// of it: the session is ended so neither the thief nor the victim can
+/// continue, and the reuse is recorded. Sooner it is a retry or a concurrent
+/// refresh of the same client and only rejected.
+async fn reject_revoked(
+    tx: DatabaseTransaction,
+    guard: &SessionGuard,
+    token: &refresh_token::Model,
+    ip: Option<IpAddr>,
+    now: DateTime<Utc>,
+) -> AppError {
+    let result: Result<(), AppError> = async {
+        // Without a revocation time a concurrent refresh rotated it just now.
+        let rotated_at = token.revoked_at.map_or(now, |at| at.with_timezone(&Utc));
+        if now - rotated_at < refresh_tokens::REUSE_GRACE
+            || !refresh_tokens::was_rotated(&tx, token.id).await?
+        {
+            return Ok(());
+        }
+        sessions::revoke(&tx, token.family_id, now).await?;
+        security_events::record(
+            &tx,
+            token.user_id,
+            SecurityEventKind::RefreshTokenReuse,
+            ip,
+            json!({ "session_id": token.family_id, "token_id": token.id }),
+        )
+        .await?;
+        tx.commit().await?;
+        guard.forget(token.family_id);
+        counters::security_event(SecurityEventKind::RefreshTokenReuse);
+        Ok(())
+    }
+    .await;
+    match result {
+        Ok(()) => ErrorCode::SessionExpired.into(),
+        Err(err) => err,
     }
-    Ok(())
 }
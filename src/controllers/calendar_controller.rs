@@ -1,26 +1,24 @@
-use std::collections::HashMap;
+use std::sync::Arc;
 
 use axum::{
     Json, Router,
     extract::{Path, Query, State},
-    http::StatusCode,
     routing::{get, post},
 };
-use chrono::{NaiveDate, Utc};
-use sea_orm::{ColumnTrait, Condition, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder};
+use chrono::NaiveDate;
+use sea_orm::DatabaseConnection;
 use uuid::Uuid;
 
 use crate::auth::middleware::AuthUser;
+use crate::clock::{self, Clock};
 use crate::controllers::models::calendar::{
     BusydayResponse, CalendarQuery, CalendarResponse, IsBusyRequest, PendingInviteResponse,
 };
-use crate::entities::friendship::{self, FriendshipStatus};
-use crate::entities::user_event::{UserEventResponse, UserEventRole};
-use crate::entities::{
-    Busyday, BusydayColumn, Event, EventColumn, Friendship, UserEvent, UserEventColumn,
-};
+use crate::error::{AppError, ErrorCode};
+use crate::services::{CalendarService, Calendars, FriendshipService, Friendships};
+use crate::state::AppState;
 
-pub fn router() -> Router<DatabaseConnection> {
+pub fn router() -> Router<AppState> {
     Router::new()
         .route("/calendar/is_busy", post(is_busy))
         .route("/users/me/calendar", get(get_my_calendar))
@@ -45,23 +43,14 @@ pub fn router() -> Router<DatabaseConnection> {
 )]
 pub async fn is_busy(
     auth: AuthUser,
-    State(db): State<DatabaseConnection>,
+    State(db): State<Arc<DatabaseConnection>>,
     Json(payload): Json<IsBusyRequest>,
-) -> Result<Json<bool>, (StatusCode, String)> {
+) -> Result<Json<bool>, AppError> {
     let me = auth.user_id;
-    if me != payload.id && !are_users_accepted_friends(&db, me, payload.id).await? {
-        return Err((StatusCode::FORBIDDEN, "You can only check your own or accepted friend's availability.".to_string()));
-    }
-
-    let date = parse_one_date(&payload.date)?;
-    let busy = Busyday::find()
-        .filter(BusydayColumn::UserId.eq(payload.id))
-        .filter(BusydayColumn::Date.eq(date))
-        .one(&db)
-        .await
-        .map_err(internal_error)?
-        .is_some();
+    Friendships.ensure_can_view(&*db, me, payload.id).await?;
 
+    let date = parse_one_date(&payload.date, "date")?;
+    let busy = Calendars.is_busy(&*db, payload.id, date).await?;
     Ok(Json(busy))
 }
 
@@ -82,11 +71,13 @@ pub async fn is_busy(
 )]
 pub async fn get_my_calendar(
     auth: AuthUser,
-    State(db): State<DatabaseConnection>,
+    State(db): State<Arc<DatabaseConnection>>,
+    State(clock): State<Arc<dyn Clock>>,
     Query(query): Query<CalendarQuery>,
-) -> Result<Json<CalendarResponse>, (StatusCode, String)> {
+) -> Result<Json<CalendarResponse>, AppError> {
     let me = auth.user_id;
-    Ok(Json(build_calendar_response(&db, me, &query).await?))
+    let today = clock::user_today(&*db, clock.as_ref(), me).await?;
+    Ok(Json(build_calendar_response(&db, me, &query, today).await?))
 }
 
 #[utoipa::path(
@@ -110,36 +101,38 @@ pub async fn get_my_calendar(
 )]
 pub async fn get_user_calendar(
     auth: AuthUser,
-    State(db): State<DatabaseConnection>,
+    State(db): State<Arc<DatabaseConnection>>,
+    State(clock): State<Arc<dyn Clock>>,
     Path(user_id): Path<Uuid>,
     Query(query): Query<CalendarQuery>,
-) -> Result<Json<CalendarResponse>, (StatusCode, String)> {
+) -> Result<Json<CalendarResponse>, AppError> {
     let me = auth.user_id;
-    if me != user_id && !are_users_accepted_friends(&db, me, user_id).await? {
-        return Err((StatusCode::FORBIDDEN, "You can only view your own or accepted friend's calendar.".to_string()));
-    }
+    Friendships.ensure_can_view(&*db, me, user_id).await?;
 
-    Ok(Json(build_calendar_response(&db, user_id, &query).await?))
+    // Past is judged by the viewer's day.
+    let today = clock::user_today(&*db, clock.as_ref(), me).await?;
+    Ok(Json(build_calendar_response(&db, user_id, &query, today).await?))
 }
 
 async fn build_calendar_response(
     db: &DatabaseConnection,
     user_id: Uuid,
     query: &CalendarQuery,
-) -> Result<CalendarResponse, (StatusCode, String)> {
+    today: NaiveDate,
+) -> Result<CalendarResponse, AppError> {
     let (from, to) = parse_date_range(&query.from, &query.to)?;
+    let calendar = Calendars.calendar(db, user_id, from, to).await?;
 
-    let busy_rows = Busyday::find()
-        .filter(BusydayColumn::UserId.eq(user_id))
-        .filter(BusydayColumn::Date.gte(from))
-        .filter(BusydayColumn::Date.lte(to))
-        .order_by_asc(BusydayColumn::Date)
-        .all(db)
-        .await
-        .map_err(internal_error)?;
-
-    let busy_days = busy_rows
+    let past_events = calendar
+        .busy_days
         .iter()
+        .filter(|row| row.date < today)
+        .map(|row| row.date.to_string())
+        .collect::<Vec<_>>();
+
+    let busy_days = calendar
+        .busy_days
+        .into_iter()
         .map(|row| BusydayResponse {
             id: row.id,
             user_id: row.user_id,
@@ -148,49 +141,15 @@ async fn build_calendar_response(
         })
         .collect::<Vec<_>>();
 
-    let today = Utc::now().date_naive();
-    let past_events = busy_rows
-        .iter()
-        .filter(|row| row.date < today)
-        .map(|row| row.date.to_string())
-        .collect::<Vec<_>>();
-
-    let pending_rows = UserEvent::find()
-        .filter(UserEventColumn::UserId.eq(user_id))
-        .filter(UserEventColumn::Role.eq(UserEventRole::Participant))
-        .filter(UserEventColumn::ResponseStatus.eq(UserEventResponse::Pending))
-        .all(db)
-        .await
-        .map_err(internal_error)?;
-
-    let event_ids = pending_rows
+    let pending_invites = calendar
+        .pending_invites
         .into_iter()
-        .map(|row| row.event_id)
+        .map(|event| PendingInviteResponse {
+            event_id: event.id,
+            date: event.date.to_string(),
+        })
         .collect::<Vec<_>>();
 
-    let mut pending_invites = Vec::new();
-    if !event_ids.is_empty() {
-        let events = Event::find()
-            .filter(EventColumn::Id.is_in(event_ids))
-            .filter(EventColumn::Date.gte(from))
-            .filter(EventColumn::Date.lte(to))
-            .order_by_asc(EventColumn::Date)
-            .all(db)
-            .await
-            .map_err(internal_error)?;
-
-        let mut by_date = HashMap::new();
-        for event in events {
-            by_date.insert(event.id, event.date.to_string());
-        }
-
-        pending_invites = by_date
-            .into_iter()
-            .map(|(event_id, date)| PendingInviteResponse { event_id, date })
-            .collect::<Vec<_>>();
-        pending_invites.sort_by(|a, b| a.date.cmp(&b.date));
-    }
-
     Ok(CalendarResponse {
         from: from.to_string(),
         to: to.to_string(),
@@ -200,55 +159,18 @@ async fn build_calendar_response(
     })
 }
 
-async fn are_users_accepted_friends(
-    db: &DatabaseConnection,
-    user_a: Uuid,
-    user_b: Uuid,
-) -> Result<bool, (StatusCode, String)> {
-    let row = Friendship::find()
-        .filter(friendship::Column::Status.eq(FriendshipStatus::Accepted))
-        .filter(
-            Condition::any()
-                .add(
-                    Condition::all()
-                        .add(friendship::Column::UserId.eq(user_a))
-                        .add(friendship::Column::FriendId.eq(user_b)),
-                )
-                .add(
-                    Condition::all()
-                        .add(friendship::Column::UserId.eq(user_b))
-                        .add(friendship::Column::FriendId.eq(user_a)),
-                ),
-        )
-        .one(db)
-        .await
-        .map_err(internal_error)?;
-    Ok(row.is_some())
-}
-
-fn parse_date_range(from: &str, to: &str) -> Result<(NaiveDate, NaiveDate), (StatusCode, String)> {
-    let from_date = parse_one_date(from)?;
-    let to_date = parse_one_date(to)?;
+fn parse_date_range(from: &str, to: &str) -> Result<(NaiveDate, NaiveDate), AppError> {
+    let from_date = parse_one_date(from, "from")?;
+    let to_date = parse_one_date(to, "to")?;
 
     if from_date > to_date {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            "Start date must be before or equal to end date.".to_string(),
-        ));
+        return Err(AppError::field(ErrorCode::InvalidDateRange, "from"));
     }
 
     Ok((from_date, to_date))
 }
 
-fn parse_one_date(value: &str) -> Result<NaiveDate, (StatusCode, String)> {
-    NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|_| {
-        (
-            StatusCode::BAD_REQUEST,
-            "Invalid date format. Use YYYY-MM-DD.".to_string(),
-        )
-    })
-}
-
-fn internal_error<E: std::fmt::Display>(_e: E) -> (StatusCode, String) {
-    (StatusCode::INTERNAL_SERVER_ERROR, "Something went wrong. Please try again.".to_string())
+fn parse_one_date(value: &str, field: &'static str) -> Result<NaiveDate, AppError> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map_err(|_| AppError::field(ErrorCode::InvalidDate, field))
 }
@@ -0,0 +1,149 @@
+use std::sync::Arc;
+
+use axum::{Json, Router, extract::State, http::StatusCode, routing::post};
+use chrono::{DateTime, Utc};
+use sea_orm::{
+    ActiveModelTrait, ConnectionTrait, DatabaseConnection, EntityTrait, Set, TransactionTrait,
+};
+
+use crate::auth::email_verification;
+use crate::auth::middleware::AuthUser;
+use crate::clock::Clock;
+use crate::config::Config;
+use crate::controllers::models::email::VerifyEmailBody;
+use crate::entities::{User, UserActiveModel, user};
+use crate::error::{AppError, ErrorCode};
+use crate::i18n::{self, Locale};
+use crate::mail::{self, MailTemplate, Mailer, Message};
+use crate::rate_limit::{LimitedRoute, RateLimiter};
+use crate::state::AppState;
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/users/me/email/verification", post(resend_verification))
+        .route("/auth/verify-email", post(verify_email))
+}
+
+#[utoipa::path(
+    post,
+    path = "/users/me/email/verification",
+    summary = "Resend verification mail",
+    description = "Mails a new verification link to the current user's address. Earlier links stop working.",
+    responses(
+        (status = 202, description = "A verification mail is on its way"),
+        (status = 400, description = "Validation error: the profile has no email address"),
+        (status = 401, description = "Unauthorized: invalid or missing authentication token"),
+        (status = 409, description = "Conflict: the address is already verified"),
+        (status = 429, description = "Too many verification mails, see Retry-After"),
+        (status = 500, description = "Server error: database error")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Users"
+)]
+pub async fn resend_verification(
+    auth: AuthUser,
+    State(db): State<Arc<DatabaseConnection>>,
+    State(config): State<Arc<Config>>,
+    State(limiter): State<Arc<RateLimiter>>,
+    State(clock): State<Arc<dyn Clock>>,
+    State(mailer): State<Arc<dyn Mailer>>,
+) -> Result<StatusCode, AppError> {
+    let user = User::find_by_id(auth.user_id)
+        .one(&*db)
+        .await?
+        .ok_or(ErrorCode::ProfileNotFound)?;
+    if user.email.is_none() {
+        return Err(ErrorCode::EmailNotSet.into());
+    }
+    if user.email_verified_at.is_some() {
+        return Err(ErrorCode::EmailAlreadyVerified.into());
+    }
+    limiter
+        .check(
+            LimitedRoute::EmailVerification,
+            None,
+            Some(&user.id.to_string()),
+        )
+        .await?;
+
+    send_verification_mail(&*db, &config, clock.now(), mailer, &user).await?;
+    Ok(StatusCode::ACCEPTED)
+}
+
+#[utoipa::path(
+    post,
+    path = "/auth/verify-email",
+    summary = "Verify email address",
+    description = "Marks the address a verification mail went to as verified. The token works once, expires after `mail.email_verification_ttl_secs` and only while the account still has that address.",
+    request_body = VerifyEmailBody,
+    responses(
+        (status = 204, description = "Address verified"),
+        (status = 400, description = "Validation error: the token is invalid, used or expired, or the address has changed since"),
+        (status = 500, description = "Server error: database error")
+    ),
+    tag = "Auth"
+)]
+pub async fn verify_email(
+    State(db): State<Arc<DatabaseConnection>>,
+    State(clock): State<Arc<dyn Clock>>,
+    Json(body): Json<VerifyEmailBody>,
+) -> Result<StatusCode, AppError> {
+    let now = clock.now();
+    let tx = db.begin().await?;
+    let user = match email_verification::consume(&tx, &body.token, now).await? {
+        Some(token) => User::find_by_id(token.user_id)
+            .one(&tx)
+            .await?
+            .filter(|user| user.email.as_deref() == Some(token.email.as_str())),
+        None => None,
+    };
+    let Some(user) = user else {
+        return Err(AppError::field(
+            ErrorCode::InvalidVerificationToken,
+            "token",
+        ));
+    };
+
+    let mut active: UserActiveModel = user.into();
+    active.email_verified_at = Set(Some(now.into()));
+    active.update(&tx).await?;
+    tx.commit().await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Mails `user` a link that verifies their current address, if they have one.
+pub(crate) async fn send_verification_mail<C: ConnectionTrait>(
+    db: &C,
+    config: &Config,
+    now: DateTime<Utc>,
+    mailer: Arc<dyn Mailer>,
+    user: &user::Model,
+) -> Result<(), AppError> {
+    let Some(email) = &user.email else {
+        return Ok(());
+    };
+    let expires_at = now + config.mail.email_verification_ttl();
+    let token = email_verification::issue(db, user.id, email, now, expires_at).await?;
+    let link = config
+        .mail
+        .email_verification_url
+        .replace("{token}", &token);
+    let hours = ((config.mail.email_verification_ttl_secs + 3599) / 3600).to_string();
+    let locale = user
+        .locale
+        .as_deref()
+        .and_then(Locale::parse)
+        .unwrap_or_else(i18n::current);
+    let message = Message::localized(
+        email,
+        MailTemplate::EmailVerification,
+        locale,
+        &[
+            ("username", &user.username),
+            ("link", &link),
+            ("hours", &hours),
+        ],
+    );
+    mail::send_later(mailer, message);
+    Ok(())
+}
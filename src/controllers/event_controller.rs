@@ -1,30 +1,38 @@
+use std::sync::Arc;
+
 use axum::{
     Json, Router,
     extract::{Path, Query, State},
     http::StatusCode,
     routing::{get, post},
 };
-use chrono::{NaiveDate, Utc};
+use chrono::NaiveDate;
+use sea_orm::sea_query::SelectStatement;
 use sea_orm::{
-    ActiveModelTrait, ColumnTrait, Condition, ConnectionTrait, DatabaseConnection, EntityTrait,
-    IntoActiveModel, PaginatorTrait, QueryFilter, QueryOrder, Set, TransactionTrait,
+    ColumnTrait, Condition, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, QuerySelect,
+    QueryTrait, Select, TransactionTrait,
 };
 use uuid::Uuid;
 
 use crate::auth::middleware::AuthUser;
+use crate::clock::{self, Clock};
+use crate::controllers::models::pagination::{Page, PageQuery};
 use crate::controllers::models::events::{
-    CreateEventBody, EventResponse, EventScope, EventScopeQuery, FinishEventBody,
+    CreateEventBody, EventHistoryResponse, EventResponse, EventScope, EventScopeQuery, FinishEventBody,
     ParticipantResponse, UserAvailabilityResponse,
 };
+use crate::controllers::pagination::{Pagination, SortKey};
 use crate::entities::event::EventStatus;
-use crate::entities::friendship::{self, FriendshipStatus};
+use crate::error::{AppError, ErrorCode};
 use crate::entities::user_event::{UserEventResponse, UserEventRole};
-use crate::entities::{
-    Busyday, BusydayActiveModel, BusydayColumn, Event, EventActiveModel, EventColumn, Friendship,
-    UserEvent, UserEventActiveModel, UserEventColumn, event,
+use crate::entities::{Event, EventColumn, UserEvent, UserEventColumn, event};
+use crate::services::{
+    CalendarService, Calendars, EventService, Events, FriendshipService, Friendships, NewEvent,
 };
+use crate::state::AppState;
+use crate::telemetry::counters;
 
-pub fn router() -> Router<DatabaseConnection> {
+pub fn router() -> Router<AppState> {
     Router::new()
         .route("/events", post(create_event).get(get_events))
         .route("/events/active", get(get_active_events))
@@ -36,6 +44,7 @@ pub fn router() -> Router<DatabaseConnection> {
         .route("/events/{id}/finish", post(finish_event))
         .route("/events/{id}/cancel", post(cancel_event))
         .route("/events/{id}/participants", get(get_event_participants))
+        .route("/events/{id}/history", get(get_event_history))
         .route("/events/{id}/accept", post(accept_event))
         .route("/events/{id}/decline", post(decline_event))
 }
@@ -59,99 +68,29 @@ pub fn router() -> Router<DatabaseConnection> {
 )]
 pub async fn create_event(
     auth: AuthUser,
-    State(db): State<DatabaseConnection>,
+    State(db): State<Arc<DatabaseConnection>>,
     Json(body): Json<CreateEventBody>,
-) -> Result<(StatusCode, Json<EventResponse>), (StatusCode, String)> {
+) -> Result<(StatusCode, Json<EventResponse>), AppError> {
     let me = auth.user_id;
     let date = parse_date(&body.date)?;
 
-    if body.title.trim().is_empty() {
-        return Err((StatusCode::BAD_REQUEST, "Please enter an event title.".to_string()));
-    }
-
-    let mut participant_ids = body.participant_ids;
-    participant_ids.retain(|id| *id != me);
-    participant_ids.sort();
-    participant_ids.dedup();
-
-    for participant_id in &participant_ids {
-        if !are_users_accepted_friends(&db, me, *participant_id).await? {
-            return Err((
-                StatusCode::FORBIDDEN,
-                "You can only invite accepted friends to events.".to_string(),
-            ));
-        }
-    }
-
-    let tx = db.begin().await.map_err(internal_error)?;
-    ensure_day_is_free(&tx, me, date).await?;
-    for participant_id in &participant_ids {
-        if let Err((status, message)) = ensure_day_is_free(&tx, *participant_id, date).await {
-            if status == StatusCode::CONFLICT {
-                return Err((
-                    StatusCode::CONFLICT,
-                    "One or more participants are already busy on this date.".to_string(),
-                ));
-            }
-            return Err((status, message));
-        }
-    }
-
-    let event = EventActiveModel {
-        creator_id: Set(me),
-        date: Set(date),
-        title: Set(body.title),
-        description: Set(body.description),
-        location: Set(body.location),
-        status: Set(EventStatus::Pending),
-        wish_place_id: Set(body.wish_place_id),
-        memory_image_base64: Set(None),
-        ..Default::default()
-    }
-    .insert(&tx)
-    .await
-    .map_err(internal_error)?;
-
-    UserEventActiveModel {
-        event_id: Set(event.id),
-        user_id: Set(me),
-        role: Set(UserEventRole::Owner),
-        response_status: Set(UserEventResponse::Accepted),
-        ..Default::default()
-    }
-    .insert(&tx)
-    .await
-    .map_err(internal_error)?;
-
-    BusydayActiveModel {
-        user_id: Set(me),
-        date: Set(date),
-        event_id: Set(Some(event.id)),
-        ..Default::default()
-    }
-    .insert(&tx)
-    .await
-    .map_err(map_db_constraint_error)?;
-
-    if !participant_ids.is_empty() {
-        let models = participant_ids
-            .into_iter()
-            .map(|participant_id| UserEventActiveModel {
-                event_id: Set(event.id),
-                user_id: Set(participant_id),
-                role: Set(UserEventRole::Participant),
-                response_status: Set(UserEventResponse::Pending),
-                ..Default::default()
-            })
-            .collect::<Vec<_>>();
-
-        UserEvent::insert_many(models)
-            .exec(&tx)
-            .await
-            .map_err(internal_error)?;
-    }
-
-    tx.commit().await.map_err(internal_error)?;
+    let tx = db.begin().await?;
+    let event = Events::new()
+        .create(
+            &tx,
+            me,
+            NewEvent {
+                date,
+                title: body.title,
+                description: body.description,
+                location: body.location,
+                wish_place_id: body.wish_place_id,
+                participant_ids: body.participant_ids,
+            },
+        )
+        .await?;
+    tx.commit().await?;
+    counters::event_created();
 
     let response = load_event_response(&db, event.id).await?;
     Ok((StatusCode::CREATED, Json(response)))
@@ -174,12 +113,12 @@ pub async fn create_event(
 )]
 pub async fn get_event(
     auth: AuthUser,
-    State(db): State<DatabaseConnection>,
+    State(db): State<Arc<DatabaseConnection>>,
     Path(id): Path<Uuid>,
-) -> Result<Json<EventResponse>, (StatusCode, String)> {
+) -> Result<Json<EventResponse>, AppError> {
     let me = auth.user_id;
-    ensure_event_access(&db, id, me).await?;
-    let event = load_event_response(&db, id).await.map_err(|_| (StatusCode::NOT_FOUND, "Event not found.".to_string()))?;
+    Events::new().ensure_access(&*db, id, me).await?;
+    let event = load_event_response(&db, id).await?;
     Ok(Json(event))
 }
 
@@ -187,10 +126,11 @@ pub async fn get_event(
     get,
     path = "/events",
     summary = "List events",
-    description = "Returns events for the current user filtered by scope (created, invited, upcoming, past).",
-    params(EventScopeQuery),
+    description = "Returns events for the current user filtered by scope (created, invited, upcoming, past). Past events are ordered newest first, all other scopes by date ascending.",
+    params(EventScopeQuery, PageQuery),
     responses(
-        (status = 200, description = "Events list retrieved successfully", body = [EventResponse]),
+        (status = 200, description = "Page of events retrieved successfully", body = Page<EventResponse>),
+        (status = 400, description = "Validation error: invalid page cursor or limit"),
         (status = 401, description = "Unauthorized: invalid or missing authentication token"),
         (status = 500, description = "Server error: failed to retrieve events")
     ),
@@ -199,89 +139,56 @@ pub async fn get_event(
 )]
 pub async fn get_events(
     auth: AuthUser,
-    State(db): State<DatabaseConnection>,
+    State(db): State<Arc<DatabaseConnection>>,
+    State(clock): State<Arc<dyn Clock>>,
     Query(query): Query<EventScopeQuery>,
-) -> Result<Json<Vec<EventResponse>>, (StatusCode, String)> {
+    pagination: Pagination,
+) -> Result<Json<Page<EventResponse>>, AppError> {
     let me = auth.user_id;
     let scope = query.scope.unwrap_or(EventScope::Upcoming);
-    let today = Utc::now().date_naive();
-
-    let events = match scope {
-        EventScope::Created => Event::find()
-            .filter(EventColumn::CreatorId.eq(me))
-            .order_by_asc(EventColumn::Date)
-            .all(&db)
-            .await
-            .map_err(internal_error)?,
-        EventScope::Invited => {
-            let event_ids = UserEvent::find()
-                .filter(UserEventColumn::UserId.eq(me))
-                .filter(UserEventColumn::Role.eq(UserEventRole::Participant))
-                .filter(UserEventColumn::ResponseStatus.ne(UserEventResponse::Declined))
-                .all(&db)
-                .await
-                .map_err(internal_error)?
-                .into_iter()
-                .map(|row| row.event_id)
-                .collect::<Vec<_>>();
-
-            if event_ids.is_empty() {
-                Vec::new()
-            } else {
-                Event::find()
-                    .filter(EventColumn::Id.is_in(event_ids))
-                    .order_by_asc(EventColumn::Date)
-                    .all(&db)
-                    .await
-                    .map_err(internal_error)?
-            }
-        }
-        EventScope::Upcoming => {
-            let event_ids = accepted_event_ids(&db, me).await?;
-            if event_ids.is_empty() {
-                Vec::new()
-            } else {
-                Event::find()
-                    .filter(EventColumn::Id.is_in(event_ids))
-                    .filter(EventColumn::Date.gte(today))
-                    .filter(EventColumn::Status.ne(EventStatus::Canceled))
-                    .order_by_asc(EventColumn::Date)
-                    .all(&db)
-                    .await
-                    .map_err(internal_error)?
-            }
-        }
-        EventScope::Past => {
-            let event_ids = accepted_event_ids(&db, me).await?;
-            if event_ids.is_empty() {
-                Vec::new()
-            } else {
-                Event::find()
-                    .filter(EventColumn::Id.is_in(event_ids))
-                    .filter(EventColumn::Date.lt(today))
-                    .order_by_desc(EventColumn::Date)
-                    .all(&db)
-                    .await
-                    .map_err(internal_error)?
-            }
-        }
+    let today = clock::user_today(&*db, clock.as_ref(), me).await?;
+
+    let (events, sort) = match scope {
+        EventScope::Created => (
+            Event::find().filter(EventColumn::CreatorId.eq(me)),
+            SortKey::asc(EventColumn::Date, EventColumn::Id),
+        ),
+        EventScope::Invited => (
+            Event::find().filter(EventColumn::Id.in_subquery(user_event_ids(
+                me,
+                Condition::all()
+                    .add(UserEventColumn::Role.eq(UserEventRole::Participant))
+                    .add(UserEventColumn::ResponseStatus.ne(UserEventResponse::Declined)),
+            ))),
+            SortKey::asc(EventColumn::Date, EventColumn::Id),
+        ),
+        EventScope::Upcoming => (
+            Event::find()
+                .filter(EventColumn::Id.in_subquery(accepted_event_ids(me)))
+                .filter(EventColumn::Date.gte(today))
+                .filter(EventColumn::Status.ne(EventStatus::Canceled)),
+            SortKey::asc(EventColumn::Date, EventColumn::Id),
+        ),
+        EventScope::Past => (
+            Event::find()
+                .filter(EventColumn::Id.in_subquery(accepted_event_ids(me)))
+                .filter(EventColumn::Date.lt(today)),
+            SortKey::desc(EventColumn::Date, EventColumn::Id),
+        ),
     };
 
-    let mut response = Vec::with_capacity(events.len());
-    for row in events {
-        response.push(load_event_response(&db, row.id).await?);
-    }
-
-    Ok(Json(response))
+    Ok(Json(fetch_events_page(&db, events, sort, &pagination).await?))
 }
 
 #[utoipa::path(
     get,
     path = "/events/active",
     summary = "Get active events",
-    description = "Returns events where all participants (including creator) have accepted status.",
+    description = "Returns events where all participants (including creator) have accepted status, ordered by date.",
+    params(PageQuery),
     responses(
-        (status = 200, description = "Active events retrieved successfully", body = [EventResponse]),
+        (status = 200, description = "Page of active events retrieved successfully", body = Page<EventResponse>),
+        (status = 400, description = "Validation error: invalid page cursor or limit"),
         (status = 401, description = "Unauthorized: invalid or missing authentication token"),
         (status = 500, description = "Server error: failed to retrieve active events")
     ),
@@ -290,75 +197,49 @@ pub async fn get_events(
 )]
 pub async fn get_active_events(
     auth: AuthUser,
-    State(db): State<DatabaseConnection>,
-) -> Result<Json<Vec<EventResponse>>, (StatusCode, String)> {
+    State(db): State<Arc<DatabaseConnection>>,
+    State(clock): State<Arc<dyn Clock>>,
+    pagination: Pagination,
+) -> Result<Json<Page<EventResponse>>, AppError> {
     let me = auth.user_id;
-    let today = Utc::now().date_naive();
-
-    let user_event_ids = UserEvent::find()
-        .filter(
-            Condition::any()
-                .add(UserEventColumn::UserId.eq(me))
-        )
-        .filter(UserEventColumn::ResponseStatus.ne(UserEventResponse::Declined))
-        .all(&db)
-        .await
-        .map_err(internal_error)?
-        .into_iter()
-        .map(|row| row.event_id)
-        .collect::<Vec<_>>();
-
-    if user_event_ids.is_empty() {
-        return Ok(Json(Vec::new()));
+    let today = clock::user_today(&*db, clock.as_ref(), me).await?;
+    let my_events = user_event_ids(
+        me,
+        Condition::all().add(UserEventColumn::ResponseStatus.ne(UserEventResponse::Declined)),
+    );
+
+    let tx = db.begin().await?;
+    let expired = Events::new().settle_past(&tx, me, today).await?;
+    tx.commit().await?;
+    for _ in 0..expired {
+        counters::event_canceled("expired");
     }
 
-    let events = Event::find()
-        .filter(EventColumn::Id.is_in(user_event_ids))
-        .all(&db)
-        .await
-        .map_err(internal_error)?;
-
-    let mut response = Vec::new();
-    for event in events {
-        if event.date < today {
-            let participants = load_participants(&db, event.id).await?;
-            let all_accepted = participants
-                .iter()
-                .all(|p| p.response_status == "accepted");
-
-            let new_status = if all_accepted {
-                EventStatus::Completed
-            } else {
-                EventStatus::Canceled
-            };
-
-            let mut active = event.into_active_model();
-            active.status = Set(new_status);
-            active.update(&db).await.map_err(internal_error)?;
-
-            continue;
-        }
-
-        let participants = load_participants(&db, event.id).await?;
-        let all_accepted = participants
-            .iter()
-            .all(|p| p.response_status == "accepted");
+    let not_accepted_by_everyone = UserEvent::find()
+        .select_only()
+        .column(UserEventColumn::EventId)
+        .filter(UserEventColumn::ResponseStatus.ne(UserEventResponse::Accepted))
+        .into_query();
 
-        if all_accepted {
-            response.push(load_event_response(&db, event.id).await?);
-        }
-    }
+    let events = Event::find()
+        .filter(EventColumn::Id.in_subquery(my_events))
+        .filter(EventColumn::Date.gte(today))
+        .filter(EventColumn::Status.ne(EventStatus::Canceled))
+        .filter(EventColumn::Id.not_in_subquery(not_accepted_by_everyone));
 
-    Ok(Json(response))
+    let sort = SortKey::asc(EventColumn::Date, EventColumn::Id);
+    Ok(Json(fetch_events_page(&db, events, sort, &pagination).await?))
 }
 
 #[utoipa::path(
     get,
     path = "/events/pending",
     summary = "Get pending events",
-    description = "Returns events where current user has accepted and event status is pending (waiting for others).",
+    description = "Returns events where current user has accepted and event status is pending (waiting for others), ordered by date.",
+    params(PageQuery),
     responses(
-        (status = 200, description = "Pending events retrieved successfully", body = [EventResponse]),
+        (status = 200, description = "Page of pending events retrieved successfully", body = Page<EventResponse>),
+        (status = 400, description = "Validation error: invalid page cursor or limit"),
         (status = 401, description = "Unauthorized: invalid or missing authentication token"),
         (status = 500, description = "Server error: failed to retrieve pending events")
     ),
@@ -367,52 +248,31 @@ pub async fn get_active_events(
 )]
 pub async fn get_pending_events(
     auth: AuthUser,
-    State(db): State<DatabaseConnection>,
-) -> Result<Json<Vec<EventResponse>>, (StatusCode, String)> {
+    State(db): State<Arc<DatabaseConnection>>,
+    pagination: Pagination,
+) -> Result<Json<Page<EventResponse>>, AppError> {
     let me = auth.user_id;
 
     // Find all events where:
     // 1. Current user is a participant with response_status == ACCEPTED
     // 2. Event status == PENDING
-    let user_events = UserEvent::find()
-        .filter(UserEventColumn::UserId.eq(me))
-        .filter(UserEventColumn::ResponseStatus.eq(UserEventResponse::Accepted))
-        .all(&db)
-        .await
-        .map_err(internal_error)?;
-
-    if user_events.is_empty() {
-        return Ok(Json(Vec::new()));
-    }
-
-    let event_ids = user_events
-        .into_iter()
-        .map(|row| row.event_id)
-        .collect::<Vec<_>>();
-
     let events = Event::find()
-        .filter(EventColumn::Id.is_in(event_ids))
-        .filter(EventColumn::Status.eq(EventStatus::Pending))
-        .order_by_asc(EventColumn::Date)
-        .all(&db)
-        .await
-        .map_err(internal_error)?;
+        .filter(EventColumn::Id.in_subquery(accepted_event_ids(me)))
+        .filter(EventColumn::Status.eq(EventStatus::Pending));
 
-    let mut response = Vec::with_capacity(events.len());
-    for event in events {
-        response.push(load_event_response(&db, event.id).await?);
-    }
-
-    Ok(Json(response))
+    let sort = SortKey::asc(EventColumn::Date, EventColumn::Id);
+    Ok(Json(fetch_events_page(&db, events, sort, &pagination).await?))
 }
 
 #[utoipa::path(
     get,
     path = "/events/waiting",
     summary = "Get events awaiting response",
-    description = "Returns events where current user is a participant and has not yet accepted the invitation.",
+    description = "Returns events where current user is a participant and has not yet accepted the invitation, ordered by date.",
+    params(PageQuery),
     responses(
-        (status = 200, description = "Events awaiting response retrieved successfully", body = [EventResponse]),
+        (status = 200, description = "Page of events awaiting response retrieved successfully", body = Page<EventResponse>),
+        (status = 400, description = "Validation error: invalid page cursor or limit"),
         (status = 401, description = "Unauthorized: invalid or missing authentication token"),
         (status = 500, description = "Server error: failed to retrieve events")
     ),
@@ -421,50 +281,34 @@ pub async fn get_pending_events(
 )]
 pub async fn get_waiting_events(
     auth: AuthUser,
-    State(db): State<DatabaseConnection>,
-) -> Result<Json<Vec<EventResponse>>, (StatusCode, String)> {
+    State(db): State<Arc<DatabaseConnection>>,
+    State(clock): State<Arc<dyn Clock>>,
+    pagination: Pagination,
+) -> Result<Json<Page<EventResponse>>, AppError> {
     let me = auth.user_id;
-    let today = Utc::now().date_naive();
+    let today = clock::user_today(&*db, clock.as_ref(), me).await?;
 
     // Find all user_events where:
     // 1. user_id == me
     // 2. role == Participant
     // 3. response_status != Accepted AND != Declined
-    let user_events = UserEvent::find()
-        .filter(UserEventColumn::UserId.eq(me))
-        .filter(UserEventColumn::Role.eq(UserEventRole::Participant))
-        .filter(UserEventColumn::ResponseStatus.ne(UserEventResponse::Accepted))
-        .filter(UserEventColumn::ResponseStatus.ne(UserEventResponse::Declined))
-        .all(&db)
-        .await
-        .map_err(internal_error)?;
-
-    if user_events.is_empty() {
-        return Ok(Json(Vec::new()));
-    }
-
-    let event_ids = user_events
-        .into_iter()
-        .map(|row| row.event_id)
-        .collect::<Vec<_>>();
-
-    // Load events and filter out past events
+    let waiting = user_event_ids(
+        me,
+        Condition::all()
+            .add(UserEventColumn::Role.eq(UserEventRole::Participant))
+            .add(UserEventColumn::ResponseStatus.ne(UserEventResponse::Accepted))
+            .add(UserEventColumn::ResponseStatus.ne(UserEventResponse::Declined)),
+    );
+
+    // Skip past and finished events
     let events = Event::find()
-        .filter(EventColumn::Id.is_in(event_ids))
+        .filter(EventColumn::Id.in_subquery(waiting))
         .filter(EventColumn::Date.gte(today))
         .filter(EventColumn::Status.ne(EventStatus::Canceled))
-        .filter(EventColumn::Status.ne(EventStatus::Completed))
-        .order_by_asc(EventColumn::Date)
-        .all(&db)
-        .await
-        .map_err(internal_error)?;
-
-    let mut response = Vec::with_capacity(events.len());
-    for event in events {
-        response.push(load_event_response(&db, event.id).await?);
-    }
+        .filter(EventColumn::Status.ne(EventStatus::Completed));
 
-    Ok(Json(response))
+    let sort = SortKey::asc(EventColumn::Date, EventColumn::Id);
+    Ok(Json(fetch_events_page(&db, events, sort, &pagination).await?))
 }
 
 #[derive(serde::Deserialize, utoipa::IntoParams)]
@@ -489,20 +333,13 @@ pub struct CheckAvailabilityQuery {
 )]
 pub async fn check_user_availability(
     auth: AuthUser,
-    State(db): State<DatabaseConnection>,
+    State(db): State<Arc<DatabaseConnection>>,
     Query(q): Query<CheckAvailabilityQuery>,
-) -> Result<Json<UserAvailabilityResponse>, (StatusCode, String)> {
+) -> Result<Json<UserAvailabilityResponse>, AppError> {
     let me = auth.user_id;
     let date = parse_date(&q.date)?;
 
-    let is_busy = Busyday::find()
-        .filter(BusydayColumn::UserId.eq(me))
-        .filter(BusydayColumn::Date.eq(date))
-        .one(&db)
-        .await
-        .map_err(internal_error)?
-        .is_some();
-
+    let is_busy = Calendars.is_busy(&*db, me, date).await?;
     Ok(Json(UserAvailabilityResponse {
         is_available: !is_busy,
     }))
@@ -525,41 +362,18 @@ pub async fn check_user_availability(
 )]
 pub async fn check_friends_availability(
     auth: AuthUser,
-    State(db): State<DatabaseConnection>,
+    State(db): State<Arc<DatabaseConnection>>,
     Query(q): Query<CheckAvailabilityQuery>,
-) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+) -> Result<Json<serde_json::Value>, AppError> {
     let me = auth.user_id;
     let date = parse_date(&q.date)?;
 
-    let accepted_friends = Friendship::find()
-        .filter(friendship::Column::Status.eq(FriendshipStatus::Accepted))
-        .filter(
-            Condition::any()
-                .add(friendship::Column::UserId.eq(me))
-                .add(friendship::Column::FriendId.eq(me)),
-        )
-        .all(&db)
-        .await
-        .map_err(internal_error)?;
-
-    let friend_ids: Vec<Uuid> = accepted_friends
-        .iter()
-        .map(|f| if f.user_id == me { f.friend_id } else { f.user_id })
-        .collect();
-
+    let friend_ids = Friendships.friend_ids(&*db, me).await?;
     if friend_ids.is_empty() {
         return Ok(Json(serde_json::json!({ "available_friends": [] })));
     }
 
-    let busy_user_ids = Busyday::find()
-        .filter(BusydayColumn::Date.eq(date))
-        .filter(BusydayColumn::UserId.is_in(friend_ids.clone()))
-        .all(&db)
-        .await
-        .map_err(internal_error)?
-        .into_iter()
-        .map(|b| b.user_id)
-        .collect::<Vec<_>>();
+    let busy_user_ids = Calendars.busy_users(&*db, friend_ids.clone(), date).await?;
 
     let available_friends = crate::entities::user::Entity::find()
         .filter(crate::entities::user::Column::Id.is_in(friend_ids))
@@ -571,9 +385,8 @@ pub async fn check_friends_availability(
             },
         )
         .order_by_asc(crate::entities::user::Column::Username)
-        .all(&db)
-        .await
-        .map_err(internal_error)?;
+        .all(&*db)
+        .await?;
 
     let response: Vec<serde_json::Value> = available_friends
         .into_iter()
@@ -610,46 +423,19 @@ pub async fn check_friends_availability(
 )]
 pub async fn finish_event(
     auth: AuthUser,
-    State(db): State<DatabaseConnection>,
+    State(db): State<Arc<DatabaseConnection>>,
+    State(clock): State<Arc<dyn Clock>>,
     Path(id): Path<Uuid>,
     Json(body): Json<FinishEventBody>,
-) -> Result<Json<EventResponse>, (StatusCode, String)> {
+) -> Result<Json<EventResponse>, AppError> {
     let me = auth.user_id;
-    let today = Utc::now().date_naive();
-
-    let event = Event::find_by_id(id)
-        .filter(EventColumn::CreatorId.eq(me))
-        .one(&db)
-        .await
-        .map_err(internal_error)?
-        .ok_or((StatusCode::NOT_FOUND, "Event not found or you are not the creator.".to_string()))?;
-
-    if matches!(event.status, EventStatus::Canceled | EventStatus::Completed) {
-        return Err((
-            StatusCode::CONFLICT,
-            "This event has already been completed or canceled.".to_string(),
-        ));
-    }
-
-    if today < event.date {
-        return Err((
-            StatusCode::CONFLICT,
-            "You can only complete an event on or after the event date.".to_string(),
-        ));
-    }
-
-    if body.memory_image_base64.trim().is_empty() {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            "Please add a memory image to complete the event.".to_string(),
-        ));
-    }
-
-    let mut active = event.into_active_model();
-    active.memory_image_base64 = Set(Some(body.memory_image_base64));
-    active.status = Set(EventStatus::Completed);
-    active.update(&db).await.map_err(internal_error)?;
+    let today = clock::user_today(&*db, clock.as_ref(), me).await?;
 
+    let tx = db.begin().await?;
+    Events::new()
+        .finish(&tx, id, me, body.memory_image_base64, today)
+        .await?;
+    tx.commit().await?;
     Ok(Json(load_event_response(&db, id).await?))
 }
 
@@ -657,13 +443,13 @@ pub async fn finish_event(
     post,
     path = "/events/{id}/cancel",
     summary = "Cancel event",
-    description = "Cancels and deletes event. Only creator can cancel. Removes all participant reservations.",
+    description = "Cancels event. Only creator can cancel. Removes all participant reservations; the event stays visible to participants with canceled status.",
     params(("id" = Uuid, Path, description = "Event ID")),
     responses(
         (status = 204, description = "Event canceled successfully"),
         (status = 401, description = "Unauthorized: invalid or missing authentication token"),
         (status = 404, description = "Event not found or you are not the creator"),
-        (status = 409, description = "Conflict: completed events cannot be canceled"),
+        (status = 409, description = "Conflict: event is already canceled or completed"),
         (status = 500, description = "Server error: failed to cancel event")
     ),
     security(("bearer_auth" = [])),
@@ -671,45 +457,14 @@ pub async fn finish_event(
 )]
 pub async fn cancel_event(
     auth: AuthUser,
-    State(db): State<DatabaseConnection>,
+    State(db): State<Arc<DatabaseConnection>>,
     Path(id): Path<Uuid>,
-) -> Result<StatusCode, (StatusCode, String)> {
+) -> Result<StatusCode, AppError> {
     let me = auth.user_id;
-    let tx = db.begin().await.map_err(internal_error)?;
-
-    let event = Event::find_by_id(id)
-        .filter(EventColumn::CreatorId.eq(me))
-        .one(&tx)
-        .await
-        .map_err(internal_error)?
-        .ok_or((StatusCode::NOT_FOUND, "event not found".to_string()))?;
-
-    if matches!(event.status, EventStatus::Completed) {
-        return Err((
-            StatusCode::CONFLICT,
-            "Completed events cannot be canceled.".to_string(),
-        ));
-    }
-
-    Busyday::delete_many()
-        .filter(BusydayColumn::EventId.eq(id))
-        .exec(&tx)
-        .await
-        .map_err(internal_error)?;
-
-    UserEvent::delete_many()
-        .filter(UserEventColumn::EventId.eq(id))
-        .exec(&tx)
-        .await
-        .map_err(internal_error)?;
-
-    event
-        .into_active_model()
-        .delete(&tx)
-        .await
-        .map_err(internal_error)?;
-
-    tx.commit().await.map_err(internal_error)?;
+    let tx = db.begin().await?;
+    Events::new().cancel(&tx, id, me).await?;
+    tx.commit().await?;
+    counters::event_canceled("owner");
 
     Ok(StatusCode::NO_CONTENT)
 }
@@ -732,16 +487,54 @@ pub async fn cancel_event(
 )]
 pub async fn get_event_participants(
     auth: AuthUser,
-    State(db): State<DatabaseConnection>,
+    State(db): State<Arc<DatabaseConnection>>,
     Path(id): Path<Uuid>,
-) -> Result<Json<Vec<ParticipantResponse>>, (StatusCode, String)> {
+) -> Result<Json<Vec<ParticipantResponse>>, AppError> {
     let me = auth.user_id;
-    ensure_event_access(&db, id, me).await?;
+    Events::new().ensure_access(&*db, id, me).await?;
 
     let participants = load_participants(&db, id).await?;
     Ok(Json(participants))
 }
 
+#[utoipa::path(
+    get,
+    path = "/events/{id}/history",
+    summary = "Get event history",
+    description = "Returns every status change of the event, oldest first, with who made it and why.",
+    params(("id" = Uuid, Path, description = "Event ID")),
+    responses(
+        (status = 200, description = "Event history retrieved successfully", body = [EventHistoryResponse]),
+        (status = 401, description = "Unauthorized: invalid or missing authentication token"),
+        (status = 403, description = "Forbidden: you are not a participant in this event"),
+        (status = 404, description = "Event not found"),
+        (status = 500, description = "Server error: failed to retrieve event history")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Events"
+)]
+pub async fn get_event_history(
+    auth: AuthUser,
+    State(db): State<Arc<DatabaseConnection>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<EventHistoryResponse>>, AppError> {
+    let me = auth.user_id;
+    let history = Events::new().history(&*db, id, me).await?;
+
+    Ok(Json(
+        history
+            .into_iter()
+            .map(|row| EventHistoryResponse {
+                actor_id: row.actor_id,
+                from_status: row.from_status.map(|status| status.to_string()),
+                to_status: row.to_status.to_string(),
+                reason: row.reason.to_string(),
+                created_at: row.created_at.to_rfc3339(),
+            })
+            .collect(),
+    ))
+}
+
 #[utoipa::path(
     post,
     path = "/events/{id}/accept",
@@ -760,71 +553,17 @@ pub async fn get_event_participants(
 )]
 pub async fn accept_event(
     auth: AuthUser,
-    State(db): State<DatabaseConnection>,
+    State(db): State<Arc<DatabaseConnection>>,
     Path(id): Path<Uuid>,
-) -> Result<Json<EventResponse>, (StatusCode, String)> {
+) -> Result<Json<EventResponse>, AppError> {
     let me = auth.user_id;
-    let tx = db.begin().await.map_err(internal_error)?;
-
-    let event = Event::find_by_id(id)
-        .one(&tx)
-        .await
-        .map_err(internal_error)?
-        .ok_or((StatusCode::NOT_FOUND, "Event not found.".to_string()))?;
-
-    if matches!(event.status, EventStatus::Canceled | EventStatus::Completed) {
-        return Err((
-            StatusCode::CONFLICT,
-            "This event has already been completed or canceled.".to_string(),
-        ));
-    }
-
-    let participant = UserEvent::find()
-        .filter(UserEventColumn::EventId.eq(id))
-        .filter(UserEventColumn::UserId.eq(me))
-        .filter(UserEventColumn::Role.eq(UserEventRole::Participant))
-        .filter(UserEventColumn::ResponseStatus.eq(UserEventResponse::Pending))
-        .one(&tx)
-        .await
-        .map_err(internal_error)?
-        .ok_or((
-            StatusCode::NOT_FOUND,
-            "You are not a pending participant in this event.".to_string(),
-        ))?;
-
-    ensure_day_is_free(&tx, me, event.date).await?;
-
-    let mut active = participant.into_active_model();
-    active.response_status = Set(UserEventResponse::Accepted);
-    active.update(&tx).await.map_err(internal_error)?;
-
-    BusydayActiveModel {
-        user_id: Set(me),
-        date: Set(event.date),
-        event_id: Set(Some(id)),
-        ..Default::default()
-    }
-    .insert(&tx)
-    .await
-    .map_err(map_db_constraint_error)?;
-
-    let non_accepted_exists = UserEvent::find()
-        .filter(UserEventColumn::EventId.eq(id))
-        .filter(UserEventColumn::Role.eq(UserEventRole::Participant))
-        .filter(UserEventColumn::ResponseStatus.ne(UserEventResponse::Accepted))
-        .one(&tx)
-        .await
-        .map_err(internal_error)?
-        .is_some();
-
-    if !non_accepted_exists {
-        let mut event_active = event.into_active_model();
-        event_active.status = Set(EventStatus::Confirmed);
-        event_active.update(&tx).await.map_err(internal_error)?;
+    let tx = db.begin().await?;
+    let status = Events::new().accept(&tx, id, me).await?;
+    tx.commit().await?;
+    if status == EventStatus::Confirmed {
+        counters::event_confirmed();
     }
 
-    tx.commit().await.map_err(internal_error)?;
-
     Ok(Json(load_event_response(&db, id).await?))
 }
 
@@ -846,118 +585,28 @@ pub async fn accept_event(
 )]
 pub async fn decline_event(
     auth: AuthUser,
-    State(db): State<DatabaseConnection>,
+    State(db): State<Arc<DatabaseConnection>>,
     Path(id): Path<Uuid>,
-) -> Result<StatusCode, (StatusCode, String)> {
+) -> Result<StatusCode, AppError> {
     let me = auth.user_id;
-    let tx = db.begin().await.map_err(internal_error)?;
-
-    let event = Event::find_by_id(id)
-        .one(&tx)
-        .await
-        .map_err(internal_error)?
-        .ok_or((StatusCode::NOT_FOUND, "Event not found.".to_string()))?;
-
-    if matches!(event.status, EventStatus::Canceled | EventStatus::Completed) {
-        return Err((
-            StatusCode::CONFLICT,
-            "This event has already been completed or canceled.".to_string(),
-        ));
-    }
-
-    let participant = UserEvent::find()
-        .filter(UserEventColumn::EventId.eq(id))
-        .filter(UserEventColumn::UserId.eq(me))
-        .filter(UserEventColumn::Role.eq(UserEventRole::Participant))
-        .filter(
-            Condition::any()
-                .add(UserEventColumn::ResponseStatus.eq(UserEventResponse::Pending))
-                .add(UserEventColumn::ResponseStatus.eq(UserEventResponse::Accepted)),
-        )
-        .one(&tx)
-        .await
-        .map_err(internal_error)?
-        .ok_or((StatusCode::NOT_FOUND, "You are not a participant in this event.".to_string()))?;
-
-    let mut participant_active = participant.into_active_model();
-    participant_active.response_status = Set(UserEventResponse::Declined);
-    participant_active
-        .update(&tx)
-        .await
-        .map_err(internal_error)?;
-
-    Busyday::delete_many()
-        .filter(BusydayColumn::EventId.eq(id))
-        .filter(BusydayColumn::UserId.eq(me))
-        .exec(&tx)
-        .await
-        .map_err(internal_error)?;
-
-    let participant_total = UserEvent::find()
-        .filter(UserEventColumn::EventId.eq(id))
-        .filter(UserEventColumn::Role.eq(UserEventRole::Participant))
-        .count(&tx)
-        .await
-        .map_err(internal_error)?;
-
-    let was_confirmed = matches!(event.status, EventStatus::Confirmed);
-    let mut event_active = event.into_active_model();
-    if participant_total <= 1 {
-        event_active.status = Set(EventStatus::Canceled);
-        Busyday::delete_many()
-            .filter(BusydayColumn::EventId.eq(id))
-            .exec(&tx)
-            .await
-            .map_err(internal_error)?;
-    } else if was_confirmed {
-        event_active.status = Set(EventStatus::Pending);
+    let tx = db.begin().await?;
+    let status = Events::new().decline(&tx, id, me).await?;
+    tx.commit().await?;
+    if status == EventStatus::Canceled {
+        counters::event_canceled("declined");
     }
-    event_active.update(&tx).await.map_err(internal_error)?;
-
-    tx.commit().await.map_err(internal_error)?;
 
     Ok(StatusCode::NO_CONTENT)
 }
 
-async fn ensure_event_access(
-    db: &DatabaseConnection,
-    event_id: Uuid,
-    user_id: Uuid,
-) -> Result<(), (StatusCode, String)> {
-    let event_exists = Event::find_by_id(event_id)
-        .one(db)
-        .await
-        .map_err(internal_error)?
-        .is_some();
-
-    if !event_exists {
-        return Err((StatusCode::NOT_FOUND, "event not found".to_string()));
-    }
-
-    let has_access = UserEvent::find()
-        .filter(UserEventColumn::EventId.eq(event_id))
-        .filter(UserEventColumn::UserId.eq(user_id))
-        .one(db)
-        .await
-        .map_err(internal_error)?
-        .is_some();
-
-    if !has_access {
-        return Err((StatusCode::FORBIDDEN, "forbidden".to_string()));
-    }
-
-    Ok(())
-}
-
 async fn load_event_response(
     db: &DatabaseConnection,
     event_id: Uuid,
-) -> Result<EventResponse, (StatusCode, String)> {
+) -> Result<EventResponse, AppError> {
     let event = event::Entity::find_by_id(event_id)
         .one(db)
-        .await
-        .map_err(internal_error)?
-        .ok_or((StatusCode::NOT_FOUND, "Event not found.".to_string()))?;
+        .await?
+        .ok_or(ErrorCode::EventNotFound)?;
 
     let participants = load_participants(db, event_id).await?;
 
@@ -979,15 +628,14 @@ async fn load_event_response(
 async fn load_participants(
     db: &DatabaseConnection,
     event_id: Uuid,
-) -> Result<Vec<ParticipantResponse>, (StatusCode, String)> {
+) -> Result<Vec<ParticipantResponse>, AppError> {
     use crate::entities::User;
 
     let rows = UserEvent::find()
         .filter(UserEventColumn::EventId.eq(event_id))
         .order_by_asc(UserEventColumn::UserId)
         .all(db)
-        .await
-        .map_err(internal_error)?;
+        .await?;
 
     let mut participants = Vec::new();
     for row in rows {
@@ -1005,82 +653,49 @@ async fn load_participants(
     Ok(participants)
 }
 
-async fn accepted_event_ids(
+async fn fetch_events_page(
     db: &DatabaseConnection,
-    user_id: Uuid,
-) -> Result<Vec<Uuid>, (StatusCode, String)> {
-    let rows = UserEvent::find()
-        .filter(UserEventColumn::UserId.eq(user_id))
-        .filter(UserEventColumn::ResponseStatus.eq(UserEventResponse::Accepted))
-        .all(db)
-        .await
-        .map_err(internal_error)?;
-
-    Ok(rows.into_iter().map(|row| row.event_id).collect())
-}
-
-async fn ensure_day_is_free<C: ConnectionTrait>(
-    db: &C,
-    user_id: Uuid,
-    date: NaiveDate,
-) -> Result<(), (StatusCode, String)> {
-    let exists = Busyday::find()
-        .filter(BusydayColumn::UserId.eq(user_id))
-        .filter(BusydayColumn::Date.eq(date))
-        .one(db)
-        .await
-        .map_err(internal_error)?
-        .is_some();
+    events: Select<Event>,
+    sort: SortKey<EventColumn>,
+    pagination: &Pagination,
+) -> Result<Page<EventResponse>, AppError> {
+    let page = pagination
+        .fetch::<_, NaiveDate, _>(db, events, sort, true, |event| {
+            (event.date.to_string(), event.id)
+        })
+        .await?;
 
-    if exists {
-        return Err((
-            StatusCode::CONFLICT,
-            "This date is already reserved.".to_string(),
-        ));
+    let mut items = Vec::with_capacity(page.items.len());
+    for event in &page.items {
+        items.push(load_event_response(db, event.id).await?);
     }
 
-    Ok(())
-}
-
-async fn are_users_accepted_friends(
-    db: &DatabaseConnection,
-    user_a: Uuid,
-    user_b: Uuid,
-) -> Result<bool, (StatusCode, String)> {
-    let row = Friendship::find()
-        .filter(friendship::Column::Status.eq(FriendshipStatus::Accepted))
-        .filter(
-            Condition::any()
-                .add(
-                    Condition::all()
-                        .add(friendship::Column::UserId.eq(user_a))
-                        .add(friendship::Column::FriendId.eq(user_b)),
-                )
-                .add(
-                    Condition::all()
-                        .add(friendship::Column::UserId.eq(user_b))
-                        .add(friendship::Column::FriendId.eq(user_a)),
-                ),
-        )
-        .one(db)
-        .await
-        .map_err(internal_error)?;
-    Ok(row.is_some())
+    Ok(Page {
+        items,
+        limit: page.limit,
+        next_cursor: page.next_cursor,
+        total: page.total,
+    })
 }
 
-fn parse_date(value: &str) -> Result<NaiveDate, (StatusCode, String)> {
-    NaiveDate::parse_from_str(value, "%Y-%m-%d")
-        .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid date format. Use YYYY-MM-DD.".to_string()))
+/// Subquery selecting ids of the user's events whose `user_events` row matches `condition`.
+fn user_event_ids(user_id: Uuid, condition: Condition) -> SelectStatement {
+    UserEvent::find()
+        .select_only()
+        .column(UserEventColumn::EventId)
+        .filter(UserEventColumn::UserId.eq(user_id))
+        .filter(condition)
+        .into_query()
 }
 
-fn map_db_constraint_error(err: sea_orm::DbErr) -> (StatusCode, String) {
-    let message = err.to_string();
-    if message.contains("unique") || message.contains("duplicate key") {
-        return (StatusCode::CONFLICT, "This date is already reserved.".to_string());
-    }
-    internal_error(err)
+fn accepted_event_ids(user_id: Uuid) -> SelectStatement {
+    user_event_ids(
+        user_id,
+        Condition::all().add(UserEventColumn::ResponseStatus.eq(UserEventResponse::Accepted)),
+    )
 }
 
-fn internal_error<E: std::fmt::Display>(_e: E) -> (StatusCode, String) {
-    (StatusCode::INTERNAL_SERVER_ERROR, "Something went wrong. Please try again.".to_string())
+fn parse_date(value: &str) -> Result<NaiveDate, AppError> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map_err(|_| AppError::field(ErrorCode::InvalidDate, "date"))
 }
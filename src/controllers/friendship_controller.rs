@@ -1,21 +1,25 @@
 use crate::auth::middleware::AuthUser;
+use crate::controllers::models::pagination::{Page, PageQuery};
 use crate::controllers::models::{FriendIdBody, UserDTO};
+use crate::controllers::pagination::{Pagination, SortKey};
 use crate::entities::friendship::FriendshipStatus;
-use crate::entities::{Friendship, User, UserColumn, friendship, user};
+use crate::error::AppError;
+use crate::entities::{Friendship, FriendshipColumn, User, UserColumn, user};
+use crate::services::{FriendshipService, Friendships};
+use crate::state::AppState;
+use crate::telemetry::counters;
+use std::sync::Arc;
 use axum::extract::{Path, State};
 use axum::http::StatusCode;
 use axum::routing::{delete, get, post};
 use axum::{Json, Router};
 use sea_orm::{
-    ActiveModelTrait, ColumnTrait, Condition, DatabaseConnection, EntityTrait, IntoActiveModel,
-    QueryFilter, Set,
+    ColumnTrait, Condition, DatabaseConnection, EntityTrait, QueryFilter, QuerySelect, QueryTrait,
+    Select,
 };
 use uuid::Uuid;
 
-pub use friendship::ActiveModel as FriendshipActive;
-pub use friendship::Column as FriendshipColumn;
-
-pub fn router() -> Router<DatabaseConnection> {
+pub fn router() -> Router<AppState> {
     Router::new()
         .route("/friends", get(get_friends))
         .route("/friends/request", post(friend_request))
@@ -29,8 +33,10 @@ pub fn router() -> Router<DatabaseConnection> {
 #[utoipa::path(
     get,
     path = "/friends",
+    params(PageQuery),
     responses(
-        (status = 200, description = "List of accepted friends retrieved successfully", body = [UserDTO]),
+        (status = 200, description = "Page of accepted friends ordered by username", body = Page<UserDTO>),
+        (status = 400, description = "Validation error: invalid page cursor or limit"),
         (status = 401, description = "Unauthorized: invalid or missing authentication token"),
         (status = 500, description = "Server error: failed to retrieve friends list")
     ),
@@ -41,51 +47,40 @@ pub fn router() -> Router<DatabaseConnection> {
 )]
 pub async fn get_friends(
     auth: AuthUser,
-    State(db_connection): State<DatabaseConnection>,
-) -> Result<Json<Vec<UserDTO>>, (StatusCode, String)> {
+    State(db_connection): State<Arc<DatabaseConnection>>,
+    pagination: Pagination,
+) -> Result<Json<Page<UserDTO>>, AppError> {
     let me = auth.user_id;
-    let rows = Friendship::find()
-        .filter(friendship::Column::Status.eq(FriendshipStatus::Accepted))
-        .filter(
-            Condition::any()
-                .add(FriendshipColumn::UserId.eq(me))
-                .add(FriendshipColumn::FriendId.eq(me)),
-        )
-        .all(&db_connection)
-        .await
-        .map_err(internal_error)?;
-
-    let ids: Vec<Uuid> = rows
-        .into_iter()
-        .map(|row| {
-            if row.friend_id == me {
-                row.user_id
-            } else {
-                row.friend_id
-            }
-        })
-        .collect();
-
-    if ids.is_empty() {
-        return Ok(Json(vec![]));
-    }
+    let added_by_me = Friendship::find()
+        .select_only()
+        .column(FriendshipColumn::FriendId)
+        .filter(FriendshipColumn::UserId.eq(me))
+        .filter(FriendshipColumn::Status.eq(FriendshipStatus::Accepted))
+        .into_query();
+    let added_me = Friendship::find()
+        .select_only()
+        .column(FriendshipColumn::UserId)
+        .filter(FriendshipColumn::FriendId.eq(me))
+        .filter(FriendshipColumn::Status.eq(FriendshipStatus::Accepted))
+        .into_query();
 
-    let users = User::find()
-        .filter(UserColumn::Id.is_in(ids))
-        .all(&db_connection)
-        .await
-        .map_err(internal_error)?;
+    let users = User::find().filter(
+        Condition::any()
+            .add(UserColumn::Id.in_subquery(added_by_me))
+            .add(UserColumn::Id.in_subquery(added_me)),
+    );
 
-    Ok(Json(
-        users.into_iter().map(|model| to_user_dto(&model)).collect(),
-    ))
+    let page = fetch_users_page(&db_connection, users, &pagination).await?;
+    Ok(Json(page))
 }
 
 #[utoipa::path(
     get,
     path = "/friends/incoming",
+    params(PageQuery),
     responses(
-        (status = 200, description = "List of incoming friend requests retrieved successfully", body = [UserDTO]),
+        (status = 200, description = "Page of users who sent you a friend request, ordered by username", body = Page<UserDTO>),
+        (status = 400, description = "Validation error: invalid page cursor or limit"),
         (status = 401, description = "Unauthorized: invalid or missing authentication token"),
         (status = 500, description = "Server error: failed to retrieve incoming requests")
     ),
@@ -96,38 +91,30 @@ pub async fn get_friends(
 )]
 pub async fn get_incoming(
     auth: AuthUser,
-    State(db_connection): State<DatabaseConnection>,
-) -> Result<Json<Vec<UserDTO>>, (StatusCode, String)> {
+    State(db_connection): State<Arc<DatabaseConnection>>,
+    pagination: Pagination,
+) -> Result<Json<Page<UserDTO>>, AppError> {
     let me = auth.user_id;
 
-    let rows = Friendship::find()
+    let senders = Friendship::find()
+        .select_only()
+        .column(FriendshipColumn::UserId)
         .filter(FriendshipColumn::FriendId.eq(me))
         .filter(FriendshipColumn::Status.eq(FriendshipStatus::Pending))
-        .all(&db_connection)
-        .await
-        .map_err(internal_error)?;
-
-    let ids: Vec<Uuid> = rows.into_iter().map(|row| row.user_id).collect();
-    if ids.is_empty() {
-        return Ok(Json(vec![]));
-    }
+        .into_query();
 
-    let users = User::find()
-        .filter(UserColumn::Id.is_in(ids))
-        .all(&db_connection)
-        .await
-        .map_err(internal_error)?;
-
-    Ok(Json(
-        users.into_iter().map(|model| to_user_dto(&model)).collect(),
-    ))
+    let users = User::find().filter(UserColumn::Id.in_subquery(senders));
+    let page = fetch_users_page(&db_connection, users, &pagination).await?;
+    Ok(Json(page))
 }
 
 #[utoipa::path(
     get,
     path = "/friends/outgoing",
+    params(PageQuery),
     responses(
-        (status = 200, description = "List of outgoing friend requests retrieved successfully", body = [UserDTO]),
+        (status = 200, description = "Page of users you sent a friend request to, ordered by username", body = Page<UserDTO>),
+        (status = 400, description = "Validation error: invalid page cursor or limit"),
         (status = 401, description = "Unauthorized: invalid or missing authentication token"),
         (status = 500, description = "Server error: failed to retrieve outgoing requests")
     ),
@@ -138,31 +125,21 @@ pub async fn get_incoming(
 )]
 pub async fn get_outgoing(
     auth: AuthUser,
-    State(db_connection): State<DatabaseConnection>,
-) -> Result<Json<Vec<UserDTO>>, (StatusCode, String)> {
+    State(db_connection): State<Arc<DatabaseConnection>>,
+    pagination: Pagination,
+) -> Result<Json<Page<UserDTO>>, AppError> {
     let me = auth.user_id;
 
-    let rows = Friendship::find()
+    let receivers = Friendship::find()
+        .select_only()
+        .column(FriendshipColumn::FriendId)
         .filter(FriendshipColumn::UserId.eq(me))
         .filter(FriendshipColumn::Status.eq(FriendshipStatus::Pending))
-        .all(&db_connection)
-        .await
-        .map_err(internal_error)?;
-
-    let ids: Vec<Uuid> = rows.into_iter().map(|row| row.friend_id).collect();
-    if ids.is_empty() {
-        return Ok(Json(vec![]));
-    }
+        .into_query();
 
-    let users = User::find()
-        .filter(UserColumn::Id.is_in(ids))
-        .all(&db_connection)
-        .await
-        .map_err(internal_error)?;
-
-    Ok(Json(
-        users.into_iter().map(|model| to_user_dto(&model)).collect(),
-    ))
+    let users = User::find().filter(UserColumn::Id.in_subquery(receivers));
+    let page = fetch_users_page(&db_connection, users, &pagination).await?;
+    Ok(Json(page))
 }
 
 #[utoipa::path(
@@ -173,6 +150,7 @@ pub async fn get_outgoing(
         (status = 201, description = "Friend request sent successfully"),
         (status = 400, description = "Validation error: cannot add yourself as a friend"),
         (status = 401, description = "Unauthorized: invalid or missing authentication token"),
+        (status = 409, description = "Conflict: a friend request to this user already exists"),
         (status = 500, description = "Server error: failed to create friend request")
     ),
     security(
@@ -182,25 +160,13 @@ pub async fn get_outgoing(
 )]
 pub async fn friend_request(
     auth: AuthUser,
-    State(db_connection): State<DatabaseConnection>,
+    State(db_connection): State<Arc<DatabaseConnection>>,
     Json(body): Json<FriendIdBody>,
-) -> Result<StatusCode, (StatusCode, String)> {
-    let me = auth.user_id;
-    if body.friend_id == me {
-        return Err((StatusCode::BAD_REQUEST, "You cannot add yourself as a friend.".to_string()));
-    }
-
-    let frindship_active = FriendshipActive {
-        user_id: Set(me),
-        friend_id: Set(body.friend_id),
-        status: Set(FriendshipStatus::Pending),
-        ..Default::default()
-    };
-
-    frindship_active
-        .insert(&db_connection)
-        .await
-        .map_err(internal_error)?;
+) -> Result<StatusCode, AppError> {
+    Friendships
+        .send_request(&*db_connection, auth.user_id, body.friend_id)
+        .await?;
+    counters::friend_request_sent();
     Ok(StatusCode::CREATED)
 }
 
@@ -224,38 +190,12 @@ pub async fn friend_request(
 )]
 pub async fn remove_friend(
     auth: AuthUser,
-    State(db_connection): State<DatabaseConnection>,
+    State(db_connection): State<Arc<DatabaseConnection>>,
     Path(friend_id): Path<Uuid>,
-) -> Result<StatusCode, (StatusCode, String)> {
-    let me = auth.user_id;
-    if me == friend_id {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            "You cannot remove yourself.".to_string(),
-        ));
-    }
-    let result = Friendship::delete_many()
-        .filter(FriendshipColumn::Status.eq(FriendshipStatus::Accepted))
-        .filter(
-            Condition::any()
-                .add(
-                    Condition::all()
-                        .add(FriendshipColumn::UserId.eq(me))
-                        .add(FriendshipColumn::FriendId.eq(friend_id)),
-                )
-                .add(
-                    Condition::all()
-                        .add(FriendshipColumn::UserId.eq(friend_id))
-                        .add(FriendshipColumn::FriendId.eq(me)),
-                ),
-        )
-        .exec(&db_connection)
-        .await
-        .map_err(internal_error)?;
-    if result.rows_affected == 0 {
-        return Err((StatusCode::NOT_FOUND, "This friendship does not exist.".to_string()));
-    };
-
+) -> Result<StatusCode, AppError> {
+    Friendships
+        .remove(&*db_connection, auth.user_id, friend_id)
+        .await?;
     Ok(StatusCode::NO_CONTENT)
 }
 
@@ -279,38 +219,13 @@ pub async fn remove_friend(
 )]
 pub async fn accept_friend_request(
     auth: AuthUser,
-    State(db_connection): State<DatabaseConnection>,
+    State(db_connection): State<Arc<DatabaseConnection>>,
     Path(sender_user_id): Path<Uuid>,
-) -> Result<StatusCode, (StatusCode, String)> {
-    let me = auth.user_id;
-
-    if sender_user_id == me {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            "You cannot accept your own request.".to_string(),
-        ));
-    }
-
-    let row = Friendship::find()
-        .filter(FriendshipColumn::UserId.eq(sender_user_id))
-        .filter(FriendshipColumn::FriendId.eq(me))
-        .filter(FriendshipColumn::Status.eq(FriendshipStatus::Pending))
-        .one(&db_connection)
-        .await
-        .map_err(internal_error)?;
-
-    let Some(row) = row else {
-        return Err((StatusCode::NOT_FOUND, "This friend request does not exist.".to_string()));
-    };
-
-    let mut active = row.into_active_model();
-    active.status = Set(FriendshipStatus::Accepted);
-
-    active
-        .update(&db_connection)
-        .await
-        .map_err(internal_error)?;
-
+) -> Result<StatusCode, AppError> {
+    Friendships
+        .accept_request(&*db_connection, auth.user_id, sender_user_id)
+        .await?;
+    counters::friend_request_accepted();
     Ok(StatusCode::NO_CONTENT)
 }
 
@@ -334,40 +249,31 @@ pub async fn accept_friend_request(
 )]
 pub async fn reject_friend_request(
     auth: AuthUser,
-    State(db_connection): State<DatabaseConnection>,
+    State(db_connection): State<Arc<DatabaseConnection>>,
     Path(sender_user_id): Path<Uuid>,
-) -> Result<StatusCode, (StatusCode, String)> {
-    let me = auth.user_id;
-    if sender_user_id == me {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            "You cannot reject your own request.".to_string(),
-        ));
-    }
-
-    let row = Friendship::find()
-        .filter(FriendshipColumn::UserId.eq(sender_user_id))
-        .filter(FriendshipColumn::FriendId.eq(me))
-        .filter(FriendshipColumn::Status.eq(FriendshipStatus::Pending))
-        .one(&db_connection)
-        .await
-        .map_err(internal_error)?;
-
-    let Some(row) = row else {
-        return Err((StatusCode::NOT_FOUND, "This friend request does not exist.".to_string()));
-    };
-
-    row.into_active_model()
-        .delete(&db_connection)
-        .await
-        .map_err(internal_error)?;
-
+) -> Result<StatusCode, AppError> {
+    Friendships
+        .reject_request(&*db_connection, auth.user_id, sender_user_id)
+        .await?;
     Ok(StatusCode::NO_CONTENT)
 }
 
 // MARK: Helper methods
-fn internal_error<E: std::fmt::Display>(_e: E) -> (StatusCode, String) {
-    (StatusCode::INTERNAL_SERVER_ERROR, "Something went wrong. Please try again.".to_string())
+async fn fetch_users_page(
+    db_connection: &DatabaseConnection,
+    users: Select<User>,
+    pagination: &Pagination,
+) -> Result<Page<UserDTO>, AppError> {
+    let page = pagination
+        .fetch::<_, String, _>(
+            db_connection,
+            users,
+            SortKey::asc(UserColumn::Username, UserColumn::Id),
+            true,
+            |model| (model.username.clone(), model.id),
+        )
+        .await?;
+    Ok(page.map(|model| to_user_dto(&model)))
 }
 
 fn to_user_dto(u: &user::Model) -> UserDTO {
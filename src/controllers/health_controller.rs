@@ -0,0 +1,78 @@
+use std::sync::Arc;
+
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::{Json, Router};
+use sea_orm::DatabaseConnection;
+use sea_orm_migration::MigratorTrait;
+use tokio_util::sync::CancellationToken;
+
+use crate::controllers::models::health::{HealthResponse, ReadinessResponse};
+use crate::migration::Migrator;
+use crate::state::AppState;
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/healthz", get(healthz))
+        .route("/readyz", get(readyz))
+}
+
+#[utoipa::path(
+    get,
+    path = "/healthz",
+    responses(
+        (status = 200, description = "The process is running", body = HealthResponse)
+    ),
+    tag = "Monitoring"
+)]
+pub async fn healthz() -> Json<HealthResponse> {
+    Json(HealthResponse {
+        status: "ok".to_string(),
+    })
+}
+
+#[utoipa::path(
+    get,
+    path = "/readyz",
+    responses(
+        (status = 200, description = "Ready to serve traffic", body = ReadinessResponse),
+        (status = 503, description = "Database unreachable, migrations pending or shutting down", body = ReadinessResponse)
+    ),
+    tag = "Monitoring"
+)]
+pub async fn readyz(
+    State(db): State<Arc<DatabaseConnection>>,
+    State(shutdown): State<CancellationToken>,
+) -> (StatusCode, Json<ReadinessResponse>) {
+    let database = db.ping().await.is_ok();
+    let pending_migrations = if database {
+        match Migrator::get_pending_migrations(&*db).await {
+            Ok(pending) => Some(pending.len()),
+            Err(err) => {
+                tracing::warn!(error = %err, "cannot read migration status");
+                None
+            }
+        }
+    } else {
+        None
+    };
+    let shutting_down = shutdown.is_cancelled();
+
+    let ready = database && pending_migrations == Some(0) && !shutting_down;
+    let status = if ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    (
+        status,
+        Json(ReadinessResponse {
+            ready,
+            database,
+            pending_migrations,
+            shutting_down,
+        }),
+    )
+}
@@ -0,0 +1,286 @@
+use std::sync::Arc;
+
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+};
+use chrono::{DateTime, Utc};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QuerySelect,
+    Set, TransactionTrait,
+};
+
+use crate::auth::keys::JwtKeys;
+use crate::auth::middleware::AuthUser;
+use crate::auth::{accounts, identities, passkeys};
+use crate::clock::Clock;
+use crate::config::Config;
+use crate::controllers::auth_controller::{complete_login, device_info, start_session};
+use crate::controllers::models::identity::{IdentityResponse, LinkIdentityBody, OidcLoginBody};
+use crate::controllers::models::{LoginResponse, TwoFactorChallengeResponse};
+use crate::controllers::users_controller::EMAIL_UNIQUE_INDEX;
+use crate::db;
+use crate::entities::{User, UserActiveModel, UserColumn, user, user_identity};
+use crate::error::{AppError, ErrorCode};
+use crate::oidc::{IdTokenClaims, OidcVerifier};
+use crate::rate_limit::{ClientIp, LimitedRoute, RateLimiter};
+use crate::state::AppState;
+use crate::telemetry::counters;
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/auth/oidc/{provider}", post(oidc_login))
+        .route("/users/me/identities", get(get_identities))
+        .route(
+            "/users/me/identities/{provider}",
+            post(link_identity).delete(unlink_identity),
+        )
+}
+
+#[utoipa::path(
+    post,
+    path = "/auth/oidc/{provider}",
+    summary = "Log in with a sign-in provider",
+    description = "Logs in with an ID token from a provider in `oidc.providers`, e.g. Sign in with Apple or Google. The first login creates an account without a password; its verified address is taken over unless another account already has it. Accounts with two-factor login get a challenge like at `/auth/login`.",
+    params(("provider" = String, Path, description = "Provider name, e.g. `google`")),
+    request_body = OidcLoginBody,
+    responses(
+        (status = 200, description = "Login successful", body = LoginResponse),
+        (status = 201, description = "Account created and logged in", body = LoginResponse),
+        (status = 202, description = "The account wants a second factor: finish at `/auth/login/2fa`", body = TwoFactorChallengeResponse),
+        (status = 401, description = "Unauthorized: the ID token is invalid, expired or for another app"),
+        (status = 403, description = "Forbidden: the account is disabled"),
+        (status = 404, description = "Not found: no such provider is configured"),
+        (status = 409, description = "Conflict: an account with the verified address exists, log in and link the provider"),
+        (status = 429, description = "Too many attempts, see Retry-After"),
+        (status = 500, description = "Server error: database error or the provider's keys could not be fetched")
+    ),
+    tag = "Auth"
+)]
+#[allow(clippy::too_many_arguments)]
+pub async fn oidc_login(
+    State(db_connection): State<Arc<DatabaseConnection>>,
+    State(keys): State<Arc<JwtKeys>>,
+    State(config): State<Arc<Config>>,
+    State(limiter): State<Arc<RateLimiter>>,
+    State(clock): State<Arc<dyn Clock>>,
+    State(oidc): State<Arc<OidcVerifier>>,
+    ClientIp(ip): ClientIp,
+    Path(provider): Path<String>,
+    headers: HeaderMap,
+    Json(body): Json<OidcLoginBody>,
+) -> Result<Response, AppError> {
+    limiter.check(LimitedRoute::Login, ip, None).await?;
+    let claims = oidc
+        .verify(&provider, &body.id_token, &body.nonce)
+        .await?;
+    let now = clock.now();
+    let device = device_info(body.device_name, body.platform, ip, &headers);
+
+    let Some(identity) = identities::find(&*db_connection, &provider, &claims.subject).await? else {
+        let model = create_account(&db_connection, &provider, &claims, now).await?;
+        tracing::info!(user_id = %model.id, %provider, "account created through sign-in provider");
+        let response = start_session(&db_connection, &keys, model, device, now).await?;
+        counters::login_succeeded();
+        return Ok((StatusCode::CREATED, Json(response)).into_response());
+    };
+
+    let model = User::find_by_id(identity.user_id)
+        .one(&*db_connection)
+        .await?
+        .ok_or(ErrorCode::InvalidIdToken)?;
+    if model.disabled_at.is_some() {
+        counters::login_failed();
+        return Err(ErrorCode::AccountDisabled.into());
+    }
+    identities::touch(&*db_connection, identity, &claims, now).await?;
+    let outcome =
+        complete_login(&db_connection, &keys, &config, &limiter, model, device, now).await?;
+    Ok(outcome.into_response())
+}
+
+#[utoipa::path(
+    get,
+    path = "/users/me/identities",
+    summary = "Linked sign-in providers",
+    responses(
+        (status = 200, description = "Provider accounts linked to the current user", body = Vec<IdentityResponse>),
+        (status = 401, description = "Unauthorized: invalid or missing authentication token"),
+        (status = 500, description = "Server error: database error")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Auth"
+)]
+pub async fn get_identities(
+    auth: AuthUser,
+    State(db): State<Arc<DatabaseConnection>>,
+) -> Result<Json<Vec<IdentityResponse>>, AppError> {
+    let identities = identities::list(&*db, auth.user_id).await?;
+    Ok(Json(identities.into_iter().map(to_response).collect()))
+}
+
+#[utoipa::path(
+    post,
+    path = "/users/me/identities/{provider}",
+    summary = "Link a sign-in provider",
+    description = "Links the provider account behind an ID token to the current user, so it can log in with it too.",
+    params(("provider" = String, Path, description = "Provider name, e.g. `google`")),
+    request_body = LinkIdentityBody,
+    responses(
+        (status = 201, description = "Provider linked", body = IdentityResponse),
+        (status = 401, description = "Unauthorized: invalid authentication or ID token"),
+        (status = 404, description = "Not found: no such provider is configured"),
+        (status = 409, description = "Conflict: an account of this provider is already linked, or this one is linked to another user"),
+        (status = 500, description = "Server error: database error or the provider's keys could not be fetched")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Auth"
+)]
+pub async fn link_identity(
+    auth: AuthUser,
+    State(db): State<Arc<DatabaseConnection>>,
+    State(clock): State<Arc<dyn Clock>>,
+    State(oidc): State<Arc<OidcVerifier>>,
+    Path(provider): Path<String>,
+    Json(body): Json<LinkIdentityBody>,
+) -> Result<(StatusCode, Json<IdentityResponse>), AppError> {
+    let claims = oidc
+        .verify(&provider, &body.id_token, &body.nonce)
+        .await?;
+    if let Some(identity) = identities::find(&*db, &provider, &claims.subject).await? {
+        return Err(if identity.user_id == auth.user_id {
+            ErrorCode::ProviderAlreadyLinked.into()
+        } else {
+            ErrorCode::IdentityLinkedElsewhere.into()
+        });
+    }
+
+    let identity = identities::link(&*db, auth.user_id, &provider, &claims, clock.now())
+        .await
+        .map_err(|err| {
+            if db::constraint(&err).as_deref() == Some(identities::SUBJECT_UNIQUE_INDEX) {
+                return ErrorCode::IdentityLinkedElsewhere.into();
+            }
+            AppError::on_unique_violation(err, ErrorCode::ProviderAlreadyLinked)
+        })?;
+    tracing::info!(user_id = %auth.user_id, %provider, "sign-in provider linked");
+    Ok((StatusCode::CREATED, Json(to_response(identity))))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/users/me/identities/{provider}",
+    summary = "Unlink a sign-in provider",
+    description = "Refused for the only provider of an account without a password or passkey; reset the password or add a passkey first.",
+    params(("provider" = String, Path, description = "Provider name, e.g. `google`")),
+    responses(
+        (status = 204, description = "Provider unlinked"),
+        (status = 401, description = "Unauthorized: invalid or missing authentication token"),
+        (status = 404, description = "Not found: no account of this provider is linked"),
+        (status = 409, description = "Conflict: it is the only way to log in"),
+        (status = 500, description = "Server error: database error")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Auth"
+)]
+pub async fn unlink_identity(
+    auth: AuthUser,
+    State(db): State<Arc<DatabaseConnection>>,
+    Path(provider): Path<String>,
+) -> Result<StatusCode, AppError> {
+    let tx = db.begin().await?;
+    // Locking the user keeps a concurrent removal of a passkey from taking
+    // away the other remaining way to sign in.
+    let user = User::find_by_id(auth.user_id)
+        .lock_exclusive()
+        .one(&tx)
+        .await?
+        .ok_or(ErrorCode::ProfileNotFound)?;
+    let linked = identities::list(&tx, user.id).await?;
+    if !linked.iter().any(|identity| identity.provider == provider) {
+        return Err(ErrorCode::IdentityNotFound.into());
+    }
+    if user.password_hash.is_empty()
+        && linked.len() == 1
+        && passkeys::count(&tx, user.id).await? == 0
+    {
+        return Err(ErrorCode::LastSignInMethod.into());
+    }
+    identities::unlink(&tx, user.id, &provider).await?;
+    tx.commit().await?;
+
+    tracing::info!(user_id = %user.id, %provider, "sign-in provider unlinked");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Creates a password-less account for the first login through `provider`,
+/// linked to it. A verified address of the token becomes the account's
+/// unless another account has it already: a verified one there means the
+/// person should log in and link instead.
+async fn create_account(
+    db_connection: &DatabaseConnection,
+    provider: &str,
+    claims: &IdTokenClaims,
+    now: DateTime<Utc>,
+) -> Result<user::Model, AppError> {
+    let mut email = claims
+        .email
+        .as_deref()
+        .filter(|_| claims.email_verified)
+        .map(accounts::normalize_email);
+    if let Some(address) = &email {
+        let owner = User::find()
+            .filter(UserColumn::Email.eq(address))
+            .one(db_connection)
+            .await?;
+        match owner {
+            Some(owner) if owner.email_verified_at.is_some() => {
+                return Err(ErrorCode::IdentityEmailInUse.into());
+            }
+            Some(_) => email = None,
+            None => {}
+        }
+    }
+
+    let hint = claims
+        .email
+        .as_deref()
+        .and_then(|address| address.split('@').next())
+        .or(claims.name.as_deref())
+        .unwrap_or_default();
+    let username = accounts::free_username(db_connection, hint).await?;
+
+    let tx = db_connection.begin().await?;
+    let model = UserActiveModel {
+        username: Set(username),
+        password_hash: Set(String::new()),
+        email_verified_at: Set(email.as_ref().map(|_| now.into())),
+        email: Set(email),
+        ..Default::default()
+    }
+    .insert(&tx)
+    .await
+    .map_err(|err| {
+        if db::constraint(&err).as_deref() == Some(EMAIL_UNIQUE_INDEX) {
+            return ErrorCode::IdentityEmailInUse.into();
+        }
+        AppError::on_unique_violation(err, ErrorCode::UsernameTaken)
+    })?;
+    identities::link(&tx, model.id, provider, claims, now)
+        .await
+        .map_err(|err| AppError::on_unique_violation(err, ErrorCode::IdentityLinkedElsewhere))?;
+    tx.commit().await?;
+    Ok(model)
+}
+
+fn to_response(identity: user_identity::Model) -> IdentityResponse {
+    IdentityResponse {
+        provider: identity.provider,
+        email: identity.email,
+        created_at: identity.created_at.to_rfc3339(),
+        last_used_at: identity.last_used_at.map(|at| at.to_rfc3339()),
+    }
+}
@@ -1,7 +1,16 @@
+pub mod account_controller;
 pub mod auth_controller;
 pub mod calendar_controller;
+pub mod email_controller;
 pub mod event_controller;
 pub mod friendship_controller;
+pub mod health_controller;
+pub mod identity_controller;
 pub mod models;
+pub mod pagination;
+pub mod passkey_controller;
+pub mod password_controller;
+pub mod session_controller;
+pub mod two_factor_controller;
 pub mod users_controller;
 pub mod wish_place_controller;
@@ -0,0 +1,8 @@
+use serde::Serialize;
+use utoipa::ToSchema;
+
+#[derive(Serialize, ToSchema)]
+pub struct AccountDeletionResponse {
+    /// When the account is purged unless the deletion is undone first.
+    pub deletion_scheduled_at: String,
+}
@@ -0,0 +1,21 @@
+use serde::Serialize;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+#[derive(Serialize, ToSchema)]
+pub struct DataExportResponse {
+    pub id: Uuid,
+    /// `pending` while the archive is written, then `ready` or `failed`.
+    #[schema(example = "pending")]
+    pub status: String,
+    pub created_at: String,
+    pub completed_at: Option<String>,
+    /// Size of the archive in bytes once it is ready.
+    pub size_bytes: Option<i64>,
+    /// The download link stops working at this time.
+    pub expires_at: String,
+    /// Download link, only returned when the export is started; it works once
+    /// the export is ready and needs no login.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub download_url: Option<String>,
+}
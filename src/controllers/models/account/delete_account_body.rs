@@ -0,0 +1,9 @@
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+#[derive(Deserialize, ToSchema)]
+pub struct DeleteAccountBody {
+    /// Current password; accounts without one leave it out.
+    #[serde(default)]
+    pub password: Option<String>,
+}
@@ -0,0 +1,7 @@
+pub mod account_deletion_response;
+pub mod data_export_response;
+pub mod delete_account_body;
+
+pub use account_deletion_response::*;
+pub use data_export_response::*;
+pub use delete_account_body::*;
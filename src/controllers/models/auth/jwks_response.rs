@@ -0,0 +1,27 @@
+use serde::Serialize;
+use utoipa::ToSchema;
+
+/// JSON Web Key Set (RFC 7517) with the public keys tokens are signed with.
+#[derive(Serialize, ToSchema)]
+pub struct JwksResponse {
+    pub keys: Vec<JwkResponse>,
+}
+
+/// An Ed25519 public key (RFC 8037).
+#[derive(Serialize, ToSchema)]
+pub struct JwkResponse {
+    #[schema(example = "OKP")]
+    pub kty: &'static str,
+    #[schema(example = "Ed25519")]
+    pub crv: &'static str,
+    #[schema(example = "EdDSA")]
+    pub alg: &'static str,
+    #[serde(rename = "use")]
+    #[schema(example = "sig")]
+    pub key_use: &'static str,
+    /// Matches the `kid` header of the tokens the key signed.
+    #[schema(example = "20261018T120000.000Z-3fa2")]
+    pub kid: String,
+    /// The public key, base64url encoded.
+    pub x: String,
+}
@@ -3,6 +3,13 @@ use utoipa::ToSchema;
 
 #[derive(Deserialize, ToSchema)]
 pub struct LoginRequestBody {
+    /// Username, or the email address of the account once it is verified.
     pub username: String,
     pub password: String,
+    /// Shown in the list of sessions, e.g. "Alice's iPhone".
+    #[serde(default)]
+    pub device_name: Option<String>,
+    #[serde(default)]
+    #[schema(example = "ios")]
+    pub platform: Option<String>,
 }
@@ -1,16 +1,23 @@
 pub mod auth_request_body;
 pub mod friend_id_body;
+pub mod jwks_response;
 pub mod login_request_body;
 pub mod login_response;
 pub mod refresh_token_request;
 pub mod refresh_token_response;
+pub mod two_factor_challenge_response;
+pub mod two_factor_login_body;
 
 pub use auth_request_body::AuthRequestBody;
 
+pub use jwks_response::{JwkResponse, JwksResponse};
+
 pub use login_request_body::LoginRequestBody;
 
 pub use login_response::LoginResponse;
 pub use refresh_token_request::RefreshTokenRequest;
 pub use refresh_token_response::RefreshTokenResponse;
+pub use two_factor_challenge_response::TwoFactorChallengeResponse;
+pub use two_factor_login_body::TwoFactorLoginBody;
 
 pub use friend_id_body::FriendIdBody;
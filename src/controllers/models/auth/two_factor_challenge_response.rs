@@ -0,0 +1,11 @@
+use serde::Serialize;
+use utoipa::ToSchema;
+
+/// Answer to a correct password when the account has two-factor login on.
+#[derive(Serialize, ToSchema)]
+pub struct TwoFactorChallengeResponse {
+    /// Exchanged for the token pair at `/auth/login/2fa`.
+    pub challenge_token: String,
+    /// Seconds until the challenge token expires.
+    pub expires_in: i64,
+}
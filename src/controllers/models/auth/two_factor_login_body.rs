@@ -0,0 +1,15 @@
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+#[derive(Deserialize, ToSchema)]
+pub struct TwoFactorLoginBody {
+    pub challenge_token: String,
+    /// Six-digit code from the authenticator app, or a recovery code.
+    pub code: String,
+    /// Shown in the list of sessions, e.g. "Alice's iPhone".
+    #[serde(default)]
+    pub device_name: Option<String>,
+    #[serde(default)]
+    #[schema(example = "ios")]
+    pub platform: Option<String>,
+}
@@ -0,0 +1,3 @@
+pub mod verify_email_body;
+
+pub use verify_email_body::*;
@@ -0,0 +1,8 @@
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+#[derive(Deserialize, ToSchema)]
+pub struct VerifyEmailBody {
+    /// Token from the verification mail.
+    pub token: String,
+}
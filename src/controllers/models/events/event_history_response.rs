@@ -0,0 +1,14 @@
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+#[derive(serde::Serialize, ToSchema)]
+pub struct EventHistoryResponse {
+    /// Empty when the server changed the status, e.g. on expiry.
+    pub actor_id: Option<Uuid>,
+    /// Empty for the initial status.
+    pub from_status: Option<String>,
+    pub to_status: String,
+    #[schema(example = "confirmed")]
+    pub reason: String,
+    pub created_at: String,
+}
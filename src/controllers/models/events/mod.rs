@@ -1,4 +1,5 @@
 pub mod create_event_body;
+pub mod event_history_response;
 pub mod event_response;
 pub mod event_scope_query;
 pub mod finish_event_body;
@@ -6,6 +7,7 @@ pub mod participant_response;
 pub mod user_availability_response;
 
 pub use create_event_body::*;
+pub use event_history_response::*;
 pub use event_response::*;
 pub use event_scope_query::*;
 pub use finish_event_body::*;
@@ -1,8 +0,0 @@
-use serde::Deserialize;
-use utoipa::IntoParams;
-use uuid::Uuid;
-
-#[derive(Deserialize, IntoParams)]
-pub struct FriendIdQuery {
-    pub id: Uuid,
-}
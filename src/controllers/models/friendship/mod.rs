@@ -1,5 +1,2 @@
 pub mod user_dto;
 pub use user_dto::*;
-
-pub mod friend_id_query;
-pub use friend_id_query::*;
@@ -0,0 +1,5 @@
+pub mod health_response;
+pub mod readiness_response;
+
+pub use health_response::HealthResponse;
+pub use readiness_response::ReadinessResponse;
@@ -0,0 +1,11 @@
+use serde::Serialize;
+use utoipa::ToSchema;
+
+#[derive(Serialize, ToSchema)]
+pub struct ReadinessResponse {
+    pub ready: bool,
+    pub database: bool,
+    /// Unknown when the database is unreachable.
+    pub pending_migrations: Option<usize>,
+    pub shutting_down: bool,
+}
@@ -0,0 +1,12 @@
+use serde::Serialize;
+use utoipa::ToSchema;
+
+#[derive(Serialize, ToSchema)]
+pub struct IdentityResponse {
+    #[schema(example = "google")]
+    pub provider: String,
+    /// Address the provider last reported.
+    pub email: Option<String>,
+    pub created_at: String,
+    pub last_used_at: Option<String>,
+}
@@ -0,0 +1,10 @@
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+#[derive(Deserialize, ToSchema)]
+pub struct LinkIdentityBody {
+    /// ID token the app got from the provider's sign-in SDK.
+    pub id_token: String,
+    /// The nonce the app passed to the provider, checked against the token.
+    pub nonce: String,
+}
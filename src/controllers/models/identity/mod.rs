@@ -0,0 +1,7 @@
+pub mod identity_response;
+pub mod link_identity_body;
+pub mod oidc_login_body;
+
+pub use identity_response::*;
+pub use link_identity_body::*;
+pub use oidc_login_body::*;
@@ -0,0 +1,16 @@
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+#[derive(Deserialize, ToSchema)]
+pub struct OidcLoginBody {
+    /// ID token the app got from the provider's sign-in SDK.
+    pub id_token: String,
+    /// The nonce the app passed to the provider, checked against the token.
+    pub nonce: String,
+    /// Shown in the list of sessions, e.g. "Alice's iPhone".
+    #[serde(default)]
+    pub device_name: Option<String>,
+    #[serde(default)]
+    #[schema(example = "ios")]
+    pub platform: Option<String>,
+}
@@ -1,13 +1,19 @@
 pub mod user;
 pub use user::*;
+pub mod account;
 pub mod auth;
 pub mod calendar;
+pub mod email;
 pub mod events;
 mod friendship;
+pub mod health;
+pub mod identity;
+pub mod pagination;
+pub mod passkey;
+pub mod password;
+pub mod session;
+pub mod two_factor;
 pub mod wish_place;
 
 pub use auth::*;
-pub use calendar::*;
-pub use events::*;
 pub use friendship::*;
-pub use wish_place::*;
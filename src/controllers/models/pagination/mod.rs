@@ -0,0 +1,5 @@
+pub mod page;
+pub mod page_query;
+
+pub use page::Page;
+pub use page_query::PageQuery;
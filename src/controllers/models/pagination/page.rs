@@ -0,0 +1,23 @@
+use serde::Serialize;
+use utoipa::ToSchema;
+
+#[derive(Serialize, ToSchema)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub limit: u64,
+    /// Cursor for the next page, `null` when this is the last one.
+    pub next_cursor: Option<String>,
+    /// Total number of items matching the request, when cheap to compute.
+    pub total: Option<u64>,
+}
+
+impl<T> Page<T> {
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> Page<U> {
+        Page {
+            items: self.items.into_iter().map(f).collect(),
+            limit: self.limit,
+            next_cursor: self.next_cursor,
+            total: self.total,
+        }
+    }
+}
@@ -0,0 +1,11 @@
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+#[derive(Deserialize, IntoParams)]
+pub struct PageQuery {
+    /// Opaque cursor taken from `next_cursor` of the previous page.
+    pub cursor: Option<String>,
+    /// Page size, from 1 to 100. Defaults to 20.
+    #[param(minimum = 1, maximum = 100, example = 20)]
+    pub limit: Option<u64>,
+}
@@ -0,0 +1,25 @@
+use serde::Serialize;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// Options for `navigator.credentials.get()`. `public_key` is in the JSON
+/// form of `PublicKeyCredential.parseRequestOptionsFromJSON()`; no
+/// credentials are listed, the user picks any passkey for the site.
+#[derive(Serialize, ToSchema)]
+pub struct LoginOptionsResponse {
+    /// Sent back with the assertion.
+    pub ceremony_id: Uuid,
+    pub public_key: RequestOptions,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestOptions {
+    /// Base64url.
+    pub challenge: String,
+    /// Milliseconds.
+    pub timeout: i64,
+    pub rp_id: String,
+    #[schema(example = "required")]
+    pub user_verification: String,
+}
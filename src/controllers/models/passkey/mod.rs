@@ -0,0 +1,13 @@
+pub mod login_options_response;
+pub mod passkey_login_body;
+pub mod passkey_name_body;
+pub mod passkey_response;
+pub mod register_passkey_body;
+pub mod registration_options_response;
+
+pub use login_options_response::*;
+pub use passkey_login_body::*;
+pub use passkey_name_body::*;
+pub use passkey_response::*;
+pub use register_passkey_body::*;
+pub use registration_options_response::*;
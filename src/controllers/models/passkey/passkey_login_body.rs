@@ -0,0 +1,36 @@
+use serde::Deserialize;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+#[derive(Deserialize, ToSchema)]
+pub struct PasskeyLoginBody {
+    /// From `/auth/passkey/options`.
+    pub ceremony_id: Uuid,
+    pub credential: AssertionCredential,
+    /// Shown in the list of sessions, e.g. "Alice's iPhone".
+    #[serde(default)]
+    pub device_name: Option<String>,
+    #[serde(default)]
+    #[schema(example = "ios")]
+    pub platform: Option<String>,
+}
+
+/// The result of `navigator.credentials.get()` as `toJSON()` gives it.
+/// Binary fields are base64url.
+#[derive(Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AssertionCredential {
+    pub raw_id: String,
+    pub response: AssertionResponse,
+}
+
+#[derive(Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AssertionResponse {
+    #[serde(rename = "clientDataJSON")]
+    pub client_data_json: String,
+    pub authenticator_data: String,
+    pub signature: String,
+    #[serde(default)]
+    pub user_handle: Option<String>,
+}
@@ -0,0 +1,9 @@
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+#[derive(Deserialize, ToSchema)]
+pub struct PasskeyNameBody {
+    /// 1 to 64 characters, e.g. the device the passkey lives on.
+    #[schema(example = "iPhone")]
+    pub name: String,
+}
@@ -0,0 +1,12 @@
+use serde::Serialize;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+#[derive(Serialize, ToSchema)]
+pub struct PasskeyResponse {
+    pub id: Uuid,
+    #[schema(example = "iPhone")]
+    pub name: String,
+    pub created_at: String,
+    pub last_used_at: Option<String>,
+}
@@ -0,0 +1,30 @@
+use serde::Deserialize;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+#[derive(Deserialize, ToSchema)]
+pub struct RegisterPasskeyBody {
+    /// From `/users/me/passkeys/register/options`.
+    pub ceremony_id: Uuid,
+    /// 1 to 64 characters, e.g. the device the passkey lives on.
+    #[schema(example = "iPhone")]
+    pub name: String,
+    pub credential: RegistrationCredential,
+}
+
+/// The result of `navigator.credentials.create()` as `toJSON()` gives it.
+/// Binary fields are base64url.
+#[derive(Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RegistrationCredential {
+    pub raw_id: String,
+    pub response: AttestationResponse,
+}
+
+#[derive(Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AttestationResponse {
+    #[serde(rename = "clientDataJSON")]
+    pub client_data_json: String,
+    pub attestation_object: String,
+}
@@ -0,0 +1,72 @@
+use serde::Serialize;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// Options for `navigator.credentials.create()`. `public_key` is in the JSON
+/// form of `PublicKeyCredential.parseCreationOptionsFromJSON()`.
+#[derive(Serialize, ToSchema)]
+pub struct RegistrationOptionsResponse {
+    /// Sent back with the new credential.
+    pub ceremony_id: Uuid,
+    pub public_key: CreationOptions,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CreationOptions {
+    pub rp: RelyingPartyEntity,
+    pub user: UserEntity,
+    /// Base64url.
+    pub challenge: String,
+    pub pub_key_cred_params: Vec<CredentialParameters>,
+    /// Milliseconds.
+    pub timeout: i64,
+    /// Passkeys the user has already, so an authenticator is not registered twice.
+    pub exclude_credentials: Vec<CredentialDescriptor>,
+    pub authenticator_selection: AuthenticatorSelection,
+    #[schema(example = "none")]
+    pub attestation: String,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct RelyingPartyEntity {
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UserEntity {
+    /// The user id as base64url; assertions return it as `userHandle`.
+    pub id: String,
+    pub name: String,
+    pub display_name: String,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct CredentialParameters {
+    #[serde(rename = "type")]
+    #[schema(example = "public-key")]
+    pub kind: String,
+    /// COSE algorithm: -7 (ES256), -8 (EdDSA) or -257 (RS256).
+    pub alg: i32,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct CredentialDescriptor {
+    #[serde(rename = "type")]
+    #[schema(example = "public-key")]
+    pub kind: String,
+    /// Base64url credential id.
+    pub id: String,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthenticatorSelection {
+    #[schema(example = "required")]
+    pub resident_key: String,
+    pub require_resident_key: bool,
+    #[schema(example = "required")]
+    pub user_verification: String,
+}
@@ -0,0 +1,8 @@
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+#[derive(Deserialize, ToSchema)]
+pub struct ChangePasswordRequestBody {
+    pub current_password: String,
+    pub new_password: String,
+}
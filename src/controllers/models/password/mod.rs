@@ -0,0 +1,7 @@
+pub mod change_password_request_body;
+pub mod password_reset_confirm_body;
+pub mod password_reset_request_body;
+
+pub use change_password_request_body::*;
+pub use password_reset_confirm_body::*;
+pub use password_reset_request_body::*;
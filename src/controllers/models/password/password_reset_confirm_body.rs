@@ -0,0 +1,9 @@
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+#[derive(Deserialize, ToSchema)]
+pub struct PasswordResetConfirmBody {
+    /// Token from the reset mail.
+    pub token: String,
+    pub new_password: String,
+}
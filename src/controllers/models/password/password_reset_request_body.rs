@@ -0,0 +1,8 @@
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+#[derive(Deserialize, ToSchema)]
+pub struct PasswordResetRequestBody {
+    /// Username, or the verified email address of the account.
+    pub username: String,
+}
@@ -0,0 +1,5 @@
+pub mod revoked_sessions_response;
+pub mod session_response;
+
+pub use revoked_sessions_response::*;
+pub use session_response::*;
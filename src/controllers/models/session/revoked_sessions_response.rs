@@ -0,0 +1,6 @@
+use utoipa::ToSchema;
+
+#[derive(serde::Serialize, ToSchema)]
+pub struct RevokedSessionsResponse {
+    pub revoked: u64,
+}
@@ -0,0 +1,17 @@
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+#[derive(serde::Serialize, ToSchema)]
+pub struct SessionResponse {
+    pub id: Uuid,
+    pub device_name: Option<String>,
+    #[schema(example = "ios")]
+    pub platform: Option<String>,
+    /// Address of the last login or refresh.
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub created_at: String,
+    pub last_used_at: String,
+    /// The session the request was made with.
+    pub current: bool,
+}
@@ -0,0 +1,11 @@
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+#[derive(Deserialize, ToSchema)]
+pub struct DisableTotpBody {
+    /// Current password; accounts without one leave it out.
+    #[serde(default)]
+    pub password: Option<String>,
+    /// Code from the authenticator app, or a recovery code.
+    pub code: String,
+}
@@ -0,0 +1,13 @@
+pub mod disable_totp_body;
+pub mod recovery_codes_response;
+pub mod start_totp_body;
+pub mod totp_enrollment_response;
+pub mod two_factor_code_body;
+pub mod two_factor_status_response;
+
+pub use disable_totp_body::*;
+pub use recovery_codes_response::*;
+pub use start_totp_body::*;
+pub use totp_enrollment_response::*;
+pub use two_factor_code_body::*;
+pub use two_factor_status_response::*;
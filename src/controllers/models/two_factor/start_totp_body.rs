@@ -0,0 +1,10 @@
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+#[derive(Deserialize, ToSchema)]
+pub struct StartTotpBody {
+    /// Current password; accounts without one leave it out and must have
+    /// logged in within the last few minutes instead.
+    #[serde(default)]
+    pub password: Option<String>,
+}
@@ -0,0 +1,13 @@
+use serde::Serialize;
+use utoipa::ToSchema;
+
+#[derive(Serialize, ToSchema)]
+pub struct TotpEnrollmentResponse {
+    /// Base32 secret, for typing into the authenticator by hand.
+    pub secret: String,
+    /// The same secret as an `otpauth://` URI, usually shown as a QR code.
+    #[schema(
+        example = "otpauth://totp/Friends:alice?secret=JBSWY3DPEHPK3PXP&issuer=Friends&algorithm=SHA1&digits=6&period=30"
+    )]
+    pub otpauth_uri: String,
+}
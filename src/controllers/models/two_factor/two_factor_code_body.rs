@@ -0,0 +1,9 @@
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+#[derive(Deserialize, ToSchema)]
+pub struct TwoFactorCodeBody {
+    /// Six-digit code from the authenticator app.
+    #[schema(example = "123456")]
+    pub code: String,
+}
@@ -0,0 +1,9 @@
+use serde::Serialize;
+use utoipa::ToSchema;
+
+#[derive(Serialize, ToSchema)]
+pub struct TwoFactorStatusResponse {
+    pub enabled: bool,
+    /// Unused recovery codes; zero while two-factor login is off.
+    pub recovery_codes_left: u64,
+}
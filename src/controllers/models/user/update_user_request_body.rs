@@ -6,4 +6,11 @@ pub struct UpdateUserRequestBody {
     pub username: Option<String>,
     pub avatar_url: Option<String>,
     pub bio: Option<String>,
+    /// A new address is unverified until the link mailed to it is opened.
+    /// An empty string removes it.
+    pub email: Option<String>,
+    /// Preferred language for messages: `en` or `ru`.
+    pub locale: Option<String>,
+    /// IANA time zone, e.g. `Asia/Vladivostok`. Decides which day is "today".
+    pub timezone: Option<String>,
 }
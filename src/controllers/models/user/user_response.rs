@@ -8,4 +8,21 @@ pub struct UserResponse {
     pub username: String,
     pub avatar_url: Option<String>,
     pub bio: Option<String>,
+    /// Email address, only shown to the user themselves.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    /// Whether `email` has been confirmed from a verification mail, only
+    /// shown to the user themselves.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email_verified: Option<bool>,
+    /// Preferred language, only shown to the user themselves.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+    /// Time zone, only shown to the user themselves.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<String>,
+    /// When the account will be purged after the user asked to delete it,
+    /// only shown to the user themselves.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deletion_scheduled_at: Option<String>,
 }
@@ -0,0 +1,173 @@
+use std::str::FromStr;
+
+use axum::{
+    extract::{FromRequestParts, Query},
+    http::request::Parts,
+};
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use sea_orm::{
+    ColumnTrait, Condition, ConnectionTrait, EntityTrait, FromQueryResult, Order, PaginatorTrait,
+    QueryFilter, QueryOrder, QuerySelect, Select, Value,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::controllers::models::pagination::{Page, PageQuery};
+use crate::error::{AppError, ErrorCode};
+
+pub const DEFAULT_PAGE_LIMIT: u64 = 20;
+pub const MAX_PAGE_LIMIT: u64 = 100;
+
+/// Position of the last returned row: its sort key and id as a tie-breaker.
+#[derive(Serialize, Deserialize)]
+struct Cursor {
+    key: String,
+    id: Uuid,
+}
+
+impl Cursor {
+    fn encode(&self) -> String {
+        let json = serde_json::to_vec(self).unwrap_or_default();
+        URL_SAFE_NO_PAD.encode(json)
+    }
+
+    fn decode(value: &str) -> Option<Self> {
+        let json = URL_SAFE_NO_PAD.decode(value).ok()?;
+        serde_json::from_slice(&json).ok()
+    }
+}
+
+/// Stable ordering for a paginated query: the sort column plus the primary key,
+/// so rows with equal sort values are never skipped or repeated between pages.
+pub struct SortKey<C> {
+    pub column: C,
+    pub id_column: C,
+    pub order: Order,
+}
+
+impl<C> SortKey<C> {
+    pub fn asc(column: C, id_column: C) -> Self {
+        Self {
+            column,
+            id_column,
+            order: Order::Asc,
+        }
+    }
+
+    pub fn desc(column: C, id_column: C) -> Self {
+        Self {
+            column,
+            id_column,
+            order: Order::Desc,
+        }
+    }
+}
+
+/// Cursor pagination parameters shared by every list endpoint.
+pub struct Pagination {
+    pub limit: u64,
+    cursor: Option<Cursor>,
+}
+
+impl<S> FromRequestParts<S> for Pagination
+where
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let Query(query) = Query::<PageQuery>::from_request_parts(parts, state)
+            .await
+            .map_err(|_| AppError::from(ErrorCode::InvalidPagination))?;
+
+        let limit = query.limit.unwrap_or(DEFAULT_PAGE_LIMIT);
+        if !(1..=MAX_PAGE_LIMIT).contains(&limit) {
+            return Err(AppError::field(ErrorCode::InvalidPagination, "limit"));
+        }
+
+        let cursor = match query.cursor.as_deref() {
+            None | Some("") => None,
+            Some(value) => Some(
+                Cursor::decode(value)
+                    .ok_or_else(|| AppError::field(ErrorCode::InvalidPagination, "cursor"))?,
+            ),
+        };
+
+        Ok(Pagination { limit, cursor })
+    }
+}
+
+impl Pagination {
+    /// Loads one page of `select` ordered by `sort`. `V` is the Rust type of the
+    /// sort column and `cursor_of` returns the sort value and id of a row.
+    pub async fn fetch<E, V, C>(
+        &self,
+        db: &C,
+        select: Select<E>,
+        sort: SortKey<E::Column>,
+        with_total: bool,
+        cursor_of: impl Fn(&E::Model) -> (String, Uuid),
+    ) -> Result<Page<E::Model>, AppError>
+    where
+        E: EntityTrait,
+        E::Model: FromQueryResult + Send + Sync,
+        V: FromStr + Into<Value> + Clone,
+        C: ConnectionTrait,
+    {
+        let total = if with_total {
+            Some(select.clone().count(db).await?)
+        } else {
+            None
+        };
+
+        let mut rows = select
+            .filter(self.after::<V, _>(&sort)?)
+            .order_by(sort.column, sort.order.clone())
+            .order_by(sort.id_column, sort.order)
+            .limit(self.limit + 1)
+            .all(db)
+            .await?;
+
+        let next_cursor = if rows.len() as u64 > self.limit {
+            rows.truncate(self.limit as usize);
+            rows.last().map(|row| {
+                let (key, id) = cursor_of(row);
+                Cursor { key, id }.encode()
+            })
+        } else {
+            None
+        };
+
+        Ok(Page {
+            items: rows,
+            limit: self.limit,
+            next_cursor,
+            total,
+        })
+    }
+
+    fn after<V, Col>(&self, sort: &SortKey<Col>) -> Result<Condition, AppError>
+    where
+        V: FromStr + Into<Value> + Clone,
+        Col: ColumnTrait,
+    {
+        let Some(cursor) = &self.cursor else {
+            return Ok(Condition::all());
+        };
+        let key = cursor
+            .key
+            .parse::<V>()
+            .map_err(|_| AppError::field(ErrorCode::InvalidPagination, "cursor"))?;
+
+        let (past_key, past_id) = match sort.order {
+            Order::Desc => (sort.column.lt(key.clone()), sort.id_column.lt(cursor.id)),
+            _ => (sort.column.gt(key.clone()), sort.id_column.gt(cursor.id)),
+        };
+
+        Ok(Condition::any().add(past_key).add(
+            Condition::all()
+                .add(sort.column.eq(key))
+                .add(past_id),
+        ))
+    }
+}
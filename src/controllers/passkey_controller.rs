@@ -0,0 +1,455 @@
+use std::sync::Arc;
+
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    routing::{get, patch, post},
+};
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use sea_orm::{DatabaseConnection, EntityTrait, QuerySelect, TransactionTrait};
+use uuid::Uuid;
+
+use crate::auth::keys::JwtKeys;
+use crate::auth::middleware::AuthUser;
+use crate::auth::{identities, passkeys};
+use crate::clock::Clock;
+use crate::config::Config;
+use crate::controllers::auth_controller::{device_info, start_session};
+use crate::controllers::models::LoginResponse;
+use crate::controllers::models::passkey::{
+    AuthenticatorSelection, CreationOptions, CredentialDescriptor, CredentialParameters,
+    LoginOptionsResponse, PasskeyLoginBody, PasskeyNameBody, PasskeyResponse, RegisterPasskeyBody,
+    RegistrationOptionsResponse, RelyingPartyEntity, RequestOptions, UserEntity,
+};
+use crate::entities::webauthn_challenge::CeremonyPurpose;
+use crate::entities::{User, passkey};
+use crate::error::{AppError, ErrorCode};
+use crate::rate_limit::{ClientIp, LimitedRoute, RateLimiter};
+use crate::state::AppState;
+use crate::telemetry::counters;
+use crate::webauthn::{RelyingParty, public_key};
+
+const MAX_NAME_LEN: usize = 64;
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/auth/passkey/options", post(login_options))
+        .route("/auth/passkey", post(passkey_login))
+        .route("/users/me/passkeys", get(get_passkeys))
+        .route(
+            "/users/me/passkeys/register/options",
+            post(registration_options),
+        )
+        .route("/users/me/passkeys/register", post(register_passkey))
+        .route(
+            "/users/me/passkeys/{id}",
+            patch(rename_passkey).delete(delete_passkey),
+        )
+}
+
+#[utoipa::path(
+    post,
+    path = "/users/me/passkeys/register/options",
+    summary = "Start adding a passkey",
+    description = "Starts a registration ceremony. Pass `public_key` to `navigator.credentials.create()` (or the platform's passkey API) and send the result to `/users/me/passkeys/register` within `webauthn.challenge_ttl_secs`.",
+    responses(
+        (status = 200, description = "Options for the authenticator", body = RegistrationOptionsResponse),
+        (status = 401, description = "Unauthorized: invalid or missing authentication token"),
+        (status = 500, description = "Server error: database error")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Auth"
+)]
+pub async fn registration_options(
+    auth: AuthUser,
+    State(db): State<Arc<DatabaseConnection>>,
+    State(config): State<Arc<Config>>,
+    State(clock): State<Arc<dyn Clock>>,
+) -> Result<Json<RegistrationOptionsResponse>, AppError> {
+    let user = User::find_by_id(auth.user_id)
+        .one(&*db)
+        .await?
+        .ok_or(ErrorCode::ProfileNotFound)?;
+    let existing = passkeys::list(&*db, user.id).await?;
+
+    let webauthn = &config.webauthn;
+    let now = clock.now();
+    let (ceremony_id, challenge) = passkeys::issue_challenge(
+        &*db,
+        Some(user.id),
+        CeremonyPurpose::Registration,
+        now,
+        now + webauthn.challenge_ttl(),
+    )
+    .await?;
+
+    Ok(Json(RegistrationOptionsResponse {
+        ceremony_id,
+        public_key: CreationOptions {
+            rp: RelyingPartyEntity {
+                id: webauthn.rp_id.clone(),
+                name: webauthn.rp_name.clone(),
+            },
+            user: UserEntity {
+                id: URL_SAFE_NO_PAD.encode(user.id.as_bytes()),
+                name: user.username.clone(),
+                display_name: user.username,
+            },
+            challenge,
+            pub_key_cred_params: public_key::ALGORITHMS
+                .iter()
+                .map(|alg| CredentialParameters {
+                    kind: "public-key".to_string(),
+                    alg: *alg as i32,
+                })
+                .collect(),
+            timeout: webauthn.challenge_ttl().num_milliseconds(),
+            exclude_credentials: existing
+                .into_iter()
+                .map(|passkey| CredentialDescriptor {
+                    kind: "public-key".to_string(),
+                    id: passkey.credential_id,
+                })
+                .collect(),
+            authenticator_selection: AuthenticatorSelection {
+                resident_key: "required".to_string(),
+                require_resident_key: true,
+                user_verification: "required".to_string(),
+            },
+            attestation: "none".to_string(),
+        },
+    }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/users/me/passkeys/register",
+    summary = "Add a passkey",
+    description = "Finishes a registration ceremony with the credential the authenticator created. The authenticator must have verified the user, e.g. with biometrics or a PIN.",
+    request_body = RegisterPasskeyBody,
+    responses(
+        (status = 201, description = "Passkey added", body = PasskeyResponse),
+        (status = 400, description = "Validation error: bad name, expired ceremony or a credential that does not verify"),
+        (status = 401, description = "Unauthorized: invalid or missing authentication token"),
+        (status = 409, description = "Conflict: the passkey is already registered"),
+        (status = 500, description = "Server error: database error")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Auth"
+)]
+pub async fn register_passkey(
+    auth: AuthUser,
+    State(db): State<Arc<DatabaseConnection>>,
+    State(config): State<Arc<Config>>,
+    State(clock): State<Arc<dyn Clock>>,
+    Json(body): Json<RegisterPasskeyBody>,
+) -> Result<(StatusCode, Json<PasskeyResponse>), AppError> {
+    let name = validate_name(&body.name)?;
+    let now = clock.now();
+    let Some(challenge) = passkeys::consume_challenge(
+        &*db,
+        body.ceremony_id,
+        Some(auth.user_id),
+        CeremonyPurpose::Registration,
+        now,
+    )
+    .await?
+    else {
+        return Err(AppError::field(
+            ErrorCode::PasskeyChallengeExpired,
+            "ceremony_id",
+        ));
+    };
+
+    let credential = &body.credential;
+    let invalid = || AppError::field(ErrorCode::InvalidPasskeyRegistration, "credential");
+    let raw_id = decode(&credential.raw_id).ok_or_else(invalid)?;
+    let client_data_json = decode(&credential.response.client_data_json).ok_or_else(invalid)?;
+    let attestation_object = decode(&credential.response.attestation_object).ok_or_else(invalid)?;
+    let new_credential = RelyingParty::new(&config.webauthn)
+        .verify_registration(&challenge, &client_data_json, &attestation_object)
+        .map_err(|err| {
+            tracing::info!(user_id = %auth.user_id, error = %err, "passkey registration rejected");
+            invalid()
+        })?;
+    if new_credential.id != raw_id {
+        return Err(invalid());
+    }
+
+    let credential_id = URL_SAFE_NO_PAD.encode(&new_credential.id);
+    let passkey = passkeys::create(&*db, auth.user_id, credential_id, new_credential, name, now)
+        .await
+        .map_err(|err| AppError::on_unique_violation(err, ErrorCode::PasskeyAlreadyRegistered))?;
+    tracing::info!(user_id = %auth.user_id, passkey_id = %passkey.id, "passkey added");
+    Ok((StatusCode::CREATED, Json(to_response(passkey))))
+}
+
+#[utoipa::path(
+    get,
+    path = "/users/me/passkeys",
+    summary = "Passkeys",
+    responses(
+        (status = 200, description = "Passkeys of the current user, oldest first", body = Vec<PasskeyResponse>),
+        (status = 401, description = "Unauthorized: invalid or missing authentication token"),
+        (status = 500, description = "Server error: database error")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Auth"
+)]
+pub async fn get_passkeys(
+    auth: AuthUser,
+    State(db): State<Arc<DatabaseConnection>>,
+) -> Result<Json<Vec<PasskeyResponse>>, AppError> {
+    let passkeys = passkeys::list(&*db, auth.user_id).await?;
+    Ok(Json(passkeys.into_iter().map(to_response).collect()))
+}
+
+#[utoipa::path(
+    patch,
+    path = "/users/me/passkeys/{id}",
+    summary = "Rename a passkey",
+    params(("id" = Uuid, Path, description = "Passkey id")),
+    request_body = PasskeyNameBody,
+    responses(
+        (status = 200, description = "Passkey renamed", body = PasskeyResponse),
+        (status = 400, description = "Validation error: bad name"),
+        (status = 401, description = "Unauthorized: invalid or missing authentication token"),
+        (status = 404, description = "Not found: no such passkey of the current user"),
+        (status = 500, description = "Server error: database error")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Auth"
+)]
+pub async fn rename_passkey(
+    auth: AuthUser,
+    State(db): State<Arc<DatabaseConnection>>,
+    Path(id): Path<Uuid>,
+    Json(body): Json<PasskeyNameBody>,
+) -> Result<Json<PasskeyResponse>, AppError> {
+    let name = validate_name(&body.name)?;
+    let passkey = passkeys::find(&*db, auth.user_id, id)
+        .await?
+        .ok_or(ErrorCode::PasskeyNotFound)?;
+    let passkey = passkeys::rename(&*db, passkey, name).await?;
+    Ok(Json(to_response(passkey)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/users/me/passkeys/{id}",
+    summary = "Remove a passkey",
+    description = "Refused for the last way to log in of an account without a password or linked provider.",
+    params(("id" = Uuid, Path, description = "Passkey id")),
+    responses(
+        (status = 204, description = "Passkey removed"),
+        (status = 401, description = "Unauthorized: invalid or missing authentication token"),
+        (status = 404, description = "Not found: no such passkey of the current user"),
+        (status = 409, description = "Conflict: it is the only way to log in"),
+        (status = 500, description = "Server error: database error")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Auth"
+)]
+pub async fn delete_passkey(
+    auth: AuthUser,
+    State(db): State<Arc<DatabaseConnection>>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    let tx = db.begin().await?;
+    // Locking the user keeps a concurrent unlink of a provider from taking
+    // away the other remaining way to sign in.
+    let user = User::find_by_id(auth.user_id)
+        .lock_exclusive()
+        .one(&tx)
+        .await?
+        .ok_or(ErrorCode::ProfileNotFound)?;
+    let passkey = passkeys::find(&tx, user.id, id)
+        .await?
+        .ok_or(ErrorCode::PasskeyNotFound)?;
+    if user.password_hash.is_empty()
+        && identities::list(&tx, user.id).await?.is_empty()
+        && passkeys::count(&tx, user.id).await? == 1
+    {
+        return Err(ErrorCode::LastSignInMethod.into());
+    }
+    passkeys::delete(&tx, passkey).await?;
+    tx.commit().await?;
+
+    tracing::info!(user_id = %user.id, passkey_id = %id, "passkey removed");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    post,
+    path = "/auth/passkey/options",
+    summary = "Start a passkey login",
+    description = "Starts an authentication ceremony. Pass `public_key` to `navigator.credentials.get()` (or the platform's passkey API) and send the result to `/auth/passkey` within `webauthn.challenge_ttl_secs`.",
+    responses(
+        (status = 200, description = "Options for the authenticator", body = LoginOptionsResponse),
+        (status = 429, description = "Too many attempts, see Retry-After"),
+        (status = 500, description = "Server error: database error")
+    ),
+    tag = "Auth"
+)]
+pub async fn login_options(
+    State(db): State<Arc<DatabaseConnection>>,
+    State(config): State<Arc<Config>>,
+    State(limiter): State<Arc<RateLimiter>>,
+    State(clock): State<Arc<dyn Clock>>,
+    ClientIp(ip): ClientIp,
+) -> Result<Json<LoginOptionsResponse>, AppError> {
+    limiter.check(LimitedRoute::Login, ip, None).await?;
+    let webauthn = &config.webauthn;
+    let now = clock.now();
+    let (ceremony_id, challenge) = passkeys::issue_challenge(
+        &*db,
+        None,
+        CeremonyPurpose::Login,
+        now,
+        now + webauthn.challenge_ttl(),
+    )
+    .await?;
+
+    Ok(Json(LoginOptionsResponse {
+        ceremony_id,
+        public_key: RequestOptions {
+            challenge,
+            timeout: webauthn.challenge_ttl().num_milliseconds(),
+            rp_id: webauthn.rp_id.clone(),
+            user_verification: "required".to_string(),
+        },
+    }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/auth/passkey",
+    summary = "Log in with a passkey",
+    description = "Finishes an authentication ceremony and issues the same token pair as `/auth/login`. The authenticator verified the user, so accounts with two-factor login are not asked for a code.",
+    request_body = PasskeyLoginBody,
+    responses(
+        (status = 200, description = "Login successful", body = LoginResponse),
+        (status = 400, description = "Validation error: the ceremony is unknown or expired"),
+        (status = 401, description = "Unauthorized: unknown passkey or an assertion that does not verify"),
+        (status = 403, description = "Forbidden: the account is disabled"),
+        (status = 429, description = "Too many attempts, see Retry-After"),
+        (status = 500, description = "Server error: database error")
+    ),
+    tag = "Auth"
+)]
+#[allow(clippy::too_many_arguments)]
+pub async fn passkey_login(
+    State(db): State<Arc<DatabaseConnection>>,
+    State(keys): State<Arc<JwtKeys>>,
+    State(config): State<Arc<Config>>,
+    State(limiter): State<Arc<RateLimiter>>,
+    State(clock): State<Arc<dyn Clock>>,
+    ClientIp(ip): ClientIp,
+    headers: HeaderMap,
+    Json(body): Json<PasskeyLoginBody>,
+) -> Result<Json<LoginResponse>, AppError> {
+    limiter.check(LimitedRoute::Login, ip, None).await?;
+    let now = clock.now();
+    let Some(challenge) =
+        passkeys::consume_challenge(&*db, body.ceremony_id, None, CeremonyPurpose::Login, now)
+            .await?
+    else {
+        return Err(AppError::field(
+            ErrorCode::PasskeyChallengeExpired,
+            "ceremony_id",
+        ));
+    };
+
+    let passkey = verify_assertion(&db, &config, &body, &challenge).await;
+    let Some((passkey, sign_count)) = passkey? else {
+        counters::login_failed();
+        return Err(ErrorCode::InvalidPasskey.into());
+    };
+    if !passkeys::record_use(&*db, &passkey, sign_count, now).await? {
+        counters::login_failed();
+        return Err(ErrorCode::InvalidPasskey.into());
+    }
+
+    let model = User::find_by_id(passkey.user_id)
+        .one(&*db)
+        .await?
+        .ok_or(ErrorCode::InvalidPasskey)?;
+    if model.disabled_at.is_some() {
+        counters::login_failed();
+        return Err(ErrorCode::AccountDisabled.into());
+    }
+    let device = device_info(body.device_name, body.platform, ip, &headers);
+    let response = start_session(&db, &keys, model, device, now).await?;
+    limiter.login_succeeded(&response.user.username).await?;
+    counters::login_succeeded();
+    Ok(Json(response))
+}
+
+/// The passkey behind an assertion and its new signature counter, or `None`
+/// when the passkey is unknown or the assertion does not verify.
+async fn verify_assertion(
+    db: &DatabaseConnection,
+    config: &Config,
+    body: &PasskeyLoginBody,
+    challenge: &str,
+) -> Result<Option<(passkey::Model, u32)>, AppError> {
+    let response = &body.credential.response;
+    let (Some(raw_id), Some(client_data_json), Some(authenticator_data), Some(signature)) = (
+        decode(&body.credential.raw_id),
+        decode(&response.client_data_json),
+        decode(&response.authenticator_data),
+        decode(&response.signature),
+    ) else {
+        return Ok(None);
+    };
+    let credential_id = URL_SAFE_NO_PAD.encode(raw_id);
+    let Some(passkey) = passkeys::find_by_credential(db, &credential_id).await? else {
+        return Ok(None);
+    };
+    // A discoverable credential names its user; it has to be the owner.
+    if let Some(user_handle) = &response.user_handle
+        && decode(user_handle).as_deref() != Some(passkey.user_id.as_bytes().as_slice())
+    {
+        return Ok(None);
+    }
+
+    let verified = RelyingParty::new(&config.webauthn).verify_assertion(
+        challenge,
+        &passkey.public_key,
+        passkey.sign_count as u32,
+        &client_data_json,
+        &authenticator_data,
+        &signature,
+    );
+    match verified {
+        Ok(sign_count) => Ok(Some((passkey, sign_count))),
+        Err(err) => {
+            tracing::warn!(passkey_id = %passkey.id, error = %err, "passkey assertion rejected");
+            Ok(None)
+        }
+    }
+}
+
+fn validate_name(name: &str) -> Result<String, AppError> {
+    let name = name.trim();
+    if name.is_empty() || name.chars().count() > MAX_NAME_LEN {
+        return Err(AppError::field(ErrorCode::InvalidPasskeyName, "name"));
+    }
+    Ok(name.to_string())
+}
+
+/// Base64url with or without padding, as browsers and platform APIs differ.
+fn decode(value: &str) -> Option<Vec<u8>> {
+    URL_SAFE_NO_PAD.decode(value.trim_end_matches('=')).ok()
+}
+
+fn to_response(passkey: passkey::Model) -> PasskeyResponse {
+    PasskeyResponse {
+        id: passkey.id,
+        name: passkey.name,
+        created_at: passkey.created_at.to_rfc3339(),
+        last_used_at: passkey.last_used_at.map(|at| at.to_rfc3339()),
+    }
+}
@@ -0,0 +1,216 @@
+use std::sync::Arc;
+
+use axum::{Json, Router, extract::State, http::StatusCode, routing::post};
+use sea_orm::{ActiveModelTrait, DatabaseConnection, EntityTrait, Set, TransactionTrait};
+
+use crate::auth::middleware::AuthUser;
+use crate::auth::password::{hash_password, verify_password};
+use crate::auth::session_guard::SessionGuard;
+use crate::auth::{accounts, password_reset, sessions};
+use crate::clock::Clock;
+use crate::config::Config;
+use crate::controllers::models::password::{
+    ChangePasswordRequestBody, PasswordResetConfirmBody, PasswordResetRequestBody,
+};
+use crate::entities::{User, UserActiveModel, user};
+use crate::error::{AppError, ErrorCode, ResultExt};
+use crate::i18n::{self, Locale};
+use crate::mail::{self, MailTemplate, Mailer, Message};
+use crate::rate_limit::{ClientIp, LimitedRoute, RateLimiter};
+use crate::state::AppState;
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/auth/change-password", post(change_password))
+        .route("/auth/password-reset/request", post(request_password_reset))
+        .route("/auth/password-reset/confirm", post(confirm_password_reset))
+}
+
+#[utoipa::path(
+    post,
+    path = "/auth/change-password",
+    summary = "Change password",
+    description = "Sets a new password after checking the current one. Every other session is logged out; the one making the request stays logged in. Wrong current passwords count towards the login lockout.",
+    request_body = ChangePasswordRequestBody,
+    responses(
+        (status = 204, description = "Password changed"),
+        (status = 400, description = "Validation error: the current password is wrong or the new one is empty"),
+        (status = 401, description = "Unauthorized: invalid or missing authentication token"),
+        (status = 429, description = "The account is locked after failed logins, see Retry-After"),
+        (status = 500, description = "Server error: hashing or database error")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Auth"
+)]
+pub async fn change_password(
+    auth: AuthUser,
+    State(db): State<Arc<DatabaseConnection>>,
+    State(limiter): State<Arc<RateLimiter>>,
+    State(guard): State<Arc<SessionGuard>>,
+    State(clock): State<Arc<dyn Clock>>,
+    Json(body): Json<ChangePasswordRequestBody>,
+) -> Result<StatusCode, AppError> {
+    if body.new_password.trim().is_empty() {
+        return Err(AppError::field(ErrorCode::PasswordRequired, "new_password"));
+    }
+
+    let user = User::find_by_id(auth.user_id)
+        .one(&*db)
+        .await?
+        .ok_or(ErrorCode::ProfileNotFound)?;
+    require_password(&limiter, &user, &body.current_password, "current_password").await?;
+
+    let password_hash = hash_password(&body.new_password)
+        .map_err(|e| e.to_string())
+        .or_internal()?;
+
+    let now = clock.now();
+    let tx = db.begin().await?;
+    let mut active: UserActiveModel = user.into();
+    active.password_hash = Set(password_hash);
+    active.update(&tx).await?;
+    let revoked = sessions::revoke_all(&tx, Some(auth.user_id), auth.session_id, now).await?;
+    password_reset::discard(&tx, auth.user_id, now).await?;
+    tx.commit().await?;
+
+    guard.forget_user(auth.user_id);
+    tracing::info!(user_id = %auth.user_id, revoked, "password changed");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Checks the password of a signed-in user before a sensitive change. Wrong
+/// passwords count towards the login lockout, like failed logins.
+pub(crate) async fn require_password(
+    limiter: &RateLimiter,
+    user: &user::Model,
+    password: &str,
+    field: &'static str,
+) -> Result<(), AppError> {
+    limiter.ensure_not_locked(&user.username).await?;
+    let password_matches = verify_password(&user.password_hash, password)
+        .map_err(|e| e.to_string())
+        .or_internal()?;
+    if !password_matches {
+        limiter.login_failed(&user.username).await?;
+        return Err(AppError::field(ErrorCode::CurrentPasswordIncorrect, field));
+    }
+    Ok(())
+}
+
+#[utoipa::path(
+    post,
+    path = "/auth/password-reset/request",
+    summary = "Request password reset",
+    description = "Mails a single-use reset link to the verified address of the account, named by username or that address. The answer is the same whether or not the account exists or has an address, so it reveals nothing about either.",
+    request_body = PasswordResetRequestBody,
+    responses(
+        (status = 202, description = "A reset mail is on its way if the account has a verified address"),
+        (status = 400, description = "Validation error: missing username"),
+        (status = 429, description = "Too many reset requests, see Retry-After"),
+        (status = 500, description = "Server error: database error")
+    ),
+    tag = "Auth"
+)]
+pub async fn request_password_reset(
+    State(db): State<Arc<DatabaseConnection>>,
+    State(config): State<Arc<Config>>,
+    State(limiter): State<Arc<RateLimiter>>,
+    State(clock): State<Arc<dyn Clock>>,
+    State(mailer): State<Arc<dyn Mailer>>,
+    ClientIp(ip): ClientIp,
+    Json(body): Json<PasswordResetRequestBody>,
+) -> Result<StatusCode, AppError> {
+    if body.username.trim().is_empty() {
+        return Err(AppError::field(ErrorCode::MissingCredentials, "username"));
+    }
+    limiter
+        .check(LimitedRoute::PasswordReset, ip, Some(&body.username))
+        .await?;
+
+    // Reset mail only goes to an address the user has proven to own.
+    let user = accounts::find_by_login(&*db, &body.username)
+        .await?
+        .filter(|user| user.disabled_at.is_none() && user.email_verified_at.is_some());
+    let Some((user, email)) = user.and_then(|user| user.email.clone().map(|email| (user, email)))
+    else {
+        return Ok(StatusCode::ACCEPTED);
+    };
+
+    let now = clock.now();
+    let token =
+        password_reset::issue(&*db, user.id, now, now + config.mail.password_reset_ttl()).await?;
+    let link = config.mail.password_reset_url.replace("{token}", &token);
+    let minutes = ((config.mail.password_reset_ttl_secs + 59) / 60).to_string();
+    let locale = user
+        .locale
+        .as_deref()
+        .and_then(Locale::parse)
+        .unwrap_or_else(i18n::current);
+    let message = Message::localized(
+        &email,
+        MailTemplate::PasswordReset,
+        locale,
+        &[
+            ("username", &user.username),
+            ("link", &link),
+            ("minutes", &minutes),
+        ],
+    );
+    mail::send_later(mailer, message);
+    Ok(StatusCode::ACCEPTED)
+}
+
+#[utoipa::path(
+    post,
+    path = "/auth/password-reset/confirm",
+    summary = "Reset password",
+    description = "Sets a new password with the token from a reset mail. The token works once and expires after `mail.password_reset_ttl_secs`. Every session of the account is logged out and a login lockout is lifted.",
+    request_body = PasswordResetConfirmBody,
+    responses(
+        (status = 204, description = "Password reset, log in with the new one"),
+        (status = 400, description = "Validation error: the token is invalid, used or expired, or the new password is empty"),
+        (status = 429, description = "Too many reset attempts from this address, see Retry-After"),
+        (status = 500, description = "Server error: hashing or database error")
+    ),
+    tag = "Auth"
+)]
+pub async fn confirm_password_reset(
+    State(db): State<Arc<DatabaseConnection>>,
+    State(limiter): State<Arc<RateLimiter>>,
+    State(guard): State<Arc<SessionGuard>>,
+    State(clock): State<Arc<dyn Clock>>,
+    ClientIp(ip): ClientIp,
+    Json(body): Json<PasswordResetConfirmBody>,
+) -> Result<StatusCode, AppError> {
+    if body.new_password.trim().is_empty() {
+        return Err(AppError::field(ErrorCode::PasswordRequired, "new_password"));
+    }
+    limiter.check(LimitedRoute::PasswordReset, ip, None).await?;
+
+    let now = clock.now();
+    let tx = db.begin().await?;
+    let user = match password_reset::consume(&tx, &body.token, now).await? {
+        Some(user_id) => User::find_by_id(user_id).one(&tx).await?,
+        None => None,
+    };
+    let Some(user) = user.filter(|user| user.disabled_at.is_none()) else {
+        return Err(AppError::field(ErrorCode::InvalidResetToken, "token"));
+    };
+
+    let password_hash = hash_password(&body.new_password)
+        .map_err(|e| e.to_string())
+        .or_internal()?;
+    let user_id = user.id;
+    let username = user.username.clone();
+    let mut active: UserActiveModel = user.into();
+    active.password_hash = Set(password_hash);
+    active.update(&tx).await?;
+    let revoked = sessions::revoke_all(&tx, Some(user_id), None, now).await?;
+    password_reset::discard(&tx, user_id, now).await?;
+    tx.commit().await?;
+
+    guard.forget_user(user_id);
+    limiter.login_succeeded(&username).await?;
+    tracing::info!(user_id = %user_id, revoked, "password reset");
+    Ok(StatusCode::NO_CONTENT)
+}
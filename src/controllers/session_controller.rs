@@ -0,0 +1,133 @@
+use std::sync::Arc;
+
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    http::StatusCode,
+    routing::{delete, get, post},
+};
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, TransactionTrait};
+use uuid::Uuid;
+
+use crate::auth::middleware::AuthUser;
+use crate::auth::session_guard::SessionGuard;
+use crate::auth::sessions;
+use crate::clock::Clock;
+use crate::controllers::models::session::{RevokedSessionsResponse, SessionResponse};
+use crate::entities::{Session, SessionColumn, session};
+use crate::error::{AppError, ErrorCode};
+use crate::state::AppState;
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/users/me/sessions", get(get_sessions))
+        .route("/users/me/sessions/{id}", delete(revoke_session))
+        .route(
+            "/users/me/sessions/revoke-others",
+            post(revoke_other_sessions),
+        )
+}
+
+#[utoipa::path(
+    get,
+    path = "/users/me/sessions",
+    summary = "List my sessions",
+    description = "Returns the devices the current user is logged in on, most recently used first.",
+    responses(
+        (status = 200, description = "Active sessions", body = Vec<SessionResponse>),
+        (status = 401, description = "Unauthorized: invalid or missing authentication token"),
+        (status = 500, description = "Server error: failed to retrieve sessions")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Sessions"
+)]
+pub async fn get_sessions(
+    auth: AuthUser,
+    State(db): State<Arc<DatabaseConnection>>,
+    State(clock): State<Arc<dyn Clock>>,
+) -> Result<Json<Vec<SessionResponse>>, AppError> {
+    let sessions = sessions::list_active(&*db, auth.user_id, clock.now()).await?;
+    Ok(Json(
+        sessions
+            .into_iter()
+            .map(|model| to_response(model, auth.session_id))
+            .collect(),
+    ))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/users/me/sessions/{id}",
+    summary = "Revoke session",
+    description = "Logs the current user out on one device. Its refresh token stops working at once, its access tokens within seconds.",
+    params(("id" = Uuid, Path, description = "Session ID")),
+    responses(
+        (status = 204, description = "Session revoked"),
+        (status = 401, description = "Unauthorized: invalid or missing authentication token"),
+        (status = 404, description = "Not found: no active session with this ID"),
+        (status = 500, description = "Server error: failed to revoke session")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Sessions"
+)]
+pub async fn revoke_session(
+    auth: AuthUser,
+    State(db): State<Arc<DatabaseConnection>>,
+    State(guard): State<Arc<SessionGuard>>,
+    State(clock): State<Arc<dyn Clock>>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    let tx = db.begin().await?;
+    Session::find_by_id(id)
+        .filter(SessionColumn::UserId.eq(auth.user_id))
+        .filter(SessionColumn::RevokedAt.is_null())
+        .one(&tx)
+        .await?
+        .ok_or(ErrorCode::SessionNotFound)?;
+    sessions::revoke(&tx, id, clock.now()).await?;
+    tx.commit().await?;
+
+    guard.forget(id);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    post,
+    path = "/users/me/sessions/revoke-others",
+    summary = "Log out everywhere else",
+    description = "Revokes every session of the current user except the one making the request.",
+    responses(
+        (status = 200, description = "Number of sessions revoked", body = RevokedSessionsResponse),
+        (status = 401, description = "Unauthorized: invalid or missing authentication token"),
+        (status = 500, description = "Server error: failed to revoke sessions")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Sessions"
+)]
+pub async fn revoke_other_sessions(
+    auth: AuthUser,
+    State(db): State<Arc<DatabaseConnection>>,
+    State(guard): State<Arc<SessionGuard>>,
+    State(clock): State<Arc<dyn Clock>>,
+) -> Result<Json<RevokedSessionsResponse>, AppError> {
+    let tx = db.begin().await?;
+    let revoked =
+        sessions::revoke_all(&tx, Some(auth.user_id), auth.session_id, clock.now()).await?;
+    tx.commit().await?;
+
+    guard.forget_user(auth.user_id);
+    Ok(Json(RevokedSessionsResponse { revoked }))
+}
+
+fn to_response(model: session::Model, current: Option<Uuid>) -> SessionResponse {
+    SessionResponse {
+        current: current == Some(model.id),
+        id: model.id,
+        device_name: model.device_name,
+        platform: model.platform,
+        ip: model.ip,
+        user_agent: model.user_agent,
+        created_at: model.created_at.to_rfc3339(),
+        last_used_at: model.last_used_at.to_rfc3339(),
+    }
+}
@@ -0,0 +1,270 @@
+use std::sync::Arc;
+
+use axum::{
+    Json, Router,
+    extract::State,
+    http::StatusCode,
+    routing::{get, post},
+};
+use chrono::{DateTime, TimeDelta, Utc};
+use sea_orm::{DatabaseConnection, EntityTrait, TransactionTrait};
+
+use crate::auth::middleware::AuthUser;
+use crate::auth::{totp, two_factor};
+use crate::clock::Clock;
+use crate::config::Config;
+use crate::controllers::models::two_factor::{
+    DisableTotpBody, RecoveryCodesResponse, StartTotpBody, TotpEnrollmentResponse,
+    TwoFactorCodeBody, TwoFactorStatusResponse,
+};
+use crate::controllers::password_controller::require_password;
+use crate::entities::{Session, User, user};
+use crate::error::{AppError, ErrorCode};
+use crate::rate_limit::{ClientIp, LimitedRoute, RateLimiter};
+use crate::state::AppState;
+
+/// How long after logging in an account without a password may start
+/// enrollment, standing in for the password check.
+const RECENT_LOGIN: TimeDelta = TimeDelta::minutes(10);
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/users/me/2fa", get(status))
+        .route("/users/me/2fa/totp", post(start_totp).delete(disable_totp))
+        .route("/users/me/2fa/totp/confirm", post(confirm_totp))
+        .route(
+            "/users/me/2fa/recovery-codes",
+            post(regenerate_recovery_codes),
+        )
+}
+
+#[utoipa::path(
+    get,
+    path = "/users/me/2fa",
+    summary = "Two-factor status",
+    responses(
+        (status = 200, description = "Whether two-factor login is on", body = TwoFactorStatusResponse),
+        (status = 401, description = "Unauthorized: invalid or missing authentication token"),
+        (status = 500, description = "Server error: database error")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Auth"
+)]
+pub async fn status(
+    auth: AuthUser,
+    State(db): State<Arc<DatabaseConnection>>,
+) -> Result<Json<TwoFactorStatusResponse>, AppError> {
+    let enabled = two_factor::enabled(&*db, auth.user_id).await?.is_some();
+    let recovery_codes_left = if enabled {
+        two_factor::recovery_codes_left(&*db, auth.user_id).await?
+    } else {
+        0
+    };
+    Ok(Json(TwoFactorStatusResponse {
+        enabled,
+        recovery_codes_left,
+    }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/users/me/2fa/totp",
+    summary = "Start TOTP enrollment",
+    description = "Creates a new authenticator secret after checking the password, or for accounts without one that the session was started in the last 10 minutes. Two-factor login stays off until the first code is confirmed; starting again replaces an unconfirmed secret.",
+    request_body = StartTotpBody,
+    responses(
+        (status = 200, description = "Secret to add to the authenticator app", body = TotpEnrollmentResponse),
+        (status = 400, description = "Validation error: the password is wrong"),
+        (status = 401, description = "Unauthorized: invalid or missing authentication token"),
+        (status = 403, description = "Forbidden: the account has no password and the login is not recent, log in again"),
+        (status = 409, description = "Conflict: two-factor login is already on"),
+        (status = 429, description = "The account is locked after failed logins, see Retry-After"),
+        (status = 500, description = "Server error: hashing or database error")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Auth"
+)]
+pub async fn start_totp(
+    auth: AuthUser,
+    State(db): State<Arc<DatabaseConnection>>,
+    State(config): State<Arc<Config>>,
+    State(limiter): State<Arc<RateLimiter>>,
+    State(clock): State<Arc<dyn Clock>>,
+    Json(body): Json<StartTotpBody>,
+) -> Result<Json<TotpEnrollmentResponse>, AppError> {
+    let now = clock.now();
+    let user = current_user(&db, &auth).await?;
+    if user.password_hash.is_empty() {
+        require_recent_login(&db, &auth, now).await?;
+    } else {
+        let password = body.password.as_deref().unwrap_or_default();
+        require_password(&limiter, &user, password, "password").await?;
+    }
+    if two_factor::enabled(&*db, user.id).await?.is_some() {
+        return Err(ErrorCode::TwoFactorAlreadyEnabled.into());
+    }
+
+    let secret = two_factor::start_enrollment(&*db, user.id, now).await?;
+    let otpauth_uri = totp::otpauth_uri(&config.two_factor.issuer, &user.username, &secret);
+    Ok(Json(TotpEnrollmentResponse {
+        secret,
+        otpauth_uri,
+    }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/users/me/2fa/totp/confirm",
+    summary = "Confirm TOTP enrollment",
+    description = "Turns two-factor login on with a first code from the authenticator app and returns the recovery codes. They are shown only this once. Wrong codes count towards the login lockout.",
+    request_body = TwoFactorCodeBody,
+    responses(
+        (status = 200, description = "Two-factor login is on", body = RecoveryCodesResponse),
+        (status = 400, description = "Validation error: the code is wrong, or no enrollment was started"),
+        (status = 401, description = "Unauthorized: invalid or missing authentication token"),
+        (status = 409, description = "Conflict: two-factor login is already on"),
+        (status = 429, description = "Too many attempts or the account is locked after failed logins, see Retry-After"),
+        (status = 500, description = "Server error: hashing or database error")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Auth"
+)]
+pub async fn confirm_totp(
+    auth: AuthUser,
+    State(db): State<Arc<DatabaseConnection>>,
+    State(limiter): State<Arc<RateLimiter>>,
+    State(clock): State<Arc<dyn Clock>>,
+    ClientIp(ip): ClientIp,
+    Json(body): Json<TwoFactorCodeBody>,
+) -> Result<Json<RecoveryCodesResponse>, AppError> {
+    limiter.check(LimitedRoute::Login, ip, None).await?;
+    let user = current_user(&db, &auth).await?;
+    limiter.ensure_not_locked(&user.username).await?;
+
+    let tx = db.begin().await?;
+    let pending = two_factor::find(&tx, user.id)
+        .await?
+        .ok_or(ErrorCode::TwoFactorNotEnrolled)?;
+    if pending.confirmed_at.is_some() {
+        return Err(ErrorCode::TwoFactorAlreadyEnabled.into());
+    }
+    let Some(recovery_codes) = two_factor::confirm(&tx, pending, &body.code, clock.now()).await?
+    else {
+        limiter.login_failed(&user.username).await?;
+        return Err(AppError::field(ErrorCode::InvalidTwoFactorCode, "code"));
+    };
+    tx.commit().await?;
+
+    tracing::info!(user_id = %auth.user_id, "two-factor login enabled");
+    Ok(Json(RecoveryCodesResponse { recovery_codes }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/users/me/2fa/recovery-codes",
+    summary = "Regenerate recovery codes",
+    description = "Replaces the recovery codes with new ones after checking a code from the authenticator app. The old ones stop working. Wrong codes count towards the login lockout.",
+    request_body = TwoFactorCodeBody,
+    responses(
+        (status = 200, description = "New recovery codes", body = RecoveryCodesResponse),
+        (status = 400, description = "Validation error: the code is wrong, or two-factor login is off"),
+        (status = 401, description = "Unauthorized: invalid or missing authentication token"),
+        (status = 429, description = "Too many attempts or the account is locked after failed logins, see Retry-After"),
+        (status = 500, description = "Server error: hashing or database error")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Auth"
+)]
+pub async fn regenerate_recovery_codes(
+    auth: AuthUser,
+    State(db): State<Arc<DatabaseConnection>>,
+    State(limiter): State<Arc<RateLimiter>>,
+    State(clock): State<Arc<dyn Clock>>,
+    ClientIp(ip): ClientIp,
+    Json(body): Json<TwoFactorCodeBody>,
+) -> Result<Json<RecoveryCodesResponse>, AppError> {
+    limiter.check(LimitedRoute::Login, ip, None).await?;
+    let user = current_user(&db, &auth).await?;
+    limiter.ensure_not_locked(&user.username).await?;
+
+    let now = clock.now();
+    let tx = db.begin().await?;
+    let totp = two_factor::enabled(&tx, user.id)
+        .await?
+        .ok_or(ErrorCode::TwoFactorNotEnabled)?;
+    if !two_factor::verify_code(&tx, &totp, &body.code, now).await? {
+        limiter.login_failed(&user.username).await?;
+        return Err(AppError::field(ErrorCode::InvalidTwoFactorCode, "code"));
+    }
+    let recovery_codes = two_factor::replace_recovery_codes(&tx, user.id, now).await?;
+    tx.commit().await?;
+    Ok(Json(RecoveryCodesResponse { recovery_codes }))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/users/me/2fa/totp",
+    summary = "Turn two-factor login off",
+    description = "Needs the password, unless the account has none, and a code from the authenticator app or a recovery code. The secret and all recovery codes are deleted.",
+    request_body = DisableTotpBody,
+    responses(
+        (status = 204, description = "Two-factor login is off"),
+        (status = 400, description = "Validation error: the password or code is wrong, or two-factor login is off"),
+        (status = 401, description = "Unauthorized: invalid or missing authentication token"),
+        (status = 429, description = "The account is locked after failed logins, see Retry-After"),
+        (status = 500, description = "Server error: hashing or database error")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Auth"
+)]
+pub async fn disable_totp(
+    auth: AuthUser,
+    State(db): State<Arc<DatabaseConnection>>,
+    State(limiter): State<Arc<RateLimiter>>,
+    State(clock): State<Arc<dyn Clock>>,
+    Json(body): Json<DisableTotpBody>,
+) -> Result<StatusCode, AppError> {
+    let user = current_user(&db, &auth).await?;
+    if !user.password_hash.is_empty() {
+        let password = body.password.as_deref().unwrap_or_default();
+        require_password(&limiter, &user, password, "password").await?;
+    }
+
+    let tx = db.begin().await?;
+    let totp = two_factor::enabled(&tx, user.id)
+        .await?
+        .ok_or(ErrorCode::TwoFactorNotEnabled)?;
+    if !two_factor::verify_code(&tx, &totp, &body.code, clock.now()).await? {
+        limiter.login_failed(&user.username).await?;
+        return Err(AppError::field(ErrorCode::InvalidTwoFactorCode, "code"));
+    }
+    two_factor::disable(&tx, user.id).await?;
+    tx.commit().await?;
+
+    tracing::info!(user_id = %user.id, "two-factor login disabled");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Stands in for the password of an account without one: the session must
+/// have been started within [`RECENT_LOGIN`].
+async fn require_recent_login(
+    db: &DatabaseConnection,
+    auth: &AuthUser,
+    now: DateTime<Utc>,
+) -> Result<(), AppError> {
+    let session = match auth.session_id {
+        Some(id) => Session::find_by_id(id).one(db).await?,
+        None => None,
+    };
+    match session {
+        Some(session) if now - session.created_at.with_timezone(&Utc) < RECENT_LOGIN => Ok(()),
+        _ => Err(ErrorCode::RecentLoginRequired.into()),
+    }
+}
+
+async fn current_user(db: &DatabaseConnection, auth: &AuthUser) -> Result<user::Model, AppError> {
+    Ok(User::find_by_id(auth.user_id)
+        .one(db)
+        .await?
+        .ok_or(ErrorCode::ProfileNotFound)?)
+}
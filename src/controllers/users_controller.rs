@@ -1,18 +1,36 @@
+use std::sync::Arc;
+
+use crate::auth::accounts;
 use crate::auth::middleware::AuthUser;
+use crate::auth::session_guard::SessionGuard;
+use crate::clock::{self, Clock};
+use crate::config::Config;
+use crate::controllers::email_controller;
+use crate::controllers::models::pagination::{Page, PageQuery};
 use crate::controllers::models::update_user_request_body::UpdateUserRequestBody;
 use crate::controllers::models::user_name_search_query::UserNameSearchQuery;
 use crate::controllers::models::user_response::UserResponse;
+use crate::controllers::pagination::{Pagination, SortKey};
+use crate::db;
 use crate::entities::{User, UserActiveModel, UserColumn};
+use crate::error::{AppError, ErrorCode};
+use crate::i18n::Locale;
+use crate::mail::Mailer;
+use crate::rate_limit::{LimitedRoute, RateLimiter};
+use crate::state::AppState;
 use axum::{
     Json, Router,
     extract::{Query, State},
-    http::StatusCode,
     routing::get,
 };
 use sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, Set};
 use uuid::Uuid;
 
-pub fn router() -> Router<DatabaseConnection> {
+/// Longest address an SMTP path allows.
+const MAX_EMAIL_LEN: usize = 254;
+pub(crate) const EMAIL_UNIQUE_INDEX: &str = "idx_users_email_unique";
+
+pub fn router() -> Router<AppState> {
     Router::new()
         .route("/users/me", get(get_me).patch(update_me))
         .route("/users/{id}", get(get_user_by_id))
@@ -34,17 +52,16 @@ pub fn router() -> Router<DatabaseConnection> {
 )]
 pub async fn get_me(
     auth_user: AuthUser,
-    State(db_connection): State<DatabaseConnection>,
-) -> Result<Json<UserResponse>, (StatusCode, String)> {
+    State(db_connection): State<Arc<DatabaseConnection>>,
+) -> Result<Json<UserResponse>, AppError> {
     let user_id = auth_user.user_id;
 
     let model = User::find_by_id(user_id)
-        .one(&db_connection)
-        .await
-        .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "Unable to retrieve your profile. Please try again.".to_string()))?;
+        .one(&*db_connection)
+        .await?;
 
     let Some(model) = model else {
-        return Err((StatusCode::NOT_FOUND, "Your profile could not be found.".to_string()));
+        return Err(ErrorCode::ProfileNotFound.into());
     };
 
     Ok(Json(UserResponse {
@@ -52,6 +69,11 @@ pub async fn get_me(
         username: model.username,
         avatar_url: model.avatar_url,
         bio: model.bio,
+        email_verified: Some(model.email_verified_at.is_some()),
+        email: model.email,
+        locale: model.locale,
+        timezone: model.timezone,
+        deletion_scheduled_at: model.deletion_scheduled_at.map(|at| at.to_rfc3339()),
     }))
 }
 
@@ -61,8 +83,11 @@ pub async fn get_me(
     request_body = UpdateUserRequestBody,
     responses(
         (status = 200, description = "User profile updated successfully", body = UserResponse),
+        (status = 400, description = "Validation error: invalid email, unsupported locale or time zone"),
         (status = 401, description = "Unauthorized: invalid or missing authentication token"),
         (status = 404, description = "User profile not found"),
+        (status = 409, description = "Conflict: username or email is already taken"),
+        (status = 429, description = "Too many email address changes, see Retry-After"),
         (status = 500, description = "Server error: failed to update profile")
     ),
     security(
@@ -70,22 +95,28 @@ pub async fn get_me(
     ),
     tag = "Users"
 )]
+#[allow(clippy::too_many_arguments)]
 pub async fn update_me(
     auth: AuthUser,
-    State(db): State<DatabaseConnection>,
+    State(db): State<Arc<DatabaseConnection>>,
+    State(config): State<Arc<Config>>,
+    State(limiter): State<Arc<RateLimiter>>,
+    State(clock): State<Arc<dyn Clock>>,
+    State(mailer): State<Arc<dyn Mailer>>,
+    State(guard): State<Arc<SessionGuard>>,
     Json(payload): Json<UpdateUserRequestBody>,
-) -> Result<Json<UserResponse>, (StatusCode, String)> {
+) -> Result<Json<UserResponse>, AppError> {
     let user_id = auth.user_id;
 
     let model = User::find_by_id(user_id)
-        .one(&db)
-        .await
-        .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "Unable to retrieve your profile. Please try again.".to_string()))?;
+        .one(&*db)
+        .await?;
 
     let Some(model) = model else {
-        return Err((StatusCode::NOT_FOUND, "Your profile could not be found.".to_string()));
+        return Err(ErrorCode::ProfileNotFound.into());
     };
 
+    let current_email = model.email.clone();
     let mut active: UserActiveModel = model.into();
 
     if let Some(username) = payload.username {
@@ -97,17 +128,67 @@ pub async fn update_me(
     if let Some(bio) = payload.bio {
         active.bio = Set(Some(bio));
     }
+    let mut email_changed = false;
+    if let Some(email) = payload.email {
+        let email = accounts::normalize_email(&email);
+        if !email.is_empty() && !is_valid_email(&email) {
+            return Err(AppError::field(ErrorCode::InvalidEmail, "email"));
+        }
+        let email = (!email.is_empty()).then_some(email);
+        if email != current_email {
+            if email.is_some() {
+                // Each new address gets a mail, so changes are throttled too.
+                limiter
+                    .check(
+                        LimitedRoute::EmailVerification,
+                        None,
+                        Some(&user_id.to_string()),
+                    )
+                    .await?;
+            }
+            email_changed = true;
+            active.email = Set(email);
+            active.email_verified_at = Set(None);
+        }
+    }
+    let locale_changed = payload.locale.is_some();
+    if let Some(locale) = payload.locale {
+        let locale = Locale::parse(&locale)
+            .ok_or_else(|| AppError::field(ErrorCode::UnsupportedLocale, "locale"))?;
+        active.locale = Set(Some(locale.code().to_string()));
+    }
+    if let Some(timezone) = payload.timezone {
+        let tz = clock::parse_timezone(&timezone)
+            .ok_or_else(|| AppError::field(ErrorCode::UnsupportedTimezone, "timezone"))?;
+        active.timezone = Set(Some(tz.name().to_string()));
+    }
 
-    let model = active
-        .update(&db)
-        .await
-        .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "Failed to update profile. Please try again.".to_string()))?;
+    let model = active.update(&*db).await.map_err(|err| {
+        if db::constraint(&err).as_deref() == Some(EMAIL_UNIQUE_INDEX) {
+            AppError::field(ErrorCode::EmailTaken, "email")
+        } else {
+            AppError::on_unique_violation(err, ErrorCode::UsernameTaken)
+        }
+    })?;
+    if locale_changed {
+        // Sessions carry the language of their user, see `SessionGuard`.
+        guard.forget_user(user_id);
+    }
+    if email_changed {
+        email_controller::send_verification_mail(&*db, &config, clock.now(), mailer, &model)
+            .await?;
+    }
 
     Ok(Json(UserResponse {
         id: model.id,
         username: model.username,
         avatar_url: model.avatar_url,
         bio: model.bio,
+        email_verified: Some(model.email_verified_at.is_some()),
+        email: model.email,
+        locale: model.locale,
+        timezone: model.timezone,
+        deletion_scheduled_at: model.deletion_scheduled_at.map(|at| at.to_rfc3339()),
     }))
 }
 
@@ -124,16 +205,15 @@ pub async fn update_me(
     tag = "Users"
 )]
 pub async fn get_user_by_id(
-    State(db): State<DatabaseConnection>,
+    State(db): State<Arc<DatabaseConnection>>,
     axum::extract::Path(id): axum::extract::Path<Uuid>,
-) -> Result<Json<UserResponse>, (StatusCode, String)> {
+) -> Result<Json<UserResponse>, AppError> {
     let model = User::find_by_id(id)
-        .one(&db)
-        .await
-        .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "Unable to retrieve user profile. Please try again.".to_string()))?;
+        .one(&*db)
+        .await?;
 
     let Some(model) = model else {
-        return Err((StatusCode::NOT_FOUND, "This user profile does not exist.".to_string()));
+        return Err(ErrorCode::UserNotFound.into());
     };
 
     Ok(Json(UserResponse {
@@ -141,47 +221,79 @@ pub async fn get_user_by_id(
         username: model.username,
         avatar_url: model.avatar_url,
         bio: model.bio,
+        email: None,
+        email_verified: None,
+        locale: None,
+        timezone: None,
+        deletion_scheduled_at: None,
     }))
 }
 
 #[utoipa::path(
     get,
     path = "/users/search",
-    params(UserNameSearchQuery),
+    params(UserNameSearchQuery, PageQuery),
     responses(
-        (status = 200, description = "Users found by username prefix", body = [UserResponse]),
-        (status = 400, description = "Validation error: username query parameter is required"),
+        (status = 200, description = "Page of users found by username prefix, ordered by username", body = Page<UserResponse>),
+        (status = 400, description = "Validation error: username query parameter is required, or invalid page cursor or limit"),
         (status = 500, description = "Server error: failed to search users")
     ),
     tag = "Users"
 )]
 pub async fn search_users(
-    State(db): State<DatabaseConnection>,
+    State(db): State<Arc<DatabaseConnection>>,
     Query(query): Query<UserNameSearchQuery>,
-) -> Result<Json<Vec<UserResponse>>, (StatusCode, String)> {
+    pagination: Pagination,
+) -> Result<Json<Page<UserResponse>>, AppError> {
     let username = query.username.unwrap_or_default();
     if username.trim().is_empty() {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            "Please enter a username to search.".to_string(),
-        ));
+        return Err(AppError::field(ErrorCode::SearchQueryRequired, "username"));
     }
 
-    let models = User::find()
-        .filter(UserColumn::Username.starts_with(username))
-        .all(&db)
-        .await
-        .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "Failed to search users. Please try again.".to_string()))?;
-
-    let users = models
-        .into_iter()
-        .map(|model| UserResponse {
-            id: model.id,
-            username: model.username,
-            avatar_url: model.avatar_url,
-            bio: model.bio,
-        })
-        .collect();
-
-    Ok(Json(users))
+    // A prefix search can match a large part of the table, so no total here.
+    let page = pagination
+        .fetch::<_, String, _>(
+            &*db,
+            User::find()
+                .filter(UserColumn::Username.starts_with(username))
+                .filter(UserColumn::DeletedAt.is_null()),
+            SortKey::asc(UserColumn::Username, UserColumn::Id),
+            false,
+            |model| (model.username.clone(), model.id),
+        )
+        .await?;
+
+    Ok(Json(page.map(|model| UserResponse {
+        id: model.id,
+        username: model.username,
+        avatar_url: model.avatar_url,
+        bio: model.bio,
+        email: None,
+        email_verified: None,
+        locale: None,
+        timezone: None,
+        deletion_scheduled_at: None,
+    })))
+}
+
+/// A plausible single address: one `@` with something on both sides, a dot
+/// in the domain and no spaces. Whether it exists is up to the mail server.
+fn is_valid_email(email: &str) -> bool {
+    if email.len() > MAX_EMAIL_LEN
+        || email
+            .chars()
+            .any(|c| c.is_whitespace() || c.is_control())
+    {
+        return false;
+    }
+    match email.split_once('@') {
+        Some((local, domain)) => {
+            !local.is_empty()
+                && !domain.contains('@')
+                && domain.contains('.')
+                && !domain.starts_with('.')
+                && !domain.ends_with('.')
+        }
+        None => false,
+    }
 }
@@ -1,25 +1,32 @@
+use std::sync::Arc;
+
 use axum::{
     Json, Router,
     extract::{Path, Query, State},
     http::StatusCode,
     routing::{get, patch, post},
 };
+use sea_orm::prelude::DateTimeWithTimeZone;
 use sea_orm::{
-    ActiveModelTrait, ColumnTrait, Condition, DatabaseConnection, EntityTrait, IntoActiveModel,
-    QueryFilter, QueryOrder, Set,
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, IntoActiveModel,
+    QueryFilter, Set,
 };
 use uuid::Uuid;
 
 use crate::auth::middleware::AuthUser;
+use crate::controllers::models::pagination::{Page, PageQuery};
 use crate::controllers::models::wish_place::{
     CreateWishPlaceBody, UpdateWishPlaceBody, VisitWishPlaceBody, WishPlaceQuery,
     WishPlaceResponse, WishPlaceStatusDto,
 };
-use crate::entities::friendship::{self, FriendshipStatus};
+use crate::controllers::pagination::{Pagination, SortKey};
 use crate::entities::wish_place::{self, WishPlaceStatus};
-use crate::entities::{Event, Friendship, WishPlace, WishPlaceActiveModel, WishPlaceColumn};
+use crate::error::{AppError, ErrorCode};
+use crate::entities::{Event, WishPlace, WishPlaceActiveModel, WishPlaceColumn};
+use crate::services::{FriendshipService, Friendships};
+use crate::state::AppState;
 
-pub fn router() -> Router<DatabaseConnection> {
+pub fn router() -> Router<AppState> {
     Router::new()
         .route("/wish-places", get(get_wish_places).post(create_wish_place))
         .route(
@@ -34,9 +41,10 @@ pub fn router() -> Router<DatabaseConnection> {
     path = "/wish-places",
     summary = "Get wish places",
     description = "Returns wish places for the specified user. Access: self or accepted friend only.",
-    params(WishPlaceQuery),
+    params(WishPlaceQuery, PageQuery),
     responses(
-        (status = 200, description = "Wish places list retrieved successfully", body = [WishPlaceResponse]),
+        (status = 200, description = "Page of wish places, newest first", body = Page<WishPlaceResponse>),
+        (status = 400, description = "Validation error: invalid page cursor or limit"),
         (status = 401, description = "Unauthorized: invalid or missing authentication token"),
         (status = 403, description = "Forbidden: you can only view your own or accepted friend's wish places"),
         (status = 500, description = "Server error: failed to retrieve wish places")
@@ -46,22 +54,24 @@ pub fn router() -> Router<DatabaseConnection> {
 )]
 pub async fn get_wish_places(
     auth: AuthUser,
-    State(db): State<DatabaseConnection>,
+    State(db): State<Arc<DatabaseConnection>>,
     Query(query): Query<WishPlaceQuery>,
-) -> Result<Json<Vec<WishPlaceResponse>>, (StatusCode, String)> {
+    pagination: Pagination,
+) -> Result<Json<Page<WishPlaceResponse>>, AppError> {
     let me = auth.user_id;
-    if me != query.user_id && !are_users_accepted_friends(&db, me, query.user_id).await? {
-        return Err((StatusCode::FORBIDDEN, "You can only view your own or accepted friend's wish places.".to_string()));
-    }
+    Friendships.ensure_can_view(&*db, me, query.user_id).await?;
 
-    let rows = WishPlace::find()
-        .filter(WishPlaceColumn::UserId.eq(query.user_id))
-        .order_by_desc(WishPlaceColumn::CreatedAt)
-        .all(&db)
-        .await
-        .map_err(internal_error)?;
+    let page = pagination
+        .fetch::<_, DateTimeWithTimeZone, _>(
+            &*db,
+            WishPlace::find().filter(WishPlaceColumn::UserId.eq(query.user_id)),
+            SortKey::desc(WishPlaceColumn::CreatedAt, WishPlaceColumn::Id),
+            true,
+            |model| (model.created_at.to_rfc3339(), model.id),
+        )
+        .await?;
 
-    Ok(Json(rows.into_iter().map(to_response).collect()))
+    Ok(Json(page.map(to_response)))
 }
 
 #[utoipa::path(
@@ -81,11 +91,11 @@ pub async fn get_wish_places(
 )]
 pub async fn create_wish_place(
     auth: AuthUser,
-    State(db): State<DatabaseConnection>,
+    State(db): State<Arc<DatabaseConnection>>,
     Json(body): Json<CreateWishPlaceBody>,
-) -> Result<(StatusCode, Json<WishPlaceResponse>), (StatusCode, String)> {
+) -> Result<(StatusCode, Json<WishPlaceResponse>), AppError> {
     if body.title.trim().is_empty() {
-        return Err((StatusCode::BAD_REQUEST, "Please enter a title for the wish place.".to_string()));
+        return Err(AppError::field(ErrorCode::TitleRequired, "title"));
     }
 
     let model = WishPlaceActiveModel {
@@ -98,9 +108,8 @@ pub async fn create_wish_place(
         visited_event_id: Set(None),
         ..Default::default()
     }
-    .insert(&db)
-    .await
-    .map_err(internal_error)?;
+    .insert(&*db)
+    .await?;
 
     Ok((StatusCode::CREATED, Json(to_response(model))))
 }
@@ -124,16 +133,15 @@ pub async fn create_wish_place(
 )]
 pub async fn update_wish_place(
     auth: AuthUser,
-    State(db): State<DatabaseConnection>,
+    State(db): State<Arc<DatabaseConnection>>,
     Path(id): Path<Uuid>,
     Json(body): Json<UpdateWishPlaceBody>,
-) -> Result<Json<WishPlaceResponse>, (StatusCode, String)> {
+) -> Result<Json<WishPlaceResponse>, AppError> {
     let row = WishPlace::find_by_id(id)
         .filter(WishPlaceColumn::UserId.eq(auth.user_id))
-        .one(&db)
-        .await
-        .map_err(internal_error)?
-        .ok_or((StatusCode::NOT_FOUND, "Wish place not found or you are not the owner.".to_string()))?;
+        .one(&*db)
+        .await?
+        .ok_or(ErrorCode::WishPlaceNotFound)?;
 
     if body.title.is_none()
         && body.description.is_none()
@@ -141,14 +149,14 @@ pub async fn update_wish_place(
         && body.link.is_none()
         && body.status.is_none()
     {
-        return Err((StatusCode::BAD_REQUEST, "Please provide at least one field to update.".to_string()));
+        return Err(ErrorCode::NothingToUpdate.into());
     }
 
     let mut active = row.into_active_model();
 
     if let Some(title) = body.title {
         if title.trim().is_empty() {
-            return Err((StatusCode::BAD_REQUEST, "Title cannot be empty.".to_string()));
+            return Err(AppError::field(ErrorCode::TitleRequired, "title"));
         }
         active.title = Set(title);
     }
@@ -168,7 +176,7 @@ pub async fn update_wish_place(
         }
     }
 
-    let updated = active.update(&db).await.map_err(internal_error)?;
+    let updated = active.update(&*db).await?;
     Ok(Json(to_response(updated)))
 }
 
@@ -191,35 +199,30 @@ pub async fn update_wish_place(
 )]
 pub async fn visit_wish_place(
     auth: AuthUser,
-    State(db): State<DatabaseConnection>,
+    State(db): State<Arc<DatabaseConnection>>,
     Path(id): Path<Uuid>,
     Json(body): Json<VisitWishPlaceBody>,
-) -> Result<Json<WishPlaceResponse>, (StatusCode, String)> {
+) -> Result<Json<WishPlaceResponse>, AppError> {
     let row = WishPlace::find_by_id(id)
         .filter(WishPlaceColumn::UserId.eq(auth.user_id))
-        .one(&db)
-        .await
-        .map_err(internal_error)?
-        .ok_or((StatusCode::NOT_FOUND, "Wish place not found or you are not the owner.".to_string()))?;
+        .one(&*db)
+        .await?
+        .ok_or(ErrorCode::WishPlaceNotFound)?;
 
     let event = Event::find_by_id(body.event_id)
-        .one(&db)
-        .await
-        .map_err(internal_error)?
-        .ok_or((StatusCode::NOT_FOUND, "Event not found.".to_string()))?;
+        .one(&*db)
+        .await?
+        .ok_or(ErrorCode::EventNotFound)?;
 
     if event.creator_id != auth.user_id {
-        return Err((
-            StatusCode::FORBIDDEN,
-            "Only the event creator can mark a place as visited.".to_string(),
-        ));
+        return Err(ErrorCode::NotEventCreator.into());
     }
 
     let mut active = row.into_active_model();
     active.status = Set(WishPlaceStatus::Visited);
     active.visited_event_id = Set(Some(body.event_id));
 
-    let updated = active.update(&db).await.map_err(internal_error)?;
+    let updated = active.update(&*db).await?;
     Ok(Json(to_response(updated)))
 }
 
@@ -240,19 +243,18 @@ pub async fn visit_wish_place(
 )]
 pub async fn delete_wish_place(
     auth: AuthUser,
-    State(db): State<DatabaseConnection>,
+    State(db): State<Arc<DatabaseConnection>>,
     Path(id): Path<Uuid>,
-) -> Result<StatusCode, (StatusCode, String)> {
+) -> Result<StatusCode, AppError> {
     let row = WishPlace::find_by_id(id)
         .filter(WishPlaceColumn::UserId.eq(auth.user_id))
-        .one(&db)
-        .await
-        .map_err(internal_error)?
-        .ok_or((StatusCode::NOT_FOUND, "Wish place not found or you are not the owner.".to_string()))?;
+        .one(&*db)
+        .await?
+        .ok_or(ErrorCode::WishPlaceNotFound)?;
 
     let mut active = row.into_active_model();
     active.status = Set(WishPlaceStatus::Archived);
-    active.update(&db).await.map_err(internal_error)?;
+    active.update(&*db).await?;
 
     Ok(StatusCode::NO_CONTENT)
 }
@@ -278,33 +280,3 @@ fn map_status(value: WishPlaceStatusDto) -> WishPlaceStatus {
         WishPlaceStatusDto::Archived => WishPlaceStatus::Archived,
     }
 }
-
-async fn are_users_accepted_friends(
-    db: &DatabaseConnection,
-    user_a: Uuid,
-    user_b: Uuid,
-) -> Result<bool, (StatusCode, String)> {
-    let row = Friendship::find()
-        .filter(friendship::Column::Status.eq(FriendshipStatus::Accepted))
-        .filter(
-            Condition::any()
-                .add(
-                    Condition::all()
-                        .add(friendship::Column::UserId.eq(user_a))
-                        .add(friendship::Column::FriendId.eq(user_b)),
-                )
-                .add(
-                    Condition::all()
-                        .add(friendship::Column::UserId.eq(user_b))
-                        .add(friendship::Column::FriendId.eq(user_a)),
-                ),
-        )
-        .one(db)
-        .await
-        .map_err(internal_error)?;
-    Ok(row.is_some())
-}
-
-fn internal_error<E: std::fmt::Display>(_e: E) -> (StatusCode, String) {
-    (StatusCode::INTERNAL_SERVER_ERROR, "Something went wrong. Please try again.".to_string())
-}
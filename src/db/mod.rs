@@ -1,7 +1,44 @@
-use sea_orm::{Database, DatabaseConnection};
-use std::env;
+use log::LevelFilter;
+use sea_orm::{ConnectOptions, Database, DatabaseConnection, DbErr, RuntimeErr};
+use std::time::Duration;
 
-pub async fn init_db() -> Result<DatabaseConnection, sea_orm::DbErr> {
-    let database_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
-    Database::connect(database_url).await
+use crate::config::DatabaseConfig;
+
+/// SQLSTATE of a unique index violation.
+pub const UNIQUE_VIOLATION: &str = "23505";
+
+pub async fn init_db(config: &DatabaseConfig) -> Result<DatabaseConnection, sea_orm::DbErr> {
+    let mut options = ConnectOptions::new(&config.url);
+    options
+        .max_connections(config.max_connections)
+        .min_connections(config.min_connections)
+        .acquire_timeout(Duration::from_secs(config.acquire_timeout_secs))
+        .sqlx_logging_level(LevelFilter::Debug)
+        .sqlx_slow_statements_logging_settings(
+            LevelFilter::Warn,
+            Duration::from_millis(config.slow_query_ms),
+        );
+    Database::connect(options).await
+}
+
+/// Name of the constraint or index a failed statement violated.
+pub fn constraint(err: &DbErr) -> Option<String> {
+    match err {
+        DbErr::Exec(RuntimeErr::SqlxError(sqlx::Error::Database(err)))
+        | DbErr::Query(RuntimeErr::SqlxError(sqlx::Error::Database(err))) => {
+            err.constraint().map(str::to_string)
+        }
+        _ => None,
+    }
+}
+
+/// SQLSTATE reported by Postgres for a failed statement, e.g. `23505`.
+pub fn sqlstate(err: &DbErr) -> Option<String> {
+    match err {
+        DbErr::Exec(RuntimeErr::SqlxError(sqlx::Error::Database(err)))
+        | DbErr::Query(RuntimeErr::SqlxError(sqlx::Error::Database(err))) => {
+            err.code().map(|code| code.into_owned())
+        }
+        _ => None,
+    }
 }
@@ -0,0 +1,50 @@
+use sea_orm::entity::prelude::*;
+use std::fmt;
+
+/// Where an export is in its life.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, EnumIter, DeriveActiveEnum)]
+#[sea_orm(rs_type = "String", db_type = "String(StringLen::N(16))")]
+pub enum DataExportStatus {
+    /// The archive is being written.
+    #[sea_orm(string_value = "pending")]
+    Pending,
+    /// The archive can be downloaded until the export expires.
+    #[sea_orm(string_value = "ready")]
+    Ready,
+    #[sea_orm(string_value = "failed")]
+    Failed,
+}
+
+impl fmt::Display for DataExportStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            DataExportStatus::Pending => "pending",
+            DataExportStatus::Ready => "ready",
+            DataExportStatus::Failed => "failed",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// A takeout archive of a user's data. The download token is only stored as
+/// its SHA-256 hash, the archive lives in `storage.data_dir/exports`.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "data_exports")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub status: DataExportStatus,
+    #[sea_orm(unique)]
+    pub token_hash: String,
+    pub size_bytes: Option<i64>,
+    pub created_at: DateTimeWithTimeZone,
+    pub completed_at: Option<DateTimeWithTimeZone>,
+    /// The download link stops working and the archive is deleted.
+    pub expires_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,70 @@
+use sea_orm::entity::prelude::*;
+use std::fmt;
+
+use crate::entities::event::EventStatus;
+
+/// Why an event changed status.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, EnumIter, DeriveActiveEnum)]
+#[sea_orm(rs_type = "String", db_type = "String(StringLen::N(32))")]
+pub enum EventTransition {
+    /// The owner created the event.
+    #[sea_orm(string_value = "created")]
+    Created,
+    /// The last pending participant accepted.
+    #[sea_orm(string_value = "confirmed")]
+    Confirmed,
+    /// A participant of a confirmed event declined.
+    #[sea_orm(string_value = "reopened")]
+    Reopened,
+    /// The only participant declined.
+    #[sea_orm(string_value = "declined")]
+    Declined,
+    /// The owner canceled the event.
+    #[sea_orm(string_value = "canceled_by_owner")]
+    CanceledByOwner,
+    /// The owner added the memory image on or after the event date.
+    #[sea_orm(string_value = "finished")]
+    Finished,
+    /// The date passed before everyone accepted.
+    #[sea_orm(string_value = "expired")]
+    Expired,
+    /// The date passed with everyone accepted.
+    #[sea_orm(string_value = "elapsed")]
+    Elapsed,
+}
+
+impl fmt::Display for EventTransition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            EventTransition::Created => "created",
+            EventTransition::Confirmed => "confirmed",
+            EventTransition::Reopened => "reopened",
+            EventTransition::Declined => "declined",
+            EventTransition::CanceledByOwner => "canceled_by_owner",
+            EventTransition::Finished => "finished",
+            EventTransition::Expired => "expired",
+            EventTransition::Elapsed => "elapsed",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// One status change of an event. `actor_id` is empty for changes made by the
+/// server itself, such as expiry.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "event_history")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: Uuid,
+    pub event_id: Uuid,
+    pub actor_id: Option<Uuid>,
+    pub from_status: Option<EventStatus>,
+    pub to_status: EventStatus,
+    pub reason: EventTransition,
+    pub created_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
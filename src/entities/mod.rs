@@ -1,28 +1,72 @@
 pub mod busyday;
+pub mod data_export;
+pub mod email_verification_token;
 pub mod event;
+pub mod event_history;
 pub mod friendship;
+pub mod passkey;
+pub mod password_reset_token;
+pub mod recovery_code;
 pub mod refresh_token;
+pub mod security_event;
+pub mod session;
 pub mod user;
 pub mod user_event;
+pub mod user_identity;
+pub mod user_totp;
+pub mod webauthn_challenge;
 pub mod wish_place;
 pub use busyday::ActiveModel as BusydayActiveModel;
 pub use busyday::Column as BusydayColumn;
 pub use busyday::Entity as Busyday;
+pub use data_export::ActiveModel as DataExportActiveModel;
+pub use data_export::Column as DataExportColumn;
+pub use data_export::Entity as DataExport;
+pub use email_verification_token::ActiveModel as EmailVerificationTokenActiveModel;
+pub use email_verification_token::Column as EmailVerificationTokenColumn;
+pub use email_verification_token::Entity as EmailVerificationToken;
 pub use event::ActiveModel as EventActiveModel;
 pub use event::Column as EventColumn;
 pub use event::Entity as Event;
+pub use event_history::ActiveModel as EventHistoryActiveModel;
+pub use event_history::Column as EventHistoryColumn;
+pub use event_history::Entity as EventHistory;
 pub use friendship::ActiveModel as FriendshipActiveModel;
 pub use friendship::Column as FriendshipColumn;
 pub use friendship::Entity as Friendship;
+pub use passkey::ActiveModel as PasskeyActiveModel;
+pub use passkey::Column as PasskeyColumn;
+pub use passkey::Entity as Passkey;
+pub use password_reset_token::ActiveModel as PasswordResetTokenActiveModel;
+pub use password_reset_token::Column as PasswordResetTokenColumn;
+pub use password_reset_token::Entity as PasswordResetToken;
+pub use recovery_code::ActiveModel as RecoveryCodeActiveModel;
+pub use recovery_code::Column as RecoveryCodeColumn;
+pub use recovery_code::Entity as RecoveryCode;
 pub use refresh_token::ActiveModel as RefreshTokenActiveModel;
 pub use refresh_token::Column as RefreshTokenColumn;
 pub use refresh_token::Entity as RefreshToken;
+pub use security_event::ActiveModel as SecurityEventActiveModel;
+pub use security_event::Column as SecurityEventColumn;
+pub use security_event::Entity as SecurityEvent;
+pub use session::ActiveModel as SessionActiveModel;
+pub use session::Column as SessionColumn;
+pub use session::Entity as Session;
 pub use user::ActiveModel as UserActiveModel;
 pub use user::Column as UserColumn;
 pub use user::Entity as User;
 pub use user_event::ActiveModel as UserEventActiveModel;
 pub use user_event::Column as UserEventColumn;
 pub use user_event::Entity as UserEvent;
+pub use user_identity::ActiveModel as UserIdentityActiveModel;
+pub use user_identity::Column as UserIdentityColumn;
+pub use user_identity::Entity as UserIdentity;
+pub use user_totp::ActiveModel as UserTotpActiveModel;
+pub use user_totp::Column as UserTotpColumn;
+pub use user_totp::Entity as UserTotp;
+pub use webauthn_challenge::ActiveModel as WebauthnChallengeActiveModel;
+pub use webauthn_challenge::Column as WebauthnChallengeColumn;
+pub use webauthn_challenge::Entity as WebauthnChallenge;
 pub use wish_place::ActiveModel as WishPlaceActiveModel;
 pub use wish_place::Column as WishPlaceColumn;
 pub use wish_place::Entity as WishPlace;
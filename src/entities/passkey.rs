@@ -0,0 +1,25 @@
+use sea_orm::entity::prelude::*;
+
+/// A WebAuthn credential a user logs in with instead of a password.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "passkeys")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: Uuid,
+    pub user_id: Uuid,
+    /// Credential id as base64url, the form browsers send it in.
+    pub credential_id: String,
+    /// COSE key from the registration.
+    pub public_key: Vec<u8>,
+    /// COSE algorithm of `public_key`.
+    pub algorithm: i32,
+    pub sign_count: i64,
+    pub name: String,
+    pub created_at: DateTimeWithTimeZone,
+    pub last_used_at: Option<DateTimeWithTimeZone>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
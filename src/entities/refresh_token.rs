@@ -6,6 +6,10 @@ pub struct Model {
     #[sea_orm(primary_key)]
     pub id: Uuid,
     pub user_id: Uuid,
+    /// Id of the first token issued at login, shared by every rotation of it.
+    pub family_id: Uuid,
+    /// The token this one was rotated from, empty for the first of a family.
+    pub parent_id: Option<Uuid>,
     pub expires_at: DateTimeWithTimeZone,
     pub revoked_at: Option<DateTimeWithTimeZone>,
     pub created_at: DateTimeWithTimeZone,
@@ -0,0 +1,39 @@
+use sea_orm::entity::prelude::*;
+use std::fmt;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, EnumIter, DeriveActiveEnum)]
+#[sea_orm(rs_type = "String", db_type = "String(StringLen::N(32))")]
+pub enum SecurityEventKind {
+    /// A refresh token that had already been rotated was presented again.
+    /// Its family was revoked.
+    #[sea_orm(string_value = "refresh_token_reuse")]
+    RefreshTokenReuse,
+}
+
+impl fmt::Display for SecurityEventKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            SecurityEventKind::RefreshTokenReuse => "refresh_token_reuse",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// Something suspicious that happened to an account. `details` depends on the
+/// kind.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "security_events")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub kind: SecurityEventKind,
+    pub ip: Option<String>,
+    pub details: Option<Json>,
+    pub created_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,25 @@
+use sea_orm::entity::prelude::*;
+
+/// One login of a user on one device. Its id is the family of the refresh
+/// tokens rotated from that login, and the `sid` claim of its access tokens.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "sessions")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub device_name: Option<String>,
+    pub platform: Option<String>,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub created_at: DateTimeWithTimeZone,
+    pub last_used_at: DateTimeWithTimeZone,
+    /// Expiry of the newest refresh token of the session.
+    pub expires_at: DateTimeWithTimeZone,
+    pub revoked_at: Option<DateTimeWithTimeZone>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -9,6 +9,18 @@ pub struct Model {
     pub password_hash: String,
     pub avatar_url: Option<String>,
     pub bio: Option<String>,
+    /// Lower-case and unique. Password reset mail and logins by address
+    /// need it verified.
+    pub email: Option<String>,
+    pub email_verified_at: Option<DateTimeWithTimeZone>,
+    pub locale: Option<String>,
+    /// IANA zone name used for "today", UTC when empty.
+    pub timezone: Option<String>,
+    pub disabled_at: Option<DateTimeWithTimeZone>,
+    /// When the account is purged unless the user changes their mind.
+    pub deletion_scheduled_at: Option<DateTimeWithTimeZone>,
+    /// Set once purged; the row stays, anonymised, for events of others.
+    pub deleted_at: Option<DateTimeWithTimeZone>,
     pub created_at: DateTimeWithTimeZone,
 }
 
@@ -0,0 +1,23 @@
+use sea_orm::entity::prelude::*;
+
+/// An account at a sign-in provider linked to a user, identified by the
+/// `sub` claim of its ID tokens.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "user_identities")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: Uuid,
+    pub user_id: Uuid,
+    /// Name of the provider in `oidc.providers`.
+    pub provider: String,
+    pub subject: String,
+    /// Address the provider last reported, for display only.
+    pub email: Option<String>,
+    pub created_at: DateTimeWithTimeZone,
+    pub last_used_at: Option<DateTimeWithTimeZone>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,21 @@
+use sea_orm::entity::prelude::*;
+
+/// The TOTP secret of a user. Two-factor login is on once `confirmed_at` is
+/// set; before that the enrollment is pending.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "user_totp")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub user_id: Uuid,
+    /// Base32, as shown to the authenticator app.
+    pub secret: String,
+    pub confirmed_at: Option<DateTimeWithTimeZone>,
+    /// Time step of the last accepted code, so no code works twice.
+    pub last_used_step: Option<i64>,
+    pub created_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,32 @@
+use sea_orm::entity::prelude::*;
+
+/// Which ceremony a challenge belongs to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, EnumIter, DeriveActiveEnum)]
+#[sea_orm(rs_type = "String", db_type = "String(StringLen::N(16))")]
+pub enum CeremonyPurpose {
+    /// Adding a passkey to a signed-in user.
+    #[sea_orm(string_value = "registration")]
+    Registration,
+    /// Logging in with any passkey.
+    #[sea_orm(string_value = "login")]
+    Login,
+}
+
+/// A challenge handed to an authenticator, used at most once.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "webauthn_challenges")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: Uuid,
+    /// The registering user; empty for logins.
+    pub user_id: Option<Uuid>,
+    pub purpose: CeremonyPurpose,
+    pub challenge: String,
+    pub expires_at: DateTimeWithTimeZone,
+    pub created_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,166 @@
+use std::error::Error;
+use std::fmt;
+use std::panic::Location;
+
+use axum::Json;
+use axum::http::{HeaderValue, header};
+use axum::response::{IntoResponse, Response};
+use sea_orm::DbErr;
+
+use crate::db;
+use crate::error::{ErrorBody, ErrorCode, FieldError};
+use crate::i18n::{self, Localized};
+use crate::request_id;
+
+pub type BoxError = Box<dyn Error + Send + Sync>;
+
+#[derive(Debug)]
+pub enum AppError {
+    /// Expected failure caused by the request, reported to the client by its code.
+    /// `fields` names the request fields at fault.
+    Client {
+        code: ErrorCode,
+        fields: Vec<&'static str>,
+    },
+    /// Request refused by a rate limit, the client may retry after `retry_after_secs`.
+    Throttled {
+        code: ErrorCode,
+        retry_after_secs: u64,
+    },
+    /// Unexpected failure. The client only sees `internal_error`, the cause is logged.
+    Internal {
+        source: BoxError,
+        location: &'static Location<'static>,
+    },
+}
+
+impl AppError {
+    pub fn field(code: ErrorCode, field: &'static str) -> Self {
+        AppError::Client {
+            code,
+            fields: vec![field],
+        }
+    }
+
+    #[track_caller]
+    pub fn internal(source: impl Into<BoxError>) -> Self {
+        AppError::Internal {
+            source: source.into(),
+            location: Location::caller(),
+        }
+    }
+
+    /// Reports a unique constraint violation as `code`, anything else as an internal error.
+    #[track_caller]
+    pub fn on_unique_violation(err: DbErr, code: ErrorCode) -> Self {
+        if db::sqlstate(&err).as_deref() == Some(db::UNIQUE_VIOLATION) {
+            return code.into();
+        }
+        AppError::internal(err)
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            AppError::Client { code, .. } | AppError::Throttled { code, .. } => *code,
+            AppError::Internal { .. } => ErrorCode::InternalError,
+        }
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AppError::Client { code, .. } => write!(f, "{code:?}"),
+            AppError::Throttled {
+                code,
+                retry_after_secs,
+            } => write!(f, "{code:?} (retry after {retry_after_secs}s)"),
+            AppError::Internal { source, location } => write!(f, "{source} (at {location})"),
+        }
+    }
+}
+
+impl Error for AppError {}
+
+impl From<ErrorCode> for AppError {
+    fn from(code: ErrorCode) -> Self {
+        AppError::Client {
+            code,
+            fields: Vec::new(),
+        }
+    }
+}
+
+impl From<DbErr> for AppError {
+    #[track_caller]
+    fn from(err: DbErr) -> Self {
+        AppError::internal(err)
+    }
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        let request_id = request_id::current();
+        let locale = i18n::current();
+        let code = self.code();
+
+        let mut retry_after = None;
+        let details = match self {
+            AppError::Client { fields, .. } => {
+                tracing::info!(?code, ?fields, "request rejected");
+                fields
+                    .into_iter()
+                    .map(|field| FieldError {
+                        field,
+                        message: code.message(locale).to_string(),
+                    })
+                    .collect()
+            }
+            AppError::Throttled {
+                retry_after_secs, ..
+            } => {
+                tracing::warn!(?code, retry_after_secs, "request throttled");
+                retry_after = Some(retry_after_secs);
+                Vec::new()
+            }
+            AppError::Internal { source, location } => {
+                tracing::error!(
+                    %location,
+                    error = %source,
+                    debug = ?source,
+                    "request failed with an internal error"
+                );
+                Vec::new()
+            }
+        };
+
+        let body = ErrorBody {
+            code,
+            message: code.message(locale).to_string(),
+            details,
+            request_id,
+        };
+        let mut response = (code.status(), Json(body)).into_response();
+        if let Some(secs) = retry_after {
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from(secs));
+        }
+        response
+    }
+}
+
+pub trait ResultExt<T> {
+    /// Turns any failure into `AppError::Internal`, recording the caller location.
+    fn or_internal(self) -> Result<T, AppError>;
+}
+
+impl<T, E: Into<BoxError>> ResultExt<T> for Result<T, E> {
+    #[track_caller]
+    fn or_internal(self) -> Result<T, AppError> {
+        match self {
+            Ok(value) => Ok(value),
+            Err(err) => Err(AppError::internal(err)),
+        }
+    }
+}
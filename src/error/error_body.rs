@@ -0,0 +1,21 @@
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::error::ErrorCode;
+
+/// JSON body of every error response.
+#[derive(Serialize, ToSchema)]
+pub struct ErrorBody {
+    pub code: ErrorCode,
+    pub message: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub details: Vec<FieldError>,
+    pub request_id: Option<String>,
+}
+
+/// Validation problem with a single request field.
+#[derive(Clone, Debug, Serialize, ToSchema)]
+pub struct FieldError {
+    pub field: &'static str,
+    pub message: String,
+}
@@ -0,0 +1,183 @@
+use axum::http::StatusCode;
+use serde::Serialize;
+use utoipa::ToSchema;
+
+/// Stable machine-readable error codes. Clients match on these, so existing
+/// values must never be renamed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    // Generic
+    InternalError,
+    InvalidPagination,
+    InvalidDate,
+    InvalidDateRange,
+    TitleRequired,
+    NothingToUpdate,
+    FriendsOnly,
+    UnsupportedLocale,
+    UnsupportedTimezone,
+
+    // Auth
+    MissingCredentials,
+    UsernameTaken,
+    InvalidCredentials,
+    SessionExpired,
+    InvalidToken,
+    AccountDisabled,
+    TooManyRequests,
+    AccountLocked,
+    SessionNotFound,
+    PasswordRequired,
+    CurrentPasswordIncorrect,
+    InvalidResetToken,
+    TwoFactorChallengeExpired,
+    InvalidTwoFactorCode,
+    TwoFactorAlreadyEnabled,
+    TwoFactorNotEnrolled,
+    TwoFactorNotEnabled,
+    RecentLoginRequired,
+    UnknownIdentityProvider,
+    InvalidIdToken,
+    IdentityEmailInUse,
+    IdentityLinkedElsewhere,
+    ProviderAlreadyLinked,
+    IdentityNotFound,
+    LastSignInMethod,
+    PasskeyChallengeExpired,
+    InvalidPasskeyRegistration,
+    InvalidPasskey,
+    InvalidPasskeyName,
+    PasskeyAlreadyRegistered,
+    PasskeyNotFound,
+
+    // Users
+    ProfileNotFound,
+    UserNotFound,
+    SearchQueryRequired,
+    InvalidEmail,
+    EmailTaken,
+    EmailNotSet,
+    EmailAlreadyVerified,
+    InvalidVerificationToken,
+    AccountDeletionPending,
+    AccountDeletionNotScheduled,
+    DataExportPending,
+    DataExportNotFound,
+    DataExportNotReady,
+
+    // Friends
+    CannotBefriendSelf,
+    CannotRemoveSelf,
+    OwnFriendRequest,
+    FriendRequestExists,
+    FriendRequestNotFound,
+    FriendshipNotFound,
+
+    // Events
+    InviteFriendsOnly,
+    ParticipantsBusy,
+    DateReserved,
+    EventNotFound,
+    EventNotOwned,
+    EventAccessDenied,
+    EventClosed,
+    EventCompleted,
+    EventNotStarted,
+    EventTransitionNotAllowed,
+    MemoryImageRequired,
+    InvitationNotFound,
+    ParticipationNotFound,
+
+    // Wish places
+    WishPlaceNotFound,
+    NotEventCreator,
+}
+
+impl ErrorCode {
+    pub fn status(self) -> StatusCode {
+        match self {
+            ErrorCode::InternalError => StatusCode::INTERNAL_SERVER_ERROR,
+
+            ErrorCode::TooManyRequests | ErrorCode::AccountLocked => {
+                StatusCode::TOO_MANY_REQUESTS
+            }
+
+            ErrorCode::InvalidPagination
+            | ErrorCode::InvalidDate
+            | ErrorCode::InvalidDateRange
+            | ErrorCode::TitleRequired
+            | ErrorCode::NothingToUpdate
+            | ErrorCode::MissingCredentials
+            | ErrorCode::PasswordRequired
+            | ErrorCode::CurrentPasswordIncorrect
+            | ErrorCode::InvalidResetToken
+            | ErrorCode::InvalidTwoFactorCode
+            | ErrorCode::TwoFactorNotEnrolled
+            | ErrorCode::TwoFactorNotEnabled
+            | ErrorCode::PasskeyChallengeExpired
+            | ErrorCode::InvalidPasskeyRegistration
+            | ErrorCode::InvalidPasskeyName
+            | ErrorCode::SearchQueryRequired
+            | ErrorCode::InvalidEmail
+            | ErrorCode::EmailNotSet
+            | ErrorCode::InvalidVerificationToken
+            | ErrorCode::AccountDeletionNotScheduled
+            | ErrorCode::CannotBefriendSelf
+            | ErrorCode::CannotRemoveSelf
+            | ErrorCode::OwnFriendRequest
+            | ErrorCode::MemoryImageRequired
+            | ErrorCode::UnsupportedLocale
+            | ErrorCode::UnsupportedTimezone => StatusCode::BAD_REQUEST,
+
+            ErrorCode::InvalidCredentials
+            | ErrorCode::SessionExpired
+            | ErrorCode::InvalidToken
+            | ErrorCode::TwoFactorChallengeExpired
+            | ErrorCode::InvalidIdToken
+            | ErrorCode::InvalidPasskey => StatusCode::UNAUTHORIZED,
+
+            ErrorCode::FriendsOnly
+            | ErrorCode::AccountDisabled
+            | ErrorCode::RecentLoginRequired
+            | ErrorCode::InviteFriendsOnly
+            | ErrorCode::EventAccessDenied
+            | ErrorCode::NotEventCreator => StatusCode::FORBIDDEN,
+
+            ErrorCode::ProfileNotFound
+            | ErrorCode::UserNotFound
+            | ErrorCode::FriendRequestNotFound
+            | ErrorCode::FriendshipNotFound
+            | ErrorCode::EventNotFound
+            | ErrorCode::EventNotOwned
+            | ErrorCode::InvitationNotFound
+            | ErrorCode::ParticipationNotFound
+            | ErrorCode::WishPlaceNotFound
+            | ErrorCode::SessionNotFound
+            | ErrorCode::UnknownIdentityProvider
+            | ErrorCode::IdentityNotFound
+            | ErrorCode::PasskeyNotFound
+            | ErrorCode::DataExportNotFound => StatusCode::NOT_FOUND,
+
+            ErrorCode::UsernameTaken
+            | ErrorCode::TwoFactorAlreadyEnabled
+            | ErrorCode::IdentityEmailInUse
+            | ErrorCode::IdentityLinkedElsewhere
+            | ErrorCode::ProviderAlreadyLinked
+            | ErrorCode::LastSignInMethod
+            | ErrorCode::PasskeyAlreadyRegistered
+            | ErrorCode::EmailTaken
+            | ErrorCode::EmailAlreadyVerified
+            | ErrorCode::AccountDeletionPending
+            | ErrorCode::DataExportPending
+            | ErrorCode::DataExportNotReady
+            | ErrorCode::FriendRequestExists
+            | ErrorCode::ParticipantsBusy
+            | ErrorCode::DateReserved
+            | ErrorCode::EventClosed
+            | ErrorCode::EventCompleted
+            | ErrorCode::EventNotStarted
+            | ErrorCode::EventTransitionNotAllowed => StatusCode::CONFLICT,
+        }
+    }
+}
@@ -0,0 +1,7 @@
+pub mod app_error;
+pub mod error_body;
+pub mod error_code;
+
+pub use app_error::*;
+pub use error_body::*;
+pub use error_code::*;
@@ -0,0 +1,67 @@
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use serde::Serialize;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use crate::export::Takeout;
+
+/// Writes `takeout` as a ZIP archive to `path` and returns its size. The
+/// archive is written next to `path` first, so `path` never holds half of one.
+pub fn write(path: &Path, takeout: &Takeout) -> io::Result<u64> {
+    let partial = path.with_extension("part");
+    match write_to(&partial, takeout) {
+        Ok(()) => {
+            fs::rename(&partial, path)?;
+            Ok(fs::metadata(path)?.len())
+        }
+        Err(err) => {
+            let _ = fs::remove_file(&partial);
+            Err(err)
+        }
+    }
+}
+
+fn write_to(path: &Path, takeout: &Takeout) -> io::Result<()> {
+    let modified =
+        zip::DateTime::try_from(takeout.manifest.exported_at.naive_utc()).unwrap_or_default();
+    let json = SimpleFileOptions::default()
+        .compression_method(CompressionMethod::Deflated)
+        .last_modified_time(modified);
+    // Images are compressed already.
+    let image = SimpleFileOptions::default()
+        .compression_method(CompressionMethod::Stored)
+        .last_modified_time(modified);
+
+    let mut zip = ZipWriter::new(BufWriter::new(File::create(path)?));
+    write_json(&mut zip, "manifest.json", &takeout.manifest, json)?;
+    write_json(&mut zip, "profile.json", &takeout.profile, json)?;
+    write_json(&mut zip, "friendships.json", &takeout.friendships, json)?;
+    write_json(&mut zip, "events.json", &takeout.events, json)?;
+    write_json(&mut zip, "rsvps.json", &takeout.rsvps, json)?;
+    write_json(&mut zip, "busy_days.json", &takeout.busy_days, json)?;
+    write_json(&mut zip, "wish_places.json", &takeout.wish_places, json)?;
+    for memory in &takeout.memories {
+        zip.start_file(memory.path.as_str(), image)
+            .map_err(io::Error::other)?;
+        zip.write_all(&memory.bytes)?;
+    }
+    zip.finish()
+        .map_err(io::Error::other)?
+        .into_inner()
+        .map_err(io::IntoInnerError::into_error)?
+        .sync_all()
+}
+
+fn write_json<W: Write + io::Seek, T: Serialize>(
+    zip: &mut ZipWriter<W>,
+    name: &str,
+    value: &T,
+    options: SimpleFileOptions,
+) -> io::Result<()> {
+    zip.start_file(name, options).map_err(io::Error::other)?;
+    serde_json::to_writer_pretty(&mut *zip, value)?;
+    Ok(())
+}
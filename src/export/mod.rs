@@ -0,0 +1,29 @@
+//! The takeout archive: a ZIP with a user's data as JSON and their memory
+//! images as files.
+//!
+//! Format version 1:
+//!
+//! | file | content |
+//! |------|---------|
+//! | `manifest.json` | `format`, `version`, `exported_at`, `user_id` and the list of `files` |
+//! | `profile.json` | the account: username, email, bio, avatar, locale, time zone, dates |
+//! | `friendships.json` | friends and open requests with their `direction` |
+//! | `events.json` | events the user organised or was invited to, with participants |
+//! | `rsvps.json` | the user's own role and answer per event |
+//! | `busy_days.json` | days the user marked busy or that an event took |
+//! | `wish_places.json` | the wish list |
+//! | `memories/<event id>.<ext>` | decoded memory images, linked from `events.json` |
+//!
+//! Dates are `YYYY-MM-DD`, times RFC 3339, ids UUIDs. Fields are only ever
+//! added within a version; renaming or removing one bumps [`FORMAT_VERSION`].
+
+pub mod archive;
+pub mod records;
+
+pub use archive::write;
+pub use records::{Takeout, collect};
+
+/// Name of the format in `manifest.json`.
+pub const FORMAT_NAME: &str = "friends-takeout";
+/// Version of the format in `manifest.json`.
+pub const FORMAT_VERSION: u32 = 1;
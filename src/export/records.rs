@@ -0,0 +1,378 @@
+//! What goes into an archive, read from the database.
+
+use std::collections::HashMap;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use chrono::{DateTime, NaiveDate, Utc};
+use sea_orm::prelude::DateTimeWithTimeZone;
+use sea_orm::{
+    ColumnTrait, Condition, ConnectionTrait, DbErr, EntityTrait, QueryFilter, QueryOrder,
+    QuerySelect, QueryTrait,
+};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::entities::friendship::FriendshipStatus;
+use crate::entities::{
+    Busyday, BusydayColumn, Event, EventColumn, Friendship, FriendshipColumn, User, UserColumn,
+    UserEvent, UserEventColumn, WishPlace, WishPlaceColumn, user,
+};
+use crate::export::{FORMAT_NAME, FORMAT_VERSION};
+
+/// A user's data, ready to be written as an archive.
+pub struct Takeout {
+    pub manifest: Manifest,
+    pub profile: Profile,
+    pub friendships: Vec<FriendshipRecord>,
+    pub events: Vec<EventRecord>,
+    pub rsvps: Vec<RsvpRecord>,
+    pub busy_days: Vec<BusyDayRecord>,
+    pub wish_places: Vec<WishPlaceRecord>,
+    pub memories: Vec<Memory>,
+}
+
+#[derive(Serialize)]
+pub struct Manifest {
+    pub format: &'static str,
+    pub version: u32,
+    pub exported_at: DateTime<Utc>,
+    pub user_id: Uuid,
+    pub files: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct Profile {
+    pub id: Uuid,
+    pub username: String,
+    pub email: Option<String>,
+    pub email_verified_at: Option<DateTimeWithTimeZone>,
+    pub avatar_url: Option<String>,
+    pub bio: Option<String>,
+    pub locale: Option<String>,
+    pub timezone: Option<String>,
+    pub created_at: DateTimeWithTimeZone,
+    pub deletion_scheduled_at: Option<DateTimeWithTimeZone>,
+}
+
+#[derive(Serialize)]
+pub struct FriendshipRecord {
+    pub user_id: Uuid,
+    pub username: String,
+    /// `accepted`, or `pending` for a request not answered yet.
+    pub status: &'static str,
+    /// `outgoing` when the user sent the request, `incoming` otherwise.
+    pub direction: &'static str,
+}
+
+#[derive(Serialize)]
+pub struct EventRecord {
+    pub id: Uuid,
+    pub creator_id: Uuid,
+    pub date: NaiveDate,
+    pub title: String,
+    pub description: Option<String>,
+    pub location: Option<String>,
+    pub status: String,
+    pub wish_place_id: Option<Uuid>,
+    pub created_at: DateTimeWithTimeZone,
+    pub participants: Vec<ParticipantRecord>,
+    /// Path of the memory image in the archive.
+    pub memory_image: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct ParticipantRecord {
+    pub user_id: Uuid,
+    pub username: String,
+    pub role: String,
+    pub response_status: String,
+}
+
+#[derive(Serialize)]
+pub struct RsvpRecord {
+    pub event_id: Uuid,
+    pub role: String,
+    pub response_status: String,
+}
+
+#[derive(Serialize)]
+pub struct BusyDayRecord {
+    pub date: NaiveDate,
+    /// The event that took the day; empty when the user marked it.
+    pub event_id: Option<Uuid>,
+}
+
+#[derive(Serialize)]
+pub struct WishPlaceRecord {
+    pub id: Uuid,
+    pub title: String,
+    pub description: Option<String>,
+    pub location: Option<String>,
+    pub link: Option<String>,
+    pub status: String,
+    pub visited_event_id: Option<Uuid>,
+    pub created_at: DateTimeWithTimeZone,
+    pub updated_at: DateTimeWithTimeZone,
+}
+
+/// A memory image file of the archive.
+pub struct Memory {
+    pub path: String,
+    pub bytes: Vec<u8>,
+}
+
+/// Reads everything of `user` that goes into an archive made at `now`.
+pub async fn collect<C: ConnectionTrait>(
+    db: &C,
+    user: user::Model,
+    now: DateTime<Utc>,
+) -> Result<Takeout, DbErr> {
+    let user_id = user.id;
+
+    let friendships = Friendship::find()
+        .filter(
+            Condition::any()
+                .add(FriendshipColumn::UserId.eq(user_id))
+                .add(FriendshipColumn::FriendId.eq(user_id)),
+        )
+        .all(db)
+        .await?;
+
+    let my_events = UserEvent::find()
+        .select_only()
+        .column(UserEventColumn::EventId)
+        .filter(UserEventColumn::UserId.eq(user_id))
+        .into_query();
+    let events = Event::find()
+        .filter(
+            Condition::any()
+                .add(EventColumn::CreatorId.eq(user_id))
+                .add(EventColumn::Id.in_subquery(my_events)),
+        )
+        .order_by_asc(EventColumn::Date)
+        .order_by_asc(EventColumn::CreatedAt)
+        .all(db)
+        .await?;
+    let event_ids: Vec<Uuid> = events.iter().map(|event| event.id).collect();
+    let participations = UserEvent::find()
+        .filter(UserEventColumn::EventId.is_in(event_ids))
+        .all(db)
+        .await?;
+
+    let mut others: Vec<Uuid> = participations.iter().map(|p| p.user_id).collect();
+    others.extend(friendships.iter().map(|f| {
+        if f.user_id == user_id {
+            f.friend_id
+        } else {
+            f.user_id
+        }
+    }));
+    others.sort_unstable();
+    others.dedup();
+    let usernames: HashMap<Uuid, String> = User::find()
+        .select_only()
+        .columns([UserColumn::Id, UserColumn::Username])
+        .filter(UserColumn::Id.is_in(others))
+        .into_tuple::<(Uuid, String)>()
+        .all(db)
+        .await?
+        .into_iter()
+        .collect();
+    let username = |id: Uuid| usernames.get(&id).cloned().unwrap_or_default();
+
+    let friendships = friendships
+        .into_iter()
+        .map(|friendship| {
+            let outgoing = friendship.user_id == user_id;
+            let other = if outgoing {
+                friendship.friend_id
+            } else {
+                friendship.user_id
+            };
+            FriendshipRecord {
+                user_id: other,
+                username: username(other),
+                status: match friendship.status {
+                    FriendshipStatus::Pending => "pending",
+                    FriendshipStatus::Accepted => "accepted",
+                },
+                direction: if outgoing { "outgoing" } else { "incoming" },
+            }
+        })
+        .collect();
+
+    let rsvps = participations
+        .iter()
+        .filter(|p| p.user_id == user_id)
+        .map(|p| RsvpRecord {
+            event_id: p.event_id,
+            role: p.role.to_string(),
+            response_status: p.response_status.to_string(),
+        })
+        .collect();
+
+    let mut memories = Vec::new();
+    let events = events
+        .into_iter()
+        .map(|event| {
+            let participants = participations
+                .iter()
+                .filter(|p| p.event_id == event.id)
+                .map(|p| ParticipantRecord {
+                    user_id: p.user_id,
+                    username: username(p.user_id),
+                    role: p.role.to_string(),
+                    response_status: p.response_status.to_string(),
+                })
+                .collect();
+            let memory_image = event.memory_image_base64.as_deref().map(|image| {
+                let memory = memory_file(event.id, image);
+                let path = memory.path.clone();
+                memories.push(memory);
+                path
+            });
+            EventRecord {
+                id: event.id,
+                creator_id: event.creator_id,
+                date: event.date,
+                title: event.title,
+                description: event.description,
+                location: event.location,
+                status: event.status.to_string(),
+                wish_place_id: event.wish_place_id,
+                created_at: event.created_at,
+                participants,
+                memory_image,
+            }
+        })
+        .collect();
+
+    let busy_days = Busyday::find()
+        .filter(BusydayColumn::UserId.eq(user_id))
+        .order_by_asc(BusydayColumn::Date)
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|day| BusyDayRecord {
+            date: day.date,
+            event_id: day.event_id,
+        })
+        .collect();
+
+    let wish_places = WishPlace::find()
+        .filter(WishPlaceColumn::UserId.eq(user_id))
+        .order_by_asc(WishPlaceColumn::CreatedAt)
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|place| WishPlaceRecord {
+            id: place.id,
+            title: place.title,
+            description: place.description,
+            location: place.location,
+            link: place.link,
+            status: place.status.to_string(),
+            visited_event_id: place.visited_event_id,
+            created_at: place.created_at,
+            updated_at: place.updated_at,
+        })
+        .collect();
+
+    let mut files: Vec<String> = [
+        "profile.json",
+        "friendships.json",
+        "events.json",
+        "rsvps.json",
+        "busy_days.json",
+        "wish_places.json",
+    ]
+    .map(String::from)
+    .into();
+    files.extend(memories.iter().map(|memory| memory.path.clone()));
+
+    Ok(Takeout {
+        manifest: Manifest {
+            format: FORMAT_NAME,
+            version: FORMAT_VERSION,
+            exported_at: now,
+            user_id,
+            files,
+        },
+        profile: Profile {
+            id: user.id,
+            username: user.username,
+            email: user.email,
+            email_verified_at: user.email_verified_at,
+            avatar_url: user.avatar_url,
+            bio: user.bio,
+            locale: user.locale,
+            timezone: user.timezone,
+            created_at: user.created_at,
+            deletion_scheduled_at: user.deletion_scheduled_at,
+        },
+        friendships,
+        events,
+        rsvps,
+        busy_days,
+        wish_places,
+        memories,
+    })
+}
+
+/// The memory image of `event_id` as a file. Images are stored as base64,
+/// possibly as a data URL; anything that does not decode is kept as text.
+fn memory_file(event_id: Uuid, image: &str) -> Memory {
+    let data = image.split_once(";base64,").map_or(image, |(_, data)| data);
+    let data: String = data.chars().filter(|c| !c.is_whitespace()).collect();
+    match STANDARD.decode(data) {
+        Ok(bytes) => Memory {
+            path: format!("memories/{event_id}.{}", extension(&bytes)),
+            bytes,
+        },
+        Err(_) => Memory {
+            path: format!("memories/{event_id}.txt"),
+            bytes: image.as_bytes().to_vec(),
+        },
+    }
+}
+
+/// File extension for the image format `bytes` start with.
+fn extension(bytes: &[u8]) -> &'static str {
+    match bytes {
+        [0x89, b'P', b'N', b'G', ..] => "png",
+        [0xff, 0xd8, 0xff, ..] => "jpg",
+        [b'G', b'I', b'F', b'8', ..] => "gif",
+        [
+            b'R',
+            b'I',
+            b'F',
+            b'F',
+            _,
+            _,
+            _,
+            _,
+            b'W',
+            b'E',
+            b'B',
+            b'P',
+            ..,
+        ] => "webp",
+        [
+            _,
+            _,
+            _,
+            _,
+            b'f',
+            b't',
+            b'y',
+            b'p',
+            b'h',
+            b'e',
+            b'i',
+            b'c',
+            ..,
+        ] => "heic",
+        _ => "bin",
+    }
+}
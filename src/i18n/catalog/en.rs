@@ -0,0 +1,107 @@
+use crate::error::ErrorCode;
+use crate::i18n::catalog::MailText;
+use crate::mail::MailTemplate;
+
+pub fn error(code: ErrorCode) -> &'static str {
+    match code {
+        ErrorCode::InternalError => "Something went wrong. Please try again.",
+        ErrorCode::InvalidPagination => "Invalid page cursor or limit.",
+        ErrorCode::InvalidDate => "Invalid date format. Use YYYY-MM-DD.",
+        ErrorCode::InvalidDateRange => "Start date must be before or equal to end date.",
+        ErrorCode::TitleRequired => "Please enter a title.",
+        ErrorCode::NothingToUpdate => "Please provide at least one field to update.",
+        ErrorCode::FriendsOnly => "You can only do this for yourself or an accepted friend.",
+        ErrorCode::UnsupportedLocale => "This language is not supported.",
+        ErrorCode::UnsupportedTimezone => "Unknown time zone, use a name like Europe/Moscow.",
+        ErrorCode::MissingCredentials => "Please enter both username and password.",
+        ErrorCode::UsernameTaken => "This username is already taken. Please choose another.",
+        ErrorCode::InvalidCredentials => "Invalid username or password.",
+        ErrorCode::SessionExpired => "Your session has expired. Please log in again.",
+        ErrorCode::InvalidToken => "Invalid token. Please log in again.",
+        ErrorCode::AccountDisabled => "This account has been disabled.",
+        ErrorCode::TooManyRequests => "Too many attempts. Please wait and try again.",
+        ErrorCode::AccountLocked => "Too many failed logins. Please wait before trying again.",
+        ErrorCode::SessionNotFound => "Session not found or already ended.",
+        ErrorCode::PasswordRequired => "Please enter a new password.",
+        ErrorCode::CurrentPasswordIncorrect => "The current password is incorrect.",
+        ErrorCode::InvalidResetToken => "This reset link is invalid or has expired. Please request a new one.",
+        ErrorCode::TwoFactorChallengeExpired => "The login took too long. Please enter your password again.",
+        ErrorCode::InvalidTwoFactorCode => "This code is incorrect or has already been used.",
+        ErrorCode::TwoFactorAlreadyEnabled => "Two-factor authentication is already turned on.",
+        ErrorCode::TwoFactorNotEnrolled => "Start setting up two-factor authentication first.",
+        ErrorCode::TwoFactorNotEnabled => "Two-factor authentication is not turned on.",
+        ErrorCode::RecentLoginRequired => "Please log in again to continue.",
+        ErrorCode::UnknownIdentityProvider => "This sign-in provider is not supported.",
+        ErrorCode::InvalidIdToken => "Signing in with this provider failed. Please try again.",
+        ErrorCode::IdentityEmailInUse => "An account with this email address already exists. Log in and link the provider in your settings.",
+        ErrorCode::IdentityLinkedElsewhere => "This sign-in account is already linked to another user.",
+        ErrorCode::ProviderAlreadyLinked => "An account of this provider is already linked.",
+        ErrorCode::IdentityNotFound => "No account of this provider is linked.",
+        ErrorCode::LastSignInMethod => "Set a password, link a provider or add a passkey before removing your only way to sign in.",
+        ErrorCode::PasskeyChallengeExpired => "The passkey prompt has expired. Please try again.",
+        ErrorCode::InvalidPasskeyRegistration => "This passkey could not be added. Please try again.",
+        ErrorCode::InvalidPasskey => "Signing in with this passkey failed.",
+        ErrorCode::InvalidPasskeyName => "Passkey names must be 1 to 64 characters long.",
+        ErrorCode::PasskeyAlreadyRegistered => "This passkey is already registered.",
+        ErrorCode::PasskeyNotFound => "Passkey not found.",
+        ErrorCode::ProfileNotFound => "Your profile could not be found.",
+        ErrorCode::UserNotFound => "This user profile does not exist.",
+        ErrorCode::SearchQueryRequired => "Please enter a username to search.",
+        ErrorCode::InvalidEmail => "Please enter a valid email address.",
+        ErrorCode::EmailTaken => "This email address is already used by another account.",
+        ErrorCode::EmailNotSet => "Add an email address to your profile first.",
+        ErrorCode::EmailAlreadyVerified => "This email address is already verified.",
+        ErrorCode::InvalidVerificationToken => "This verification link is invalid or has expired. Please request a new one.",
+        ErrorCode::AccountDeletionPending => "Your account is already scheduled for deletion.",
+        ErrorCode::AccountDeletionNotScheduled => "Your account is not scheduled for deletion.",
+        ErrorCode::DataExportPending => "An export of your data is already being prepared.",
+        ErrorCode::DataExportNotFound => "This download link is invalid or has expired.",
+        ErrorCode::DataExportNotReady => "The export is not ready yet, try again in a minute.",
+        ErrorCode::CannotBefriendSelf => "You cannot add yourself as a friend.",
+        ErrorCode::CannotRemoveSelf => "You cannot remove yourself.",
+        ErrorCode::OwnFriendRequest => "You cannot respond to your own friend request.",
+        ErrorCode::FriendRequestExists => "A friend request between you already exists.",
+        ErrorCode::FriendRequestNotFound => "This friend request does not exist.",
+        ErrorCode::FriendshipNotFound => "This friendship does not exist.",
+        ErrorCode::InviteFriendsOnly => "You can only invite accepted friends to events.",
+        ErrorCode::ParticipantsBusy => "One or more participants are already busy on this date.",
+        ErrorCode::DateReserved => "This date is already reserved.",
+        ErrorCode::EventNotFound => "Event not found.",
+        ErrorCode::EventNotOwned => "Event not found or you are not the creator.",
+        ErrorCode::EventAccessDenied => "You are not a participant in this event.",
+        ErrorCode::EventClosed => "This event has already been completed or canceled.",
+        ErrorCode::EventCompleted => "Completed events cannot be canceled.",
+        ErrorCode::EventNotStarted => "You can only complete an event on or after the event date.",
+        ErrorCode::EventTransitionNotAllowed => "The event cannot move to that status from its current one.",
+        ErrorCode::MemoryImageRequired => "Please add a memory image to complete the event.",
+        ErrorCode::InvitationNotFound => "You are not a pending participant in this event.",
+        ErrorCode::ParticipationNotFound => "You are not a participant in this event.",
+        ErrorCode::WishPlaceNotFound => "Wish place not found or you are not the owner.",
+        ErrorCode::NotEventCreator => "Only the event creator can mark a place as visited.",
+    }
+}
+
+pub fn mail(template: MailTemplate) -> MailText {
+    match template {
+        MailTemplate::PasswordReset => MailText {
+            subject: "Reset your Friends password",
+            body: "Hi {username},\n\nSomeone asked to reset the password of your Friends account. To choose a new one, open this link within {minutes} minutes:\n\n{link}\n\nIf it was not you, ignore this mail; your password stays the same.\n",
+        },
+        MailTemplate::EmailVerification => MailText {
+            subject: "Confirm your email for Friends",
+            body: "Hi {username},\n\nPlease confirm that this address belongs to your Friends account by opening this link within {hours} hours:\n\n{link}\n\nIf you did not add this address, ignore this mail.\n",
+        },
+        MailTemplate::AccountDeletionScheduled => MailText {
+            subject: "Your Friends account will be deleted",
+            body: "Hi {username},\n\nYour Friends account and its data will be deleted on {date} (UTC). Until then you can change your mind: log in and undo the deletion in your settings.\n\nIf it was not you, log in now, undo it and change your password.\n",
+        },
+        MailTemplate::EventCanceledByDeletion => MailText {
+            subject: "\"{title}\" on {date} is canceled",
+            body: "Hi {username},\n\nThe organizer of \"{title}\" on {date} has deleted their Friends account, so the event is canceled and the day is free again.\n",
+        },
+        MailTemplate::DataExportReady => MailText {
+            subject: "Your Friends data is ready to download",
+            body: "Hi {username},\n\nThe copy of your Friends data you asked for is ready. Download it within {hours} hours:\n\n{link}\n\nAnyone with this link can download your data, so do not share it.\n",
+        },
+    }
+}
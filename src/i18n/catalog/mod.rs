@@ -0,0 +1,34 @@
+mod en;
+mod ru;
+
+use crate::error::ErrorCode;
+use crate::i18n::Locale;
+use crate::mail::MailTemplate;
+
+/// Codes with a user-facing text in every supported language.
+pub trait Localized {
+    fn message(&self, locale: Locale) -> &'static str;
+}
+
+impl Localized for ErrorCode {
+    fn message(&self, locale: Locale) -> &'static str {
+        match locale {
+            Locale::En => en::error(*self),
+            Locale::Ru => ru::error(*self),
+        }
+    }
+}
+
+/// Subject and body of a mail. `{name}` placeholders in the body are filled
+/// in by the sender.
+pub struct MailText {
+    pub subject: &'static str,
+    pub body: &'static str,
+}
+
+pub fn mail(template: MailTemplate, locale: Locale) -> MailText {
+    match locale {
+        Locale::En => en::mail(template),
+        Locale::Ru => ru::mail(template),
+    }
+}
@@ -0,0 +1,107 @@
+use crate::error::ErrorCode;
+use crate::i18n::catalog::MailText;
+use crate::mail::MailTemplate;
+
+pub fn error(code: ErrorCode) -> &'static str {
+    match code {
+        ErrorCode::InternalError => "Что-то пошло не так. Попробуйте ещё раз.",
+        ErrorCode::InvalidPagination => "Неверный курсор или размер страницы.",
+        ErrorCode::InvalidDate => "Неверный формат даты. Используйте ГГГГ-ММ-ДД.",
+        ErrorCode::InvalidDateRange => "Дата начала должна быть не позже даты окончания.",
+        ErrorCode::TitleRequired => "Введите название.",
+        ErrorCode::NothingToUpdate => "Укажите хотя бы одно поле для изменения.",
+        ErrorCode::FriendsOnly => "Это доступно только для вас и ваших друзей.",
+        ErrorCode::UnsupportedLocale => "Этот язык не поддерживается.",
+        ErrorCode::UnsupportedTimezone => "Неизвестный часовой пояс, укажите название вроде Europe/Moscow.",
+        ErrorCode::MissingCredentials => "Введите имя пользователя и пароль.",
+        ErrorCode::UsernameTaken => "Это имя пользователя уже занято. Выберите другое.",
+        ErrorCode::InvalidCredentials => "Неверное имя пользователя или пароль.",
+        ErrorCode::SessionExpired => "Сессия истекла. Войдите снова.",
+        ErrorCode::InvalidToken => "Недействительный токен. Войдите снова.",
+        ErrorCode::AccountDisabled => "Эта учётная запись заблокирована.",
+        ErrorCode::TooManyRequests => "Слишком много попыток. Подождите и попробуйте снова.",
+        ErrorCode::AccountLocked => "Слишком много неудачных входов. Подождите, прежде чем пробовать снова.",
+        ErrorCode::SessionNotFound => "Сессия не найдена или уже завершена.",
+        ErrorCode::PasswordRequired => "Введите новый пароль.",
+        ErrorCode::CurrentPasswordIncorrect => "Текущий пароль указан неверно.",
+        ErrorCode::InvalidResetToken => "Ссылка для сброса недействительна или устарела. Запросите новую.",
+        ErrorCode::TwoFactorChallengeExpired => "Вход занял слишком много времени. Введите пароль ещё раз.",
+        ErrorCode::InvalidTwoFactorCode => "Код неверный или уже использован.",
+        ErrorCode::TwoFactorAlreadyEnabled => "Двухфакторная аутентификация уже включена.",
+        ErrorCode::TwoFactorNotEnrolled => "Сначала начните настройку двухфакторной аутентификации.",
+        ErrorCode::TwoFactorNotEnabled => "Двухфакторная аутентификация не включена.",
+        ErrorCode::RecentLoginRequired => "Чтобы продолжить, войдите снова.",
+        ErrorCode::UnknownIdentityProvider => "Этот способ входа не поддерживается.",
+        ErrorCode::InvalidIdToken => "Не удалось войти через этого провайдера. Попробуйте ещё раз.",
+        ErrorCode::IdentityEmailInUse => "Аккаунт с этим адресом уже существует. Войдите и привяжите провайдера в настройках.",
+        ErrorCode::IdentityLinkedElsewhere => "Этот аккаунт для входа уже привязан к другому пользователю.",
+        ErrorCode::ProviderAlreadyLinked => "Аккаунт этого провайдера уже привязан.",
+        ErrorCode::IdentityNotFound => "Аккаунт этого провайдера не привязан.",
+        ErrorCode::LastSignInMethod => "Задайте пароль, привяжите провайдера или добавьте ключ доступа, прежде чем удалять единственный способ входа.",
+        ErrorCode::PasskeyChallengeExpired => "Время на подтверждение ключа доступа истекло. Попробуйте ещё раз.",
+        ErrorCode::InvalidPasskeyRegistration => "Не удалось добавить ключ доступа. Попробуйте ещё раз.",
+        ErrorCode::InvalidPasskey => "Не удалось войти с этим ключом доступа.",
+        ErrorCode::InvalidPasskeyName => "Название ключа доступа должно быть длиной от 1 до 64 символов.",
+        ErrorCode::PasskeyAlreadyRegistered => "Этот ключ доступа уже зарегистрирован.",
+        ErrorCode::PasskeyNotFound => "Ключ доступа не найден.",
+        ErrorCode::ProfileNotFound => "Ваш профиль не найден.",
+        ErrorCode::UserNotFound => "Такого пользователя не существует.",
+        ErrorCode::SearchQueryRequired => "Введите имя пользователя для поиска.",
+        ErrorCode::InvalidEmail => "Введите корректный адрес электронной почты.",
+        ErrorCode::EmailTaken => "Этот адрес электронной почты уже используется другим аккаунтом.",
+        ErrorCode::EmailNotSet => "Сначала укажите адрес электронной почты в профиле.",
+        ErrorCode::EmailAlreadyVerified => "Этот адрес электронной почты уже подтверждён.",
+        ErrorCode::InvalidVerificationToken => "Ссылка для подтверждения недействительна или устарела. Запросите новую.",
+        ErrorCode::AccountDeletionPending => "Удаление аккаунта уже запланировано.",
+        ErrorCode::AccountDeletionNotScheduled => "Удаление аккаунта не запланировано.",
+        ErrorCode::DataExportPending => "Копия ваших данных уже готовится.",
+        ErrorCode::DataExportNotFound => "Ссылка для скачивания недействительна или устарела.",
+        ErrorCode::DataExportNotReady => "Копия данных ещё не готова, попробуйте через минуту.",
+        ErrorCode::CannotBefriendSelf => "Нельзя добавить в друзья самого себя.",
+        ErrorCode::CannotRemoveSelf => "Нельзя удалить самого себя.",
+        ErrorCode::OwnFriendRequest => "Нельзя ответить на собственную заявку в друзья.",
+        ErrorCode::FriendRequestExists => "Заявка в друзья между вами уже существует.",
+        ErrorCode::FriendRequestNotFound => "Такой заявки в друзья не существует.",
+        ErrorCode::FriendshipNotFound => "Вы не друзья с этим пользователем.",
+        ErrorCode::InviteFriendsOnly => "Приглашать на встречи можно только друзей.",
+        ErrorCode::ParticipantsBusy => "Кто-то из участников уже занят в этот день.",
+        ErrorCode::DateReserved => "Этот день уже занят.",
+        ErrorCode::EventNotFound => "Встреча не найдена.",
+        ErrorCode::EventNotOwned => "Встреча не найдена или вы не её организатор.",
+        ErrorCode::EventAccessDenied => "Вы не участвуете в этой встрече.",
+        ErrorCode::EventClosed => "Эта встреча уже завершена или отменена.",
+        ErrorCode::EventCompleted => "Завершённую встречу нельзя отменить.",
+        ErrorCode::EventNotStarted => "Завершить встречу можно только в её день или позже.",
+        ErrorCode::EventTransitionNotAllowed => "Встречу нельзя перевести в этот статус из текущего.",
+        ErrorCode::MemoryImageRequired => "Добавьте фото на память, чтобы завершить встречу.",
+        ErrorCode::InvitationNotFound => "У вас нет неотвеченного приглашения на эту встречу.",
+        ErrorCode::ParticipationNotFound => "Вы не участвуете в этой встрече.",
+        ErrorCode::WishPlaceNotFound => "Место не найдено или вы не его владелец.",
+        ErrorCode::NotEventCreator => "Отметить место посещённым может только организатор встречи.",
+    }
+}
+
+pub fn mail(template: MailTemplate) -> MailText {
+    match template {
+        MailTemplate::PasswordReset => MailText {
+            subject: "Сброс пароля Friends",
+            body: "Здравствуйте, {username}!\n\nКто-то запросил сброс пароля вашего аккаунта Friends. Чтобы задать новый пароль, откройте эту ссылку в течение {minutes} минут:\n\n{link}\n\nЕсли это были не вы, просто проигнорируйте письмо — пароль останется прежним.\n",
+        },
+        MailTemplate::EmailVerification => MailText {
+            subject: "Подтвердите почту для Friends",
+            body: "Здравствуйте, {username}!\n\nПодтвердите, что этот адрес принадлежит вашему аккаунту Friends, — откройте ссылку в течение {hours} ч.:\n\n{link}\n\nЕсли вы не указывали этот адрес, просто проигнорируйте письмо.\n",
+        },
+        MailTemplate::AccountDeletionScheduled => MailText {
+            subject: "Ваш аккаунт Friends будет удалён",
+            body: "Здравствуйте, {username}!\n\nВаш аккаунт Friends и его данные будут удалены {date} (UTC). До этого момента удаление можно отменить: войдите в аккаунт и отмените его в настройках.\n\nЕсли это были не вы, войдите, отмените удаление и смените пароль.\n",
+        },
+        MailTemplate::EventCanceledByDeletion => MailText {
+            subject: "«{title}» {date} отменено",
+            body: "Здравствуйте, {username}!\n\nОрганизатор события «{title}» {date} удалил аккаунт Friends, поэтому событие отменено и день снова свободен.\n",
+        },
+        MailTemplate::DataExportReady => MailText {
+            subject: "Копия ваших данных Friends готова",
+            body: "Здравствуйте, {username}!\n\nКопия данных Friends, которую вы запросили, готова. Скачайте её в течение {hours} ч.:\n\n{link}\n\nПо этой ссылке ваши данные может скачать любой, не передавайте её никому.\n",
+        },
+    }
+}
@@ -0,0 +1,90 @@
+use std::cell::Cell;
+
+use axum::{
+    extract::Request,
+    http::{HeaderValue, header},
+    middleware::Next,
+    response::Response,
+};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Locale {
+    #[default]
+    En,
+    Ru,
+}
+
+impl Locale {
+    pub const SUPPORTED: [Locale; 2] = [Locale::En, Locale::Ru];
+
+    pub fn code(self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::Ru => "ru",
+        }
+    }
+
+    /// Parses a language tag such as `ru`, `ru-RU` or `EN_us`.
+    pub fn parse(tag: &str) -> Option<Locale> {
+        let primary = tag.trim().split(['-', '_']).next()?.to_ascii_lowercase();
+        Locale::SUPPORTED
+            .into_iter()
+            .find(|locale| locale.code() == primary)
+    }
+
+    /// Picks the supported language with the highest `q` weight from an
+    /// `Accept-Language` header value.
+    pub fn from_accept_language(value: &str) -> Option<Locale> {
+        let mut best: Option<(Locale, f32)> = None;
+        for item in value.split(',') {
+            let mut parts = item.split(';');
+            let Some(locale) = parts.next().and_then(Locale::parse) else {
+                continue;
+            };
+            let weight = parts
+                .find_map(|param| param.trim().strip_prefix("q="))
+                .and_then(|q| q.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+            if weight > 0.0 && best.is_none_or(|(_, best_weight)| weight > best_weight) {
+                best = Some((locale, weight));
+            }
+        }
+        best.map(|(locale, _)| locale)
+    }
+}
+
+tokio::task_local! {
+    static LOCALE: Cell<Locale>;
+}
+
+/// Language of the request currently being handled.
+pub fn current() -> Locale {
+    LOCALE.try_with(Cell::get).unwrap_or_default()
+}
+
+/// Overrides the request language with the authenticated user's preference.
+pub fn prefer(locale: Locale) {
+    let _ = LOCALE.try_with(|current| current.set(locale));
+}
+
+/// Selects the response language from `Accept-Language`. Handlers and
+/// extractors may refine it with `prefer` once the user is known.
+pub async fn detect_locale(request: Request, next: Next) -> Response {
+    let locale = request
+        .headers()
+        .get(header::ACCEPT_LANGUAGE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(Locale::from_accept_language)
+        .unwrap_or_default();
+
+    LOCALE
+        .scope(Cell::new(locale), async move {
+            let mut response = next.run(request).await;
+            response.headers_mut().insert(
+                header::CONTENT_LANGUAGE,
+                HeaderValue::from_static(current().code()),
+            );
+            response
+        })
+        .await
+}
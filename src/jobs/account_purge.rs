@@ -0,0 +1,54 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use sea_orm::{DatabaseConnection, DbErr};
+
+use crate::jobs::Jobs;
+use crate::mail::Mailer;
+use crate::services::account_deletion;
+
+const PERIOD: Duration = Duration::from_secs(60 * 60);
+
+/// Purges accounts whose deletion grace period is over.
+pub fn spawn(jobs: &Jobs, db: Arc<DatabaseConnection>, mailer: Arc<dyn Mailer>) {
+    jobs.spawn_periodic("account_purge", PERIOD, move || {
+        let db = db.clone();
+        let mailer = mailer.clone();
+        async move {
+            match run(&db, mailer.as_ref(), Utc::now()).await {
+                Ok(purged) if purged > 0 => tracing::info!(purged, "deleted accounts purged"),
+                Ok(_) => {}
+                Err(err) => tracing::error!(error = %err, "account purge failed"),
+            }
+        }
+    });
+}
+
+/// Purges every account due at `now` and mails participants of the events
+/// that were canceled. An account that fails is tried again on the next run.
+pub async fn run(
+    db: &DatabaseConnection,
+    mailer: &dyn Mailer,
+    now: DateTime<Utc>,
+) -> Result<usize, DbErr> {
+    let mut purged = 0;
+    for user_id in account_deletion::due(db, now).await? {
+        let notices = match account_deletion::purge(db, user_id, now).await {
+            Ok(Some(notices)) => notices,
+            Ok(None) => continue,
+            Err(err) => {
+                tracing::error!(user_id = %user_id, error = %err, "purging account failed");
+                continue;
+            }
+        };
+        purged += 1;
+        tracing::info!(user_id = %user_id, notices = notices.len(), "account purged");
+        for message in notices {
+            if let Err(err) = mailer.send(&message).await {
+                tracing::error!(error = %err, subject = %message.subject, "sending mail failed");
+            }
+        }
+    }
+    Ok(purged)
+}
@@ -0,0 +1,28 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Utc;
+use sea_orm::DatabaseConnection;
+
+use crate::jobs::Jobs;
+use crate::services::data_exports;
+
+const PERIOD: Duration = Duration::from_secs(60 * 60);
+
+/// Deletes expired data exports and their archives.
+pub fn spawn(jobs: &Jobs, db: Arc<DatabaseConnection>, data_dir: PathBuf) {
+    jobs.spawn_periodic("export_cleanup", PERIOD, move || {
+        let db = db.clone();
+        let data_dir = data_dir.clone();
+        async move {
+            match data_exports::delete_expired(&db, &data_dir, Utc::now()).await {
+                Ok(deleted) if deleted > 0 => {
+                    tracing::info!(deleted, "expired data exports deleted")
+                }
+                Ok(_) => {}
+                Err(err) => tracing::error!(error = %err, "data export cleanup failed"),
+            }
+        }
+    });
+}
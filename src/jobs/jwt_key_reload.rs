@@ -0,0 +1,20 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::auth::keys::JwtKeys;
+use crate::jobs::Jobs;
+
+const PERIOD: Duration = Duration::from_secs(60);
+
+/// Re-reads the JWT key directory so keys added by `keys rotate` are published
+/// and, after their activation delay, used for signing without a restart.
+pub fn spawn(jobs: &Jobs, keys: Arc<JwtKeys>) {
+    jobs.spawn_periodic("jwt_key_reload", PERIOD, move || {
+        let keys = keys.clone();
+        async move {
+            if let Err(err) = keys.reload() {
+                tracing::error!(error = %err, "failed to reload JWT keys");
+            }
+        }
+    });
+}
@@ -0,0 +1,36 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Utc;
+use sea_orm::DatabaseConnection;
+
+use crate::auth::{email_verification, password_reset};
+use crate::jobs::Jobs;
+
+const PERIOD: Duration = Duration::from_secs(60 * 60);
+
+/// Deletes expired password reset and email verification tokens.
+pub fn spawn(jobs: &Jobs, db: Arc<DatabaseConnection>) {
+    jobs.spawn_periodic("mail_token_cleanup", PERIOD, move || {
+        let db = db.clone();
+        async move {
+            match password_reset::delete_expired(&*db, Utc::now()).await {
+                Ok(deleted) if deleted > 0 => {
+                    tracing::info!(deleted, "expired password reset tokens deleted");
+                }
+                Ok(_) => {}
+                Err(err) => tracing::error!(error = %err, "password reset token cleanup failed"),
+            }
+
+            match email_verification::delete_expired(&*db, Utc::now()).await {
+                Ok(deleted) if deleted > 0 => {
+                    tracing::info!(deleted, "expired email verification tokens deleted");
+                }
+                Ok(_) => {}
+                Err(err) => {
+                    tracing::error!(error = %err, "email verification token cleanup failed")
+                }
+            }
+        }
+    });
+}
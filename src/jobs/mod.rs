@@ -0,0 +1,54 @@
+pub mod account_purge;
+pub mod export_cleanup;
+pub mod jwt_key_reload;
+pub mod mail_token_cleanup;
+pub mod refresh_token_cleanup;
+pub mod rate_limit_cleanup;
+
+use std::future::Future;
+use std::time::Duration;
+
+use tokio_util::sync::CancellationToken;
+use tokio_util::task::TaskTracker;
+
+/// Background tasks that run next to the HTTP server and stop with it.
+#[derive(Clone)]
+pub struct Jobs {
+    shutdown: CancellationToken,
+    tracker: TaskTracker,
+}
+
+impl Jobs {
+    pub fn new(shutdown: CancellationToken) -> Self {
+        Self {
+            shutdown,
+            tracker: TaskTracker::new(),
+        }
+    }
+
+    /// Runs `job` every `period` until shutdown. A run in progress is allowed
+    /// to finish, the next one is never started.
+    pub fn spawn_periodic<F, Fut>(&self, name: &'static str, period: Duration, job: F)
+    where
+        F: Fn() -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send,
+    {
+        let shutdown = self.shutdown.clone();
+        self.tracker.spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            loop {
+                tokio::select! {
+                    _ = shutdown.cancelled() => break,
+                    _ = interval.tick() => job().await,
+                }
+            }
+            tracing::info!(job = name, "background job stopped");
+        });
+    }
+
+    /// Waits for every job to notice the shutdown and return.
+    pub async fn wait(&self) {
+        self.tracker.close();
+        self.tracker.wait().await;
+    }
+}
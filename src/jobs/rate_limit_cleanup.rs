@@ -0,0 +1,27 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Utc;
+use sea_orm::DatabaseConnection;
+
+use crate::jobs::Jobs;
+use crate::rate_limit::PostgresStore;
+
+const PERIOD: Duration = Duration::from_secs(10 * 60);
+
+/// Deletes rate limit windows that have ended. Only needed with the Postgres
+/// backend, the in-memory one prunes itself.
+pub fn spawn(jobs: &Jobs, db: Arc<DatabaseConnection>) {
+    jobs.spawn_periodic("rate_limit_cleanup", PERIOD, move || {
+        let db = db.clone();
+        async move {
+            match PostgresStore::delete_expired(&db, Utc::now()).await {
+                Ok(deleted) if deleted > 0 => {
+                    tracing::info!(deleted, "expired rate limit windows deleted");
+                }
+                Ok(_) => {}
+                Err(err) => tracing::error!(error = %err, "rate limit cleanup failed"),
+            }
+        }
+    });
+}
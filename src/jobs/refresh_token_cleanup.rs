@@ -0,0 +1,46 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Utc;
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter};
+
+use crate::entities::{RefreshToken, RefreshTokenColumn, Session, SessionColumn};
+use crate::jobs::Jobs;
+
+const PERIOD: Duration = Duration::from_secs(60 * 60);
+
+/// Deletes refresh tokens and sessions that have expired. They can no longer
+/// be used, so keeping them only grows the tables.
+pub fn spawn(jobs: &Jobs, db: Arc<DatabaseConnection>) {
+    jobs.spawn_periodic("refresh_token_cleanup", PERIOD, move || {
+        let db = db.clone();
+        async move {
+            let result = RefreshToken::delete_many()
+                .filter(RefreshTokenColumn::ExpiresAt.lt(Utc::now()))
+                .exec(&*db)
+                .await;
+            match result {
+                Ok(result) if result.rows_affected > 0 => {
+                    tracing::info!(
+                        deleted = result.rows_affected,
+                        "expired refresh tokens deleted"
+                    );
+                }
+                Ok(_) => {}
+                Err(err) => tracing::error!(error = %err, "refresh token cleanup failed"),
+            }
+
+            let result = Session::delete_many()
+                .filter(SessionColumn::ExpiresAt.lt(Utc::now()))
+                .exec(&*db)
+                .await;
+            match result {
+                Ok(result) if result.rows_affected > 0 => {
+                    tracing::info!(deleted = result.rows_affected, "expired sessions deleted");
+                }
+                Ok(_) => {}
+                Err(err) => tracing::error!(error = %err, "session cleanup failed"),
+            }
+        }
+    });
+}
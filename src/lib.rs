@@ -0,0 +1,24 @@
+pub mod api_doc;
+pub mod auth;
+pub mod cli;
+pub mod clock;
+pub mod config;
+pub mod controllers;
+pub mod db;
+pub mod entities;
+pub mod error;
+pub mod export;
+pub mod i18n;
+pub mod jobs;
+pub mod logging;
+pub mod mail;
+pub mod migration;
+pub mod oidc;
+pub mod rate_limit;
+pub mod request_id;
+pub mod seed;
+pub mod server;
+pub mod services;
+pub mod state;
+pub mod telemetry;
+pub mod webauthn;
@@ -0,0 +1,60 @@
+use std::time::Instant;
+
+use axum::{
+    extract::{MatchedPath, Request},
+    middleware::Next,
+    response::Response,
+};
+use tracing::{Instrument, field};
+use tracing_subscriber::EnvFilter;
+
+use crate::config::{LogFormat, LoggingConfig};
+use crate::request_id;
+
+/// Installs the global subscriber. Verbosity is taken from `RUST_LOG`.
+pub fn init(config: &LoggingConfig) {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| "info".into());
+    let builder = tracing_subscriber::fmt().with_env_filter(filter);
+    match config.format {
+        LogFormat::Json => builder.json().with_current_span(true).with_span_list(false).init(),
+        LogFormat::Text => builder.init(),
+    }
+}
+
+/// Wraps each request in an `http_request` span and logs its outcome.
+/// `user_id` is filled in by `AuthUser` once the caller is authenticated.
+pub async fn trace_request(request: Request, next: Next) -> Response {
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_owned())
+        .unwrap_or_else(|| request.uri().path().to_owned());
+    let request_id = request_id::current();
+
+    let span = tracing::info_span!(
+        "http_request",
+        method = %request.method(),
+        route,
+        request_id = request_id.as_deref(),
+        user_id = field::Empty,
+        status = field::Empty,
+        latency_ms = field::Empty,
+    );
+
+    let started = Instant::now();
+    let response = next.run(request).instrument(span.clone()).await;
+    let latency_ms = started.elapsed().as_millis() as u64;
+    let status = response.status();
+
+    span.record("status", status.as_u16());
+    span.record("latency_ms", latency_ms);
+    span.in_scope(|| {
+        if status.is_server_error() {
+            tracing::error!("request finished");
+        } else {
+            tracing::info!("request finished");
+        }
+    });
+
+    response
+}
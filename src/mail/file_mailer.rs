@@ -0,0 +1,44 @@
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::mail::{MailError, Mailer, Message};
+
+/// Writes each mail as an `.eml` file, which mail clients can open, instead
+/// of sending it.
+pub struct FileMailer {
+    dir: PathBuf,
+    from: String,
+}
+
+impl FileMailer {
+    pub fn new(dir: &Path, from: &str) -> Self {
+        FileMailer {
+            dir: dir.to_path_buf(),
+            from: from.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl Mailer for FileMailer {
+    async fn send(&self, message: &Message) -> Result<(), MailError> {
+        let now = Utc::now();
+        tokio::fs::create_dir_all(&self.dir).await?;
+        let path = self.dir.join(format!(
+            "{}-{}.eml",
+            now.format("%Y%m%dT%H%M%S%.3fZ"),
+            Uuid::new_v4()
+        ));
+        tokio::fs::write(&path, message.to_rfc5322(&self.from, now)).await?;
+        tracing::info!(
+            to = %message.to,
+            subject = %message.subject,
+            path = %path.display(),
+            "mail written to file"
+        );
+        Ok(())
+    }
+}
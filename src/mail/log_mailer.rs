@@ -0,0 +1,20 @@
+use async_trait::async_trait;
+
+use crate::mail::{MailError, Mailer, Message};
+
+/// Writes mail to the log instead of sending it. Links in the body, such as
+/// password reset tokens, end up in the log too, so only use it locally.
+pub struct LogMailer;
+
+#[async_trait]
+impl Mailer for LogMailer {
+    async fn send(&self, message: &Message) -> Result<(), MailError> {
+        tracing::info!(
+            to = %message.to,
+            subject = %message.subject,
+            body = %message.body,
+            "mail not sent, logged only"
+        );
+        Ok(())
+    }
+}
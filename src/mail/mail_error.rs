@@ -0,0 +1,52 @@
+use std::fmt;
+
+use lettre::address::AddressError;
+use lettre::transport::smtp;
+
+#[derive(Debug)]
+pub enum MailError {
+    Io(std::io::Error),
+    /// The sender or recipient is not a valid mailbox.
+    Address(AddressError),
+    /// The message could not be put together.
+    Message(lettre::error::Error),
+    /// The SMTP server could not be reached or refused the message.
+    Smtp(smtp::Error),
+}
+
+impl fmt::Display for MailError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MailError::Io(source) => write!(f, "mail i/o failed: {source}"),
+            MailError::Address(source) => write!(f, "invalid mail address: {source}"),
+            MailError::Message(source) => write!(f, "building mail failed: {source}"),
+            MailError::Smtp(source) => write!(f, "smtp failed: {source}"),
+        }
+    }
+}
+
+impl std::error::Error for MailError {}
+
+impl From<std::io::Error> for MailError {
+    fn from(source: std::io::Error) -> Self {
+        MailError::Io(source)
+    }
+}
+
+impl From<AddressError> for MailError {
+    fn from(source: AddressError) -> Self {
+        MailError::Address(source)
+    }
+}
+
+impl From<lettre::error::Error> for MailError {
+    fn from(source: lettre::error::Error) -> Self {
+        MailError::Message(source)
+    }
+}
+
+impl From<smtp::Error> for MailError {
+    fn from(source: smtp::Error) -> Self {
+        MailError::Smtp(source)
+    }
+}
@@ -0,0 +1,81 @@
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::i18n::{Locale, catalog};
+use crate::mail::MailTemplate;
+
+/// A plain text mail to a single recipient.
+#[derive(Clone, Debug)]
+pub struct Message {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+impl Message {
+    /// `template` in `locale`, with each `{name}` in the subject and body
+    /// replaced by its value.
+    pub fn localized(
+        to: &str,
+        template: MailTemplate,
+        locale: Locale,
+        values: &[(&str, &str)],
+    ) -> Self {
+        let text = catalog::mail(template, locale);
+        let mut subject = text.subject.to_string();
+        let mut body = text.body.to_string();
+        for (name, value) in values {
+            let placeholder = format!("{{{name}}}");
+            subject = subject.replace(&placeholder, value);
+            body = body.replace(&placeholder, value);
+        }
+        Message {
+            to: to.to_string(),
+            subject,
+            body,
+        }
+    }
+
+    /// Renders the message as an RFC 5322 document. The body is base64 so any
+    /// text survives every transport unchanged, and header values lose line
+    /// breaks so user input cannot add headers.
+    pub fn to_rfc5322(&self, from: &str, date: DateTime<Utc>) -> String {
+        let domain = from
+            .rsplit('@')
+            .next()
+            .unwrap_or("localhost")
+            .trim_end_matches('>');
+        let body = STANDARD.encode(self.body.as_bytes());
+
+        let mut text = String::new();
+        text.push_str(&format!("From: {}\r\n", header_value(from)));
+        text.push_str(&format!("To: {}\r\n", header_value(&self.to)));
+        text.push_str(&format!("Subject: {}\r\n", encode_word(&self.subject)));
+        text.push_str(&format!("Date: {}\r\n", date.to_rfc2822()));
+        text.push_str(&format!("Message-ID: <{}@{}>\r\n", Uuid::new_v4(), domain));
+        text.push_str("MIME-Version: 1.0\r\n");
+        text.push_str("Content-Type: text/plain; charset=utf-8\r\n");
+        text.push_str("Content-Transfer-Encoding: base64\r\n\r\n");
+        for line in body.as_bytes().chunks(76) {
+            text.push_str(std::str::from_utf8(line).unwrap_or_default());
+            text.push_str("\r\n");
+        }
+        text
+    }
+}
+
+fn header_value(value: &str) -> String {
+    value.replace(['\r', '\n'], " ")
+}
+
+/// Non-ASCII header text as an RFC 2047 encoded word.
+fn encode_word(value: &str) -> String {
+    let value = header_value(value);
+    if value.is_ascii() {
+        value
+    } else {
+        format!("=?utf-8?B?{}?=", STANDARD.encode(value.as_bytes()))
+    }
+}
@@ -0,0 +1,51 @@
+pub mod file_mailer;
+pub mod log_mailer;
+pub mod mail_error;
+pub mod message;
+pub mod smtp_mailer;
+
+pub use file_mailer::FileMailer;
+pub use log_mailer::LogMailer;
+pub use mail_error::MailError;
+pub use message::Message;
+pub use smtp_mailer::SmtpMailer;
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use crate::config::{MailConfig, MailTransport};
+
+/// Mail the server sends, with texts in every language of the catalog.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MailTemplate {
+    PasswordReset,
+    EmailVerification,
+    AccountDeletionScheduled,
+    EventCanceledByDeletion,
+    DataExportReady,
+}
+
+/// Delivers mail to users. Which transport is used comes from `mail.transport`.
+#[async_trait]
+pub trait Mailer: Send + Sync {
+    async fn send(&self, message: &Message) -> Result<(), MailError>;
+}
+
+pub fn from_config(config: &MailConfig) -> Result<Arc<dyn Mailer>, MailError> {
+    Ok(match config.transport {
+        MailTransport::Log => Arc::new(LogMailer),
+        MailTransport::File => Arc::new(FileMailer::new(&config.dir, &config.from)),
+        MailTransport::Smtp => Arc::new(SmtpMailer::new(&config.smtp, &config.from)?),
+    })
+}
+
+/// Sends `message` in the background so the request does not wait for the
+/// mail server, logging a failure instead of returning it.
+pub fn send_later(mailer: Arc<dyn Mailer>, message: Message) {
+    tokio::spawn(async move {
+        if let Err(err) = mailer.send(&message).await {
+            tracing::error!(error = %err, subject = %message.subject, "sending mail failed");
+        }
+    });
+}
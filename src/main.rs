@@ -1,48 +1,30 @@
-use crate::api_doc::api_doc::ApiDoc;
-use crate::controllers::{
-    auth_controller, calendar_controller, event_controller, friendship_controller,
-    wish_place_controller,
-};
-use crate::migration::Migrator;
-use axum::Router;
-use controllers::users_controller;
+use clap::Parser;
 use dotenvy::dotenv;
-use sea_orm_migration::MigratorTrait;
-use std::net::SocketAddr;
-use tokio::net::TcpListener;
-use utoipa::OpenApi;
-use utoipa_swagger_ui::SwaggerUi;
-
-mod api_doc;
-mod auth;
-mod controllers;
-mod db;
-mod entities;
-mod migration;
+use friends_server::cli::{self, Cli};
+use friends_server::config::Config;
+use friends_server::logging;
+use std::process::ExitCode;
+use std::sync::Arc;
 
 #[tokio::main]
-async fn main() {
+async fn main() -> ExitCode {
     dotenv().ok();
+    let cli = Cli::parse();
 
-    let db_connection = db::init_db().await.expect("db connection failed");
-
-    Migrator::up(&db_connection, None)
-        .await
-        .expect("migration failed");
-
-    let app_router = Router::new()
-        .merge(SwaggerUi::new("/docs").url("/api-doc/openapi.json", ApiDoc::openapi()))
-        .merge(auth_controller::router())
-        .merge(users_controller::router())
-        .merge(friendship_controller::router())
-        .merge(calendar_controller::router())
-        .merge(event_controller::router())
-        .merge(wish_place_controller::router());
+    let config = match Config::load() {
+        Ok(config) => Arc::new(config),
+        Err(err) => {
+            eprintln!("{err}");
+            return ExitCode::FAILURE;
+        }
+    };
+    logging::init(&config.logging);
 
-    let addr = SocketAddr::from(([0, 0, 0, 0], 3000));
-    println!("Starts on http://{}", addr);
-    let listener = TcpListener::bind(addr).await.unwrap();
-    axum::serve(listener, app_router.with_state(db_connection))
-        .await
-        .unwrap();
+    match cli::run(cli, config).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::FAILURE
+        }
+    }
 }
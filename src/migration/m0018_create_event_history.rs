@@ -0,0 +1,117 @@
+use crate::migration::uuid_pk;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(EventHistory::Table)
+                    .if_not_exists()
+                    .col(uuid_pk())
+                    .col(ColumnDef::new(EventHistory::EventId).uuid().not_null())
+                    .col(ColumnDef::new(EventHistory::ActorId).uuid().null())
+                    .col(
+                        ColumnDef::new(EventHistory::FromStatus)
+                            .custom(EventStatus::Table)
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(EventHistory::ToStatus)
+                            .custom(EventStatus::Table)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(EventHistory::Reason)
+                            .string_len(32)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(EventHistory::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_event_history_event_id")
+                            .from(EventHistory::Table, EventHistory::EventId)
+                            .to(Events::Table, Events::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_event_history_actor_id")
+                            .from(EventHistory::Table, EventHistory::ActorId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_event_history_event_created")
+                    .table(EventHistory::Table)
+                    .col(EventHistory::EventId)
+                    .col(EventHistory::CreatedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_event_history_event_created")
+                    .table(EventHistory::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(EventHistory::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum EventHistory {
+    Table,
+    Id,
+    EventId,
+    ActorId,
+    FromStatus,
+    ToStatus,
+    Reason,
+    CreatedAt,
+}
+
+#[derive(Iden)]
+enum Events {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum Users {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum EventStatus {
+    #[iden = "event_status"]
+    Table,
+}
@@ -0,0 +1,91 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(RefreshTokens::Table)
+                    .add_column_if_not_exists(ColumnDef::new(RefreshTokens::FamilyId).uuid().null())
+                    .add_column_if_not_exists(ColumnDef::new(RefreshTokens::ParentId).uuid().null())
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .name("fk_refresh_tokens_parent_id")
+                            .from_tbl(RefreshTokens::Table)
+                            .from_col(RefreshTokens::ParentId)
+                            .to_tbl(RefreshTokens::Table)
+                            .to_col(RefreshTokens::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Tokens issued before families existed each start their own.
+        manager
+            .exec_stmt(
+                Query::update()
+                    .table(RefreshTokens::Table)
+                    .value(RefreshTokens::FamilyId, Expr::col(RefreshTokens::Id))
+                    .and_where(Expr::col(RefreshTokens::FamilyId).is_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(RefreshTokens::Table)
+                    .modify_column(ColumnDef::new(RefreshTokens::FamilyId).uuid().not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_refresh_tokens_family_id")
+                    .table(RefreshTokens::Table)
+                    .col(RefreshTokens::FamilyId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_refresh_tokens_parent_id")
+                    .table(RefreshTokens::Table)
+                    .col(RefreshTokens::ParentId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(RefreshTokens::Table)
+                    .drop_foreign_key(Alias::new("fk_refresh_tokens_parent_id"))
+                    .drop_column(RefreshTokens::ParentId)
+                    .drop_column(RefreshTokens::FamilyId)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum RefreshTokens {
+    Table,
+    Id,
+    FamilyId,
+    ParentId,
+}
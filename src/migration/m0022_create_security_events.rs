@@ -0,0 +1,79 @@
+use crate::migration::uuid_pk;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(SecurityEvents::Table)
+                    .if_not_exists()
+                    .col(uuid_pk())
+                    .col(ColumnDef::new(SecurityEvents::UserId).uuid().not_null())
+                    .col(
+                        ColumnDef::new(SecurityEvents::Kind)
+                            .string_len(32)
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(SecurityEvents::Ip).string_len(45).null())
+                    .col(ColumnDef::new(SecurityEvents::Details).json_binary().null())
+                    .col(
+                        ColumnDef::new(SecurityEvents::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_security_events_user_id")
+                            .from(SecurityEvents::Table, SecurityEvents::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_security_events_user_created")
+                    .table(SecurityEvents::Table)
+                    .col(SecurityEvents::UserId)
+                    .col(SecurityEvents::CreatedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(SecurityEvents::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum SecurityEvents {
+    Table,
+    Id,
+    UserId,
+    Kind,
+    Ip,
+    Details,
+    CreatedAt,
+}
+
+#[derive(Iden)]
+enum Users {
+    Table,
+    Id,
+}
@@ -0,0 +1,134 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Sessions::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(Sessions::Id).uuid().not_null().primary_key())
+                    .col(ColumnDef::new(Sessions::UserId).uuid().not_null())
+                    .col(ColumnDef::new(Sessions::DeviceName).string_len(100).null())
+                    .col(ColumnDef::new(Sessions::Platform).string_len(32).null())
+                    .col(ColumnDef::new(Sessions::Ip).string_len(45).null())
+                    .col(ColumnDef::new(Sessions::UserAgent).string_len(512).null())
+                    .col(
+                        ColumnDef::new(Sessions::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(Sessions::LastUsedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(Sessions::ExpiresAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(Sessions::RevokedAt)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_sessions_user_id")
+                            .from(Sessions::Table, Sessions::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_sessions_user_revoked")
+                    .table(Sessions::Table)
+                    .col(Sessions::UserId)
+                    .col(Sessions::RevokedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        // Every existing token family becomes a session without device details.
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "INSERT INTO sessions (id, user_id, created_at, last_used_at, expires_at, revoked_at)
+                 SELECT family_id, user_id, MIN(created_at), MAX(created_at), MAX(expires_at),
+                        CASE WHEN bool_and(revoked_at IS NOT NULL) THEN MAX(revoked_at) END
+                 FROM refresh_tokens
+                 GROUP BY family_id, user_id
+                 ON CONFLICT (id) DO NOTHING",
+            )
+            .await?;
+
+        manager
+            .create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_refresh_tokens_family_id")
+                    .from(RefreshTokens::Table, RefreshTokens::FamilyId)
+                    .to(Sessions::Table, Sessions::Id)
+                    .on_delete(ForeignKeyAction::Cascade)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_foreign_key(
+                ForeignKey::drop()
+                    .name("fk_refresh_tokens_family_id")
+                    .table(RefreshTokens::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(Sessions::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum Sessions {
+    Table,
+    Id,
+    UserId,
+    DeviceName,
+    Platform,
+    Ip,
+    UserAgent,
+    CreatedAt,
+    LastUsedAt,
+    ExpiresAt,
+    RevokedAt,
+}
+
+#[derive(Iden)]
+enum RefreshTokens {
+    Table,
+    FamilyId,
+}
+
+#[derive(Iden)]
+enum Users {
+    Table,
+    Id,
+}
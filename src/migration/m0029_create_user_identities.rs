@@ -0,0 +1,98 @@
+use crate::migration::uuid_pk;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(UserIdentities::Table)
+                    .if_not_exists()
+                    .col(uuid_pk())
+                    .col(ColumnDef::new(UserIdentities::UserId).uuid().not_null())
+                    .col(
+                        ColumnDef::new(UserIdentities::Provider)
+                            .string_len(32)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(UserIdentities::Subject)
+                            .string_len(255)
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(UserIdentities::Email).string_len(254).null())
+                    .col(
+                        ColumnDef::new(UserIdentities::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(UserIdentities::LastUsedAt)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_user_identities_user_id")
+                            .from(UserIdentities::Table, UserIdentities::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_user_identities_provider_subject")
+                    .table(UserIdentities::Table)
+                    .col(UserIdentities::Provider)
+                    .col(UserIdentities::Subject)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        // One identity per provider and user; also serves lookups by user.
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_user_identities_user_provider")
+                    .table(UserIdentities::Table)
+                    .col(UserIdentities::UserId)
+                    .col(UserIdentities::Provider)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(UserIdentities::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum UserIdentities {
+    Table,
+    UserId,
+    Provider,
+    Subject,
+    Email,
+    CreatedAt,
+    LastUsedAt,
+}
+
+#[derive(Iden)]
+enum Users {
+    Table,
+    Id,
+}
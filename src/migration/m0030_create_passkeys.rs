@@ -0,0 +1,162 @@
+use crate::migration::uuid_pk;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Passkeys::Table)
+                    .if_not_exists()
+                    .col(uuid_pk())
+                    .col(ColumnDef::new(Passkeys::UserId).uuid().not_null())
+                    .col(
+                        ColumnDef::new(Passkeys::CredentialId)
+                            .string_len(1400)
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(Passkeys::PublicKey).binary().not_null())
+                    .col(ColumnDef::new(Passkeys::Algorithm).integer().not_null())
+                    .col(
+                        ColumnDef::new(Passkeys::SignCount)
+                            .big_integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(ColumnDef::new(Passkeys::Name).string_len(64).not_null())
+                    .col(
+                        ColumnDef::new(Passkeys::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(Passkeys::LastUsedAt)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_passkeys_user_id")
+                            .from(Passkeys::Table, Passkeys::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_passkeys_credential_id")
+                    .table(Passkeys::Table)
+                    .col(Passkeys::CredentialId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_passkeys_user_id")
+                    .table(Passkeys::Table)
+                    .col(Passkeys::UserId)
+                    .to_owned(),
+            )
+            .await?;
+
+        // Challenges of running ceremonies; a login has no user yet.
+        manager
+            .create_table(
+                Table::create()
+                    .table(WebauthnChallenges::Table)
+                    .if_not_exists()
+                    .col(uuid_pk())
+                    .col(ColumnDef::new(WebauthnChallenges::UserId).uuid().null())
+                    .col(
+                        ColumnDef::new(WebauthnChallenges::Purpose)
+                            .string_len(16)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(WebauthnChallenges::Challenge)
+                            .string_len(64)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(WebauthnChallenges::ExpiresAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(WebauthnChallenges::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_webauthn_challenges_user_id")
+                            .from(WebauthnChallenges::Table, WebauthnChallenges::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_webauthn_challenges_expires_at")
+                    .table(WebauthnChallenges::Table)
+                    .col(WebauthnChallenges::ExpiresAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(WebauthnChallenges::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(Passkeys::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum Passkeys {
+    Table,
+    UserId,
+    CredentialId,
+    PublicKey,
+    Algorithm,
+    SignCount,
+    Name,
+    CreatedAt,
+    LastUsedAt,
+}
+
+#[derive(Iden)]
+enum WebauthnChallenges {
+    Table,
+    UserId,
+    Purpose,
+    Challenge,
+    ExpiresAt,
+    CreatedAt,
+}
+
+#[derive(Iden)]
+enum Users {
+    Table,
+    Id,
+}
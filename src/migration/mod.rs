@@ -1,3 +1,6 @@
+// `Iden` enums describe the whole table even when a migration only touches some columns.
+#![allow(dead_code)]
+
 use sea_orm_migration::prelude::*;
 
 mod m0001_create_users;
@@ -15,6 +18,23 @@ mod m0012_drop_event_participants;
 mod m0013_event_finished_memory_image;
 mod m0014_event_memory_image_base64;
 mod m0015_create_refresh_tokens;
+mod m0016_users_locale;
+mod m0017_users_disabled_at;
+mod m0018_create_event_history;
+mod m0019_users_timezone;
+mod m0020_create_rate_limits;
+mod m0021_refresh_token_families;
+mod m0022_create_security_events;
+mod m0023_create_sessions;
+mod m0024_users_email;
+mod m0025_create_password_reset_tokens;
+mod m0026_users_email_verified;
+mod m0027_create_email_verification_tokens;
+mod m0028_create_two_factor;
+mod m0029_create_user_identities;
+mod m0030_create_passkeys;
+mod m0031_users_deletion;
+mod m0032_create_data_exports;
 
 pub fn uuid_pk() -> ColumnDef {
     ColumnDef::new(Alias::new("id"))
@@ -46,6 +66,23 @@ impl MigratorTrait for Migrator {
             Box::new(m0013_event_finished_memory_image::Migration),
             Box::new(m0014_event_memory_image_base64::Migration),
             Box::new(m0015_create_refresh_tokens::Migration),
+            Box::new(m0016_users_locale::Migration),
+            Box::new(m0017_users_disabled_at::Migration),
+            Box::new(m0018_create_event_history::Migration),
+            Box::new(m0019_users_timezone::Migration),
+            Box::new(m0020_create_rate_limits::Migration),
+            Box::new(m0021_refresh_token_families::Migration),
+            Box::new(m0022_create_security_events::Migration),
+            Box::new(m0023_create_sessions::Migration),
+            Box::new(m0024_users_email::Migration),
+            Box::new(m0025_create_password_reset_tokens::Migration),
+            Box::new(m0026_users_email_verified::Migration),
+            Box::new(m0027_create_email_verification_tokens::Migration),
+            Box::new(m0028_create_two_factor::Migration),
+            Box::new(m0029_create_user_identities::Migration),
+            Box::new(m0030_create_passkeys::Migration),
+            Box::new(m0031_users_deletion::Migration),
+            Box::new(m0032_create_data_exports::Migration),
         ]
     }
 }
@@ -0,0 +1,5 @@
+pub mod oidc_error;
+pub mod verifier;
+
+pub use oidc_error::OidcError;
+pub use verifier::{IdTokenClaims, OidcVerifier};
@@ -0,0 +1,46 @@
+use std::fmt;
+
+use crate::error::{AppError, ErrorCode};
+
+#[derive(Debug)]
+pub enum OidcError {
+    /// No provider of that name is configured.
+    UnknownProvider,
+    /// The ID token is malformed, expired, signed by an unknown key or meant
+    /// for someone else.
+    InvalidToken(String),
+    /// The JWKS of the provider could not be fetched.
+    Fetch(String),
+}
+
+impl fmt::Display for OidcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OidcError::UnknownProvider => f.write_str("unknown identity provider"),
+            OidcError::InvalidToken(reason) => write!(f, "invalid id token: {reason}"),
+            OidcError::Fetch(reason) => write!(f, "fetching jwks failed: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for OidcError {}
+
+impl From<reqwest::Error> for OidcError {
+    fn from(source: reqwest::Error) -> Self {
+        OidcError::Fetch(source.to_string())
+    }
+}
+
+impl From<OidcError> for AppError {
+    #[track_caller]
+    fn from(err: OidcError) -> Self {
+        match err {
+            OidcError::UnknownProvider => ErrorCode::UnknownIdentityProvider.into(),
+            OidcError::InvalidToken(reason) => {
+                tracing::info!(%reason, "id token rejected");
+                AppError::field(ErrorCode::InvalidIdToken, "id_token")
+            }
+            OidcError::Fetch(_) => AppError::internal(err),
+        }
+    }
+}
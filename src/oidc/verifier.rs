@@ -0,0 +1,206 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{Algorithm, DecodingKey, Validation, decode, decode_header};
+use serde::{Deserialize, Deserializer};
+use tokio::sync::RwLock;
+
+use crate::config::{OidcConfig, OidcProviderConfig};
+use crate::oidc::OidcError;
+
+/// A key set is fetched again at most this often when a token names a key
+/// it does not contain, so made-up `kid`s cannot hammer the provider.
+const MIN_REFETCH: Duration = Duration::from_secs(60);
+const ALGORITHMS: [Algorithm; 5] = [
+    Algorithm::RS256,
+    Algorithm::RS384,
+    Algorithm::RS512,
+    Algorithm::ES256,
+    Algorithm::EdDSA,
+];
+
+/// What the server keeps of a verified ID token.
+#[derive(Clone, Debug)]
+pub struct IdTokenClaims {
+    pub subject: String,
+    pub email: Option<String>,
+    /// Whether the provider vouches for `email`.
+    pub email_verified: bool,
+    pub name: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RawClaims {
+    sub: String,
+    #[serde(default)]
+    email: Option<String>,
+    /// Apple sends a string, everyone else a boolean.
+    #[serde(default, deserialize_with = "lenient_bool")]
+    email_verified: bool,
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    nonce: Option<String>,
+}
+
+struct CachedKeys {
+    keys: JwkSet,
+    fetched_at: Instant,
+}
+
+/// Checks ID tokens of the providers in `oidc.providers` against their
+/// published keys, which are cached for `oidc.jwks_cache_secs`.
+pub struct OidcVerifier {
+    providers: Vec<OidcProviderConfig>,
+    cache_ttl: Duration,
+    http: reqwest::Client,
+    keys: RwLock<HashMap<String, CachedKeys>>,
+}
+
+impl OidcVerifier {
+    pub fn new(config: &OidcConfig) -> Self {
+        OidcVerifier {
+            providers: config.providers.clone(),
+            cache_ttl: Duration::from_secs(config.jwks_cache_secs),
+            http: reqwest::Client::builder()
+                .timeout(Duration::from_secs(config.timeout_secs.max(1)))
+                .user_agent("friends-server")
+                .build()
+                .expect("http client"),
+            keys: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Verifies `id_token` from `provider`: signature, issuer, audience,
+    /// expiry and the `nonce` the app sent to the provider.
+    pub async fn verify(
+        &self,
+        provider: &str,
+        id_token: &str,
+        nonce: &str,
+    ) -> Result<IdTokenClaims, OidcError> {
+        let config = self
+            .providers
+            .iter()
+            .find(|config| config.name == provider)
+            .ok_or(OidcError::UnknownProvider)?;
+        let header = decode_header(id_token).map_err(invalid)?;
+        if !ALGORITHMS.contains(&header.alg) {
+            return Err(invalid(format!(
+                "algorithm {:?} is not accepted",
+                header.alg
+            )));
+        }
+        let kid = header
+            .kid
+            .as_deref()
+            .ok_or_else(|| invalid("token has no kid"))?;
+
+        let key = self.decoding_key(config, kid, header.alg).await?;
+        let mut validation = Validation::new(header.alg);
+        validation.set_issuer(&[&config.issuer]);
+        validation.set_audience(&config.client_ids);
+        validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+        let claims = decode::<RawClaims>(id_token, &key, &validation)
+            .map_err(invalid)?
+            .claims;
+
+        match claims.nonce.as_deref() {
+            None => return Err(invalid("token has no nonce")),
+            Some(claim) if claim != nonce => return Err(invalid("nonce does not match")),
+            Some(_) => {}
+        }
+        Ok(IdTokenClaims {
+            subject: claims.sub,
+            email: claims.email,
+            email_verified: claims.email_verified,
+            name: claims.name,
+        })
+    }
+
+    async fn decoding_key(
+        &self,
+        config: &OidcProviderConfig,
+        kid: &str,
+        alg: Algorithm,
+    ) -> Result<DecodingKey, OidcError> {
+        let refetch = {
+            let cache = self.keys.read().await;
+            match cache.get(&config.name) {
+                Some(cached) => {
+                    let age = cached.fetched_at.elapsed();
+                    let known = cached.keys.find(kid).is_some();
+                    if known && age < self.cache_ttl {
+                        return key_from(&cached.keys, kid, alg);
+                    }
+                    age >= MIN_REFETCH || age >= self.cache_ttl
+                }
+                None => true,
+            }
+        };
+        if !refetch {
+            return Err(invalid(format!("unknown key {kid}")));
+        }
+
+        let keys = match self.fetch(config).await {
+            Ok(keys) => keys,
+            // An outage of the provider should not end logins while the
+            // keys it had published still verify.
+            Err(err) => {
+                let cache = self.keys.read().await;
+                let Some(cached) = cache.get(&config.name) else {
+                    return Err(err);
+                };
+                tracing::warn!(provider = %config.name, error = %err, "using cached jwks");
+                return key_from(&cached.keys, kid, alg);
+            }
+        };
+        let key = key_from(&keys, kid, alg);
+        self.keys.write().await.insert(
+            config.name.clone(),
+            CachedKeys {
+                keys,
+                fetched_at: Instant::now(),
+            },
+        );
+        key
+    }
+
+    async fn fetch(&self, config: &OidcProviderConfig) -> Result<JwkSet, OidcError> {
+        let response = self
+            .http
+            .get(&config.jwks_url)
+            .send()
+            .await?
+            .error_for_status()?;
+        response
+            .json()
+            .await
+            .map_err(|err| OidcError::Fetch(format!("malformed jwks: {err}")))
+    }
+}
+
+fn key_from(keys: &JwkSet, kid: &str, alg: Algorithm) -> Result<DecodingKey, OidcError> {
+    let jwk = keys
+        .find(kid)
+        .ok_or_else(|| invalid(format!("unknown key {kid}")))?;
+    if let Some(key_alg) = jwk.common.key_algorithm
+        && key_alg.to_string() != format!("{alg:?}")
+    {
+        return Err(invalid(format!("key {kid} is not for {alg:?}")));
+    }
+    DecodingKey::from_jwk(jwk).map_err(invalid)
+}
+
+fn invalid(reason: impl ToString) -> OidcError {
+    OidcError::InvalidToken(reason.to_string())
+}
+
+fn lenient_bool<'de, D: Deserializer<'de>>(deserializer: D) -> Result<bool, D::Error> {
+    Ok(match serde_json::Value::deserialize(deserializer)? {
+        serde_json::Value::Bool(value) => value,
+        serde_json::Value::String(value) => value == "true",
+        _ => false,
+    })
+}
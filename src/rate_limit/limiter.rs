@@ -0,0 +1,168 @@
+use std::fmt;
+use std::net::IpAddr;
+use std::sync::Arc;
+
+use chrono::{DateTime, TimeDelta, Utc};
+use sea_orm::DatabaseConnection;
+
+use crate::clock::Clock;
+use crate::config::{Limit, RateLimitBackend, RateLimitConfig, RouteLimits};
+use crate::error::{AppError, ErrorCode};
+use crate::rate_limit::{MemoryStore, PostgresStore, RateLimitStore, Window};
+use crate::telemetry::counters;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LimitedRoute {
+    Login,
+    Register,
+    Refresh,
+    PasswordReset,
+    EmailVerification,
+}
+
+impl fmt::Display for LimitedRoute {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            LimitedRoute::Login => "login",
+            LimitedRoute::Register => "register",
+            LimitedRoute::Refresh => "refresh",
+            LimitedRoute::PasswordReset => "password_reset",
+            LimitedRoute::EmailVerification => "email_verification",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    store: Arc<dyn RateLimitStore>,
+    clock: Arc<dyn Clock>,
+}
+
+impl RateLimiter {
+    pub fn new(
+        config: RateLimitConfig,
+        store: Arc<dyn RateLimitStore>,
+        clock: Arc<dyn Clock>,
+    ) -> Self {
+        RateLimiter {
+            config,
+            store,
+            clock,
+        }
+    }
+
+    /// Uses the store named by `config.backend`.
+    pub fn from_config(
+        config: &RateLimitConfig,
+        db: Arc<DatabaseConnection>,
+        clock: Arc<dyn Clock>,
+    ) -> Self {
+        let store: Arc<dyn RateLimitStore> = match config.backend {
+            RateLimitBackend::Memory => Arc::new(MemoryStore::default()),
+            RateLimitBackend::Postgres => Arc::new(PostgresStore::new(db)),
+        };
+        RateLimiter::new(config.clone(), store, clock)
+    }
+
+    /// Counts a request to `route` against the client address and the
+    /// username it names, failing with `TooManyRequests` once either is over
+    /// its limit.
+    pub async fn check(
+        &self,
+        route: LimitedRoute,
+        ip: Option<IpAddr>,
+        username: Option<&str>,
+    ) -> Result<(), AppError> {
+        if !self.config.enabled {
+            return Ok(());
+        }
+        let limits = self.limits(route);
+        if let (Some(limit), Some(ip)) = (limits.per_ip, ip) {
+            self.count(route, &format!("{route}:ip:{ip}"), limit)
+                .await?;
+        }
+        if let (Some(limit), Some(username)) = (limits.per_username, username) {
+            self.count(route, &format!("{route}:user:{username}"), limit)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Fails with `AccountLocked` while `username` has too many recent failed
+    /// logins. Checked before the password so a locked account costs no
+    /// hashing.
+    pub async fn ensure_not_locked(&self, username: &str) -> Result<(), AppError> {
+        if !self.lockout_enabled() {
+            return Ok(());
+        }
+        let now = self.clock.now();
+        match self.store.peek(&lockout_key(username), now).await? {
+            Some(window) if window.count >= self.config.lockout_failures => {
+                Err(throttled(ErrorCode::AccountLocked, window, now))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Records a failed login for `username`, whether or not it exists.
+    pub async fn login_failed(&self, username: &str) -> Result<(), AppError> {
+        if !self.lockout_enabled() {
+            return Ok(());
+        }
+        let window = TimeDelta::seconds(self.config.lockout_secs as i64);
+        let now = self.clock.now();
+        self.store.hit(&lockout_key(username), window, now).await?;
+        Ok(())
+    }
+
+    /// Forgets earlier failures of `username`.
+    pub async fn login_succeeded(&self, username: &str) -> Result<(), AppError> {
+        if !self.lockout_enabled() {
+            return Ok(());
+        }
+        self.store.clear(&lockout_key(username)).await
+    }
+
+    fn limits(&self, route: LimitedRoute) -> &RouteLimits {
+        match route {
+            LimitedRoute::Login => &self.config.login,
+            LimitedRoute::Register => &self.config.register,
+            LimitedRoute::Refresh => &self.config.refresh,
+            LimitedRoute::PasswordReset => &self.config.password_reset,
+            LimitedRoute::EmailVerification => &self.config.email_verification,
+        }
+    }
+
+    fn lockout_enabled(&self) -> bool {
+        self.config.enabled && self.config.lockout_failures > 0
+    }
+
+    async fn count(&self, route: LimitedRoute, key: &str, limit: Limit) -> Result<(), AppError> {
+        let now = self.clock.now();
+        let window = TimeDelta::seconds(limit.window_secs as i64);
+        let window = self.store.hit(key, window, now).await?;
+        if window.count > limit.requests {
+            counters::request_throttled(route);
+            return Err(throttled(ErrorCode::TooManyRequests, window, now));
+        }
+        Ok(())
+    }
+}
+
+fn lockout_key(username: &str) -> String {
+    format!("lockout:{username}")
+}
+
+/// Rounds the wait up to whole seconds, at least one.
+fn throttled(code: ErrorCode, window: Window, now: DateTime<Utc>) -> AppError {
+    let wait = window.resets_at - now;
+    let mut retry_after_secs = wait.num_seconds();
+    if wait > TimeDelta::seconds(retry_after_secs) {
+        retry_after_secs += 1;
+    }
+    AppError::Throttled {
+        code,
+        retry_after_secs: retry_after_secs.max(1) as u64,
+    }
+}
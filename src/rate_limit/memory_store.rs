@@ -0,0 +1,58 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use chrono::{DateTime, TimeDelta, Utc};
+
+use crate::error::AppError;
+use crate::rate_limit::{RateLimitStore, Window};
+
+/// Ended windows are dropped once the map grows past this many keys.
+const PRUNE_AT: usize = 10_000;
+
+/// Counters of this process only. Each instance of a multi-instance
+/// deployment limits on its own.
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    windows: Mutex<HashMap<String, Window>>,
+}
+
+#[async_trait::async_trait]
+impl RateLimitStore for MemoryStore {
+    async fn hit(
+        &self,
+        key: &str,
+        window: TimeDelta,
+        now: DateTime<Utc>,
+    ) -> Result<Window, AppError> {
+        let mut windows = self.windows.lock().unwrap();
+        if windows.len() >= PRUNE_AT {
+            windows.retain(|_, window| window.resets_at > now);
+        }
+
+        let entry = windows.entry(key.to_string()).or_insert(Window {
+            count: 0,
+            resets_at: now + window,
+        });
+        if entry.resets_at <= now {
+            *entry = Window {
+                count: 0,
+                resets_at: now + window,
+            };
+        }
+        entry.count += 1;
+        Ok(*entry)
+    }
+
+    async fn peek(&self, key: &str, now: DateTime<Utc>) -> Result<Option<Window>, AppError> {
+        let windows = self.windows.lock().unwrap();
+        Ok(windows
+            .get(key)
+            .filter(|window| window.resets_at > now)
+            .copied())
+    }
+
+    async fn clear(&self, key: &str) -> Result<(), AppError> {
+        self.windows.lock().unwrap().remove(key);
+        Ok(())
+    }
+}
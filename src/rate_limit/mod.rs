@@ -0,0 +1,14 @@
+//! Request limits and login lockout for the auth endpoints. Counters use fixed
+//! windows and live in a [`RateLimitStore`], in memory or in Postgres.
+
+pub mod client_ip;
+pub mod limiter;
+pub mod memory_store;
+pub mod postgres_store;
+pub mod store;
+
+pub use client_ip::*;
+pub use limiter::*;
+pub use memory_store::*;
+pub use postgres_store::*;
+pub use store::*;
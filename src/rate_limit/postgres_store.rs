@@ -0,0 +1,97 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, TimeDelta, Utc};
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, QueryResult, Statement};
+
+use crate::error::AppError;
+use crate::rate_limit::{RateLimitStore, Window};
+
+const HIT: &str = "INSERT INTO rate_limits (key, count, resets_at) VALUES ($1, 1, $3)
+    ON CONFLICT (key) DO UPDATE SET
+        count = CASE WHEN rate_limits.resets_at <= $2 THEN 1 ELSE rate_limits.count + 1 END,
+        resets_at = CASE WHEN rate_limits.resets_at <= $2
+            THEN EXCLUDED.resets_at ELSE rate_limits.resets_at END
+    RETURNING count, resets_at";
+const PEEK: &str = "SELECT count, resets_at FROM rate_limits WHERE key = $1 AND resets_at > $2";
+const CLEAR: &str = "DELETE FROM rate_limits WHERE key = $1";
+const DELETE_EXPIRED: &str = "DELETE FROM rate_limits WHERE resets_at <= $1";
+
+/// Counters in the `rate_limits` table, shared by every instance that uses
+/// the same database.
+pub struct PostgresStore {
+    db: Arc<DatabaseConnection>,
+}
+
+impl PostgresStore {
+    pub fn new(db: Arc<DatabaseConnection>) -> Self {
+        PostgresStore { db }
+    }
+
+    /// Removes windows that ended before `now`, returns how many.
+    pub async fn delete_expired(
+        db: &DatabaseConnection,
+        now: DateTime<Utc>,
+    ) -> Result<u64, AppError> {
+        let result = db
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                DELETE_EXPIRED,
+                [now.into()],
+            ))
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
+
+#[async_trait::async_trait]
+impl RateLimitStore for PostgresStore {
+    async fn hit(
+        &self,
+        key: &str,
+        window: TimeDelta,
+        now: DateTime<Utc>,
+    ) -> Result<Window, AppError> {
+        let row = self
+            .db
+            .query_one(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                HIT,
+                [key.into(), now.into(), (now + window).into()],
+            ))
+            .await?
+            .ok_or_else(|| AppError::internal("rate limit upsert returned no row"))?;
+        read_window(&row)
+    }
+
+    async fn peek(&self, key: &str, now: DateTime<Utc>) -> Result<Option<Window>, AppError> {
+        let row = self
+            .db
+            .query_one(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                PEEK,
+                [key.into(), now.into()],
+            ))
+            .await?;
+        row.as_ref().map(read_window).transpose()
+    }
+
+    async fn clear(&self, key: &str) -> Result<(), AppError> {
+        self.db
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                CLEAR,
+                [key.into()],
+            ))
+            .await?;
+        Ok(())
+    }
+}
+
+fn read_window(row: &QueryResult) -> Result<Window, AppError> {
+    let count: i32 = row.try_get("", "count")?;
+    let resets_at: DateTime<Utc> = row.try_get("", "resets_at")?;
+    Ok(Window {
+        count: count.max(0) as u32,
+        resets_at,
+    })
+}
@@ -0,0 +1,27 @@
+use chrono::{DateTime, TimeDelta, Utc};
+
+use crate::error::AppError;
+
+/// Hits counted against one key in the current window.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Window {
+    pub count: u32,
+    pub resets_at: DateTime<Utc>,
+}
+
+#[async_trait::async_trait]
+pub trait RateLimitStore: Send + Sync {
+    /// Counts one hit against `key` and returns its window. A window that has
+    /// ended by `now` starts over with a length of `window`.
+    async fn hit(
+        &self,
+        key: &str,
+        window: TimeDelta,
+        now: DateTime<Utc>,
+    ) -> Result<Window, AppError>;
+
+    /// The window of `key` if it is still running at `now`.
+    async fn peek(&self, key: &str, now: DateTime<Utc>) -> Result<Option<Window>, AppError>;
+
+    async fn clear(&self, key: &str) -> Result<(), AppError>;
+}
@@ -0,0 +1,407 @@
+//! Reproducible demo data. The same seed, options and anchor date always
+//! produce the same users, friendships, wish places, busy days and events.
+
+use std::collections::HashSet;
+
+use chrono::{Days, NaiveDate, TimeDelta};
+use rand::seq::{IndexedRandom, SliceRandom};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use sea_orm::{ActiveModelTrait, ConnectionTrait, DbErr, Set};
+use uuid::Uuid;
+
+use crate::entities::event::EventStatus;
+use crate::entities::event_history::EventTransition;
+use crate::entities::friendship::FriendshipStatus;
+use crate::entities::user_event::{UserEventResponse, UserEventRole};
+use crate::entities::wish_place::WishPlaceStatus;
+use crate::entities::{
+    BusydayActiveModel, EventActiveModel, EventHistoryActiveModel, FriendshipActiveModel,
+    UserActiveModel, UserEventActiveModel, WishPlaceActiveModel,
+};
+
+const BIOS: &[&str] = &[
+    "Board games and long walks.",
+    "Always up for coffee.",
+    "Looking for a climbing buddy.",
+    "Weekend hiker, weekday coder.",
+    "Collects vinyl and bad puns.",
+];
+const PLACES: &[(&str, &str)] = &[
+    ("Rooftop cinema", "Gorky Park"),
+    ("Ramen bar", "Downtown"),
+    ("Escape room", "Old Town"),
+    ("Botanical garden", "North District"),
+    ("Karaoke night", "Arbat"),
+    ("Bowling alley", "Mall"),
+    ("Lake picnic", "Lake Shore"),
+    ("Science museum", "City Center"),
+];
+const EVENT_TITLES: &[&str] = &[
+    "Dinner",
+    "Movie night",
+    "Birthday party",
+    "Bike ride",
+    "Game night",
+    "Museum trip",
+    "Picnic",
+    "Concert",
+];
+/// 1x1 transparent PNG, enough for the memory image of a completed event.
+const MEMORY_IMAGE_BASE64: &str =
+    "iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAQAAAC1HAwCAAAAC0lEQVR42mNkYAAAAAYAAjCB0C8AAAAASUVORK5CYII=";
+const STATUSES: [EventStatus; 4] = [
+    EventStatus::Pending,
+    EventStatus::Confirmed,
+    EventStatus::Completed,
+    EventStatus::Canceled,
+];
+
+pub struct SeedOptions {
+    pub seed: u64,
+    pub users: usize,
+    pub events: usize,
+    /// Usernames are `{prefix}01`, `{prefix}02`, ...
+    pub prefix: String,
+    pub password_hash: String,
+    /// Past and future dates are spread around this day.
+    pub anchor_date: NaiveDate,
+}
+
+#[derive(Default)]
+pub struct SeedSummary {
+    pub users: usize,
+    pub friendships_accepted: usize,
+    pub friendships_pending: usize,
+    pub wish_places: usize,
+    pub busy_days: usize,
+    pub events: [usize; 4],
+}
+
+struct Generator<'a, C> {
+    db: &'a C,
+    rng: ChaCha8Rng,
+    options: &'a SeedOptions,
+    user_ids: Vec<Uuid>,
+    friends: Vec<Vec<usize>>,
+    busy: HashSet<(usize, NaiveDate)>,
+    summary: SeedSummary,
+}
+
+/// Writes the generated data through the entities. Run it inside a
+/// transaction so a failure leaves nothing behind.
+pub async fn run<C: ConnectionTrait>(db: &C, options: &SeedOptions) -> Result<SeedSummary, DbErr> {
+    let mut generator = Generator {
+        db,
+        rng: ChaCha8Rng::seed_from_u64(options.seed),
+        options,
+        user_ids: Vec::new(),
+        friends: vec![Vec::new(); options.users],
+        busy: HashSet::new(),
+        summary: SeedSummary::default(),
+    };
+    generator.users().await?;
+    generator.friendships().await?;
+    generator.wish_places().await?;
+    generator.events().await?;
+    generator.free_busy_days().await?;
+    Ok(generator.summary)
+}
+
+impl<C: ConnectionTrait> Generator<'_, C> {
+    async fn users(&mut self) -> Result<(), DbErr> {
+        for n in 1..=self.options.users {
+            let bio = self
+                .rng
+                .random_bool(0.7)
+                .then(|| BIOS.choose(&mut self.rng).unwrap().to_string());
+            let locale = if self.rng.random_bool(0.5) {
+                "en"
+            } else {
+                "ru"
+            };
+            let user = UserActiveModel {
+                username: Set(format!("{}{n:02}", self.options.prefix)),
+                password_hash: Set(self.options.password_hash.clone()),
+                bio: Set(bio),
+                locale: Set(Some(locale.to_string())),
+                ..Default::default()
+            }
+            .insert(self.db)
+            .await?;
+            self.user_ids.push(user.id);
+        }
+        self.summary.users = self.user_ids.len();
+        Ok(())
+    }
+
+    async fn friendships(&mut self) -> Result<(), DbErr> {
+        let count = self.user_ids.len();
+        for a in 0..count {
+            for b in a + 1..count {
+                let roll: f64 = self.rng.random();
+                let status = if roll < 0.45 {
+                    FriendshipStatus::Accepted
+                } else if roll < 0.6 {
+                    FriendshipStatus::Pending
+                } else {
+                    continue;
+                };
+                let (from, to) = if self.rng.random_bool(0.5) {
+                    (a, b)
+                } else {
+                    (b, a)
+                };
+
+                if status == FriendshipStatus::Accepted {
+                    self.friends[a].push(b);
+                    self.friends[b].push(a);
+                    self.summary.friendships_accepted += 1;
+                } else {
+                    self.summary.friendships_pending += 1;
+                }
+
+                FriendshipActiveModel {
+                    user_id: Set(self.user_ids[from]),
+                    friend_id: Set(self.user_ids[to]),
+                    status: Set(status),
+                    ..Default::default()
+                }
+                .insert(self.db)
+                .await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn wish_places(&mut self) -> Result<(), DbErr> {
+        for user in 0..self.user_ids.len() {
+            for _ in 0..self.rng.random_range(0..=3) {
+                let (title, location) = *PLACES.choose(&mut self.rng).unwrap();
+                let status = if self.rng.random_bool(0.2) {
+                    WishPlaceStatus::Archived
+                } else {
+                    WishPlaceStatus::Active
+                };
+                WishPlaceActiveModel {
+                    user_id: Set(self.user_ids[user]),
+                    title: Set(title.to_string()),
+                    location: Set(Some(location.to_string())),
+                    status: Set(status),
+                    ..Default::default()
+                }
+                .insert(self.db)
+                .await?;
+                self.summary.wish_places += 1;
+            }
+        }
+        Ok(())
+    }
+
+    /// The first four events cover every status, the rest are random.
+    async fn events(&mut self) -> Result<(), DbErr> {
+        let creators: Vec<usize> = (0..self.user_ids.len())
+            .filter(|user| !self.friends[*user].is_empty())
+            .collect();
+        if creators.is_empty() {
+            return Ok(());
+        }
+
+        for n in 0..self.options.events {
+            let status = match STATUSES.get(n) {
+                Some(status) => status.clone(),
+                None => STATUSES.choose(&mut self.rng).unwrap().clone(),
+            };
+            let creator = *creators.choose(&mut self.rng).unwrap();
+            let mut invited = self.friends[creator].clone();
+            invited.shuffle(&mut self.rng);
+            invited.truncate(self.rng.random_range(1..=3));
+
+            let mut participants: Vec<(usize, UserEventResponse)> = invited
+                .into_iter()
+                .map(|user| (user, self.response(&status)))
+                .collect();
+            if status == EventStatus::Pending {
+                participants[0].1 = UserEventResponse::Pending;
+            }
+
+            // Canceled events keep no busy days.
+            let busy_users: Vec<usize> = if status == EventStatus::Canceled {
+                Vec::new()
+            } else {
+                std::iter::once(creator)
+                    .chain(
+                        participants
+                            .iter()
+                            .filter(|(_, response)| *response == UserEventResponse::Accepted)
+                            .map(|(user, _)| *user),
+                    )
+                    .collect()
+            };
+            let Some(date) = self.free_date(&status, &busy_users) else {
+                continue;
+            };
+
+            let event_id = self.event(creator, &participants, status, date).await?;
+            for user in busy_users {
+                self.busy_day(user, date, Some(event_id)).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Participant answer that is consistent with the event status.
+    fn response(&mut self, status: &EventStatus) -> UserEventResponse {
+        match status {
+            EventStatus::Confirmed | EventStatus::Completed => UserEventResponse::Accepted,
+            EventStatus::Pending if self.rng.random_bool(0.5) => UserEventResponse::Accepted,
+            EventStatus::Pending => UserEventResponse::Pending,
+            EventStatus::Canceled => UserEventResponse::Declined,
+        }
+    }
+
+    /// Past dates for finished events, future ones otherwise, on a day every
+    /// user in `users` is still free.
+    fn free_date(&mut self, status: &EventStatus, users: &[usize]) -> Option<NaiveDate> {
+        let past = matches!(status, EventStatus::Completed | EventStatus::Canceled);
+        for _ in 0..20 {
+            let offset = Days::new(self.rng.random_range(1..=60));
+            let date = if past {
+                self.options.anchor_date.checked_sub_days(offset)?
+            } else {
+                self.options.anchor_date.checked_add_days(offset)?
+            };
+            if users.iter().all(|user| !self.busy.contains(&(*user, date))) {
+                return Some(date);
+            }
+        }
+        None
+    }
+
+    async fn event(
+        &mut self,
+        creator: usize,
+        participants: &[(usize, UserEventResponse)],
+        status: EventStatus,
+        date: NaiveDate,
+    ) -> Result<Uuid, DbErr> {
+        let title = *EVENT_TITLES.choose(&mut self.rng).unwrap();
+        let memory_image =
+            (status == EventStatus::Completed).then(|| MEMORY_IMAGE_BASE64.to_string());
+        let index = STATUSES
+            .iter()
+            .position(|s| *s == status)
+            .unwrap_or_default();
+
+        let event = EventActiveModel {
+            creator_id: Set(self.user_ids[creator]),
+            date: Set(date),
+            title: Set(title.to_string()),
+            status: Set(status.clone()),
+            memory_image_base64: Set(memory_image),
+            ..Default::default()
+        }
+        .insert(self.db)
+        .await?;
+
+        let owner = self.user_ids[creator];
+        let mut history = vec![(None, EventTransition::Created, owner)];
+        if matches!(status, EventStatus::Confirmed | EventStatus::Completed)
+            && let Some((last, _)) = participants.last()
+        {
+            history.push((
+                Some(EventStatus::Pending),
+                EventTransition::Confirmed,
+                self.user_ids[*last],
+            ));
+        }
+        let closing = match status {
+            EventStatus::Completed => Some(EventTransition::Finished),
+            EventStatus::Canceled => Some(EventTransition::CanceledByOwner),
+            _ => None,
+        };
+        if let Some(transition) = closing {
+            let from = history.last().map(|(_, t, _)| t.target());
+            history.push((from, transition, owner));
+        }
+        // Rows written in one transaction share `now()`, so spread them out.
+        for (seconds, (from, transition, actor)) in (0..).zip(history) {
+            EventHistoryActiveModel {
+                event_id: Set(event.id),
+                created_at: Set(event.created_at + TimeDelta::seconds(seconds)),
+                actor_id: Set(Some(actor)),
+                from_status: Set(from),
+                to_status: Set(transition.target()),
+                reason: Set(transition),
+                ..Default::default()
+            }
+            .insert(self.db)
+            .await?;
+        }
+
+        UserEventActiveModel {
+            event_id: Set(event.id),
+            user_id: Set(self.user_ids[creator]),
+            role: Set(UserEventRole::Owner),
+            response_status: Set(UserEventResponse::Accepted),
+            ..Default::default()
+        }
+        .insert(self.db)
+        .await?;
+
+        for (participant, response) in participants {
+            UserEventActiveModel {
+                event_id: Set(event.id),
+                user_id: Set(self.user_ids[*participant]),
+                role: Set(UserEventRole::Participant),
+                response_status: Set(response.clone()),
+                ..Default::default()
+            }
+            .insert(self.db)
+            .await?;
+        }
+
+        self.summary.events[index] += 1;
+        Ok(event.id)
+    }
+
+    /// Days marked busy by hand, without an event.
+    async fn free_busy_days(&mut self) -> Result<(), DbErr> {
+        for user in 0..self.user_ids.len() {
+            for _ in 0..self.rng.random_range(0..=3) {
+                let offset = self.rng.random_range(0..=60);
+                let Some(date) = self
+                    .options
+                    .anchor_date
+                    .checked_add_days(Days::new(offset))
+                    .and_then(|date| date.checked_sub_days(Days::new(30)))
+                else {
+                    continue;
+                };
+                if !self.busy.contains(&(user, date)) {
+                    self.busy_day(user, date, None).await?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn busy_day(
+        &mut self,
+        user: usize,
+        date: NaiveDate,
+        event_id: Option<Uuid>,
+    ) -> Result<(), DbErr> {
+        BusydayActiveModel {
+            user_id: Set(self.user_ids[user]),
+            date: Set(date),
+            event_id: Set(event_id),
+            ..Default::default()
+        }
+        .insert(self.db)
+        .await?;
+        self.busy.insert((user, date));
+        self.summary.busy_days += 1;
+        Ok(())
+    }
+}
@@ -0,0 +1,205 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::http::HeaderValue;
+use axum::{Router, middleware};
+use sea_orm_migration::MigratorTrait;
+use tokio::net::TcpListener;
+use tokio_util::sync::CancellationToken;
+use tower_http::cors::{AllowOrigin, Any, CorsLayer};
+use utoipa::OpenApi;
+use utoipa_swagger_ui::SwaggerUi;
+
+use crate::api_doc::api_doc::ApiDoc;
+use crate::auth::keys::JwtKeys;
+use crate::auth::session_guard::SessionGuard;
+use crate::clock::SystemClock;
+use crate::config::{Config, CorsConfig, RateLimitBackend};
+use crate::controllers::{
+    account_controller, auth_controller, calendar_controller, email_controller, event_controller,
+    friendship_controller, health_controller, identity_controller, passkey_controller,
+    password_controller, session_controller, two_factor_controller, users_controller,
+    wish_place_controller,
+};
+use crate::error::BoxError;
+use crate::jobs::{
+    Jobs, account_purge, export_cleanup, jwt_key_reload, mail_token_cleanup, rate_limit_cleanup,
+    refresh_token_cleanup,
+};
+use crate::rate_limit::RateLimiter;
+use crate::migration::Migrator;
+use crate::oidc::OidcVerifier;
+use crate::state::AppState;
+use crate::{db, i18n, logging, mail, request_id, telemetry};
+
+/// Every route of the API with the middleware stack, without state.
+pub fn router(config: &Config) -> Router<AppState> {
+    let mut router = Router::new()
+        .merge(SwaggerUi::new("/docs").url("/api-doc/openapi.json", ApiDoc::openapi()))
+        .merge(auth_controller::router())
+        .merge(password_controller::router())
+        .merge(two_factor_controller::router())
+        .merge(identity_controller::router())
+        .merge(passkey_controller::router())
+        .merge(users_controller::router())
+        .merge(account_controller::router())
+        .merge(email_controller::router())
+        .merge(session_controller::router())
+        .merge(friendship_controller::router())
+        .merge(calendar_controller::router())
+        .merge(event_controller::router())
+        .merge(wish_place_controller::router())
+        .merge(telemetry::router())
+        .merge(health_controller::router())
+        .layer(middleware::from_fn(i18n::detect_locale))
+        .layer(middleware::from_fn(telemetry::track_http))
+        .layer(middleware::from_fn(logging::trace_request))
+        .layer(middleware::from_fn(request_id::assign_request_id));
+
+    if let Some(cors) = cors_layer(&config.cors) {
+        router = router.layer(cors);
+    }
+    router
+}
+
+/// Runs the HTTP server until SIGTERM or Ctrl+C, then drains requests,
+/// stops background jobs and closes the pool.
+pub async fn serve(config: Arc<Config>) -> Result<(), BoxError> {
+    let metrics = telemetry::install();
+
+    std::fs::create_dir_all(&config.storage.data_dir)?;
+
+    let mut db_connection = db::init_db(&config.database).await?;
+    telemetry::track_db(&mut db_connection);
+
+    let pending = Migrator::get_pending_migrations(&db_connection).await?;
+    if !pending.is_empty() {
+        tracing::warn!(
+            pending = pending.len(),
+            "database has pending migrations, run `migrate up`; /readyz reports not ready"
+        );
+    }
+    let db_connection = Arc::new(db_connection);
+
+    let jwt_keys = Arc::new(JwtKeys::load(&config.jwt)?);
+
+    let mailer = mail::from_config(&config.mail)?;
+
+    let shutdown = CancellationToken::new();
+    let jobs = Jobs::new(shutdown.clone());
+    if jwt_keys.key_dir().is_some() {
+        jwt_key_reload::spawn(&jobs, jwt_keys.clone());
+    }
+    refresh_token_cleanup::spawn(&jobs, db_connection.clone());
+    mail_token_cleanup::spawn(&jobs, db_connection.clone());
+    account_purge::spawn(&jobs, db_connection.clone(), mailer.clone());
+    export_cleanup::spawn(
+        &jobs,
+        db_connection.clone(),
+        config.storage.data_dir.clone(),
+    );
+    if config.rate_limit.backend == RateLimitBackend::Postgres {
+        rate_limit_cleanup::spawn(&jobs, db_connection.clone());
+    }
+
+    let addr = config.server.listen_addr;
+    let drain_timeout = Duration::from_secs(config.server.shutdown_timeout_secs);
+    let app_router = router(&config);
+    let clock = Arc::new(SystemClock);
+    let rate_limiter =
+        RateLimiter::from_config(&config.rate_limit, db_connection.clone(), clock.clone());
+    let sessions = SessionGuard::new(
+        db_connection.clone(),
+        Duration::from_secs(config.jwt.session_check_secs),
+    );
+    let oidc = Arc::new(OidcVerifier::new(&config.oidc));
+    let state = AppState {
+        db: db_connection.clone(),
+        config,
+        jwt_keys,
+        metrics,
+        clock,
+        rate_limiter: Arc::new(rate_limiter),
+        sessions: Arc::new(sessions),
+        mailer,
+        oidc,
+        shutdown: shutdown.clone(),
+    };
+
+    tokio::spawn(wait_for_signal(shutdown.clone()));
+
+    tracing::info!("Starts on http://{}", addr);
+    let listener = TcpListener::bind(addr).await?;
+    let server = axum::serve(
+        listener,
+        app_router
+            .with_state(state)
+            .into_make_service_with_connect_info::<SocketAddr>(),
+    )
+        .with_graceful_shutdown(shutdown.clone().cancelled_owned());
+
+    tokio::select! {
+        result = server => result?,
+        _ = async {
+            shutdown.cancelled().await;
+            tokio::time::sleep(drain_timeout).await;
+        } => tracing::warn!("in-flight requests did not finish in time"),
+    }
+
+    jobs.wait().await;
+    if let Err(err) = db_connection.close_by_ref().await {
+        tracing::error!(error = %err, "failed to close database pool");
+    }
+    tracing::info!("Stopped");
+    Ok(())
+}
+
+/// Cancels `shutdown` on SIGTERM or Ctrl+C.
+async fn wait_for_signal(shutdown: CancellationToken) {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("cannot listen for Ctrl+C");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("cannot listen for SIGTERM")
+            .recv()
+            .await;
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+    tracing::info!("Shutdown signal received, draining requests");
+    shutdown.cancel();
+}
+
+fn cors_layer(config: &CorsConfig) -> Option<CorsLayer> {
+    if config.allowed_origins.is_empty() {
+        return None;
+    }
+    let origins = if config.allowed_origins.iter().any(|origin| origin == "*") {
+        AllowOrigin::any()
+    } else {
+        // Origins are checked when the config is loaded.
+        AllowOrigin::list(
+            config
+                .allowed_origins
+                .iter()
+                .filter_map(|origin| HeaderValue::from_str(origin).ok()),
+        )
+    };
+    Some(
+        CorsLayer::new()
+            .allow_origin(origins)
+            .allow_methods(Any)
+            .allow_headers(Any),
+    )
+}
@@ -0,0 +1,293 @@
+//! Deleting an account: the user asks, gets a grace period to change their
+//! mind, and then a background job purges the account.
+//!
+//! The `users` row stays as an anonymous tombstone so events the user took
+//! part in keep their other participants and history. Everything else goes:
+//! open events they organised are canceled, past ones are handed over to a
+//! participant (or deleted with their memory image when nobody else was
+//! there), and their own data, sessions and credentials are deleted.
+
+use chrono::{DateTime, Utc};
+use sea_orm::sea_query::Expr;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, Condition, ConnectionTrait, DatabaseConnection, DbErr,
+    EntityTrait, IntoActiveModel, QueryFilter, QueryOrder, QuerySelect, QueryTrait, Set,
+    TransactionTrait,
+};
+use uuid::Uuid;
+
+use crate::entities::event::EventStatus;
+use crate::entities::user_event::{UserEventResponse, UserEventRole};
+use crate::entities::{
+    Busyday, BusydayColumn, DataExport, DataExportColumn, EmailVerificationToken,
+    EmailVerificationTokenColumn, Event, EventColumn, Friendship, FriendshipColumn, Passkey,
+    PasskeyColumn, PasswordResetToken, PasswordResetTokenColumn, RecoveryCode, RecoveryCodeColumn,
+    RefreshToken, RefreshTokenColumn, SecurityEvent, SecurityEventColumn, Session, SessionColumn,
+    User, UserActiveModel, UserColumn, UserEvent, UserEventColumn, UserIdentity,
+    UserIdentityColumn, UserTotp, UserTotpColumn, WebauthnChallenge, WebauthnChallengeColumn,
+    WishPlace, WishPlaceColumn, event, user,
+};
+use crate::error::AppError;
+use crate::i18n::Locale;
+use crate::mail::{MailTemplate, Message};
+use crate::services::{CalendarService, Calendars, EventService, Events};
+
+/// Marks `user` for purging at `at`.
+pub async fn schedule<C: ConnectionTrait>(
+    db: &C,
+    user: user::Model,
+    at: DateTime<Utc>,
+) -> Result<user::Model, DbErr> {
+    let mut active: UserActiveModel = user.into();
+    active.deletion_scheduled_at = Set(Some(at.into()));
+    active.update(db).await
+}
+
+/// Takes back a scheduled deletion, returning whether there was one.
+pub async fn cancel<C: ConnectionTrait>(db: &C, user_id: Uuid) -> Result<bool, DbErr> {
+    let result = User::update_many()
+        .col_expr(
+            UserColumn::DeletionScheduledAt,
+            Expr::value(Option::<DateTime<Utc>>::None),
+        )
+        .filter(UserColumn::Id.eq(user_id))
+        .filter(UserColumn::DeletionScheduledAt.is_not_null())
+        .filter(UserColumn::DeletedAt.is_null())
+        .exec(db)
+        .await?;
+    Ok(result.rows_affected > 0)
+}
+
+/// Accounts whose grace period ended by `now`.
+pub async fn due<C: ConnectionTrait>(db: &C, now: DateTime<Utc>) -> Result<Vec<Uuid>, DbErr> {
+    User::find()
+        .select_only()
+        .column(UserColumn::Id)
+        .filter(UserColumn::DeletionScheduledAt.lte(now))
+        .filter(UserColumn::DeletedAt.is_null())
+        .order_by_asc(UserColumn::DeletionScheduledAt)
+        .into_tuple()
+        .all(db)
+        .await
+}
+
+/// Purges the account `user_id` if its deletion is still due, in a single
+/// transaction. Returns the mail telling participants about canceled events,
+/// to send once the purge is committed, or `None` when it was undone meanwhile.
+pub async fn purge(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    now: DateTime<Utc>,
+) -> Result<Option<Vec<Message>>, AppError> {
+    let tx = db.begin().await?;
+    // Locked so an undo arriving now either wins or waits and finds nothing.
+    let user = User::find_by_id(user_id)
+        .filter(UserColumn::DeletionScheduledAt.lte(now))
+        .filter(UserColumn::DeletedAt.is_null())
+        .lock_exclusive()
+        .one(&tx)
+        .await?;
+    let Some(user) = user else {
+        return Ok(None);
+    };
+
+    let events = Events::new();
+    let mut notices = Vec::new();
+    let owned = Event::find()
+        .filter(EventColumn::CreatorId.eq(user_id))
+        .order_by_asc(EventColumn::Date)
+        .all(&tx)
+        .await?;
+    for event in owned {
+        if matches!(event.status, EventStatus::Pending | EventStatus::Confirmed) {
+            notices.extend(cancel_notices(&tx, &event).await?);
+            events.cancel(&tx, event.id, user_id).await?;
+        } else {
+            hand_over(&tx, event, user_id).await?;
+        }
+    }
+
+    // Invitations to open events of others are declined, so the event moves
+    // on as if the user had answered.
+    let open_participations = UserEvent::find()
+        .filter(UserEventColumn::UserId.eq(user_id))
+        .filter(UserEventColumn::Role.eq(UserEventRole::Participant))
+        .filter(UserEventColumn::ResponseStatus.ne(UserEventResponse::Declined))
+        .all(&tx)
+        .await?;
+    for participation in open_participations {
+        let open = Event::find_by_id(participation.event_id)
+            .filter(EventColumn::Status.is_in([EventStatus::Pending, EventStatus::Confirmed]))
+            .one(&tx)
+            .await?
+            .is_some();
+        if open {
+            events.decline(&tx, participation.event_id, user_id).await?;
+        }
+    }
+
+    WishPlace::delete_many()
+        .filter(WishPlaceColumn::UserId.eq(user_id))
+        .exec(&tx)
+        .await?;
+    Busyday::delete_many()
+        .filter(BusydayColumn::UserId.eq(user_id))
+        .exec(&tx)
+        .await?;
+    // Their archives go with the next export cleanup.
+    DataExport::delete_many()
+        .filter(DataExportColumn::UserId.eq(user_id))
+        .exec(&tx)
+        .await?;
+    Friendship::delete_many()
+        .filter(
+            Condition::any()
+                .add(FriendshipColumn::UserId.eq(user_id))
+                .add(FriendshipColumn::FriendId.eq(user_id)),
+        )
+        .exec(&tx)
+        .await?;
+    delete_credentials(&tx, user_id).await?;
+
+    let mut active: UserActiveModel = user.into();
+    active.username = Set(format!("deleted-{}", user_id.simple()));
+    active.password_hash = Set(String::new());
+    active.avatar_url = Set(None);
+    active.bio = Set(None);
+    active.email = Set(None);
+    active.email_verified_at = Set(None);
+    active.locale = Set(None);
+    active.timezone = Set(None);
+    active.disabled_at = Set(Some(now.into()));
+    active.deletion_scheduled_at = Set(None);
+    active.deleted_at = Set(Some(now.into()));
+    active.update(&tx).await?;
+
+    tx.commit().await?;
+    Ok(Some(notices))
+}
+
+/// Gives a past event to the first participant who was there, so their
+/// memories survive. An event nobody else attended goes with the account.
+async fn hand_over<C: ConnectionTrait>(
+    db: &C,
+    event: event::Model,
+    owner: Uuid,
+) -> Result<(), AppError> {
+    let heir = UserEvent::find()
+        .filter(UserEventColumn::EventId.eq(event.id))
+        .filter(UserEventColumn::UserId.ne(owner))
+        .filter(UserEventColumn::ResponseStatus.eq(UserEventResponse::Accepted))
+        .order_by_asc(UserEventColumn::Id)
+        .one(db)
+        .await?;
+    let Some(heir) = heir else {
+        Calendars.release(db, event.id, None).await?;
+        Event::delete_by_id(event.id).exec(db).await?;
+        return Ok(());
+    };
+
+    UserEvent::delete_many()
+        .filter(UserEventColumn::EventId.eq(event.id))
+        .filter(UserEventColumn::UserId.eq(owner))
+        .exec(db)
+        .await?;
+    let heir_id = heir.user_id;
+    let mut participation = heir.into_active_model();
+    participation.role = Set(UserEventRole::Owner);
+    participation.update(db).await?;
+    let mut active = event.into_active_model();
+    active.creator_id = Set(heir_id);
+    active.update(db).await?;
+    Ok(())
+}
+
+/// Mail for every participant of `event` who has not declined and has a
+/// verified address.
+async fn cancel_notices<C: ConnectionTrait>(
+    db: &C,
+    event: &event::Model,
+) -> Result<Vec<Message>, AppError> {
+    let participants = UserEvent::find()
+        .select_only()
+        .column(UserEventColumn::UserId)
+        .filter(UserEventColumn::EventId.eq(event.id))
+        .filter(UserEventColumn::Role.eq(UserEventRole::Participant))
+        .filter(UserEventColumn::ResponseStatus.ne(UserEventResponse::Declined))
+        .into_query();
+    let recipients = User::find()
+        .filter(UserColumn::Id.in_subquery(participants))
+        .filter(UserColumn::EmailVerifiedAt.is_not_null())
+        .filter(UserColumn::DeletedAt.is_null())
+        .all(db)
+        .await?;
+
+    let date = event.date.to_string();
+    Ok(recipients
+        .into_iter()
+        .filter_map(|recipient| {
+            let locale = recipient
+                .locale
+                .as_deref()
+                .and_then(Locale::parse)
+                .unwrap_or_default();
+            let email = recipient.email?;
+            Some(Message::localized(
+                &email,
+                MailTemplate::EventCanceledByDeletion,
+                locale,
+                &[
+                    ("username", &recipient.username),
+                    ("title", &event.title),
+                    ("date", &date),
+                ],
+            ))
+        })
+        .collect())
+}
+
+/// Sessions, refresh tokens, second factors, sign-in methods and the
+/// security log. Deleting a session ends its access tokens as well.
+async fn delete_credentials<C: ConnectionTrait>(db: &C, user_id: Uuid) -> Result<(), DbErr> {
+    RefreshToken::delete_many()
+        .filter(RefreshTokenColumn::UserId.eq(user_id))
+        .exec(db)
+        .await?;
+    Session::delete_many()
+        .filter(SessionColumn::UserId.eq(user_id))
+        .exec(db)
+        .await?;
+    PasswordResetToken::delete_many()
+        .filter(PasswordResetTokenColumn::UserId.eq(user_id))
+        .exec(db)
+        .await?;
+    EmailVerificationToken::delete_many()
+        .filter(EmailVerificationTokenColumn::UserId.eq(user_id))
+        .exec(db)
+        .await?;
+    UserTotp::delete_many()
+        .filter(UserTotpColumn::UserId.eq(user_id))
+        .exec(db)
+        .await?;
+    RecoveryCode::delete_many()
+        .filter(RecoveryCodeColumn::UserId.eq(user_id))
+        .exec(db)
+        .await?;
+    UserIdentity::delete_many()
+        .filter(UserIdentityColumn::UserId.eq(user_id))
+        .exec(db)
+        .await?;
+    Passkey::delete_many()
+        .filter(PasskeyColumn::UserId.eq(user_id))
+        .exec(db)
+        .await?;
+    WebauthnChallenge::delete_many()
+        .filter(WebauthnChallengeColumn::UserId.eq(user_id))
+        .exec(db)
+        .await?;
+    SecurityEvent::delete_many()
+        .filter(SecurityEventColumn::UserId.eq(user_id))
+        .exec(db)
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,225 @@
+use chrono::NaiveDate;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter, QueryOrder, Set,
+    Statement,
+};
+use uuid::Uuid;
+
+use crate::entities::event::EventStatus;
+use crate::entities::user_event::{UserEventResponse, UserEventRole};
+use crate::entities::{
+    Busyday, BusydayActiveModel, BusydayColumn, Event, EventColumn, UserEvent, UserEventColumn,
+    busyday, event,
+};
+use crate::error::{AppError, ErrorCode};
+
+/// A user's busy days and open invitations within a date range.
+pub struct Calendar {
+    pub busy_days: Vec<busyday::Model>,
+    /// Events the user has not answered yet, ordered by date.
+    pub pending_invites: Vec<event::Model>,
+}
+
+/// Busy days: a user has at most one per date, either taken by an event or
+/// marked by hand.
+#[async_trait::async_trait]
+pub trait CalendarService: Send + Sync {
+    async fn is_busy<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        user_id: Uuid,
+        date: NaiveDate,
+    ) -> Result<bool, AppError>;
+
+    async fn ensure_free<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        user_id: Uuid,
+        date: NaiveDate,
+    ) -> Result<(), AppError> {
+        if self.is_busy(db, user_id, date).await? {
+            return Err(ErrorCode::DateReserved.into());
+        }
+        Ok(())
+    }
+
+    /// Takes a lock on `date` for each of `user_ids` that is held until the
+    /// transaction ends, so checking a day and reserving it cannot interleave
+    /// with another reservation. Locks are taken in id order to rule out
+    /// deadlocks between overlapping sets of users.
+    async fn lock_days<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        user_ids: &[Uuid],
+        date: NaiveDate,
+    ) -> Result<(), AppError>;
+
+    /// Marks the day busy. A reservation that slips past [`lock_days`] is
+    /// still reported as `DateReserved` by the unique index.
+    ///
+    /// [`lock_days`]: CalendarService::lock_days
+    async fn reserve<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        user_id: Uuid,
+        date: NaiveDate,
+        event_id: Option<Uuid>,
+    ) -> Result<(), AppError>;
+
+    /// Frees the days taken by `event_id`, only for `user_id` when given.
+    async fn release<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        event_id: Uuid,
+        user_id: Option<Uuid>,
+    ) -> Result<u64, AppError>;
+
+    /// Which of `user_ids` are busy on `date`.
+    async fn busy_users<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        user_ids: Vec<Uuid>,
+        date: NaiveDate,
+    ) -> Result<Vec<Uuid>, AppError>;
+
+    async fn calendar<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        user_id: Uuid,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Calendar, AppError>;
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Calendars;
+
+#[async_trait::async_trait]
+impl CalendarService for Calendars {
+    async fn is_busy<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        user_id: Uuid,
+        date: NaiveDate,
+    ) -> Result<bool, AppError> {
+        let row = Busyday::find()
+            .filter(BusydayColumn::UserId.eq(user_id))
+            .filter(BusydayColumn::Date.eq(date))
+            .one(db)
+            .await?;
+        Ok(row.is_some())
+    }
+
+    async fn lock_days<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        user_ids: &[Uuid],
+        date: NaiveDate,
+    ) -> Result<(), AppError> {
+        let mut user_ids = user_ids.to_vec();
+        user_ids.sort();
+        user_ids.dedup();
+        for user_id in user_ids {
+            db.execute(Statement::from_sql_and_values(
+                db.get_database_backend(),
+                "SELECT pg_advisory_xact_lock(hashtextextended($1, 0))",
+                [format!("busyday:{user_id}:{date}").into()],
+            ))
+            .await?;
+        }
+        Ok(())
+    }
+
+    async fn reserve<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        user_id: Uuid,
+        date: NaiveDate,
+        event_id: Option<Uuid>,
+    ) -> Result<(), AppError> {
+        BusydayActiveModel {
+            user_id: Set(user_id),
+            date: Set(date),
+            event_id: Set(event_id),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+        .map_err(|err| AppError::on_unique_violation(err, ErrorCode::DateReserved))?;
+        Ok(())
+    }
+
+    async fn release<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        event_id: Uuid,
+        user_id: Option<Uuid>,
+    ) -> Result<u64, AppError> {
+        let mut query = Busyday::delete_many().filter(BusydayColumn::EventId.eq(event_id));
+        if let Some(user_id) = user_id {
+            query = query.filter(BusydayColumn::UserId.eq(user_id));
+        }
+        Ok(query.exec(db).await?.rows_affected)
+    }
+
+    async fn busy_users<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        user_ids: Vec<Uuid>,
+        date: NaiveDate,
+    ) -> Result<Vec<Uuid>, AppError> {
+        if user_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let rows = Busyday::find()
+            .filter(BusydayColumn::Date.eq(date))
+            .filter(BusydayColumn::UserId.is_in(user_ids))
+            .all(db)
+            .await?;
+        Ok(rows.into_iter().map(|row| row.user_id).collect())
+    }
+
+    async fn calendar<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        user_id: Uuid,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Calendar, AppError> {
+        let busy_days = Busyday::find()
+            .filter(BusydayColumn::UserId.eq(user_id))
+            .filter(BusydayColumn::Date.gte(from))
+            .filter(BusydayColumn::Date.lte(to))
+            .order_by_asc(BusydayColumn::Date)
+            .all(db)
+            .await?;
+
+        let event_ids = UserEvent::find()
+            .filter(UserEventColumn::UserId.eq(user_id))
+            .filter(UserEventColumn::Role.eq(UserEventRole::Participant))
+            .filter(UserEventColumn::ResponseStatus.eq(UserEventResponse::Pending))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|row| row.event_id)
+            .collect::<Vec<_>>();
+
+        let pending_invites = if event_ids.is_empty() {
+            Vec::new()
+        } else {
+            Event::find()
+                .filter(EventColumn::Id.is_in(event_ids))
+                .filter(EventColumn::Status.is_in([EventStatus::Pending, EventStatus::Confirmed]))
+                .filter(EventColumn::Date.gte(from))
+                .filter(EventColumn::Date.lte(to))
+                .order_by_asc(EventColumn::Date)
+                .all(db)
+                .await?
+        };
+
+        Ok(Calendar {
+            busy_days,
+            pending_invites,
+        })
+    }
+}
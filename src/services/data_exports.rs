@@ -0,0 +1,231 @@
+//! Personal data exports: the user asks for a copy of their data, a
+//! background task writes the archive, and a link with a token downloads it
+//! until the export expires. See [`crate::export`] for the archive format.
+
+use std::collections::HashSet;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, TimeDelta, Utc};
+use sea_orm::sea_query::Expr;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait,
+    IntoActiveModel, QueryFilter, QueryOrder, QuerySelect, Set,
+};
+use uuid::Uuid;
+
+use crate::auth::mail_token;
+use crate::entities::data_export::{self, DataExportStatus};
+use crate::entities::{DataExport, DataExportActiveModel, DataExportColumn, User};
+use crate::error::{AppError, ErrorCode, ResultExt};
+use crate::export;
+
+/// A pending export older than this was lost, e.g. to a restart.
+const STALE_AFTER: TimeDelta = TimeDelta::hours(1);
+
+/// Where the archive of export `id` is kept.
+pub fn archive_path(data_dir: &Path, id: Uuid) -> PathBuf {
+    exports_dir(data_dir).join(format!("{id}.zip"))
+}
+
+fn exports_dir(data_dir: &Path) -> PathBuf {
+    data_dir.join("exports")
+}
+
+/// Whether an export of `user_id` is still being written.
+pub async fn is_pending<C: ConnectionTrait>(
+    db: &C,
+    user_id: Uuid,
+    now: DateTime<Utc>,
+) -> Result<bool, DbErr> {
+    let pending = DataExport::find()
+        .filter(DataExportColumn::UserId.eq(user_id))
+        .filter(DataExportColumn::Status.eq(DataExportStatus::Pending))
+        .filter(DataExportColumn::CreatedAt.gt(now - STALE_AFTER))
+        .one(db)
+        .await?;
+    Ok(pending.is_some())
+}
+
+/// Records a pending export of `user_id` and returns it with the token of its
+/// download link.
+pub async fn create<C: ConnectionTrait>(
+    db: &C,
+    user_id: Uuid,
+    now: DateTime<Utc>,
+    expires_at: DateTime<Utc>,
+) -> Result<(data_export::Model, String), DbErr> {
+    let token = mail_token::generate();
+    let model = DataExportActiveModel {
+        id: Set(Uuid::new_v4()),
+        user_id: Set(user_id),
+        status: Set(DataExportStatus::Pending),
+        token_hash: Set(mail_token::hash(&token)),
+        size_bytes: Set(None),
+        created_at: Set(now.into()),
+        completed_at: Set(None),
+        expires_at: Set(expires_at.into()),
+    }
+    .insert(db)
+    .await?;
+    Ok((model, token))
+}
+
+/// Writes the archive of `export` and marks it ready, or failed when that
+/// did not work.
+pub async fn build(
+    db: &DatabaseConnection,
+    data_dir: &Path,
+    export: data_export::Model,
+    now: DateTime<Utc>,
+) -> Result<data_export::Model, AppError> {
+    let path = archive_path(data_dir, export.id);
+    let result = write_archive(db, &path, export.user_id, now).await;
+
+    let mut active = export.into_active_model();
+    active.completed_at = Set(Some(now.into()));
+    match result {
+        Ok(size) => {
+            active.status = Set(DataExportStatus::Ready);
+            active.size_bytes = Set(Some(size as i64));
+            Ok(active.update(db).await?)
+        }
+        Err(err) => {
+            active.status = Set(DataExportStatus::Failed);
+            active.update(db).await?;
+            Err(err)
+        }
+    }
+}
+
+async fn write_archive(
+    db: &DatabaseConnection,
+    path: &Path,
+    user_id: Uuid,
+    now: DateTime<Utc>,
+) -> Result<u64, AppError> {
+    let user = User::find_by_id(user_id)
+        .one(db)
+        .await?
+        .ok_or(ErrorCode::ProfileNotFound)?;
+    let takeout = export::collect(db, user, now).await?;
+    let path = path.to_path_buf();
+    tokio::task::spawn_blocking(move || {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        export::write(&path, &takeout)
+    })
+    .await
+    .or_internal()?
+    .or_internal()
+}
+
+/// Exports of `user_id` that have not expired, newest first.
+pub async fn list<C: ConnectionTrait>(
+    db: &C,
+    user_id: Uuid,
+    now: DateTime<Utc>,
+) -> Result<Vec<data_export::Model>, DbErr> {
+    DataExport::find()
+        .filter(DataExportColumn::UserId.eq(user_id))
+        .filter(DataExportColumn::ExpiresAt.gt(now))
+        .order_by_desc(DataExportColumn::CreatedAt)
+        .all(db)
+        .await
+}
+
+/// The unexpired export behind the download token `token`.
+pub async fn find_by_token<C: ConnectionTrait>(
+    db: &C,
+    token: &str,
+    now: DateTime<Utc>,
+) -> Result<Option<data_export::Model>, DbErr> {
+    DataExport::find()
+        .filter(DataExportColumn::TokenHash.eq(mail_token::hash(token)))
+        .filter(DataExportColumn::ExpiresAt.gt(now))
+        .one(db)
+        .await
+}
+
+/// Deletes expired exports with their archives, gives up on lost pending
+/// ones and removes archives nothing refers to anymore, such as those of
+/// purged accounts. Returns how many exports were deleted.
+pub async fn delete_expired(
+    db: &DatabaseConnection,
+    data_dir: &Path,
+    now: DateTime<Utc>,
+) -> Result<u64, DbErr> {
+    DataExport::update_many()
+        .col_expr(
+            DataExportColumn::Status,
+            Expr::value(DataExportStatus::Failed),
+        )
+        .filter(DataExportColumn::Status.eq(DataExportStatus::Pending))
+        .filter(DataExportColumn::CreatedAt.lte(now - STALE_AFTER))
+        .exec(db)
+        .await?;
+    let expired: Vec<Uuid> = DataExport::find()
+        .select_only()
+        .column(DataExportColumn::Id)
+        .filter(DataExportColumn::ExpiresAt.lte(now))
+        .into_tuple()
+        .all(db)
+        .await?;
+    let deleted = DataExport::delete_many()
+        .filter(DataExportColumn::Id.is_in(expired.clone()))
+        .exec(db)
+        .await?
+        .rows_affected;
+
+    let live: HashSet<Uuid> = DataExport::find()
+        .select_only()
+        .column(DataExportColumn::Id)
+        .into_tuple()
+        .all(db)
+        .await?
+        .into_iter()
+        .collect();
+    let dir = exports_dir(data_dir);
+    let removed = tokio::task::spawn_blocking(move || remove_archives(&dir, &expired, &live))
+        .await
+        .map_err(io::Error::other)
+        .and_then(|removed| removed);
+    if let Err(err) = removed {
+        tracing::warn!(error = %err, "removing expired export archives failed");
+    }
+    Ok(deleted)
+}
+
+/// Deletes the archives of `expired` exports and every older file in `dir`
+/// that is not the archive of an export in `live`. Recent files are left
+/// alone, they may belong to an export started since `live` was read.
+fn remove_archives(dir: &Path, expired: &[Uuid], live: &HashSet<Uuid>) -> io::Result<()> {
+    for id in expired {
+        match std::fs::remove_file(dir.join(format!("{id}.zip"))) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err),
+            _ => {}
+        }
+    }
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err),
+    };
+    for entry in entries {
+        let entry = entry?;
+        let age = entry.metadata()?.modified()?.elapsed().unwrap_or_default();
+        if age < STALE_AFTER.to_std().unwrap_or_default() {
+            continue;
+        }
+        let path = entry.path();
+        let id = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .and_then(|stem| Uuid::parse_str(stem).ok());
+        if id.is_none_or(|id| !live.contains(&id)) {
+            std::fs::remove_file(&path)?;
+        }
+    }
+    Ok(())
+}
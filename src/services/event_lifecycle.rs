@@ -0,0 +1,141 @@
+//! The event state machine. Every status change goes through [`apply`], which
+//! checks it against the table below and appends it to the event history.
+//!
+//! ```text
+//! (new)      --created-------------------------> pending
+//! pending    --confirmed-----------------------> confirmed
+//! confirmed  --reopened------------------------> pending
+//! pending    --declined / canceled_by_owner / expired--> canceled
+//! confirmed  --declined / canceled_by_owner / expired--> canceled
+//! pending    --finished / elapsed--------------> completed
+//! confirmed  --finished / elapsed--------------> completed
+//! ```
+
+use sea_orm::{ActiveModelTrait, ConnectionTrait, Set};
+use uuid::Uuid;
+
+use crate::entities::event::EventStatus;
+use crate::entities::event_history::EventTransition;
+use crate::entities::{EventActiveModel, EventHistoryActiveModel, event};
+use crate::error::{AppError, ErrorCode};
+
+const OPEN: &[EventStatus] = &[EventStatus::Pending, EventStatus::Confirmed];
+
+impl EventTransition {
+    /// Status the event is in afterwards.
+    pub fn target(self) -> EventStatus {
+        match self {
+            EventTransition::Created | EventTransition::Reopened => EventStatus::Pending,
+            EventTransition::Confirmed => EventStatus::Confirmed,
+            EventTransition::Declined
+            | EventTransition::CanceledByOwner
+            | EventTransition::Expired => EventStatus::Canceled,
+            EventTransition::Finished | EventTransition::Elapsed => EventStatus::Completed,
+        }
+    }
+
+    /// Statuses the transition may start from. `Created` starts from none.
+    pub fn sources(self) -> &'static [EventStatus] {
+        match self {
+            EventTransition::Created => &[],
+            EventTransition::Confirmed => &[EventStatus::Pending],
+            EventTransition::Reopened => &[EventStatus::Confirmed],
+            EventTransition::Declined
+            | EventTransition::CanceledByOwner
+            | EventTransition::Expired
+            | EventTransition::Finished
+            | EventTransition::Elapsed => OPEN,
+        }
+    }
+
+    /// Checks that an event in `from` may take this transition.
+    pub fn check(self, from: Option<&EventStatus>) -> Result<(), AppError> {
+        let Some(from) = from else {
+            return match self {
+                EventTransition::Created => Ok(()),
+                _ => Err(ErrorCode::EventTransitionNotAllowed.into()),
+            };
+        };
+        if self.sources().contains(from) {
+            return Ok(());
+        }
+        Err(match (self, from) {
+            (EventTransition::CanceledByOwner, EventStatus::Completed) => ErrorCode::EventCompleted,
+            (_, EventStatus::Canceled | EventStatus::Completed) => ErrorCode::EventClosed,
+            _ => ErrorCode::EventTransitionNotAllowed,
+        }
+        .into())
+    }
+}
+
+/// Whether the event still accepts answers and changes.
+pub fn is_open(status: &EventStatus) -> bool {
+    OPEN.contains(status)
+}
+
+/// What happens after a participant accepted.
+pub fn after_accept(status: &EventStatus, everyone_accepted: bool) -> Option<EventTransition> {
+    (everyone_accepted && *status == EventStatus::Pending).then_some(EventTransition::Confirmed)
+}
+
+/// What happens after a participant declined, given how many participants
+/// the event has in total.
+pub fn after_decline(status: &EventStatus, participant_total: u64) -> Option<EventTransition> {
+    if participant_total <= 1 {
+        Some(EventTransition::Declined)
+    } else if *status == EventStatus::Confirmed {
+        Some(EventTransition::Reopened)
+    } else {
+        None
+    }
+}
+
+/// What happens to an open event once its date has passed.
+pub fn after_date(everyone_accepted: bool) -> EventTransition {
+    if everyone_accepted {
+        EventTransition::Elapsed
+    } else {
+        EventTransition::Expired
+    }
+}
+
+/// Moves the event along `transition`, saving any other changes made to
+/// `event`, and appends the change to its history. `actor` is `None` for
+/// changes made by the server.
+pub async fn apply<C: ConnectionTrait>(
+    db: &C,
+    mut event: EventActiveModel,
+    transition: EventTransition,
+    actor: Option<Uuid>,
+) -> Result<event::Model, AppError> {
+    let from = event.status.as_ref().clone();
+    transition.check(Some(&from))?;
+
+    event.status = Set(transition.target());
+    let model = event.update(db).await?;
+    record(db, model.id, Some(from), transition, actor).await?;
+    Ok(model)
+}
+
+/// Appends a history row without touching the event, for the initial status.
+pub async fn record<C: ConnectionTrait>(
+    db: &C,
+    event_id: Uuid,
+    from: Option<EventStatus>,
+    transition: EventTransition,
+    actor: Option<Uuid>,
+) -> Result<(), AppError> {
+    transition.check(from.as_ref())?;
+
+    EventHistoryActiveModel {
+        event_id: Set(event_id),
+        actor_id: Set(actor),
+        from_status: Set(from),
+        to_status: Set(transition.target()),
+        reason: Set(transition),
+        ..Default::default()
+    }
+    .insert(db)
+    .await?;
+    Ok(())
+}
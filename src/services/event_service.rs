@@ -0,0 +1,456 @@
+use chrono::NaiveDate;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, Condition, ConnectionTrait, EntityTrait, IntoActiveModel,
+    PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, QueryTrait, Set,
+};
+use uuid::Uuid;
+
+use crate::entities::event::EventStatus;
+use crate::entities::event_history::EventTransition;
+use crate::entities::user_event::{UserEventResponse, UserEventRole};
+use crate::entities::{
+    Event, EventActiveModel, EventColumn, EventHistory, EventHistoryColumn, UserEvent,
+    UserEventActiveModel, UserEventColumn, event, event_history,
+};
+use crate::error::{AppError, ErrorCode};
+use crate::services::event_lifecycle;
+use crate::services::{CalendarService, Calendars, FriendshipService, Friendships};
+
+/// Everything needed to create an event, already parsed.
+pub struct NewEvent {
+    pub date: NaiveDate,
+    pub title: String,
+    pub description: Option<String>,
+    pub location: Option<String>,
+    pub wish_place_id: Option<Uuid>,
+    pub participant_ids: Vec<Uuid>,
+}
+
+/// Event lifecycle rules. Status changes follow [`event_lifecycle`] and are
+/// recorded in the event history. Methods that write more than one row expect
+/// to be given a transaction.
+#[async_trait::async_trait]
+pub trait EventService: Send + Sync {
+    /// Creates a pending event owned by `creator` and takes the creator's day.
+    /// Only accepted friends who are free that day can be invited.
+    async fn create<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        creator: Uuid,
+        event: NewEvent,
+    ) -> Result<event::Model, AppError>;
+
+    /// Only the owner and the invited users can see an event.
+    async fn ensure_access<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        event_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<(), AppError>;
+
+    /// Accepts a pending invitation and takes the day. Returns the new event
+    /// status, `Confirmed` once every participant has accepted.
+    async fn accept<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        event_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<EventStatus, AppError>;
+
+    /// Declines an invitation and frees the day. Returns the new event status:
+    /// the event is canceled when its only participant declines, and a
+    /// confirmed event goes back to pending otherwise.
+    async fn decline<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        event_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<EventStatus, AppError>;
+
+    /// Cancels an open event and frees everyone's day. The event and its
+    /// invitations are kept so participants can still see what happened.
+    async fn cancel<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        event_id: Uuid,
+        owner: Uuid,
+    ) -> Result<(), AppError>;
+
+    /// Completes an open event on or after its date.
+    async fn finish<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        event_id: Uuid,
+        owner: Uuid,
+        memory_image_base64: String,
+        today: NaiveDate,
+    ) -> Result<event::Model, AppError>;
+
+    /// Closes the user's open events dated before `today`: completed when
+    /// everyone accepted, canceled otherwise. Returns how many were canceled.
+    async fn settle_past<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        user_id: Uuid,
+        today: NaiveDate,
+    ) -> Result<usize, AppError>;
+
+    /// Status changes of an event, oldest first. Visible to anyone who can see
+    /// the event.
+    async fn history<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        event_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Vec<event_history::Model>, AppError>;
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Events<F = Friendships, K = Calendars> {
+    pub friendships: F,
+    pub calendar: K,
+}
+
+impl Events {
+    pub const fn new() -> Self {
+        Events {
+            friendships: Friendships,
+            calendar: Calendars,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<F: FriendshipService, K: CalendarService> EventService for Events<F, K> {
+    async fn create<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        creator: Uuid,
+        event: NewEvent,
+    ) -> Result<event::Model, AppError> {
+        if event.title.trim().is_empty() {
+            return Err(AppError::field(ErrorCode::TitleRequired, "title"));
+        }
+
+        let mut participant_ids = event.participant_ids;
+        participant_ids.retain(|id| *id != creator);
+        participant_ids.sort();
+        participant_ids.dedup();
+
+        for participant_id in &participant_ids {
+            if !self
+                .friendships
+                .are_friends(db, creator, *participant_id)
+                .await?
+            {
+                return Err(AppError::field(
+                    ErrorCode::InviteFriendsOnly,
+                    "invited_friend_ids",
+                ));
+            }
+        }
+
+        let everyone = [&[creator][..], &participant_ids].concat();
+        self.calendar.lock_days(db, &everyone, event.date).await?;
+        self.calendar.ensure_free(db, creator, event.date).await?;
+        for participant_id in &participant_ids {
+            self.calendar
+                .ensure_free(db, *participant_id, event.date)
+                .await
+                .map_err(|err| match err.code() {
+                    ErrorCode::DateReserved => ErrorCode::ParticipantsBusy.into(),
+                    _ => err,
+                })?;
+        }
+
+        let model = EventActiveModel {
+            creator_id: Set(creator),
+            date: Set(event.date),
+            title: Set(event.title),
+            description: Set(event.description),
+            location: Set(event.location),
+            status: Set(EventStatus::Pending),
+            wish_place_id: Set(event.wish_place_id),
+            memory_image_base64: Set(None),
+            ..Default::default()
+        }
+        .insert(db)
+        .await?;
+        event_lifecycle::record(db, model.id, None, EventTransition::Created, Some(creator))
+            .await?;
+
+        UserEventActiveModel {
+            event_id: Set(model.id),
+            user_id: Set(creator),
+            role: Set(UserEventRole::Owner),
+            response_status: Set(UserEventResponse::Accepted),
+            ..Default::default()
+        }
+        .insert(db)
+        .await?;
+
+        self.calendar
+            .reserve(db, creator, model.date, Some(model.id))
+            .await?;
+
+        if !participant_ids.is_empty() {
+            let invitations = participant_ids
+                .into_iter()
+                .map(|participant_id| UserEventActiveModel {
+                    event_id: Set(model.id),
+                    user_id: Set(participant_id),
+                    role: Set(UserEventRole::Participant),
+                    response_status: Set(UserEventResponse::Pending),
+                    ..Default::default()
+                })
+                .collect::<Vec<_>>();
+            UserEvent::insert_many(invitations).exec(db).await?;
+        }
+
+        Ok(model)
+    }
+
+    async fn ensure_access<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        event_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<(), AppError> {
+        if Event::find_by_id(event_id).one(db).await?.is_none() {
+            return Err(ErrorCode::EventNotFound.into());
+        }
+
+        let has_access = UserEvent::find()
+            .filter(UserEventColumn::EventId.eq(event_id))
+            .filter(UserEventColumn::UserId.eq(user_id))
+            .one(db)
+            .await?
+            .is_some();
+        if !has_access {
+            return Err(ErrorCode::EventAccessDenied.into());
+        }
+        Ok(())
+    }
+
+    async fn accept<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        event_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<EventStatus, AppError> {
+        let event = open_event(db, event_id).await?;
+
+        let invitation = UserEvent::find()
+            .filter(UserEventColumn::EventId.eq(event_id))
+            .filter(UserEventColumn::UserId.eq(user_id))
+            .filter(UserEventColumn::Role.eq(UserEventRole::Participant))
+            .filter(UserEventColumn::ResponseStatus.eq(UserEventResponse::Pending))
+            .one(db)
+            .await?
+            .ok_or(ErrorCode::InvitationNotFound)?;
+
+        self.calendar.lock_days(db, &[user_id], event.date).await?;
+        self.calendar.ensure_free(db, user_id, event.date).await?;
+
+        let mut active = invitation.into_active_model();
+        active.response_status = Set(UserEventResponse::Accepted);
+        active.update(db).await?;
+
+        self.calendar
+            .reserve(db, user_id, event.date, Some(event_id))
+            .await?;
+
+        let everyone_accepted = UserEvent::find()
+            .filter(UserEventColumn::EventId.eq(event_id))
+            .filter(UserEventColumn::Role.eq(UserEventRole::Participant))
+            .filter(UserEventColumn::ResponseStatus.ne(UserEventResponse::Accepted))
+            .one(db)
+            .await?
+            .is_none();
+        match event_lifecycle::after_accept(&event.status, everyone_accepted) {
+            Some(transition) => {
+                let event = event_lifecycle::apply(
+                    db,
+                    event.into_active_model(),
+                    transition,
+                    Some(user_id),
+                )
+                .await?;
+                Ok(event.status)
+            }
+            None => Ok(event.status),
+        }
+    }
+
+    async fn decline<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        event_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<EventStatus, AppError> {
+        let event = open_event(db, event_id).await?;
+
+        let participation = UserEvent::find()
+            .filter(UserEventColumn::EventId.eq(event_id))
+            .filter(UserEventColumn::UserId.eq(user_id))
+            .filter(UserEventColumn::Role.eq(UserEventRole::Participant))
+            .filter(
+                Condition::any()
+                    .add(UserEventColumn::ResponseStatus.eq(UserEventResponse::Pending))
+                    .add(UserEventColumn::ResponseStatus.eq(UserEventResponse::Accepted)),
+            )
+            .one(db)
+            .await?
+            .ok_or(ErrorCode::ParticipationNotFound)?;
+
+        let mut active = participation.into_active_model();
+        active.response_status = Set(UserEventResponse::Declined);
+        active.update(db).await?;
+
+        self.calendar.release(db, event_id, Some(user_id)).await?;
+
+        let participant_total = UserEvent::find()
+            .filter(UserEventColumn::EventId.eq(event_id))
+            .filter(UserEventColumn::Role.eq(UserEventRole::Participant))
+            .count(db)
+            .await?;
+
+        let Some(transition) = event_lifecycle::after_decline(&event.status, participant_total)
+        else {
+            return Ok(event.status);
+        };
+        if transition.target() == EventStatus::Canceled {
+            self.calendar.release(db, event_id, None).await?;
+        }
+        let event =
+            event_lifecycle::apply(db, event.into_active_model(), transition, Some(user_id))
+                .await?;
+        Ok(event.status)
+    }
+
+    async fn cancel<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        event_id: Uuid,
+        owner: Uuid,
+    ) -> Result<(), AppError> {
+        let event = owned_event(db, event_id, owner).await?;
+        EventTransition::CanceledByOwner.check(Some(&event.status))?;
+
+        self.calendar.release(db, event_id, None).await?;
+        event_lifecycle::apply(
+            db,
+            event.into_active_model(),
+            EventTransition::CanceledByOwner,
+            Some(owner),
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn finish<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        event_id: Uuid,
+        owner: Uuid,
+        memory_image_base64: String,
+        today: NaiveDate,
+    ) -> Result<event::Model, AppError> {
+        let event = owned_event(db, event_id, owner).await?;
+
+        EventTransition::Finished.check(Some(&event.status))?;
+        if today < event.date {
+            return Err(ErrorCode::EventNotStarted.into());
+        }
+        if memory_image_base64.trim().is_empty() {
+            return Err(AppError::field(
+                ErrorCode::MemoryImageRequired,
+                "memory_image_base64",
+            ));
+        }
+
+        let mut active = event.into_active_model();
+        active.memory_image_base64 = Set(Some(memory_image_base64));
+        event_lifecycle::apply(db, active, EventTransition::Finished, Some(owner)).await
+    }
+
+    async fn settle_past<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        user_id: Uuid,
+        today: NaiveDate,
+    ) -> Result<usize, AppError> {
+        let my_events = UserEvent::find()
+            .select_only()
+            .column(UserEventColumn::EventId)
+            .filter(UserEventColumn::UserId.eq(user_id))
+            .filter(UserEventColumn::ResponseStatus.ne(UserEventResponse::Declined))
+            .into_query();
+
+        let past_events = Event::find()
+            .filter(EventColumn::Id.in_subquery(my_events))
+            .filter(EventColumn::Date.lt(today))
+            .filter(EventColumn::Status.is_in([EventStatus::Pending, EventStatus::Confirmed]))
+            .lock_exclusive()
+            .all(db)
+            .await?;
+
+        let mut canceled = 0;
+        for event in past_events {
+            let everyone_accepted = UserEvent::find()
+                .filter(UserEventColumn::EventId.eq(event.id))
+                .filter(UserEventColumn::ResponseStatus.ne(UserEventResponse::Accepted))
+                .one(db)
+                .await?
+                .is_none();
+            let transition = event_lifecycle::after_date(everyone_accepted);
+            if transition.target() == EventStatus::Canceled {
+                canceled += 1;
+            }
+            event_lifecycle::apply(db, event.into_active_model(), transition, None).await?;
+        }
+        Ok(canceled)
+    }
+
+    async fn history<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        event_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Vec<event_history::Model>, AppError> {
+        self.ensure_access(db, event_id, user_id).await?;
+        Ok(EventHistory::find()
+            .filter(EventHistoryColumn::EventId.eq(event_id))
+            .order_by_asc(EventHistoryColumn::CreatedAt)
+            .order_by_asc(EventHistoryColumn::Id)
+            .all(db)
+            .await?)
+    }
+}
+
+/// Event that still accepts answers, locked for the rest of the transaction
+/// so concurrent answers see each other.
+async fn open_event<C: ConnectionTrait>(db: &C, event_id: Uuid) -> Result<event::Model, AppError> {
+    let event = Event::find_by_id(event_id)
+        .lock_exclusive()
+        .one(db)
+        .await?
+        .ok_or(ErrorCode::EventNotFound)?;
+    if !event_lifecycle::is_open(&event.status) {
+        return Err(ErrorCode::EventClosed.into());
+    }
+    Ok(event)
+}
+
+async fn owned_event<C: ConnectionTrait>(
+    db: &C,
+    event_id: Uuid,
+    owner: Uuid,
+) -> Result<event::Model, AppError> {
+    Event::find_by_id(event_id)
+        .filter(EventColumn::CreatorId.eq(owner))
+        .lock_exclusive()
+        .one(db)
+        .await?
+        .ok_or_else(|| ErrorCode::EventNotOwned.into())
+}
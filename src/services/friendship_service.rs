@@ -0,0 +1,215 @@
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, Condition, ConnectionTrait, EntityTrait, IntoActiveModel,
+    QueryFilter, Set,
+};
+use uuid::Uuid;
+
+use crate::entities::friendship::FriendshipStatus;
+use crate::entities::{Friendship, FriendshipActiveModel, FriendshipColumn};
+use crate::error::{AppError, ErrorCode};
+
+/// Friend requests and the friendship checks other rules rely on.
+#[async_trait::async_trait]
+pub trait FriendshipService: Send + Sync {
+    async fn are_friends<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        user_a: Uuid,
+        user_b: Uuid,
+    ) -> Result<bool, AppError>;
+
+    /// Ids of everyone `user_id` has an accepted friendship with.
+    async fn friend_ids<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        user_id: Uuid,
+    ) -> Result<Vec<Uuid>, AppError>;
+
+    /// Users see their own data and the data of accepted friends only.
+    async fn ensure_can_view<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        viewer: Uuid,
+        owner: Uuid,
+    ) -> Result<(), AppError> {
+        if viewer != owner && !self.are_friends(db, viewer, owner).await? {
+            return Err(ErrorCode::FriendsOnly.into());
+        }
+        Ok(())
+    }
+
+    async fn send_request<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        from: Uuid,
+        to: Uuid,
+    ) -> Result<(), AppError>;
+
+    async fn accept_request<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        me: Uuid,
+        sender: Uuid,
+    ) -> Result<(), AppError>;
+
+    async fn reject_request<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        me: Uuid,
+        sender: Uuid,
+    ) -> Result<(), AppError>;
+
+    async fn remove<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        me: Uuid,
+        friend: Uuid,
+    ) -> Result<(), AppError>;
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Friendships;
+
+#[async_trait::async_trait]
+impl FriendshipService for Friendships {
+    async fn are_friends<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        user_a: Uuid,
+        user_b: Uuid,
+    ) -> Result<bool, AppError> {
+        let row = Friendship::find()
+            .filter(FriendshipColumn::Status.eq(FriendshipStatus::Accepted))
+            .filter(between(user_a, user_b))
+            .one(db)
+            .await?;
+        Ok(row.is_some())
+    }
+
+    async fn friend_ids<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        user_id: Uuid,
+    ) -> Result<Vec<Uuid>, AppError> {
+        let rows = Friendship::find()
+            .filter(FriendshipColumn::Status.eq(FriendshipStatus::Accepted))
+            .filter(
+                Condition::any()
+                    .add(FriendshipColumn::UserId.eq(user_id))
+                    .add(FriendshipColumn::FriendId.eq(user_id)),
+            )
+            .all(db)
+            .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|f| {
+                if f.user_id == user_id {
+                    f.friend_id
+                } else {
+                    f.user_id
+                }
+            })
+            .collect())
+    }
+
+    async fn send_request<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        from: Uuid,
+        to: Uuid,
+    ) -> Result<(), AppError> {
+        if from == to {
+            return Err(AppError::field(ErrorCode::CannotBefriendSelf, "friend_id"));
+        }
+
+        FriendshipActiveModel {
+            user_id: Set(from),
+            friend_id: Set(to),
+            status: Set(FriendshipStatus::Pending),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+        .map_err(|err| AppError::on_unique_violation(err, ErrorCode::FriendRequestExists))?;
+        Ok(())
+    }
+
+    async fn accept_request<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        me: Uuid,
+        sender: Uuid,
+    ) -> Result<(), AppError> {
+        let row = pending_request(db, me, sender).await?;
+        let mut active = row.into_active_model();
+        active.status = Set(FriendshipStatus::Accepted);
+        active.update(db).await?;
+        Ok(())
+    }
+
+    async fn reject_request<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        me: Uuid,
+        sender: Uuid,
+    ) -> Result<(), AppError> {
+        let row = pending_request(db, me, sender).await?;
+        row.into_active_model().delete(db).await?;
+        Ok(())
+    }
+
+    async fn remove<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        me: Uuid,
+        friend: Uuid,
+    ) -> Result<(), AppError> {
+        if me == friend {
+            return Err(ErrorCode::CannotRemoveSelf.into());
+        }
+        let result = Friendship::delete_many()
+            .filter(FriendshipColumn::Status.eq(FriendshipStatus::Accepted))
+            .filter(between(me, friend))
+            .exec(db)
+            .await?;
+        if result.rows_affected == 0 {
+            return Err(ErrorCode::FriendshipNotFound.into());
+        }
+        Ok(())
+    }
+}
+
+/// Pending request sent by `sender` to `me`.
+async fn pending_request<C: ConnectionTrait>(
+    db: &C,
+    me: Uuid,
+    sender: Uuid,
+) -> Result<crate::entities::friendship::Model, AppError> {
+    if sender == me {
+        return Err(ErrorCode::OwnFriendRequest.into());
+    }
+
+    Friendship::find()
+        .filter(FriendshipColumn::UserId.eq(sender))
+        .filter(FriendshipColumn::FriendId.eq(me))
+        .filter(FriendshipColumn::Status.eq(FriendshipStatus::Pending))
+        .one(db)
+        .await?
+        .ok_or_else(|| ErrorCode::FriendRequestNotFound.into())
+}
+
+/// Friendship rows between two users, in either direction.
+fn between(user_a: Uuid, user_b: Uuid) -> Condition {
+    Condition::any()
+        .add(
+            Condition::all()
+                .add(FriendshipColumn::UserId.eq(user_a))
+                .add(FriendshipColumn::FriendId.eq(user_b)),
+        )
+        .add(
+            Condition::all()
+                .add(FriendshipColumn::UserId.eq(user_b))
+                .add(FriendshipColumn::FriendId.eq(user_a)),
+        )
+}
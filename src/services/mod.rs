@@ -0,0 +1,10 @@
+pub mod account_deletion;
+pub mod data_exports;
+pub mod calendar_service;
+pub mod event_lifecycle;
+pub mod event_service;
+pub mod friendship_service;
+
+pub use calendar_service::*;
+pub use event_service::*;
+pub use friendship_service::*;
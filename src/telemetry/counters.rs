@@ -0,0 +1,45 @@
+//! Business counters. Call them after the change is committed.
+
+use crate::entities::security_event::SecurityEventKind;
+use crate::rate_limit::LimitedRoute;
+
+pub fn event_created() {
+    metrics::counter!("events_created_total").increment(1);
+}
+
+pub fn event_confirmed() {
+    metrics::counter!("events_confirmed_total").increment(1);
+}
+
+/// `reason` is one of `owner`, `declined` or `expired`.
+pub fn event_canceled(reason: &'static str) {
+    metrics::counter!("events_canceled_total", "reason" => reason).increment(1);
+}
+
+pub fn friend_request_sent() {
+    metrics::counter!("friend_requests_sent_total").increment(1);
+}
+
+pub fn friend_request_accepted() {
+    metrics::counter!("friend_requests_accepted_total").increment(1);
+}
+
+pub fn login_succeeded() {
+    metrics::counter!("logins_total", "outcome" => "success").increment(1);
+}
+
+pub fn login_failed() {
+    metrics::counter!("logins_total", "outcome" => "failure").increment(1);
+}
+
+pub fn refresh_token_rotated() {
+    metrics::counter!("refresh_token_rotations_total").increment(1);
+}
+
+pub fn request_throttled(route: LimitedRoute) {
+    metrics::counter!("requests_throttled_total", "route" => route.to_string()).increment(1);
+}
+
+pub fn security_event(kind: SecurityEventKind) {
+    metrics::counter!("security_events_total", "kind" => kind.to_string()).increment(1);
+}
@@ -0,0 +1,101 @@
+pub mod counters;
+
+use std::sync::Arc;
+use std::time::Instant;
+
+use axum::{
+    Router,
+    extract::{MatchedPath, Request, State},
+    middleware::Next,
+    response::Response,
+    routing::get,
+};
+use metrics_exporter_prometheus::{Matcher, PrometheusBuilder, PrometheusHandle};
+use sea_orm::{DatabaseConnection, metric::Info};
+
+use crate::state::AppState;
+
+const LATENCY_BUCKETS: &[f64] = &[
+    0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+pub fn router() -> Router<AppState> {
+    Router::new().route("/metrics", get(metrics_handler))
+}
+
+/// Installs the global Prometheus recorder. The handle renders `/metrics`.
+pub fn install() -> PrometheusHandle {
+    PrometheusBuilder::new()
+        .set_buckets_for_metric(
+            Matcher::Suffix("_duration_seconds".to_string()),
+            LATENCY_BUCKETS,
+        )
+        .expect("latency buckets are not empty")
+        .install_recorder()
+        .expect("metrics recorder is installed once")
+}
+
+/// Records latency of every SQL statement the connection executes.
+pub fn track_db(db: &mut DatabaseConnection) {
+    db.set_metric_callback(|info: &Info<'_>| {
+        let operation = info
+            .statement
+            .sql
+            .split_whitespace()
+            .next()
+            .map(str::to_ascii_uppercase)
+            .unwrap_or_default();
+        metrics::histogram!(
+            "db_query_duration_seconds",
+            "operation" => operation,
+            "failed" => info.failed.to_string(),
+        )
+        .record(info.elapsed.as_secs_f64());
+    });
+}
+
+/// Records request count and latency per route and status.
+pub async fn track_http(request: Request, next: Next) -> Response {
+    // Unmatched paths are not used as labels to keep series count bounded.
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_owned())
+        .unwrap_or_else(|| "unmatched".to_owned());
+    let method = request.method().to_string();
+
+    let started = Instant::now();
+    let response = next.run(request).await;
+
+    metrics::histogram!(
+        "http_request_duration_seconds",
+        "method" => method,
+        "route" => route,
+        "status" => response.status().as_u16().to_string(),
+    )
+    .record(started.elapsed().as_secs_f64());
+
+    response
+}
+
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    responses(
+        (status = 200, description = "Metrics in Prometheus text format", content_type = "text/plain")
+    ),
+    tag = "Monitoring"
+)]
+pub async fn metrics_handler(
+    State(handle): State<PrometheusHandle>,
+    State(db): State<Arc<DatabaseConnection>>,
+) -> String {
+    let pool = db.get_postgres_connection_pool();
+    let size = pool.size() as f64;
+    let idle = pool.num_idle() as f64;
+    metrics::gauge!("db_pool_connections", "state" => "idle").set(idle);
+    metrics::gauge!("db_pool_connections", "state" => "in_use").set(size - idle);
+    metrics::gauge!("db_pool_max_connections").set(pool.options().get_max_connections() as f64);
+
+    handle.render()
+}
@@ -0,0 +1,6 @@
+pub mod public_key;
+pub mod relying_party;
+pub mod webauthn_error;
+
+pub use relying_party::{NewCredential, RelyingParty, generate_challenge};
+pub use webauthn_error::WebauthnError;
@@ -0,0 +1,134 @@
+//! Public keys in COSE form (RFC 9053) as authenticators hand them out, and
+//! the signatures WebAuthn makes with them.
+
+use ciborium::Value;
+use coset::iana::{self, EnumI64};
+use coset::{CborSerializable, CoseKey, KeyType, Label, RegisteredLabelWithPrivate};
+use ed25519_dalek::Verifier as _;
+use rsa::{BigUint, Pkcs1v15Sign, RsaPublicKey};
+use sha2::{Digest, Sha256};
+
+use crate::webauthn::WebauthnError;
+
+/// COSE algorithms the server accepts, in order of preference.
+pub const ALGORITHMS: [iana::Algorithm; 3] = [
+    iana::Algorithm::ES256,
+    iana::Algorithm::EdDSA,
+    iana::Algorithm::RS256,
+];
+/// Smaller RSA keys are not worth trusting.
+const MIN_RSA_BITS: usize = 2048;
+
+pub enum PublicKey {
+    Es256(p256::ecdsa::VerifyingKey),
+    EdDsa(ed25519_dalek::VerifyingKey),
+    Rs256(RsaPublicKey),
+}
+
+impl PublicKey {
+    /// Reads a COSE key as stored with a passkey.
+    pub fn from_cose(bytes: &[u8]) -> Result<Self, WebauthnError> {
+        let key = CoseKey::from_slice(bytes).map_err(|err| invalid(&err.to_string()))?;
+        Self::from_key(&key)
+    }
+
+    pub fn from_key(key: &CoseKey) -> Result<Self, WebauthnError> {
+        let param = |label: i64| {
+            key.params
+                .iter()
+                .find(|(name, _)| *name == Label::Int(label))
+                .map(|(_, value)| value)
+        };
+        let curve = |label: i64| {
+            param(label)
+                .and_then(Value::as_integer)
+                .and_then(|curve| i64::try_from(curve).ok())
+        };
+        let bytes = |label: i64| {
+            param(label)
+                .and_then(Value::as_bytes)
+                .map(Vec::as_slice)
+                .ok_or_else(|| invalid("key parameter missing"))
+        };
+        let algorithm = match &key.alg {
+            Some(RegisteredLabelWithPrivate::Assigned(algorithm)) => Some(*algorithm),
+            _ => None,
+        };
+        match (&key.kty, algorithm) {
+            (KeyType::Assigned(iana::KeyType::EC2), Some(iana::Algorithm::ES256))
+                if curve(iana::Ec2KeyParameter::Crv.to_i64())
+                    == Some(iana::EllipticCurve::P_256.to_i64()) =>
+            {
+                let x = bytes(iana::Ec2KeyParameter::X.to_i64())?;
+                let y = bytes(iana::Ec2KeyParameter::Y.to_i64())?;
+                if x.len() != 32 || y.len() != 32 {
+                    return Err(invalid("p-256 coordinates must be 32 bytes"));
+                }
+                let mut point = Vec::with_capacity(65);
+                point.push(0x04);
+                point.extend_from_slice(x);
+                point.extend_from_slice(y);
+                p256::ecdsa::VerifyingKey::from_sec1_bytes(&point)
+                    .map(PublicKey::Es256)
+                    .map_err(|_| invalid("point is not on p-256"))
+            }
+            (KeyType::Assigned(iana::KeyType::OKP), Some(iana::Algorithm::EdDSA))
+                if curve(iana::OkpKeyParameter::Crv.to_i64())
+                    == Some(iana::EllipticCurve::Ed25519.to_i64()) =>
+            {
+                let x: [u8; 32] = bytes(iana::OkpKeyParameter::X.to_i64())?
+                    .try_into()
+                    .map_err(|_| invalid("ed25519 keys are 32 bytes"))?;
+                ed25519_dalek::VerifyingKey::from_bytes(&x)
+                    .map(PublicKey::EdDsa)
+                    .map_err(|_| invalid("bad ed25519 key"))
+            }
+            (KeyType::Assigned(iana::KeyType::RSA), Some(iana::Algorithm::RS256)) => {
+                let n = BigUint::from_bytes_be(bytes(iana::RsaKeyParameter::N.to_i64())?);
+                let e = BigUint::from_bytes_be(bytes(iana::RsaKeyParameter::E.to_i64())?);
+                let key = RsaPublicKey::new(n, e).map_err(|_| invalid("bad rsa key"))?;
+                if rsa::traits::PublicKeyParts::n(&key).bits() < MIN_RSA_BITS {
+                    return Err(invalid("rsa key too short"));
+                }
+                Ok(PublicKey::Rs256(key))
+            }
+            _ => Err(invalid("unsupported key type or algorithm")),
+        }
+    }
+
+    /// The COSE algorithm identifier of the key.
+    pub fn algorithm(&self) -> i32 {
+        let algorithm = match self {
+            PublicKey::Es256(_) => iana::Algorithm::ES256,
+            PublicKey::EdDsa(_) => iana::Algorithm::EdDSA,
+            PublicKey::Rs256(_) => iana::Algorithm::RS256,
+        };
+        algorithm.to_i64() as i32
+    }
+
+    /// Checks `signature` over `message`. ES256 signatures come DER encoded.
+    pub fn verify(&self, message: &[u8], signature: &[u8]) -> Result<(), WebauthnError> {
+        let verified = match self {
+            PublicKey::Es256(key) => p256::ecdsa::Signature::from_der(signature)
+                .is_ok_and(|signature| key.verify(message, &signature).is_ok()),
+            PublicKey::EdDsa(key) => ed25519_dalek::Signature::from_slice(signature)
+                .is_ok_and(|signature| key.verify(message, &signature).is_ok()),
+            PublicKey::Rs256(key) => key
+                .verify(
+                    Pkcs1v15Sign::new::<Sha256>(),
+                    &Sha256::digest(message),
+                    signature,
+                )
+                .is_ok(),
+        };
+        if verified {
+            Ok(())
+        } else {
+            Err(invalid("signature does not verify"))
+        }
+    }
+}
+
+fn invalid(reason: &str) -> WebauthnError {
+    WebauthnError(format!("cose key: {reason}"))
+}
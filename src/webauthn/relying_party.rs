@@ -0,0 +1,217 @@
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use ciborium::Value;
+use coset::{AsCborValue, CoseKey};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+use crate::config::WebauthnConfig;
+use crate::webauthn::WebauthnError;
+use crate::webauthn::public_key::PublicKey;
+
+const USER_PRESENT: u8 = 0x01;
+const USER_VERIFIED: u8 = 0x04;
+const ATTESTED_CREDENTIAL: u8 = 0x40;
+const EXTENSIONS: u8 = 0x80;
+/// Longest credential id the WebAuthn spec allows.
+const MAX_CREDENTIAL_ID: usize = 1023;
+
+/// A credential a registration ceremony produced.
+#[derive(Debug)]
+pub struct NewCredential {
+    pub id: Vec<u8>,
+    /// The COSE key exactly as the authenticator sent it.
+    pub public_key: Vec<u8>,
+    pub algorithm: i32,
+    pub sign_count: u32,
+}
+
+#[derive(Deserialize)]
+struct ClientData {
+    #[serde(rename = "type")]
+    kind: String,
+    challenge: String,
+    origin: String,
+    #[serde(default, rename = "crossOrigin")]
+    cross_origin: bool,
+}
+
+struct AuthenticatorData<'a> {
+    rp_id_hash: &'a [u8],
+    flags: u8,
+    sign_count: u32,
+    credential: Option<(Vec<u8>, Vec<u8>, CoseKey)>,
+}
+
+/// Checks registration and authentication ceremonies for the relying party
+/// in `webauthn`. Attestation statements are not checked: the server asks
+/// for none and trusts any authenticator the user picks.
+pub struct RelyingParty<'a> {
+    rp_id_hash: [u8; 32],
+    origins: &'a [String],
+}
+
+impl<'a> RelyingParty<'a> {
+    pub fn new(config: &'a WebauthnConfig) -> Self {
+        RelyingParty {
+            rp_id_hash: Sha256::digest(config.rp_id.as_bytes()).into(),
+            origins: &config.origins,
+        }
+    }
+
+    /// Verifies the answer to `navigator.credentials.create()` for the
+    /// challenge the server handed out.
+    pub fn verify_registration(
+        &self,
+        challenge: &str,
+        client_data_json: &[u8],
+        attestation_object: &[u8],
+    ) -> Result<NewCredential, WebauthnError> {
+        self.check_client_data(client_data_json, "webauthn.create", challenge)?;
+
+        let (attestation, _) = decode(attestation_object)?;
+        let auth_data = attestation
+            .as_map()
+            .and_then(|entries| {
+                entries
+                    .iter()
+                    .find(|(key, _)| key.as_text() == Some("authData"))
+            })
+            .and_then(|(_, value)| value.as_bytes())
+            .ok_or_else(|| invalid("attestation object has no authData"))?;
+        let data = self.check_authenticator_data(auth_data)?;
+        let Some((id, public_key, key)) = data.credential else {
+            return Err(invalid("registration carries no credential"));
+        };
+        let algorithm = PublicKey::from_key(&key)?.algorithm();
+        Ok(NewCredential {
+            id,
+            public_key,
+            algorithm,
+            sign_count: data.sign_count,
+        })
+    }
+
+    /// Verifies the answer to `navigator.credentials.get()` against a stored
+    /// passkey and returns its new signature counter.
+    pub fn verify_assertion(
+        &self,
+        challenge: &str,
+        public_key: &[u8],
+        stored_count: u32,
+        client_data_json: &[u8],
+        authenticator_data: &[u8],
+        signature: &[u8],
+    ) -> Result<u32, WebauthnError> {
+        self.check_client_data(client_data_json, "webauthn.get", challenge)?;
+        let data = self.check_authenticator_data(authenticator_data)?;
+
+        let mut message = authenticator_data.to_vec();
+        message.extend_from_slice(&Sha256::digest(client_data_json));
+        PublicKey::from_cose(public_key)?.verify(&message, signature)?;
+
+        // Authenticators without a counter always send zero. One that goes
+        // backwards has been cloned.
+        if (data.sign_count != 0 || stored_count != 0) && data.sign_count <= stored_count {
+            return Err(invalid("signature counter did not increase"));
+        }
+        Ok(data.sign_count)
+    }
+
+    fn check_client_data(
+        &self,
+        client_data_json: &[u8],
+        kind: &str,
+        challenge: &str,
+    ) -> Result<(), WebauthnError> {
+        let client_data: ClientData = serde_json::from_slice(client_data_json)
+            .map_err(|err| invalid(&format!("client data: {err}")))?;
+        if client_data.kind != kind {
+            return Err(invalid("wrong ceremony type"));
+        }
+        if client_data.challenge != challenge {
+            return Err(invalid("challenge does not match"));
+        }
+        if client_data.cross_origin || !self.origins.contains(&client_data.origin) {
+            return Err(invalid("origin not allowed"));
+        }
+        Ok(())
+    }
+
+    fn check_authenticator_data<'d>(
+        &self,
+        bytes: &'d [u8],
+    ) -> Result<AuthenticatorData<'d>, WebauthnError> {
+        let data = parse_authenticator_data(bytes)?;
+        if data.rp_id_hash != self.rp_id_hash {
+            return Err(invalid("credential is for another relying party"));
+        }
+        // A passkey replaces the password, so the authenticator has to have
+        // checked the user with a PIN or biometrics.
+        if data.flags & USER_PRESENT == 0 || data.flags & USER_VERIFIED == 0 {
+            return Err(invalid("user was not verified"));
+        }
+        Ok(data)
+    }
+}
+
+/// A fresh random challenge in the base64url form client data echoes.
+pub fn generate_challenge() -> String {
+    let bytes: [u8; 32] = rand::random();
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+fn parse_authenticator_data(bytes: &[u8]) -> Result<AuthenticatorData<'_>, WebauthnError> {
+    if bytes.len() < 37 {
+        return Err(invalid("authenticator data too short"));
+    }
+    let flags = bytes[32];
+    let sign_count = u32::from_be_bytes(bytes[33..37].try_into().unwrap());
+    let mut rest = &bytes[37..];
+
+    let credential = if flags & ATTESTED_CREDENTIAL != 0 {
+        // AAGUID (16 bytes), then the id length and the id.
+        if rest.len() < 18 {
+            return Err(invalid("attested credential data too short"));
+        }
+        let id_len = u16::from_be_bytes([rest[16], rest[17]]) as usize;
+        if id_len == 0 || id_len > MAX_CREDENTIAL_ID || rest.len() < 18 + id_len {
+            return Err(invalid("bad credential id"));
+        }
+        let id = rest[18..18 + id_len].to_vec();
+        rest = &rest[18 + id_len..];
+        let (key, used) = decode(rest)?;
+        let key = CoseKey::from_cbor_value(key).map_err(|err| invalid(&err.to_string()))?;
+        let public_key = rest[..used].to_vec();
+        rest = &rest[used..];
+        Some((id, public_key, key))
+    } else {
+        None
+    };
+    if flags & EXTENSIONS != 0 {
+        let (_, used) = decode(rest)?;
+        rest = &rest[used..];
+    }
+    if !rest.is_empty() {
+        return Err(invalid("trailing bytes after authenticator data"));
+    }
+
+    Ok(AuthenticatorData {
+        rp_id_hash: &bytes[..32],
+        flags,
+        sign_count,
+        credential,
+    })
+}
+
+/// Decodes the CBOR item `bytes` start with and tells how many bytes it took.
+fn decode(bytes: &[u8]) -> Result<(Value, usize), WebauthnError> {
+    let mut reader = bytes;
+    let value = ciborium::from_reader(&mut reader)
+        .map_err(|err| invalid(&format!("malformed cbor: {err}")))?;
+    Ok((value, bytes.len() - reader.len()))
+}
+
+fn invalid(reason: &str) -> WebauthnError {
+    WebauthnError(reason.to_string())
+}
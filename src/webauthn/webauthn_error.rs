@@ -0,0 +1,14 @@
+use std::fmt;
+
+/// A credential or assertion that does not verify, with the reason for the
+/// log. Clients only learn that it failed.
+#[derive(Debug)]
+pub struct WebauthnError(pub String);
+
+impl fmt::Display for WebauthnError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for WebauthnError {}
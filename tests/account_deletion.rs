@@ -0,0 +1,208 @@
+mod common;
+
+use axum::http::StatusCode;
+use chrono::{DateTime, TimeDelta, Utc};
+use common::{PASSWORD, Session, TestApp, date};
+use friends_server::entities::{WishPlace, WishPlaceColumn};
+use friends_server::error::ErrorCode;
+use friends_server::jobs::account_purge;
+use sea_orm::{ColumnTrait, EntityTrait, PaginatorTrait, QueryFilter};
+use serde_json::json;
+
+const GRACE_SECS: i64 = 60 * 60;
+
+async fn spawn() -> TestApp {
+    TestApp::spawn_with(|config| {
+        config.account.deletion_grace_secs = GRACE_SECS;
+        config.jwt.session_check_secs = 0;
+    })
+    .await
+}
+
+async fn schedule(app: &TestApp, session: &Session) -> DateTime<Utc> {
+    let response = app
+        .post("/users/me/deletion")
+        .auth(session)
+        .json(json!({ "password": PASSWORD }))
+        .send()
+        .await
+        .assert_status(StatusCode::ACCEPTED);
+    response
+        .str("/deletion_scheduled_at")
+        .parse()
+        .expect("deletion time")
+}
+
+/// Runs the purge job as if the grace period had just ended.
+async fn purge(app: &TestApp) -> usize {
+    let after_grace = Utc::now() + TimeDelta::seconds(GRACE_SECS + 1);
+    account_purge::run(&app.db, app.outbox.as_ref(), after_grace)
+        .await
+        .expect("purge")
+}
+
+#[tokio::test]
+async fn deletion_needs_the_password_and_can_be_undone() {
+    let app = spawn().await;
+    let alice = app.register("alice").await;
+
+    app.post("/users/me/deletion")
+        .auth(&alice)
+        .json(json!({ "password": "wrong" }))
+        .send()
+        .await
+        .assert_error(ErrorCode::CurrentPasswordIncorrect);
+    app.delete("/users/me/deletion")
+        .auth(&alice)
+        .send()
+        .await
+        .assert_error(ErrorCode::AccountDeletionNotScheduled);
+
+    let at = schedule(&app, &alice).await;
+    assert!(at > Utc::now() + TimeDelta::seconds(GRACE_SECS - 60));
+    let me = app
+        .get("/users/me")
+        .auth(&alice)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    assert!(!me.str("/deletion_scheduled_at").is_empty());
+    app.post("/users/me/deletion")
+        .auth(&alice)
+        .json(json!({ "password": PASSWORD }))
+        .send()
+        .await
+        .assert_error(ErrorCode::AccountDeletionPending);
+
+    // Nothing happens before the grace period is over.
+    let purged = account_purge::run(&app.db, app.outbox.as_ref(), Utc::now())
+        .await
+        .expect("purge");
+    assert_eq!(purged, 0);
+
+    app.delete("/users/me/deletion")
+        .auth(&alice)
+        .send()
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    let me = app
+        .get("/users/me")
+        .auth(&alice)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    assert!(me.body.get("deletion_scheduled_at").is_none());
+    assert_eq!(purge(&app).await, 0);
+    app.login("alice").await;
+}
+
+#[tokio::test]
+async fn purge_anonymises_the_account_and_revokes_its_tokens() {
+    let app = spawn().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+    app.befriend(&alice, &bob).await;
+    app.post("/wish-places")
+        .auth(&alice)
+        .json(json!({ "title": "Cafe by the river" }))
+        .send()
+        .await
+        .assert_status(StatusCode::CREATED);
+    schedule(&app, &alice).await;
+
+    assert_eq!(purge(&app).await, 1);
+
+    app.get("/users/me")
+        .auth(&alice)
+        .send()
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+    app.post("/auth/refresh")
+        .json(json!({ "refresh_token": alice.refresh }))
+        .send()
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+    app.post("/auth/login")
+        .json(json!({ "username": "alice", "password": PASSWORD }))
+        .send()
+        .await
+        .assert_error(ErrorCode::InvalidCredentials);
+
+    let friends = app
+        .get("/friends")
+        .auth(&bob)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    assert!(
+        !friends
+            .body
+            .to_string()
+            .contains(&alice.user_id.to_string())
+    );
+    let found = app
+        .get("/users/search?username=deleted")
+        .auth(&bob)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    assert!(!found.body.to_string().contains(&alice.user_id.to_string()));
+
+    let wish_places = WishPlace::find()
+        .filter(WishPlaceColumn::UserId.eq(alice.user_id))
+        .count(&*app.db)
+        .await
+        .expect("count wish places");
+    assert_eq!(wish_places, 0);
+
+    // The name is free again.
+    app.register("alice").await;
+}
+
+#[tokio::test]
+async fn purge_cancels_open_events_and_hands_over_past_ones() {
+    let app = spawn().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+    app.befriend(&alice, &bob).await;
+    app.verify_email(&bob, "bob@example.com").await;
+
+    let upcoming = app.create_event(&alice, &date(3), &[&bob]).await;
+    let past = app.create_event(&alice, &date(0), &[&bob]).await;
+    app.post(&format!("/events/{past}/accept"))
+        .auth(&bob)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    app.post(&format!("/events/{past}/finish"))
+        .auth(&alice)
+        .json(json!({ "memory_image_base64": "aGVsbG8=" }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    schedule(&app, &alice).await;
+
+    let seen = app.outbox.count();
+    assert_eq!(purge(&app).await, 1);
+    let mail = app.outbox.wait_for(seen).await;
+    assert_eq!(mail.to, "bob@example.com");
+    assert!(mail.body.contains("Dinner"));
+
+    let canceled = app
+        .get(&format!("/events/{upcoming}"))
+        .auth(&bob)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    assert_eq!(canceled.body["status"], "canceled");
+
+    let kept = app
+        .get(&format!("/events/{past}"))
+        .auth(&bob)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    assert_eq!(kept.body["status"], "completed");
+    assert_eq!(kept.body["creator_id"], bob.user_id.to_string());
+    assert_eq!(kept.body["memory_image_base64"], "aGVsbG8=");
+}
@@ -0,0 +1,273 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::{PASSWORD, TestApp};
+use friends_server::auth::refresh_tokens::REUSE_GRACE;
+use friends_server::entities::security_event::SecurityEventKind;
+use friends_server::entities::{SecurityEvent, SecurityEventColumn};
+use friends_server::error::ErrorCode;
+use sea_orm::{ColumnTrait, EntityTrait, PaginatorTrait, QueryFilter};
+use serde_json::json;
+
+#[tokio::test]
+async fn register_and_login() {
+    let app = TestApp::spawn().await;
+
+    app.post("/auth/register")
+        .json(json!({ "username": "alice", "password": PASSWORD, "bio": "hi" }))
+        .send()
+        .await
+        .assert_status(StatusCode::CREATED);
+
+    let response = app
+        .post("/auth/login")
+        .json(json!({ "username": "alice", "password": PASSWORD }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    assert_eq!(response.body["user"]["username"], "alice");
+    assert_eq!(response.body["user"]["bio"], "hi");
+    assert!(!response.str("/access_token").is_empty());
+    assert!(!response.str("/refresh_token").is_empty());
+}
+
+#[tokio::test]
+async fn register_rejects_blank_and_duplicate_usernames() {
+    let app = TestApp::spawn().await;
+
+    app.post("/auth/register")
+        .json(json!({ "username": " ", "password": PASSWORD }))
+        .send()
+        .await
+        .assert_error(ErrorCode::MissingCredentials);
+
+    app.register("alice").await;
+    let response = app
+        .post("/auth/register")
+        .json(json!({ "username": "alice", "password": PASSWORD }))
+        .send()
+        .await
+        .assert_error(ErrorCode::UsernameTaken);
+    assert_eq!(response.body["details"][0]["field"], "username");
+}
+
+#[tokio::test]
+async fn login_rejects_bad_credentials() {
+    let app = TestApp::spawn().await;
+    app.register("alice").await;
+
+    app.post("/auth/login")
+        .json(json!({ "username": "alice", "password": "" }))
+        .send()
+        .await
+        .assert_error(ErrorCode::MissingCredentials);
+    app.post("/auth/login")
+        .json(json!({ "username": "alice", "password": "wrong password" }))
+        .send()
+        .await
+        .assert_error(ErrorCode::InvalidCredentials);
+    app.post("/auth/login")
+        .json(json!({ "username": "nobody", "password": PASSWORD }))
+        .send()
+        .await
+        .assert_error(ErrorCode::InvalidCredentials);
+}
+
+#[tokio::test]
+async fn refresh_rotates_the_token() {
+    let app = TestApp::spawn().await;
+    let alice = app.register("alice").await;
+
+    let response = app
+        .post("/auth/refresh")
+        .json(json!({ "refresh_token": alice.refresh }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    let rotated = response.str("/refresh_token").to_string();
+    app.get("/users/me")
+        .bearer(response.str("/access_token"))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+
+    app.post("/auth/refresh")
+        .json(json!({ "refresh_token": rotated }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+}
+
+#[tokio::test]
+async fn replaying_a_rotated_token_revokes_its_family() {
+    let app = TestApp::spawn().await;
+    let alice = app.register("alice").await;
+    let other_device = app.login("alice").await;
+
+    let rotated = app
+        .post("/auth/refresh")
+        .json(json!({ "refresh_token": alice.refresh }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK)
+        .str("/refresh_token")
+        .to_string();
+
+    app.clock.advance(REUSE_GRACE);
+    app.post("/auth/refresh")
+        .json(json!({ "refresh_token": alice.refresh }))
+        .send()
+        .await
+        .assert_error(ErrorCode::SessionExpired);
+    app.post("/auth/refresh")
+        .json(json!({ "refresh_token": rotated }))
+        .send()
+        .await
+        .assert_error(ErrorCode::SessionExpired);
+
+    let events = SecurityEvent::find()
+        .filter(SecurityEventColumn::UserId.eq(alice.user_id))
+        .all(&*app.db)
+        .await
+        .unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].kind, SecurityEventKind::RefreshTokenReuse);
+
+    // Tokens of other logins are not part of the family.
+    app.post("/auth/refresh")
+        .json(json!({ "refresh_token": other_device.refresh }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+}
+
+#[tokio::test]
+async fn a_retry_right_after_rotation_keeps_the_session() {
+    let app = TestApp::spawn().await;
+    let alice = app.register("alice").await;
+
+    let rotated = app
+        .post("/auth/refresh")
+        .json(json!({ "refresh_token": alice.refresh }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK)
+        .str("/refresh_token")
+        .to_string();
+    app.post("/auth/refresh")
+        .json(json!({ "refresh_token": alice.refresh }))
+        .send()
+        .await
+        .assert_error(ErrorCode::SessionExpired);
+
+    app.post("/auth/refresh")
+        .json(json!({ "refresh_token": rotated }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    let events = SecurityEvent::find()
+        .filter(SecurityEventColumn::UserId.eq(alice.user_id))
+        .count(&*app.db)
+        .await
+        .unwrap();
+    assert_eq!(events, 0);
+}
+
+#[tokio::test]
+async fn replaying_a_logged_out_token_is_not_reuse() {
+    let app = TestApp::spawn().await;
+    let alice = app.register("alice").await;
+
+    app.post("/auth/logout")
+        .json(json!({ "refresh_token": alice.refresh }))
+        .send()
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    app.post("/auth/refresh")
+        .json(json!({ "refresh_token": alice.refresh }))
+        .send()
+        .await
+        .assert_error(ErrorCode::SessionExpired);
+
+    let events = SecurityEvent::find()
+        .filter(SecurityEventColumn::UserId.eq(alice.user_id))
+        .count(&*app.db)
+        .await
+        .unwrap();
+    assert_eq!(events, 0);
+}
+
+#[tokio::test]
+async fn refresh_rejects_garbage_and_access_tokens() {
+    let app = TestApp::spawn().await;
+    let alice = app.register("alice").await;
+
+    app.post("/auth/refresh")
+        .json(json!({ "refresh_token": "not a jwt" }))
+        .send()
+        .await
+        .assert_error(ErrorCode::SessionExpired);
+    app.post("/auth/refresh")
+        .json(json!({ "refresh_token": alice.access }))
+        .send()
+        .await
+        .assert_error(ErrorCode::SessionExpired);
+}
+
+#[tokio::test]
+async fn logout_revokes_the_refresh_token() {
+    let app = TestApp::spawn().await;
+    let alice = app.register("alice").await;
+
+    app.post("/auth/logout")
+        .json(json!({ "refresh_token": alice.refresh }))
+        .send()
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    app.post("/auth/refresh")
+        .json(json!({ "refresh_token": alice.refresh }))
+        .send()
+        .await
+        .assert_error(ErrorCode::SessionExpired);
+
+    app.post("/auth/logout")
+        .json(json!({ "refresh_token": "not a jwt" }))
+        .send()
+        .await
+        .assert_error(ErrorCode::InvalidToken);
+}
+
+#[tokio::test]
+async fn protected_routes_require_a_valid_token() {
+    let app = TestApp::spawn().await;
+
+    app.get("/users/me")
+        .send()
+        .await
+        .assert_error(ErrorCode::SessionExpired);
+    app.get("/users/me")
+        .bearer("not a jwt")
+        .send()
+        .await
+        .assert_error(ErrorCode::SessionExpired);
+}
+
+#[tokio::test]
+async fn errors_are_localised() {
+    let app = TestApp::spawn().await;
+
+    let en = app
+        .post("/auth/login")
+        .json(json!({ "username": "nobody", "password": PASSWORD }))
+        .send()
+        .await
+        .assert_error(ErrorCode::InvalidCredentials);
+    let ru = app
+        .post("/auth/login")
+        .header("accept-language", "ru-RU,ru;q=0.9")
+        .json(json!({ "username": "nobody", "password": PASSWORD }))
+        .send()
+        .await
+        .assert_error(ErrorCode::InvalidCredentials);
+    assert_ne!(en.body["message"], ru.body["message"]);
+}
@@ -0,0 +1,135 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::{TestApp, date};
+use friends_server::error::ErrorCode;
+use serde_json::json;
+
+#[tokio::test]
+async fn my_calendar_lists_busy_days_and_invites() {
+    let app = TestApp::spawn().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+    app.befriend(&alice, &bob).await;
+    let event_id = app.create_event(&alice, &date(3), &[&bob]).await;
+
+    let range = format!("/users/me/calendar?from={}&to={}", date(0), date(7));
+    let alice_calendar = app
+        .get(&range)
+        .auth(&alice)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    assert_eq!(alice_calendar.body["busy_days"][0]["date"], date(3));
+    assert_eq!(
+        alice_calendar.body["busy_days"][0]["event_id"],
+        json!(event_id)
+    );
+
+    let bob_calendar = app
+        .get(&range)
+        .auth(&bob)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    assert_eq!(bob_calendar.body["busy_days"], json!([]));
+    assert_eq!(
+        bob_calendar.body["pending_invites"][0]["event_id"],
+        json!(event_id)
+    );
+}
+
+#[tokio::test]
+async fn calendar_rejects_bad_ranges() {
+    let app = TestApp::spawn().await;
+    let alice = app.register("alice").await;
+
+    app.get(&format!(
+        "/users/me/calendar?from={}&to={}",
+        date(5),
+        date(1)
+    ))
+    .auth(&alice)
+    .send()
+    .await
+    .assert_error(ErrorCode::InvalidDateRange);
+    app.get("/users/me/calendar?from=yesterday&to=tomorrow")
+        .auth(&alice)
+        .send()
+        .await
+        .assert_error(ErrorCode::InvalidDate);
+    app.get(&format!(
+        "/users/me/calendar?from={}&to={}",
+        date(0),
+        date(1)
+    ))
+    .send()
+    .await
+    .assert_error(ErrorCode::SessionExpired);
+}
+
+#[tokio::test]
+async fn friend_calendar_is_friends_only() {
+    let app = TestApp::spawn().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+    let carol = app.register("carol").await;
+    app.befriend(&alice, &bob).await;
+
+    let path = format!(
+        "/users/{}/calendar?from={}&to={}",
+        alice.user_id,
+        date(0),
+        date(7)
+    );
+    app.get(&path)
+        .auth(&bob)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    app.get(&path)
+        .auth(&carol)
+        .send()
+        .await
+        .assert_error(ErrorCode::FriendsOnly);
+}
+
+#[tokio::test]
+async fn is_busy_checks_self_and_friends() {
+    let app = TestApp::spawn().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+    let carol = app.register("carol").await;
+    app.befriend(&alice, &bob).await;
+    app.create_event(&alice, &date(2), &[&bob]).await;
+
+    let busy = app
+        .post("/calendar/is_busy")
+        .auth(&bob)
+        .json(json!({ "id": alice.user_id, "date": date(2) }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    assert_eq!(busy.body, json!(true));
+    let free = app
+        .post("/calendar/is_busy")
+        .auth(&alice)
+        .json(json!({ "id": alice.user_id, "date": date(3) }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    assert_eq!(free.body, json!(false));
+
+    app.post("/calendar/is_busy")
+        .auth(&carol)
+        .json(json!({ "id": alice.user_id, "date": date(2) }))
+        .send()
+        .await
+        .assert_error(ErrorCode::FriendsOnly);
+    app.post("/calendar/is_busy")
+        .auth(&alice)
+        .json(json!({ "id": alice.user_id, "date": "02.03.2026" }))
+        .send()
+        .await
+        .assert_error(ErrorCode::InvalidDate);
+}
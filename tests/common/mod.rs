@@ -0,0 +1,486 @@
+//! Integration test harness: the full router against a fresh Postgres
+//! database per test.
+//!
+//! Set `TEST_DATABASE_URL` to a connection string of a role allowed to create
+//! databases, e.g. `postgres://postgres@localhost:5432/postgres`. Migrations
+//! run once into a template database, each test clones it and drops the copy
+//! when done. Without the variable the tests fail rather than pass unchecked.
+
+#![allow(dead_code)]
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use axum::Router;
+use axum::body::{Body, to_bytes};
+use axum::http::{HeaderMap, Method, Request, StatusCode, header};
+use chrono::{Days, Utc};
+use friends_server::auth::keys::JwtKeys;
+use friends_server::auth::session_guard::SessionGuard;
+use friends_server::clock::ManualClock;
+use friends_server::config::Config;
+use friends_server::error::ErrorCode;
+use friends_server::mail::{MailError, Mailer, Message};
+use friends_server::migration::Migrator;
+use friends_server::oidc::OidcVerifier;
+use friends_server::rate_limit::RateLimiter;
+use friends_server::server;
+use friends_server::state::AppState;
+use metrics_exporter_prometheus::PrometheusBuilder;
+use sea_orm::{ConnectOptions, ConnectionTrait, Database, DatabaseConnection};
+use sea_orm_migration::MigratorTrait;
+use serde_json::{Value, json};
+use tokio_util::sync::CancellationToken;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+pub const PASSWORD: &str = "correct horse battery";
+/// Serialises template creation across concurrently running test binaries.
+const TEMPLATE_LOCK: i64 = 0x0066_7269_656e_6473;
+const JWT_SECRET: &str = "integration-tests-secret-0123456789abcdef";
+
+pub struct TestApp {
+    pub db: Arc<DatabaseConnection>,
+    /// Starts at the real time; tests move it to pin "now".
+    pub clock: Arc<ManualClock>,
+    pub jwt_keys: Arc<JwtKeys>,
+    pub outbox: Arc<Outbox>,
+    router: Router,
+    admin_url: String,
+    database: String,
+}
+
+/// Tokens and id of a registered user.
+pub struct Session {
+    pub user_id: Uuid,
+    pub username: String,
+    pub access: String,
+    pub refresh: String,
+}
+
+/// Keeps every mail the app sends instead of delivering it.
+#[derive(Default)]
+pub struct Outbox {
+    messages: Mutex<Vec<Message>>,
+}
+
+pub struct TestResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Value,
+    /// The body as received, for responses that are not JSON.
+    pub bytes: Vec<u8>,
+}
+
+pub struct Call<'a> {
+    app: &'a TestApp,
+    request: axum::http::request::Builder,
+    body: Option<Value>,
+}
+
+impl TestApp {
+    /// Panics when `TEST_DATABASE_URL` is not set.
+    pub async fn spawn() -> TestApp {
+        Self::spawn_with(|_| {}).await
+    }
+
+    /// Like [`TestApp::spawn`], with `configure` applied to the config first.
+    pub async fn spawn_with(configure: impl FnOnce(&mut Config)) -> TestApp {
+        let admin_url = std::env::var("TEST_DATABASE_URL")
+            .expect("TEST_DATABASE_URL must point to a Postgres role allowed to create databases");
+        let database = format!("friends_test_{}", Uuid::new_v4().simple());
+        create_database(&admin_url, &database).await;
+
+        let mut config = Config::default();
+        config.database.url = database_url(&admin_url, &database);
+        config.database.max_connections = 5;
+        config.jwt.access_secret = JWT_SECRET.to_string();
+        config.jwt.refresh_secret = JWT_SECRET.to_string();
+        configure(&mut config);
+
+        let db = Arc::new(
+            Database::connect(&config.database.url)
+                .await
+                .expect("connect to test database"),
+        );
+        let clock = Arc::new(ManualClock::new(Utc::now()));
+        let rate_limiter = RateLimiter::from_config(&config.rate_limit, db.clone(), clock.clone());
+        let jwt_keys = Arc::new(JwtKeys::load(&config.jwt).expect("load JWT keys"));
+        let sessions = SessionGuard::new(
+            db.clone(),
+            Duration::from_secs(config.jwt.session_check_secs),
+        );
+        let outbox = Arc::new(Outbox::default());
+        let oidc = Arc::new(OidcVerifier::new(&config.oidc));
+        let router = server::router(&config).with_state(AppState {
+            db: db.clone(),
+            config: Arc::new(config),
+            jwt_keys: jwt_keys.clone(),
+            metrics: PrometheusBuilder::new().build_recorder().handle(),
+            clock: clock.clone(),
+            rate_limiter: Arc::new(rate_limiter),
+            sessions: Arc::new(sessions),
+            mailer: outbox.clone(),
+            oidc,
+            shutdown: CancellationToken::new(),
+        });
+
+        TestApp {
+            db,
+            clock,
+            jwt_keys,
+            outbox,
+            router,
+            admin_url,
+            database,
+        }
+    }
+
+    pub fn call(&self, method: Method, uri: &str) -> Call<'_> {
+        Call {
+            app: self,
+            request: Request::builder().method(method).uri(uri),
+            body: None,
+        }
+    }
+
+    pub fn get(&self, uri: &str) -> Call<'_> {
+        self.call(Method::GET, uri)
+    }
+
+    pub fn post(&self, uri: &str) -> Call<'_> {
+        self.call(Method::POST, uri)
+    }
+
+    pub fn patch(&self, uri: &str) -> Call<'_> {
+        self.call(Method::PATCH, uri)
+    }
+
+    pub fn delete(&self, uri: &str) -> Call<'_> {
+        self.call(Method::DELETE, uri)
+    }
+
+    /// Registers `username` and logs in.
+    pub async fn register(&self, username: &str) -> Session {
+        self.post("/auth/register")
+            .json(json!({ "username": username, "password": PASSWORD }))
+            .send()
+            .await
+            .assert_status(StatusCode::CREATED);
+        self.login(username).await
+    }
+
+    pub async fn login(&self, username: &str) -> Session {
+        let response = self
+            .post("/auth/login")
+            .json(json!({ "username": username, "password": PASSWORD }))
+            .send()
+            .await
+            .assert_status(StatusCode::OK);
+        Session {
+            user_id: response.uuid("/user/id"),
+            username: username.to_string(),
+            access: response.str("/access_token").to_string(),
+            refresh: response.str("/refresh_token").to_string(),
+        }
+    }
+
+    /// Sets `email` on the profile of `session` and opens the link of the
+    /// verification mail.
+    pub async fn verify_email(&self, session: &Session, email: &str) {
+        let seen = self.outbox.count();
+        self.patch("/users/me")
+            .auth(session)
+            .json(json!({ "email": email }))
+            .send()
+            .await
+            .assert_status(StatusCode::OK);
+        let mail = self.outbox.wait_for(seen).await;
+        self.post("/auth/verify-email")
+            .json(json!({ "token": mail_token(&mail) }))
+            .send()
+            .await
+            .assert_status(StatusCode::NO_CONTENT);
+    }
+
+    /// Sends a friend request from `a` and accepts it as `b`.
+    pub async fn befriend(&self, a: &Session, b: &Session) {
+        self.post("/friends/request")
+            .auth(a)
+            .json(json!({ "friend_id": b.user_id }))
+            .send()
+            .await
+            .assert_status(StatusCode::CREATED);
+        self.post(&format!("/friends/{}/accept", a.user_id))
+            .auth(b)
+            .send()
+            .await
+            .assert_status(StatusCode::NO_CONTENT);
+    }
+
+    /// Creates an event owned by `owner` inviting `invited`, returns its id.
+    pub async fn create_event(&self, owner: &Session, date: &str, invited: &[&Session]) -> Uuid {
+        let ids: Vec<Uuid> = invited.iter().map(|s| s.user_id).collect();
+        self.post("/events")
+            .auth(owner)
+            .json(json!({ "date": date, "title": "Dinner", "invited_friend_ids": ids }))
+            .send()
+            .await
+            .assert_status(StatusCode::CREATED)
+            .uuid("/id")
+    }
+}
+
+impl Drop for TestApp {
+    fn drop(&mut self) {
+        let admin_url = self.admin_url.clone();
+        let database = self.database.clone();
+        // The test runtime may already be shutting down, so use a fresh one.
+        std::thread::spawn(move || {
+            tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .expect("runtime for cleanup")
+                .block_on(async {
+                    let admin = connect_admin(&admin_url).await;
+                    admin
+                        .execute_unprepared(&format!(
+                            "DROP DATABASE IF EXISTS \"{database}\" WITH (FORCE)"
+                        ))
+                        .await
+                        .expect("drop test database");
+                    admin.close().await.ok();
+                });
+        })
+        .join()
+        .ok();
+    }
+}
+
+#[async_trait]
+impl Mailer for Outbox {
+    async fn send(&self, message: &Message) -> Result<(), MailError> {
+        self.messages.lock().unwrap().push(message.clone());
+        Ok(())
+    }
+}
+
+/// The token in the link of a reset or verification mail.
+pub fn mail_token(message: &Message) -> String {
+    message
+        .body
+        .split("token=")
+        .nth(1)
+        .and_then(|rest| rest.split_whitespace().next())
+        .expect("link with a token in mail")
+        .to_string()
+}
+
+impl Outbox {
+    /// Waits for the mail after the first `seen` ones. Mail is sent in the
+    /// background, so it may arrive a little after the response.
+    pub async fn wait_for(&self, seen: usize) -> Message {
+        for _ in 0..200 {
+            if let Some(message) = self.messages.lock().unwrap().get(seen) {
+                return message.clone();
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("no mail after the first {seen}");
+    }
+
+    pub fn count(&self) -> usize {
+        self.messages.lock().unwrap().len()
+    }
+}
+
+impl Call<'_> {
+    pub fn auth(self, session: &Session) -> Self {
+        self.bearer(&session.access)
+    }
+
+    pub fn bearer(mut self, token: &str) -> Self {
+        self.request = self
+            .request
+            .header(header::AUTHORIZATION, format!("Bearer {token}"));
+        self
+    }
+
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.request = self.request.header(name, value);
+        self
+    }
+
+    pub fn json(mut self, body: Value) -> Self {
+        self.body = Some(body);
+        self
+    }
+
+    pub async fn send(self) -> TestResponse {
+        let request = match self.body {
+            Some(body) => self
+                .request
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body.to_string())),
+            None => self.request.body(Body::empty()),
+        }
+        .expect("valid request");
+
+        let response = self
+            .app
+            .router
+            .clone()
+            .oneshot(request)
+            .await
+            .expect("infallible router");
+        let status = response.status();
+        let headers = response.headers().clone();
+        let bytes = to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("read body");
+        let body = if bytes.is_empty() {
+            Value::Null
+        } else {
+            serde_json::from_slice(&bytes)
+                .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(&bytes).into_owned()))
+        };
+        TestResponse {
+            status,
+            headers,
+            body,
+            bytes: bytes.to_vec(),
+        }
+    }
+}
+
+impl TestResponse {
+    #[track_caller]
+    pub fn assert_status(self, status: StatusCode) -> Self {
+        assert_eq!(
+            self.status, status,
+            "unexpected status, body: {}",
+            self.body
+        );
+        self
+    }
+
+    /// Checks both the HTTP status and the `code` of the error body.
+    #[track_caller]
+    pub fn assert_error(self, code: ErrorCode) -> Self {
+        assert_eq!(
+            self.status,
+            code.status(),
+            "unexpected status, body: {}",
+            self.body
+        );
+        assert_eq!(self.body["code"], serde_json::to_value(code).unwrap());
+        self
+    }
+
+    #[track_caller]
+    pub fn str(&self, pointer: &str) -> &str {
+        self.body
+            .pointer(pointer)
+            .and_then(Value::as_str)
+            .unwrap_or_else(|| panic!("no string at {pointer} in {}", self.body))
+    }
+
+    #[track_caller]
+    pub fn uuid(&self, pointer: &str) -> Uuid {
+        self.str(pointer).parse().expect("uuid")
+    }
+
+    /// Values at `pointer` of every element of `items`.
+    pub fn pluck(&self, pointer: &str) -> Vec<Value> {
+        self.body["items"]
+            .as_array()
+            .or(self.body.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|item| item.pointer(pointer).cloned())
+            .collect()
+    }
+}
+
+/// `YYYY-MM-DD` of today plus `days`, negative for the past.
+pub fn date(days: i64) -> String {
+    let today = Utc::now().date_naive();
+    let date = if days >= 0 {
+        today + Days::new(days as u64)
+    } else {
+        today - Days::new(days.unsigned_abs())
+    };
+    date.to_string()
+}
+
+async fn connect_admin(url: &str) -> DatabaseConnection {
+    // One connection so the session advisory lock and its unlock match.
+    let mut options = ConnectOptions::new(url);
+    options.max_connections(1).sqlx_logging(false);
+    Database::connect(options)
+        .await
+        .expect("connect to TEST_DATABASE_URL")
+}
+
+/// Clones the migrated template into `database`, building the template
+/// first when the migration set changed.
+async fn create_database(admin_url: &str, database: &str) {
+    let mut hasher = DefaultHasher::new();
+    for migration in Migrator::migrations() {
+        migration.name().hash(&mut hasher);
+    }
+    let template = format!("friends_test_tpl_{:016x}", hasher.finish());
+
+    let admin = connect_admin(admin_url).await;
+    admin
+        .execute_unprepared(&format!("SELECT pg_advisory_lock({TEMPLATE_LOCK})"))
+        .await
+        .expect("lock template");
+
+    let exists = admin
+        .query_one(sea_orm::Statement::from_string(
+            admin.get_database_backend(),
+            format!("SELECT 1 FROM pg_database WHERE datname = '{template}'"),
+        ))
+        .await
+        .expect("look up template")
+        .is_some();
+    if !exists {
+        admin
+            .execute_unprepared(&format!("CREATE DATABASE \"{template}\""))
+            .await
+            .expect("create template");
+        let db = Database::connect(database_url(admin_url, &template))
+            .await
+            .expect("connect to template");
+        Migrator::up(&db, None).await.expect("migrate template");
+        db.close().await.ok();
+    }
+
+    admin
+        .execute_unprepared(&format!(
+            "CREATE DATABASE \"{database}\" TEMPLATE \"{template}\""
+        ))
+        .await
+        .expect("create test database");
+    admin
+        .execute_unprepared(&format!("SELECT pg_advisory_unlock({TEMPLATE_LOCK})"))
+        .await
+        .expect("unlock template");
+    admin.close().await.ok();
+}
+
+fn database_url(admin_url: &str, database: &str) -> String {
+    let (base, query) = match admin_url.split_once('?') {
+        Some((base, query)) => (base, Some(query)),
+        None => (admin_url, None),
+    };
+    let (server, _) = base.rsplit_once('/').expect("database url with a path");
+    match query {
+        Some(query) => format!("{server}/{database}?{query}"),
+        None => format!("{server}/{database}"),
+    }
+}
@@ -0,0 +1,224 @@
+mod common;
+
+use std::io::{Cursor, Read};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use axum::http::{StatusCode, header};
+use chrono::TimeDelta;
+use common::{Session, TestApp, date};
+use friends_server::clock::Clock;
+use friends_server::error::ErrorCode;
+use friends_server::services::data_exports;
+use serde_json::{Value, json};
+use uuid::Uuid;
+use zip::ZipArchive;
+
+const DOWNLOAD_URL: &str = "https://api.example.com/exports/{token}";
+/// The first bytes of a PNG file.
+const PNG: &[u8] = &[0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a];
+const PNG_BASE64: &str = "iVBORw0KGgo=";
+
+/// A data directory removed when dropped.
+struct DataDir(PathBuf);
+
+impl DataDir {
+    fn new() -> Self {
+        DataDir(std::env::temp_dir().join(format!("friends-data-{}", Uuid::new_v4().simple())))
+    }
+}
+
+impl Drop for DataDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+async fn spawn(dir: &DataDir) -> TestApp {
+    let path = dir.0.clone();
+    TestApp::spawn_with(|config| {
+        config.storage.data_dir = path;
+        config.account.export_download_url = DOWNLOAD_URL.to_string();
+    })
+    .await
+}
+
+/// Starts an export for `session` and returns the path of its download link.
+async fn start(app: &TestApp, session: &Session) -> String {
+    let response = app
+        .post("/users/me/exports")
+        .auth(session)
+        .send()
+        .await
+        .assert_status(StatusCode::ACCEPTED);
+    assert_eq!(response.body["status"], "pending");
+    let url = response.str("/download_url");
+    url.strip_prefix("https://api.example.com")
+        .expect("link from the template")
+        .to_string()
+}
+
+/// Waits for the only export of `session` to be written and returns it.
+async fn wait_until_ready(app: &TestApp, session: &Session) -> Value {
+    for _ in 0..200 {
+        let exports = app
+            .get("/users/me/exports")
+            .auth(session)
+            .send()
+            .await
+            .assert_status(StatusCode::OK);
+        let export = exports.body[0].clone();
+        if export["status"] != "pending" {
+            assert_eq!(export["status"], "ready");
+            return export;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    panic!("export not ready");
+}
+
+fn read_json(archive: &mut ZipArchive<Cursor<Vec<u8>>>, name: &str) -> Value {
+    let file = archive.by_name(name).expect(name);
+    serde_json::from_reader(file).expect("json file")
+}
+
+#[tokio::test]
+async fn export_holds_the_users_data_and_memories() {
+    let dir = DataDir::new();
+    let app = spawn(&dir).await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+    app.befriend(&alice, &bob).await;
+    app.verify_email(&alice, "alice@example.com").await;
+    let event = app.create_event(&alice, &date(0), &[&bob]).await;
+    app.post(&format!("/events/{event}/accept"))
+        .auth(&bob)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    app.post(&format!("/events/{event}/finish"))
+        .auth(&alice)
+        .json(json!({ "memory_image_base64": PNG_BASE64 }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    app.post("/wish-places")
+        .auth(&alice)
+        .json(json!({ "title": "Cafe by the river" }))
+        .send()
+        .await
+        .assert_status(StatusCode::CREATED);
+
+    let seen = app.outbox.count();
+    let link = start(&app, &alice).await;
+    let export = wait_until_ready(&app, &alice).await;
+    assert!(export.get("download_url").is_none());
+    assert!(export["size_bytes"].as_i64().unwrap() > 0);
+    let mail = app.outbox.wait_for(seen).await;
+    assert_eq!(mail.to, "alice@example.com");
+    assert!(
+        mail.body
+            .contains(&format!("https://api.example.com{link}"))
+    );
+
+    let download = app.get(&link).send().await.assert_status(StatusCode::OK);
+    assert_eq!(download.headers[header::CONTENT_TYPE], "application/zip");
+    let mut archive = ZipArchive::new(Cursor::new(download.bytes)).expect("zip archive");
+
+    let manifest = read_json(&mut archive, "manifest.json");
+    assert_eq!(manifest["format"], "friends-takeout");
+    assert_eq!(manifest["version"], 1);
+    assert_eq!(manifest["user_id"], alice.user_id.to_string());
+    for file in manifest["files"].as_array().unwrap() {
+        archive
+            .by_name(file.as_str().unwrap())
+            .expect("listed file");
+    }
+
+    let profile = read_json(&mut archive, "profile.json");
+    assert_eq!(profile["username"], "alice");
+    assert_eq!(profile["email"], "alice@example.com");
+
+    let friendships = read_json(&mut archive, "friendships.json");
+    assert_eq!(friendships[0]["username"], "bob");
+    assert_eq!(friendships[0]["status"], "accepted");
+    assert_eq!(friendships[0]["direction"], "outgoing");
+
+    let events = read_json(&mut archive, "events.json");
+    assert_eq!(events[0]["id"], event.to_string());
+    assert_eq!(events[0]["status"], "completed");
+    assert_eq!(events[0]["participants"].as_array().unwrap().len(), 2);
+    let memory = format!("memories/{event}.png");
+    assert_eq!(events[0]["memory_image"], memory.as_str());
+    let mut image = Vec::new();
+    archive
+        .by_name(&memory)
+        .expect("memory image")
+        .read_to_end(&mut image)
+        .unwrap();
+    assert_eq!(image, PNG);
+
+    let rsvps = read_json(&mut archive, "rsvps.json");
+    assert_eq!(rsvps[0]["event_id"], event.to_string());
+    assert_eq!(rsvps[0]["role"], "owner");
+    let busy_days = read_json(&mut archive, "busy_days.json");
+    assert_eq!(busy_days[0]["event_id"], event.to_string());
+    let wish_places = read_json(&mut archive, "wish_places.json");
+    assert_eq!(wish_places[0]["title"], "Cafe by the river");
+}
+
+#[tokio::test]
+async fn download_link_works_only_while_the_export_is_ready() {
+    let dir = DataDir::new();
+    let app = spawn(&dir).await;
+    let alice = app.register("alice").await;
+
+    app.get("/exports/unknown")
+        .send()
+        .await
+        .assert_error(ErrorCode::DataExportNotFound);
+
+    // An export still being written blocks another one.
+    let now = app.clock.now();
+    let (pending, token) =
+        data_exports::create(&*app.db, alice.user_id, now, now + TimeDelta::days(1))
+            .await
+            .unwrap();
+    app.get(&format!("/exports/{token}"))
+        .send()
+        .await
+        .assert_error(ErrorCode::DataExportNotReady);
+    app.post("/users/me/exports")
+        .auth(&alice)
+        .send()
+        .await
+        .assert_error(ErrorCode::DataExportPending);
+    data_exports::build(&app.db, &dir.0, pending, now)
+        .await
+        .unwrap();
+
+    app.clock.advance(TimeDelta::minutes(1));
+    let link = start(&app, &alice).await;
+    wait_until_ready(&app, &alice).await;
+    app.get(&link).send().await.assert_status(StatusCode::OK);
+
+    app.clock.advance(TimeDelta::days(8));
+    app.get(&link)
+        .send()
+        .await
+        .assert_error(ErrorCode::DataExportNotFound);
+    let exports = app
+        .get("/users/me/exports")
+        .auth(&alice)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    assert_eq!(exports.body, json!([]));
+
+    let deleted = data_exports::delete_expired(&app.db, &dir.0, app.clock.now())
+        .await
+        .unwrap();
+    assert_eq!(deleted, 2);
+    let left = std::fs::read_dir(dir.0.join("exports")).unwrap().count();
+    assert_eq!(left, 0);
+}
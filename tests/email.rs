@@ -0,0 +1,161 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::{PASSWORD, TestApp, TestResponse, mail_token};
+use friends_server::config::Limit;
+use friends_server::error::ErrorCode;
+use serde_json::json;
+
+async fn login(app: &TestApp, login: &str) -> TestResponse {
+    app.post("/auth/login")
+        .json(json!({ "username": login, "password": PASSWORD }))
+        .send()
+        .await
+}
+
+#[tokio::test]
+async fn new_address_is_verified_from_the_mail() {
+    let app = TestApp::spawn().await;
+    let alice = app.register("alice").await;
+
+    let response = app
+        .patch("/users/me")
+        .auth(&alice)
+        .json(json!({ "email": "Alice@Example.com" }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    assert_eq!(response.body["email"], "alice@example.com");
+    assert_eq!(response.body["email_verified"], false);
+
+    let mail = app.outbox.wait_for(0).await;
+    assert_eq!(mail.to, "alice@example.com");
+    assert_eq!(mail.subject, "Confirm your email for Friends");
+    let token = mail_token(&mail);
+    for status in [StatusCode::NO_CONTENT, StatusCode::BAD_REQUEST] {
+        app.post("/auth/verify-email")
+            .json(json!({ "token": token }))
+            .send()
+            .await
+            .assert_status(status);
+    }
+
+    let me = app
+        .get("/users/me")
+        .auth(&alice)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    assert_eq!(me.body["email_verified"], true);
+    let other = app
+        .get(&format!("/users/{}", alice.user_id))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    assert!(other.body.get("email").is_none());
+    assert!(other.body.get("email_verified").is_none());
+}
+
+#[tokio::test]
+async fn changing_the_address_voids_earlier_links() {
+    let app = TestApp::spawn().await;
+    let alice = app.register("alice").await;
+    app.verify_email(&alice, "alice@example.com").await;
+
+    app.patch("/users/me")
+        .auth(&alice)
+        .json(json!({ "email": "alice@example.org" }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    let token = mail_token(&app.outbox.wait_for(1).await);
+    let response = app
+        .patch("/users/me")
+        .auth(&alice)
+        .json(json!({ "email": "alice@example.net" }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    assert_eq!(response.body["email_verified"], false);
+
+    app.post("/auth/verify-email")
+        .json(json!({ "token": token }))
+        .send()
+        .await
+        .assert_error(ErrorCode::InvalidVerificationToken);
+}
+
+#[tokio::test]
+async fn addresses_are_unique_regardless_of_case() {
+    let app = TestApp::spawn().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+    app.verify_email(&alice, "alice@example.com").await;
+
+    app.patch("/users/me")
+        .auth(&bob)
+        .json(json!({ "email": "ALICE@example.com" }))
+        .send()
+        .await
+        .assert_error(ErrorCode::EmailTaken);
+}
+
+#[tokio::test]
+async fn login_accepts_a_verified_address() {
+    let app = TestApp::spawn().await;
+    let alice = app.register("alice").await;
+    app.patch("/users/me")
+        .auth(&alice)
+        .json(json!({ "email": "alice@example.com" }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+
+    login(&app, "alice@example.com")
+        .await
+        .assert_error(ErrorCode::InvalidCredentials);
+
+    app.post("/auth/verify-email")
+        .json(json!({ "token": mail_token(&app.outbox.wait_for(0).await) }))
+        .send()
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    let response = login(&app, "Alice@Example.com")
+        .await
+        .assert_status(StatusCode::OK);
+    assert_eq!(response.uuid("/user/id"), alice.user_id);
+    login(&app, "alice").await.assert_status(StatusCode::OK);
+}
+
+#[tokio::test]
+async fn resending_verification_is_throttled() {
+    let app = TestApp::spawn_with(|config| {
+        config.rate_limit.email_verification.per_username = Some(Limit {
+            requests: 2,
+            window_secs: 3600,
+        });
+    })
+    .await;
+    let alice = app.register("alice").await;
+
+    let resend = || app.post("/users/me/email/verification").auth(&alice).send();
+    resend().await.assert_error(ErrorCode::EmailNotSet);
+
+    app.patch("/users/me")
+        .auth(&alice)
+        .json(json!({ "email": "alice@example.com" }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    resend().await.assert_status(StatusCode::ACCEPTED);
+    let throttled = resend().await.assert_error(ErrorCode::TooManyRequests);
+    assert_eq!(throttled.status, StatusCode::TOO_MANY_REQUESTS);
+
+    // Only the latest link works.
+    let latest = app.outbox.wait_for(1).await;
+    app.post("/auth/verify-email")
+        .json(json!({ "token": mail_token(&latest) }))
+        .send()
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+}
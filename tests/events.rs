@@ -0,0 +1,563 @@
+mod common;
+
+use axum::http::StatusCode;
+use chrono::{DateTime, Utc};
+use common::{Session, TestApp, date};
+use friends_server::error::ErrorCode;
+use serde_json::json;
+use uuid::Uuid;
+
+async fn event_status(app: &TestApp, viewer: &Session, id: Uuid) -> String {
+    app.get(&format!("/events/{id}"))
+        .auth(viewer)
+        .send()
+        .await
+        .assert_status(StatusCode::OK)
+        .str("/status")
+        .to_string()
+}
+
+#[tokio::test]
+async fn create_event_with_friends() {
+    let app = TestApp::spawn().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+    app.befriend(&alice, &bob).await;
+
+    let response = app
+        .post("/events")
+        .auth(&alice)
+        .json(json!({
+            "date": date(3),
+            "title": "Dinner",
+            "location": "Downtown",
+            "invited_friend_ids": [bob.user_id, bob.user_id, alice.user_id],
+        }))
+        .send()
+        .await
+        .assert_status(StatusCode::CREATED);
+    assert_eq!(response.body["status"], "pending");
+    assert_eq!(response.body["creator_id"], json!(alice.user_id));
+    assert_eq!(response.body["participants"].as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn create_event_rejects_bad_input() {
+    let app = TestApp::spawn().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+    let carol = app.register("carol").await;
+    app.befriend(&alice, &bob).await;
+
+    app.post("/events")
+        .auth(&alice)
+        .json(json!({ "date": "soon", "title": "Dinner", "invited_friend_ids": [] }))
+        .send()
+        .await
+        .assert_error(ErrorCode::InvalidDate);
+    app.post("/events")
+        .auth(&alice)
+        .json(json!({ "date": date(1), "title": " ", "invited_friend_ids": [] }))
+        .send()
+        .await
+        .assert_error(ErrorCode::TitleRequired);
+    app.post("/events")
+        .auth(&alice)
+        .json(json!({ "date": date(1), "title": "Dinner", "invited_friend_ids": [carol.user_id] }))
+        .send()
+        .await
+        .assert_error(ErrorCode::InviteFriendsOnly);
+    app.post("/events")
+        .json(json!({ "date": date(1), "title": "Dinner", "invited_friend_ids": [] }))
+        .send()
+        .await
+        .assert_error(ErrorCode::SessionExpired);
+}
+
+#[tokio::test]
+async fn create_event_checks_busy_days() {
+    let app = TestApp::spawn().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+    let carol = app.register("carol").await;
+    app.befriend(&alice, &bob).await;
+    app.befriend(&carol, &bob).await;
+    app.befriend(&carol, &alice).await;
+
+    let event = app.create_event(&bob, &date(4), &[&alice]).await;
+    app.post(&format!("/events/{event}/accept"))
+        .auth(&alice)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+
+    // The creator's own day is taken.
+    app.post("/events")
+        .auth(&alice)
+        .json(json!({ "date": date(4), "title": "Dinner", "invited_friend_ids": [] }))
+        .send()
+        .await
+        .assert_error(ErrorCode::DateReserved);
+    // An invited friend's day is taken.
+    app.post("/events")
+        .auth(&carol)
+        .json(json!({ "date": date(4), "title": "Dinner", "invited_friend_ids": [bob.user_id] }))
+        .send()
+        .await
+        .assert_error(ErrorCode::ParticipantsBusy);
+}
+
+#[tokio::test]
+async fn get_event_and_participants_are_members_only() {
+    let app = TestApp::spawn().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+    let carol = app.register("carol").await;
+    app.befriend(&alice, &bob).await;
+    let id = app.create_event(&alice, &date(2), &[&bob]).await;
+
+    let participants = app
+        .get(&format!("/events/{id}/participants"))
+        .auth(&bob)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    let mut names = participants.pluck("/username");
+    names.sort_by_key(|name| name.to_string());
+    assert_eq!(names, vec![json!("alice"), json!("bob")]);
+
+    for path in [
+        format!("/events/{id}"),
+        format!("/events/{id}/participants"),
+    ] {
+        app.get(&path)
+            .auth(&carol)
+            .send()
+            .await
+            .assert_error(ErrorCode::EventAccessDenied);
+    }
+    for path in [
+        format!("/events/{}", Uuid::new_v4()),
+        format!("/events/{}/participants", Uuid::new_v4()),
+    ] {
+        app.get(&path)
+            .auth(&alice)
+            .send()
+            .await
+            .assert_error(ErrorCode::EventNotFound);
+    }
+}
+
+#[tokio::test]
+async fn everyone_accepting_confirms_the_event() {
+    let app = TestApp::spawn().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+    let carol = app.register("carol").await;
+    app.befriend(&alice, &bob).await;
+    app.befriend(&alice, &carol).await;
+    let id = app.create_event(&alice, &date(5), &[&bob, &carol]).await;
+
+    let waiting = app.get("/events/waiting").auth(&bob).send().await;
+    assert_eq!(waiting.pluck("/id"), vec![json!(id)]);
+
+    let after_bob = app
+        .post(&format!("/events/{id}/accept"))
+        .auth(&bob)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    assert_eq!(after_bob.body["status"], "pending");
+    app.post(&format!("/events/{id}/accept"))
+        .auth(&bob)
+        .send()
+        .await
+        .assert_error(ErrorCode::InvitationNotFound);
+
+    let after_carol = app
+        .post(&format!("/events/{id}/accept"))
+        .auth(&carol)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    assert_eq!(after_carol.body["status"], "confirmed");
+
+    let active = app.get("/events/active").auth(&alice).send().await;
+    assert_eq!(active.pluck("/id"), vec![json!(id)]);
+    let pending = app.get("/events/pending").auth(&alice).send().await;
+    assert!(pending.pluck("/id").is_empty());
+}
+
+#[tokio::test]
+async fn accept_fails_when_the_day_is_taken() {
+    let app = TestApp::spawn().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+    let carol = app.register("carol").await;
+    app.befriend(&alice, &bob).await;
+    app.befriend(&carol, &bob).await;
+
+    let first = app.create_event(&alice, &date(6), &[&bob]).await;
+    let second = app.create_event(&carol, &date(6), &[&bob]).await;
+    app.post(&format!("/events/{first}/accept"))
+        .auth(&bob)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    app.post(&format!("/events/{second}/accept"))
+        .auth(&bob)
+        .send()
+        .await
+        .assert_error(ErrorCode::DateReserved);
+    app.post(&format!("/events/{}/accept", Uuid::new_v4()))
+        .auth(&bob)
+        .send()
+        .await
+        .assert_error(ErrorCode::EventNotFound);
+}
+
+#[tokio::test]
+async fn declining_reopens_or_cancels_the_event() {
+    let app = TestApp::spawn().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+    let carol = app.register("carol").await;
+    app.befriend(&alice, &bob).await;
+    app.befriend(&alice, &carol).await;
+
+    // A confirmed event goes back to pending when one of several declines.
+    let id = app.create_event(&alice, &date(7), &[&bob, &carol]).await;
+    for guest in [&bob, &carol] {
+        app.post(&format!("/events/{id}/accept"))
+            .auth(guest)
+            .send()
+            .await
+            .assert_status(StatusCode::OK);
+    }
+    app.post(&format!("/events/{id}/decline"))
+        .auth(&bob)
+        .send()
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    assert_eq!(event_status(&app, &alice, id).await, "pending");
+    app.post(&format!("/events/{id}/decline"))
+        .auth(&bob)
+        .send()
+        .await
+        .assert_error(ErrorCode::ParticipationNotFound);
+
+    // The only participant declining cancels the event and frees the day.
+    let single = app.create_event(&alice, &date(8), &[&bob]).await;
+    app.post(&format!("/events/{single}/decline"))
+        .auth(&bob)
+        .send()
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    assert_eq!(event_status(&app, &alice, single).await, "canceled");
+    let available = app
+        .get(&format!("/events/check-user-availability?date={}", date(8)))
+        .auth(&alice)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    assert_eq!(available.body["is_available"], true);
+
+    app.post(&format!("/events/{single}/decline"))
+        .auth(&bob)
+        .send()
+        .await
+        .assert_error(ErrorCode::EventClosed);
+    app.post(&format!("/events/{single}/accept"))
+        .auth(&bob)
+        .send()
+        .await
+        .assert_error(ErrorCode::EventClosed);
+    app.post(&format!("/events/{}/decline", Uuid::new_v4()))
+        .auth(&bob)
+        .send()
+        .await
+        .assert_error(ErrorCode::EventNotFound);
+}
+
+#[tokio::test]
+async fn cancel_is_owner_only() {
+    let app = TestApp::spawn().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+    app.befriend(&alice, &bob).await;
+    let id = app.create_event(&alice, &date(2), &[&bob]).await;
+
+    app.post(&format!("/events/{id}/cancel"))
+        .auth(&bob)
+        .send()
+        .await
+        .assert_error(ErrorCode::EventNotOwned);
+    app.post(&format!("/events/{id}/cancel"))
+        .auth(&alice)
+        .send()
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    // The event stays visible to its participants, canceled.
+    assert_eq!(event_status(&app, &bob, id).await, "canceled");
+    app.post(&format!("/events/{id}/cancel"))
+        .auth(&alice)
+        .send()
+        .await
+        .assert_error(ErrorCode::EventClosed);
+    let available = app
+        .get(&format!("/events/check-user-availability?date={}", date(2)))
+        .auth(&alice)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    assert_eq!(available.body["is_available"], true);
+}
+
+#[tokio::test]
+async fn history_records_every_transition() {
+    let app = TestApp::spawn().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+    let carol = app.register("carol").await;
+    let mallory = app.register("mallory").await;
+    app.befriend(&alice, &bob).await;
+    app.befriend(&alice, &carol).await;
+    let id = app.create_event(&alice, &date(4), &[&bob, &carol]).await;
+
+    for guest in [&bob, &carol] {
+        app.post(&format!("/events/{id}/accept"))
+            .auth(guest)
+            .send()
+            .await
+            .assert_status(StatusCode::OK);
+    }
+    app.post(&format!("/events/{id}/decline"))
+        .auth(&carol)
+        .send()
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    app.post(&format!("/events/{id}/cancel"))
+        .auth(&alice)
+        .send()
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+
+    let history = app
+        .get(&format!("/events/{id}/history"))
+        .auth(&bob)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    assert_eq!(
+        history.pluck("/reason"),
+        ["created", "confirmed", "reopened", "canceled_by_owner"]
+    );
+    assert_eq!(
+        history.pluck("/to_status"),
+        ["pending", "confirmed", "pending", "canceled"]
+    );
+    assert_eq!(history.body[0]["from_status"], serde_json::Value::Null);
+    assert_eq!(history.body[1]["actor_id"], json!(carol.user_id));
+    assert_eq!(history.body[3]["actor_id"], json!(alice.user_id));
+
+    app.get(&format!("/events/{id}/history"))
+        .auth(&mallory)
+        .send()
+        .await
+        .assert_error(ErrorCode::EventAccessDenied);
+}
+
+#[tokio::test]
+async fn finish_needs_the_date_to_have_come() {
+    let app = TestApp::spawn().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+    app.befriend(&alice, &bob).await;
+    let today = app.create_event(&alice, &date(0), &[&bob]).await;
+    let future = app.create_event(&alice, &date(3), &[&bob]).await;
+    let image = json!({ "memory_image_base64": "aGVsbG8=" });
+
+    app.post(&format!("/events/{future}/finish"))
+        .auth(&alice)
+        .json(image.clone())
+        .send()
+        .await
+        .assert_error(ErrorCode::EventNotStarted);
+    app.post(&format!("/events/{today}/finish"))
+        .auth(&bob)
+        .json(image.clone())
+        .send()
+        .await
+        .assert_error(ErrorCode::EventNotOwned);
+    app.post(&format!("/events/{today}/finish"))
+        .auth(&alice)
+        .json(json!({ "memory_image_base64": "" }))
+        .send()
+        .await
+        .assert_error(ErrorCode::MemoryImageRequired);
+
+    let finished = app
+        .post(&format!("/events/{today}/finish"))
+        .auth(&alice)
+        .json(image.clone())
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    assert_eq!(finished.body["status"], "completed");
+    assert_eq!(finished.body["memory_image_base64"], "aGVsbG8=");
+
+    app.post(&format!("/events/{today}/finish"))
+        .auth(&alice)
+        .json(image)
+        .send()
+        .await
+        .assert_error(ErrorCode::EventClosed);
+    app.post(&format!("/events/{today}/cancel"))
+        .auth(&alice)
+        .send()
+        .await
+        .assert_error(ErrorCode::EventCompleted);
+}
+
+#[tokio::test]
+async fn list_scopes() {
+    let app = TestApp::spawn().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+    app.befriend(&alice, &bob).await;
+    let mine = app.create_event(&alice, &date(1), &[&bob]).await;
+    let theirs = app.create_event(&bob, &date(2), &[&alice]).await;
+    let past = app.create_event(&alice, &date(-2), &[]).await;
+
+    let created = app.get("/events?scope=created").auth(&alice).send().await;
+    assert_eq!(created.pluck("/id"), vec![json!(past), json!(mine)]);
+    let invited = app.get("/events?scope=invited").auth(&alice).send().await;
+    assert_eq!(invited.pluck("/id"), vec![json!(theirs)]);
+    let past_scope = app.get("/events?scope=past").auth(&alice).send().await;
+    assert_eq!(past_scope.pluck("/id"), vec![json!(past)]);
+
+    // Upcoming is the default and only holds accepted events.
+    let upcoming = app
+        .get("/events")
+        .auth(&alice)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    assert_eq!(upcoming.pluck("/id"), vec![json!(mine)]);
+    app.post(&format!("/events/{theirs}/accept"))
+        .auth(&alice)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    let upcoming = app.get("/events?scope=upcoming").auth(&alice).send().await;
+    assert_eq!(upcoming.pluck("/id"), vec![json!(mine), json!(theirs)]);
+
+    app.get("/events?scope=everything")
+        .auth(&alice)
+        .send()
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+    app.get("/events?limit=0")
+        .auth(&alice)
+        .send()
+        .await
+        .assert_error(ErrorCode::InvalidPagination);
+}
+
+#[tokio::test]
+async fn availability_checks() {
+    let app = TestApp::spawn().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+    let carol = app.register("carol").await;
+    app.befriend(&alice, &bob).await;
+    app.befriend(&alice, &carol).await;
+    app.befriend(&bob, &carol).await;
+    let event = app.create_event(&bob, &date(3), &[&carol]).await;
+
+    let me = app
+        .get(&format!("/events/check-user-availability?date={}", date(3)))
+        .auth(&bob)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    assert_eq!(me.body["is_available"], false);
+
+    let friends = app
+        .get(&format!("/events/check-availability?date={}", date(3)))
+        .auth(&alice)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    let names: Vec<_> = friends.body["available_friends"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|friend| friend["username"].clone())
+        .collect();
+    assert_eq!(names, vec![json!("carol")]);
+
+    app.post(&format!("/events/{event}/accept"))
+        .auth(&carol)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    let friends = app
+        .get(&format!("/events/check-availability?date={}", date(3)))
+        .auth(&alice)
+        .send()
+        .await;
+    assert_eq!(friends.body["available_friends"], json!([]));
+
+    for path in [
+        "/events/check-user-availability?date=tomorrow",
+        "/events/check-availability?date=tomorrow",
+    ] {
+        app.get(path)
+            .auth(&alice)
+            .send()
+            .await
+            .assert_error(ErrorCode::InvalidDate);
+    }
+}
+
+#[tokio::test]
+async fn today_follows_the_users_timezone() {
+    let app = TestApp::spawn().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+    app.befriend(&alice, &bob).await;
+    // 20:00 UTC on the 10th is already 10:00 on the 11th at UTC+14.
+    app.clock
+        .set("2030-06-10T20:00:00Z".parse::<DateTime<Utc>>().unwrap());
+    let tenth = app.create_event(&alice, "2030-06-10", &[]).await;
+    let eleventh = app.create_event(&alice, "2030-06-11", &[&bob]).await;
+    let image = json!({ "memory_image_base64": "aGVsbG8=" });
+
+    app.post(&format!("/events/{eleventh}/finish"))
+        .auth(&alice)
+        .json(image.clone())
+        .send()
+        .await
+        .assert_error(ErrorCode::EventNotStarted);
+    let scope = |session: &Session, scope: &str| {
+        app.get(&format!("/events?scope={scope}"))
+            .auth(session)
+            .send()
+    };
+    assert!(scope(&alice, "past").await.pluck("/id").is_empty());
+
+    app.patch("/users/me")
+        .auth(&alice)
+        .json(json!({ "timezone": "Pacific/Kiritimati" }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    assert_eq!(scope(&alice, "past").await.pluck("/id"), [json!(tenth)]);
+    app.post(&format!("/events/{eleventh}/finish"))
+        .auth(&alice)
+        .json(image)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+}
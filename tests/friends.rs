@@ -0,0 +1,155 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::TestApp;
+use friends_server::error::ErrorCode;
+use serde_json::json;
+
+#[tokio::test]
+async fn request_and_accept() {
+    let app = TestApp::spawn().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+
+    app.post("/friends/request")
+        .auth(&alice)
+        .json(json!({ "friend_id": bob.user_id }))
+        .send()
+        .await
+        .assert_status(StatusCode::CREATED);
+
+    let outgoing = app.get("/friends/outgoing").auth(&alice).send().await;
+    assert_eq!(outgoing.pluck("/username"), vec![json!("bob")]);
+    let incoming = app.get("/friends/incoming").auth(&bob).send().await;
+    assert_eq!(incoming.pluck("/username"), vec![json!("alice")]);
+
+    app.post(&format!("/friends/{}/accept", alice.user_id))
+        .auth(&bob)
+        .send()
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+
+    for (me, friend) in [(&alice, "bob"), (&bob, "alice")] {
+        let friends = app.get("/friends").auth(me).send().await;
+        assert_eq!(friends.pluck("/username"), vec![json!(friend)]);
+    }
+    let incoming = app.get("/friends/incoming").auth(&bob).send().await;
+    assert!(incoming.pluck("/username").is_empty());
+}
+
+#[tokio::test]
+async fn request_rejects_self_and_duplicates() {
+    let app = TestApp::spawn().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+
+    app.post("/friends/request")
+        .auth(&alice)
+        .json(json!({ "friend_id": alice.user_id }))
+        .send()
+        .await
+        .assert_error(ErrorCode::CannotBefriendSelf);
+
+    let request = json!({ "friend_id": bob.user_id });
+    app.post("/friends/request")
+        .auth(&alice)
+        .json(request.clone())
+        .send()
+        .await
+        .assert_status(StatusCode::CREATED);
+    app.post("/friends/request")
+        .auth(&alice)
+        .json(request)
+        .send()
+        .await
+        .assert_error(ErrorCode::FriendRequestExists);
+}
+
+#[tokio::test]
+async fn accept_and_reject_need_a_pending_request() {
+    let app = TestApp::spawn().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+
+    for action in ["accept", "reject"] {
+        app.post(&format!("/friends/{}/{action}", alice.user_id))
+            .auth(&alice)
+            .send()
+            .await
+            .assert_error(ErrorCode::OwnFriendRequest);
+        app.post(&format!("/friends/{}/{action}", alice.user_id))
+            .auth(&bob)
+            .send()
+            .await
+            .assert_error(ErrorCode::FriendRequestNotFound);
+    }
+}
+
+#[tokio::test]
+async fn reject_drops_the_request() {
+    let app = TestApp::spawn().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+
+    app.post("/friends/request")
+        .auth(&alice)
+        .json(json!({ "friend_id": bob.user_id }))
+        .send()
+        .await
+        .assert_status(StatusCode::CREATED);
+    app.post(&format!("/friends/{}/reject", alice.user_id))
+        .auth(&bob)
+        .send()
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+
+    let outgoing = app.get("/friends/outgoing").auth(&alice).send().await;
+    assert!(outgoing.pluck("/username").is_empty());
+    let friends = app.get("/friends").auth(&bob).send().await;
+    assert!(friends.pluck("/username").is_empty());
+}
+
+#[tokio::test]
+async fn remove_friend() {
+    let app = TestApp::spawn().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+    app.befriend(&alice, &bob).await;
+
+    app.delete(&format!("/friends/{}/remove", alice.user_id))
+        .auth(&alice)
+        .send()
+        .await
+        .assert_error(ErrorCode::CannotRemoveSelf);
+    app.delete(&format!("/friends/{}/remove", alice.user_id))
+        .auth(&bob)
+        .send()
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    app.delete(&format!("/friends/{}/remove", alice.user_id))
+        .auth(&bob)
+        .send()
+        .await
+        .assert_error(ErrorCode::FriendshipNotFound);
+
+    let friends = app.get("/friends").auth(&alice).send().await;
+    assert!(friends.pluck("/username").is_empty());
+}
+
+#[tokio::test]
+async fn lists_require_auth_and_valid_pages() {
+    let app = TestApp::spawn().await;
+    let alice = app.register("alice").await;
+
+    for path in ["/friends", "/friends/incoming", "/friends/outgoing"] {
+        app.get(path)
+            .send()
+            .await
+            .assert_error(ErrorCode::SessionExpired);
+        app.get(&format!("{path}?limit=101"))
+            .auth(&alice)
+            .send()
+            .await
+            .assert_error(ErrorCode::InvalidPagination);
+    }
+}
@@ -0,0 +1,40 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::TestApp;
+
+#[tokio::test]
+async fn liveness_and_readiness() {
+    let app = TestApp::spawn().await;
+
+    let health = app
+        .get("/healthz")
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    assert_eq!(health.body["status"], "ok");
+
+    let ready = app
+        .get("/readyz")
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    assert_eq!(ready.body["ready"], true);
+    assert_eq!(ready.body["pending_migrations"], 0);
+}
+
+#[tokio::test]
+async fn metrics_and_openapi_are_served() {
+    let app = TestApp::spawn().await;
+
+    app.get("/metrics")
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    let spec = app
+        .get("/api-doc/openapi.json")
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    assert!(spec.body["paths"]["/events"].is_object());
+}
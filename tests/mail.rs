@@ -0,0 +1,115 @@
+use std::path::PathBuf;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use friends_server::config::{SmtpConfig, SmtpTls};
+use friends_server::mail::{FileMailer, Mailer, Message, SmtpMailer};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+use uuid::Uuid;
+
+const FROM: &str = "Friends <no-reply@example.com>";
+
+fn message() -> Message {
+    Message {
+        to: "alice@example.com".to_string(),
+        subject: "Сброс пароля".to_string(),
+        body: "Привет!\n.\nfriends://reset-password?token=abc\n".to_string(),
+    }
+}
+
+/// The decoded body of an RFC 5322 mail with a base64 body.
+fn body(mail: &str) -> String {
+    let (_, encoded) = mail.split_once("\r\n\r\n").expect("headers end");
+    let encoded: String = encoded.split("\r\n").collect();
+    String::from_utf8(STANDARD.decode(encoded).unwrap()).unwrap()
+}
+
+#[tokio::test]
+async fn file_mailer_writes_an_eml_file() {
+    let dir = std::env::temp_dir().join(format!("friends-mail-{}", Uuid::new_v4().simple()));
+    FileMailer::new(&dir, FROM).send(&message()).await.unwrap();
+
+    let files: Vec<PathBuf> = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(files.len(), 1);
+    assert_eq!(files[0].extension().unwrap(), "eml");
+}
+
+#[tokio::test]
+async fn smtp_mailer_speaks_smtp() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let server = tokio::spawn(async move {
+        let (socket, _) = listener.accept().await.unwrap();
+        let (read, mut write) = socket.into_split();
+        let mut lines = BufReader::new(read).lines();
+        let mut commands = Vec::new();
+        let mut data = String::new();
+        write.write_all(b"220 test ready\r\n").await.unwrap();
+        while let Some(line) = lines.next_line().await.unwrap() {
+            let reply: &[u8] = match line.split(' ').next().unwrap() {
+                "EHLO" => b"250-test\r\n250 AUTH PLAIN\r\n",
+                "AUTH" => b"235 ok\r\n",
+                "DATA" => {
+                    write.write_all(b"354 go ahead\r\n").await.unwrap();
+                    while let Some(line) = lines.next_line().await.unwrap() {
+                        if line == "." {
+                            break;
+                        }
+                        data.push_str(&line);
+                        data.push_str("\r\n");
+                    }
+                    b"250 queued\r\n"
+                }
+                "QUIT" => {
+                    write.write_all(b"221 bye\r\n").await.unwrap();
+                    commands.push(line);
+                    break;
+                }
+                _ => b"250 ok\r\n",
+            };
+            write.write_all(reply).await.unwrap();
+            commands.push(line);
+        }
+        (commands, data)
+    });
+
+    let config = SmtpConfig {
+        host: "127.0.0.1".to_string(),
+        port,
+        tls: SmtpTls::None,
+        username: "mailer".to_string(),
+        password: "secret".to_string(),
+        ..SmtpConfig::default()
+    };
+    SmtpMailer::new(&config, FROM)
+        .unwrap()
+        .send(&message())
+        .await
+        .unwrap();
+
+    let (commands, data) = server.await.unwrap();
+    let credentials = STANDARD.encode("\0mailer\0secret");
+    assert_eq!(
+        commands,
+        [
+            "EHLO example.com".to_string(),
+            format!("AUTH PLAIN {credentials}"),
+            "MAIL FROM:<no-reply@example.com>".to_string(),
+            "RCPT TO:<alice@example.com>".to_string(),
+            "DATA".to_string(),
+            "QUIT".to_string(),
+        ]
+    );
+    assert!(data.contains("To: alice@example.com\r\n"));
+    assert!(data.contains(&format!(
+        "Subject: =?utf-8?b?{}?=\r\n",
+        STANDARD.encode("Сброс пароля")
+    )));
+    assert!(data.contains("Message-ID: <"));
+    assert_eq!(body(&data).replace("\r\n", "\n"), message().body);
+}
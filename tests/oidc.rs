@@ -0,0 +1,348 @@
+mod common;
+
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::{Json, Router};
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use chrono::{TimeDelta, Utc};
+use common::{PASSWORD, Session, TestApp, TestResponse};
+use ed25519_dalek::SigningKey;
+use ed25519_dalek::pkcs8::EncodePrivateKey;
+use ed25519_dalek::pkcs8::spki::der::pem::LineEnding;
+use friends_server::auth::totp;
+use friends_server::clock::Clock;
+use friends_server::config::OidcProviderConfig;
+use friends_server::error::ErrorCode;
+use jsonwebtoken::{Algorithm, EncodingKey, Header, encode};
+use rand::RngCore;
+use serde_json::{Value, json};
+
+const ISSUER: &str = "https://issuer.test";
+const CLIENT_ID: &str = "friends-app";
+const NONCE: &str = "n-0S6_WzA2Mj";
+
+/// An OIDC provider serving its JWKS over HTTP on a local port.
+struct MockIssuer {
+    jwks_url: String,
+    kid: String,
+    key: EncodingKey,
+}
+
+impl MockIssuer {
+    async fn start() -> Self {
+        let kid = "mock-key-1".to_string();
+        let (key, public_key) = ed25519_key();
+        let jwks = json!({
+            "keys": [{
+                "kty": "OKP",
+                "crv": "Ed25519",
+                "alg": "EdDSA",
+                "use": "sig",
+                "kid": kid,
+                "x": URL_SAFE_NO_PAD.encode(public_key),
+            }]
+        });
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let router = Router::new().route("/jwks", get(move || async move { Json(jwks) }));
+        tokio::spawn(async move { axum::serve(listener, router).await });
+        MockIssuer {
+            jwks_url: format!("http://{addr}/jwks"),
+            kid,
+            key,
+        }
+    }
+
+    async fn app(&self) -> TestApp {
+        let provider = OidcProviderConfig {
+            name: "mock".to_string(),
+            issuer: ISSUER.to_string(),
+            jwks_url: self.jwks_url.clone(),
+            client_ids: vec![CLIENT_ID.to_string()],
+        };
+        TestApp::spawn_with(|config| config.oidc.providers = vec![provider]).await
+    }
+
+    /// An ID token for `sub` carrying [`NONCE`], with `extra` claims added
+    /// or replaced.
+    fn token(&self, sub: &str, extra: Value) -> String {
+        let mut claims = json!({ "nonce": NONCE });
+        for (name, value) in extra.as_object().unwrap() {
+            claims[name] = value.clone();
+        }
+        sign(&self.kid, &self.key, sub, claims)
+    }
+}
+
+fn ed25519_key() -> (EncodingKey, [u8; 32]) {
+    let mut secret = [0u8; 32];
+    rand::rng().fill_bytes(&mut secret);
+    let signing = SigningKey::from_bytes(&secret);
+    let pem = signing.to_pkcs8_pem(LineEnding::LF).unwrap();
+    let key = EncodingKey::from_ed_pem(pem.as_bytes()).unwrap();
+    (key, signing.verifying_key().to_bytes())
+}
+
+fn sign(kid: &str, key: &EncodingKey, sub: &str, extra: Value) -> String {
+    let now = Utc::now().timestamp();
+    let mut claims = json!({
+        "iss": ISSUER,
+        "aud": CLIENT_ID,
+        "sub": sub,
+        "iat": now,
+        "exp": now + 600,
+    });
+    for (name, value) in extra.as_object().unwrap() {
+        claims[name] = value.clone();
+    }
+    let mut header = Header::new(Algorithm::EdDSA);
+    header.kid = Some(kid.to_string());
+    encode(&header, &claims, key).unwrap()
+}
+
+async fn oidc_login(app: &TestApp, id_token: &str) -> TestResponse {
+    app.post("/auth/oidc/mock")
+        .json(json!({ "id_token": id_token, "nonce": NONCE }))
+        .send()
+        .await
+}
+
+async fn link(app: &TestApp, session: &Session, id_token: &str) -> TestResponse {
+    app.post("/users/me/identities/mock")
+        .auth(session)
+        .json(json!({ "id_token": id_token, "nonce": NONCE }))
+        .send()
+        .await
+}
+
+fn session(response: &TestResponse) -> Session {
+    Session {
+        user_id: response.uuid("/user/id"),
+        username: response.str("/user/username").to_string(),
+        access: response.str("/access_token").to_string(),
+        refresh: response.str("/refresh_token").to_string(),
+    }
+}
+
+#[tokio::test]
+async fn first_login_creates_the_account_and_later_ones_reuse_it() {
+    let issuer = MockIssuer::start().await;
+    let app = issuer.app().await;
+    // Apple sends `email_verified` as a string.
+    let token = issuer.token(
+        "apple-001",
+        json!({ "email": "Alice@Example.com", "email_verified": "true" }),
+    );
+
+    let created = oidc_login(&app, &token)
+        .await
+        .assert_status(StatusCode::CREATED);
+    assert_eq!(created.str("/user/username"), "alice");
+    assert_eq!(created.str("/user/email"), "alice@example.com");
+    assert_eq!(created.body["user"]["email_verified"], true);
+    let alice = session(&created);
+
+    let again = oidc_login(&app, &token).await.assert_status(StatusCode::OK);
+    assert_eq!(again.uuid("/user/id"), alice.user_id);
+
+    // The account has no password to log in with.
+    app.post("/auth/login")
+        .json(json!({ "username": "alice", "password": "" }))
+        .send()
+        .await
+        .assert_error(ErrorCode::MissingCredentials);
+    app.post("/auth/login")
+        .json(json!({ "username": "alice", "password": PASSWORD }))
+        .send()
+        .await
+        .assert_error(ErrorCode::InvalidCredentials);
+
+    let identities = app
+        .get("/users/me/identities")
+        .auth(&alice)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    assert_eq!(identities.body[0]["provider"], "mock");
+    assert_eq!(identities.body[0]["email"], "Alice@Example.com");
+
+    // Another person whose address has the same local part gets a free name.
+    let other = oidc_login(
+        &app,
+        &issuer.token("apple-002", json!({ "email": "alice@other.test" })),
+    )
+    .await
+    .assert_status(StatusCode::CREATED);
+    let username = other.str("/user/username");
+    assert!(username.starts_with("alice") && username != "alice");
+    assert!(other.body["user"].get("email").is_none());
+}
+
+#[tokio::test]
+async fn invalid_tokens_are_rejected() {
+    let issuer = MockIssuer::start().await;
+    let app = issuer.app().await;
+    let now = Utc::now().timestamp();
+    let (stranger_key, _) = ed25519_key();
+
+    for token in [
+        issuer.token("sub-1", json!({ "aud": "another-app" })),
+        issuer.token("sub-1", json!({ "iss": "https://evil.test" })),
+        issuer.token("sub-1", json!({ "exp": now - 3600 })),
+        sign(&issuer.kid, &stranger_key, "sub-1", json!({})),
+        sign("unknown-kid", &stranger_key, "sub-1", json!({})),
+        sign(&issuer.kid, &issuer.key, "sub-1", json!({})),
+        "not a token".to_string(),
+    ] {
+        oidc_login(&app, &token)
+            .await
+            .assert_error(ErrorCode::InvalidIdToken);
+    }
+
+    let token = issuer.token("sub-1", json!({}));
+    app.post("/auth/oidc/mock")
+        .json(json!({ "id_token": token, "nonce": "other" }))
+        .send()
+        .await
+        .assert_error(ErrorCode::InvalidIdToken);
+    app.post("/auth/oidc/mock")
+        .json(json!({ "id_token": token }))
+        .send()
+        .await
+        .assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+    oidc_login(&app, &token)
+        .await
+        .assert_status(StatusCode::CREATED);
+
+    app.post("/auth/oidc/nope")
+        .json(json!({ "id_token": token, "nonce": NONCE }))
+        .send()
+        .await
+        .assert_error(ErrorCode::UnknownIdentityProvider);
+}
+
+#[tokio::test]
+async fn existing_accounts_link_instead_of_being_taken_over() {
+    let issuer = MockIssuer::start().await;
+    let app = issuer.app().await;
+    let bob = app.register("bob").await;
+    app.verify_email(&bob, "bob@example.com").await;
+    let token = issuer.token(
+        "google-bob",
+        json!({ "email": "bob@example.com", "email_verified": true }),
+    );
+
+    oidc_login(&app, &token)
+        .await
+        .assert_error(ErrorCode::IdentityEmailInUse);
+
+    link(&app, &bob, &token)
+        .await
+        .assert_status(StatusCode::CREATED);
+    link(&app, &bob, &token)
+        .await
+        .assert_error(ErrorCode::ProviderAlreadyLinked);
+    link(&app, &bob, &issuer.token("google-bob-2", json!({})))
+        .await
+        .assert_error(ErrorCode::ProviderAlreadyLinked);
+    let carol = app.register("carol").await;
+    link(&app, &carol, &token)
+        .await
+        .assert_error(ErrorCode::IdentityLinkedElsewhere);
+
+    let response = oidc_login(&app, &token).await.assert_status(StatusCode::OK);
+    assert_eq!(response.uuid("/user/id"), bob.user_id);
+}
+
+#[tokio::test]
+async fn the_only_way_to_log_in_cannot_be_unlinked() {
+    let issuer = MockIssuer::start().await;
+    let app = issuer.app().await;
+    let created = oidc_login(&app, &issuer.token("sub-dave", json!({ "name": "Dave" })))
+        .await
+        .assert_status(StatusCode::CREATED);
+    assert_eq!(created.str("/user/username"), "dave");
+    let dave = session(&created);
+    app.delete("/users/me/identities/mock")
+        .auth(&dave)
+        .send()
+        .await
+        .assert_error(ErrorCode::LastSignInMethod);
+
+    let bob = app.register("bob").await;
+    link(&app, &bob, &issuer.token("sub-bob", json!({})))
+        .await
+        .assert_status(StatusCode::CREATED);
+    app.delete("/users/me/identities/mock")
+        .auth(&bob)
+        .send()
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    app.delete("/users/me/identities/mock")
+        .auth(&bob)
+        .send()
+        .await
+        .assert_error(ErrorCode::IdentityNotFound);
+
+    // Unlinked, the provider account starts a new one.
+    let response = oidc_login(&app, &issuer.token("sub-bob", json!({})))
+        .await
+        .assert_status(StatusCode::CREATED);
+    assert_ne!(response.uuid("/user/id"), bob.user_id);
+}
+
+#[tokio::test]
+async fn accounts_without_a_password_can_use_two_factor_login() {
+    let issuer = MockIssuer::start().await;
+    let app = issuer.app().await;
+    let token = issuer.token("sub-erin", json!({ "name": "Erin" }));
+    let erin = session(
+        &oidc_login(&app, &token)
+            .await
+            .assert_status(StatusCode::CREATED),
+    );
+
+    // Without a password, a recent login stands in for it.
+    app.clock.advance(TimeDelta::minutes(11));
+    app.post("/users/me/2fa/totp")
+        .auth(&erin)
+        .json(json!({}))
+        .send()
+        .await
+        .assert_error(ErrorCode::RecentLoginRequired);
+    let erin = session(&oidc_login(&app, &token).await.assert_status(StatusCode::OK));
+    let secret = app
+        .post("/users/me/2fa/totp")
+        .auth(&erin)
+        .json(json!({}))
+        .send()
+        .await
+        .assert_status(StatusCode::OK)
+        .str("/secret")
+        .to_string();
+    let code = |app: &TestApp| {
+        let secret = totp::base32_decode(&secret).unwrap();
+        totp::code(&secret, totp::step_at(app.clock.now()))
+    };
+    app.post("/users/me/2fa/totp/confirm")
+        .auth(&erin)
+        .json(json!({ "code": code(&app) }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+
+    let challenge = oidc_login(&app, &token)
+        .await
+        .assert_status(StatusCode::ACCEPTED);
+    assert!(challenge.body.get("access_token").is_none());
+
+    app.clock.advance(TimeDelta::seconds(totp::STEP_SECS));
+    app.delete("/users/me/2fa/totp")
+        .auth(&erin)
+        .json(json!({ "code": code(&app) }))
+        .send()
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    oidc_login(&app, &token).await.assert_status(StatusCode::OK);
+}
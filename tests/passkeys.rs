@@ -0,0 +1,442 @@
+mod common;
+
+use axum::http::StatusCode;
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use ciborium::Value as Cbor;
+use common::{Session, TestApp, TestResponse};
+use coset::{CborSerializable, CoseKeyBuilder, iana};
+use friends_server::entities::{User, UserActiveModel};
+use friends_server::error::ErrorCode;
+use p256::ecdsa::signature::Signer;
+use p256::ecdsa::{DerSignature, SigningKey};
+use rand::RngCore;
+use sea_orm::{ActiveModelTrait, EntityTrait, Set};
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+const ORIGIN: &str = "http://localhost:3000";
+const RP_ID: &str = "localhost";
+
+/// A software authenticator holding one ES256 passkey.
+struct Authenticator {
+    key: SigningKey,
+    credential_id: Vec<u8>,
+    user_handle: Option<String>,
+    counter: u32,
+    origin: String,
+}
+
+impl Authenticator {
+    fn new() -> Self {
+        let mut secret = [0u8; 32];
+        rand::rng().fill_bytes(&mut secret);
+        let mut credential_id = vec![0u8; 16];
+        rand::rng().fill_bytes(&mut credential_id);
+        Authenticator {
+            key: SigningKey::from_slice(&secret).unwrap(),
+            credential_id,
+            user_handle: None,
+            counter: 0,
+            origin: ORIGIN.to_string(),
+        }
+    }
+
+    /// The result of `navigator.credentials.create()` for `options`.
+    fn create(&mut self, options: &Value) -> Value {
+        let public_key = &options["public_key"];
+        self.user_handle = public_key["user"]["id"].as_str().map(String::from);
+        let client_data = self.client_data("webauthn.create", public_key);
+
+        let point = self.key.verifying_key().to_encoded_point(false);
+        let cose_key = CoseKeyBuilder::new_ec2_pub_key(
+            iana::EllipticCurve::P_256,
+            point.x().unwrap().to_vec(),
+            point.y().unwrap().to_vec(),
+        )
+        .algorithm(iana::Algorithm::ES256)
+        .build()
+        .to_vec()
+        .unwrap();
+        // User present, user verified, attested credential data.
+        let mut auth_data = self.auth_data(0x45);
+        auth_data.extend_from_slice(&[0u8; 16]);
+        auth_data.extend_from_slice(&(self.credential_id.len() as u16).to_be_bytes());
+        auth_data.extend_from_slice(&self.credential_id);
+        auth_data.extend_from_slice(&cose_key);
+        let mut attestation_object = Vec::new();
+        ciborium::into_writer(
+            &Cbor::Map(vec![
+                (Cbor::from("fmt"), Cbor::from("none")),
+                (Cbor::from("attStmt"), Cbor::Map(vec![])),
+                (Cbor::from("authData"), Cbor::Bytes(auth_data)),
+            ]),
+            &mut attestation_object,
+        )
+        .unwrap();
+
+        json!({
+            "id": b64(&self.credential_id),
+            "rawId": b64(&self.credential_id),
+            "type": "public-key",
+            "response": {
+                "clientDataJSON": b64(&client_data),
+                "attestationObject": b64(&attestation_object),
+            },
+        })
+    }
+
+    /// The result of `navigator.credentials.get()` for `options`.
+    fn get(&mut self, options: &Value) -> Value {
+        self.counter += 1;
+        let client_data = self.client_data("webauthn.get", &options["public_key"]);
+        let auth_data = self.auth_data(0x05);
+        let mut message = auth_data.clone();
+        message.extend_from_slice(&Sha256::digest(&client_data));
+        let signature: DerSignature = self.key.sign(&message);
+
+        json!({
+            "id": b64(&self.credential_id),
+            "rawId": b64(&self.credential_id),
+            "type": "public-key",
+            "response": {
+                "clientDataJSON": b64(&client_data),
+                "authenticatorData": b64(&auth_data),
+                "signature": b64(signature.as_bytes()),
+                "userHandle": self.user_handle,
+            },
+        })
+    }
+
+    fn client_data(&self, kind: &str, public_key: &Value) -> Vec<u8> {
+        json!({
+            "type": kind,
+            "challenge": public_key["challenge"],
+            "origin": self.origin,
+            "crossOrigin": false,
+        })
+        .to_string()
+        .into_bytes()
+    }
+
+    fn auth_data(&self, flags: u8) -> Vec<u8> {
+        let mut data = Sha256::digest(RP_ID.as_bytes()).to_vec();
+        data.push(flags);
+        data.extend_from_slice(&self.counter.to_be_bytes());
+        data
+    }
+}
+
+fn b64(data: &[u8]) -> String {
+    URL_SAFE_NO_PAD.encode(data)
+}
+
+async fn add_passkey(
+    app: &TestApp,
+    session: &Session,
+    authenticator: &mut Authenticator,
+    name: &str,
+) -> TestResponse {
+    let options = app
+        .post("/users/me/passkeys/register/options")
+        .auth(session)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    let credential = authenticator.create(&options.body);
+    app.post("/users/me/passkeys/register")
+        .auth(session)
+        .json(json!({
+            "ceremony_id": options.body["ceremony_id"],
+            "name": name,
+            "credential": credential,
+        }))
+        .send()
+        .await
+}
+
+async fn login_options(app: &TestApp) -> Value {
+    app.post("/auth/passkey/options")
+        .send()
+        .await
+        .assert_status(StatusCode::OK)
+        .body
+}
+
+async fn passkey_login(app: &TestApp, options: &Value, credential: Value) -> TestResponse {
+    app.post("/auth/passkey")
+        .json(json!({
+            "ceremony_id": options["ceremony_id"],
+            "credential": credential,
+            "device_name": "Alice's iPhone",
+        }))
+        .send()
+        .await
+}
+
+#[tokio::test]
+async fn a_registered_passkey_logs_in() {
+    let app = TestApp::spawn().await;
+    let alice = app.register("alice").await;
+    let mut authenticator = Authenticator::new();
+
+    let added = add_passkey(&app, &alice, &mut authenticator, " iPhone ")
+        .await
+        .assert_status(StatusCode::CREATED);
+    assert_eq!(added.str("/name"), "iPhone");
+    assert!(added.body["last_used_at"].is_null());
+
+    let options = login_options(&app).await;
+    assert_eq!(options["public_key"]["rpId"], RP_ID);
+    let credential = authenticator.get(&options);
+    let login = passkey_login(&app, &options, credential.clone())
+        .await
+        .assert_status(StatusCode::OK);
+    assert_eq!(login.uuid("/user/id"), alice.user_id);
+    let me = app
+        .get("/users/me")
+        .bearer(login.str("/access_token"))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    assert_eq!(me.str("/username"), "alice");
+
+    // Each ceremony works once.
+    passkey_login(&app, &options, credential)
+        .await
+        .assert_error(ErrorCode::PasskeyChallengeExpired);
+
+    let listed = app
+        .get("/users/me/passkeys")
+        .auth(&alice)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    assert_eq!(listed.pluck("/name"), vec![json!("iPhone")]);
+    assert!(listed.body[0]["last_used_at"].is_string());
+
+    // A second authenticator is a second passkey; the first is excluded.
+    let options = app
+        .post("/users/me/passkeys/register/options")
+        .auth(&alice)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    assert_eq!(
+        options.body["public_key"]["excludeCredentials"][0]["id"],
+        b64(&authenticator.credential_id)
+    );
+    add_passkey(&app, &alice, &mut Authenticator::new(), "YubiKey")
+        .await
+        .assert_status(StatusCode::CREATED);
+    let listed = app.get("/users/me/passkeys").auth(&alice).send().await;
+    assert_eq!(
+        listed.pluck("/name"),
+        vec![json!("iPhone"), json!("YubiKey")]
+    );
+}
+
+#[tokio::test]
+async fn assertions_that_do_not_verify_are_rejected() {
+    let app = TestApp::spawn().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+    let mut authenticator = Authenticator::new();
+    add_passkey(&app, &alice, &mut authenticator, "iPhone")
+        .await
+        .assert_status(StatusCode::CREATED);
+
+    // Signed for another ceremony.
+    let options = login_options(&app).await;
+    let other = login_options(&app).await;
+    let credential = authenticator.get(&other);
+    passkey_login(&app, &options, credential)
+        .await
+        .assert_error(ErrorCode::InvalidPasskey);
+
+    // A phishing site.
+    authenticator.origin = "https://friends.evil".to_string();
+    let options = login_options(&app).await;
+    let credential = authenticator.get(&options);
+    passkey_login(&app, &options, credential)
+        .await
+        .assert_error(ErrorCode::InvalidPasskey);
+    authenticator.origin = ORIGIN.to_string();
+
+    // A tampered signature.
+    let options = login_options(&app).await;
+    let mut credential = authenticator.get(&options);
+    let mut signature = URL_SAFE_NO_PAD
+        .decode(credential["response"]["signature"].as_str().unwrap())
+        .unwrap();
+    let last = signature.len() - 1;
+    signature[last] ^= 1;
+    credential["response"]["signature"] = json!(b64(&signature));
+    passkey_login(&app, &options, credential)
+        .await
+        .assert_error(ErrorCode::InvalidPasskey);
+
+    // Claiming to be another user.
+    let options = login_options(&app).await;
+    let mut credential = authenticator.get(&options);
+    credential["response"]["userHandle"] = json!(b64(bob.user_id.as_bytes()));
+    passkey_login(&app, &options, credential)
+        .await
+        .assert_error(ErrorCode::InvalidPasskey);
+
+    // A passkey nobody registered.
+    let options = login_options(&app).await;
+    let credential = Authenticator::new().get(&options);
+    passkey_login(&app, &options, credential)
+        .await
+        .assert_error(ErrorCode::InvalidPasskey);
+
+    // A clone whose counter lags behind the original.
+    let options = login_options(&app).await;
+    let credential = authenticator.get(&options);
+    passkey_login(&app, &options, credential)
+        .await
+        .assert_status(StatusCode::OK);
+    authenticator.counter -= 2;
+    let options = login_options(&app).await;
+    let credential = authenticator.get(&options);
+    passkey_login(&app, &options, credential)
+        .await
+        .assert_error(ErrorCode::InvalidPasskey);
+
+    passkey_login(
+        &app,
+        &json!({ "ceremony_id": Uuid::new_v4() }),
+        json!({
+            "rawId": "",
+            "response": { "clientDataJSON": "", "authenticatorData": "", "signature": "" },
+        }),
+    )
+    .await
+    .assert_error(ErrorCode::PasskeyChallengeExpired);
+}
+
+#[tokio::test]
+async fn registration_checks_the_ceremony_and_the_credential() {
+    let app = TestApp::spawn().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+    let mut authenticator = Authenticator::new();
+
+    add_passkey(&app, &alice, &mut authenticator, "  ")
+        .await
+        .assert_error(ErrorCode::InvalidPasskeyName);
+    add_passkey(&app, &alice, &mut authenticator, &"x".repeat(65))
+        .await
+        .assert_error(ErrorCode::InvalidPasskeyName);
+
+    // Bob cannot finish a ceremony Alice started.
+    let options = app
+        .post("/users/me/passkeys/register/options")
+        .auth(&alice)
+        .send()
+        .await;
+    let credential = authenticator.create(&options.body);
+    app.post("/users/me/passkeys/register")
+        .auth(&bob)
+        .json(json!({
+            "ceremony_id": options.body["ceremony_id"],
+            "name": "iPhone",
+            "credential": credential,
+        }))
+        .send()
+        .await
+        .assert_error(ErrorCode::PasskeyChallengeExpired);
+
+    // Not verified against this site.
+    authenticator.origin = "https://friends.evil".to_string();
+    add_passkey(&app, &alice, &mut authenticator, "iPhone")
+        .await
+        .assert_error(ErrorCode::InvalidPasskeyRegistration);
+    authenticator.origin = ORIGIN.to_string();
+
+    // Ceremonies expire.
+    let options = app
+        .post("/users/me/passkeys/register/options")
+        .auth(&alice)
+        .send()
+        .await;
+    app.clock.advance(chrono::Duration::minutes(6));
+    let credential = authenticator.create(&options.body);
+    app.post("/users/me/passkeys/register")
+        .auth(&alice)
+        .json(json!({
+            "ceremony_id": options.body["ceremony_id"],
+            "name": "iPhone",
+            "credential": credential,
+        }))
+        .send()
+        .await
+        .assert_error(ErrorCode::PasskeyChallengeExpired);
+
+    add_passkey(&app, &alice, &mut authenticator, "iPhone")
+        .await
+        .assert_status(StatusCode::CREATED);
+    add_passkey(&app, &bob, &mut authenticator, "iPhone")
+        .await
+        .assert_error(ErrorCode::PasskeyAlreadyRegistered);
+}
+
+#[tokio::test]
+async fn passkeys_are_renamed_and_removed_but_not_the_last_sign_in() {
+    let app = TestApp::spawn().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+    let first = add_passkey(&app, &alice, &mut Authenticator::new(), "iPhone")
+        .await
+        .assert_status(StatusCode::CREATED)
+        .uuid("/id");
+    let second = add_passkey(&app, &alice, &mut Authenticator::new(), "Mac")
+        .await
+        .assert_status(StatusCode::CREATED)
+        .uuid("/id");
+
+    let renamed = app
+        .patch(&format!("/users/me/passkeys/{first}"))
+        .auth(&alice)
+        .json(json!({ "name": "Old iPhone" }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    assert_eq!(renamed.str("/name"), "Old iPhone");
+    app.patch(&format!("/users/me/passkeys/{first}"))
+        .auth(&bob)
+        .json(json!({ "name": "Mine" }))
+        .send()
+        .await
+        .assert_error(ErrorCode::PasskeyNotFound);
+    app.delete(&format!("/users/me/passkeys/{first}"))
+        .auth(&bob)
+        .send()
+        .await
+        .assert_error(ErrorCode::PasskeyNotFound);
+
+    // Without a password the passkeys are all Alice has left.
+    let user = User::find_by_id(alice.user_id)
+        .one(&*app.db)
+        .await
+        .unwrap()
+        .unwrap();
+    let mut active: UserActiveModel = user.into();
+    active.password_hash = Set(String::new());
+    active.update(&*app.db).await.unwrap();
+
+    app.delete(&format!("/users/me/passkeys/{first}"))
+        .auth(&alice)
+        .send()
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    app.delete(&format!("/users/me/passkeys/{second}"))
+        .auth(&alice)
+        .send()
+        .await
+        .assert_error(ErrorCode::LastSignInMethod);
+    let listed = app.get("/users/me/passkeys").auth(&alice).send().await;
+    assert_eq!(listed.pluck("/name"), vec![json!("Mac")]);
+}
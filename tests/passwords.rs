@@ -0,0 +1,166 @@
+mod common;
+
+use axum::http::StatusCode;
+use chrono::TimeDelta;
+use common::{PASSWORD, Session, TestApp, TestResponse, mail_token};
+use friends_server::error::ErrorCode;
+use friends_server::mail::Message;
+use serde_json::json;
+
+const NEW_PASSWORD: &str = "new staple battery horse";
+
+async fn login(app: &TestApp, password: &str) -> TestResponse {
+    app.post("/auth/login")
+        .json(json!({ "username": "alice", "password": password }))
+        .send()
+        .await
+}
+
+async fn refresh(app: &TestApp, session: &Session) -> TestResponse {
+    app.post("/auth/refresh")
+        .json(json!({ "refresh_token": session.refresh }))
+        .send()
+        .await
+}
+
+/// Requests a reset for alice and returns the mail and the token in it.
+async fn request_reset(app: &TestApp) -> (Message, String) {
+    let seen = app.outbox.count();
+    app.post("/auth/password-reset/request")
+        .json(json!({ "username": "alice" }))
+        .send()
+        .await
+        .assert_status(StatusCode::ACCEPTED);
+    let mail = app.outbox.wait_for(seen).await;
+    let token = mail_token(&mail);
+    (mail, token)
+}
+
+async fn confirm(app: &TestApp, token: &str) -> TestResponse {
+    app.post("/auth/password-reset/confirm")
+        .json(json!({ "token": token, "new_password": NEW_PASSWORD }))
+        .send()
+        .await
+}
+
+#[tokio::test]
+async fn change_password_keeps_only_the_current_session() {
+    let app = TestApp::spawn().await;
+    let current = app.register("alice").await;
+    let other = app.login("alice").await;
+
+    app.post("/auth/change-password")
+        .auth(&current)
+        .json(json!({ "current_password": "wrong", "new_password": NEW_PASSWORD }))
+        .send()
+        .await
+        .assert_error(ErrorCode::CurrentPasswordIncorrect);
+    app.post("/auth/change-password")
+        .auth(&current)
+        .json(json!({ "current_password": PASSWORD, "new_password": " " }))
+        .send()
+        .await
+        .assert_error(ErrorCode::PasswordRequired);
+
+    app.post("/auth/change-password")
+        .auth(&current)
+        .json(json!({ "current_password": PASSWORD, "new_password": NEW_PASSWORD }))
+        .send()
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+
+    refresh(&app, &other)
+        .await
+        .assert_error(ErrorCode::SessionExpired);
+    app.get("/users/me")
+        .auth(&other)
+        .send()
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+    app.get("/users/me")
+        .auth(&current)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    refresh(&app, &current).await.assert_status(StatusCode::OK);
+
+    login(&app, PASSWORD)
+        .await
+        .assert_error(ErrorCode::InvalidCredentials);
+    login(&app, NEW_PASSWORD)
+        .await
+        .assert_status(StatusCode::OK);
+}
+
+#[tokio::test]
+async fn password_reset_token_works_once_and_ends_every_session() {
+    let app = TestApp::spawn().await;
+    let session = app.register("alice").await;
+    app.verify_email(&session, "alice@example.com").await;
+
+    let (mail, token) = request_reset(&app).await;
+    assert_eq!(mail.to, "alice@example.com");
+    assert_eq!(mail.subject, "Reset your Friends password");
+    assert!(mail.body.contains("60 minutes"));
+
+    confirm(&app, &token)
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    confirm(&app, &token)
+        .await
+        .assert_error(ErrorCode::InvalidResetToken);
+
+    refresh(&app, &session)
+        .await
+        .assert_error(ErrorCode::SessionExpired);
+    login(&app, PASSWORD)
+        .await
+        .assert_error(ErrorCode::InvalidCredentials);
+    login(&app, NEW_PASSWORD)
+        .await
+        .assert_status(StatusCode::OK);
+}
+
+#[tokio::test]
+async fn password_reset_token_expires_and_is_replaced_by_a_new_one() {
+    let app = TestApp::spawn().await;
+    let session = app.register("alice").await;
+    app.verify_email(&session, "alice@example.com").await;
+
+    let (_, first) = request_reset(&app).await;
+    let (_, second) = request_reset(&app).await;
+    assert_ne!(first, second);
+    confirm(&app, &first)
+        .await
+        .assert_error(ErrorCode::InvalidResetToken);
+
+    app.clock.advance(TimeDelta::hours(1));
+    confirm(&app, &second)
+        .await
+        .assert_error(ErrorCode::InvalidResetToken);
+    login(&app, PASSWORD).await.assert_status(StatusCode::OK);
+}
+
+#[tokio::test]
+async fn password_reset_request_reveals_nothing_about_the_account() {
+    let app = TestApp::spawn().await;
+    let session = app.register("alice").await;
+    // An unverified address gets no reset mail either.
+    app.patch("/users/me")
+        .auth(&session)
+        .json(json!({ "email": "alice@example.com" }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    app.outbox.wait_for(0).await;
+
+    for username in ["alice", "alice@example.com", "nobody"] {
+        app.post("/auth/password-reset/request")
+            .json(json!({ "username": username }))
+            .send()
+            .await
+            .assert_status(StatusCode::ACCEPTED);
+    }
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    assert_eq!(app.outbox.count(), 1);
+}
@@ -0,0 +1,146 @@
+mod common;
+
+use axum::http::{StatusCode, header};
+use chrono::TimeDelta;
+use common::{PASSWORD, TestApp, TestResponse};
+use friends_server::config::{Limit, RateLimitBackend};
+use friends_server::error::ErrorCode;
+use sea_orm::{ConnectionTrait, DbBackend, Statement};
+use serde_json::json;
+
+const LOCKOUT_SECS: u64 = 600;
+
+async fn login(app: &TestApp, password: &str) -> TestResponse {
+    app.post("/auth/login")
+        .json(json!({ "username": "alice", "password": password }))
+        .send()
+        .await
+}
+
+fn retry_after(response: &TestResponse) -> u64 {
+    response
+        .headers
+        .get(header::RETRY_AFTER)
+        .expect("Retry-After header")
+        .to_str()
+        .unwrap()
+        .parse()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn failed_logins_lock_the_account() {
+    let app = TestApp::spawn_with(|config| {
+        config.rate_limit.lockout_failures = 3;
+        config.rate_limit.lockout_secs = LOCKOUT_SECS;
+    })
+    .await;
+    app.register("alice").await;
+
+    for _ in 0..3 {
+        login(&app, "wrong password")
+            .await
+            .assert_error(ErrorCode::InvalidCredentials);
+    }
+    let locked = login(&app, PASSWORD)
+        .await
+        .assert_error(ErrorCode::AccountLocked);
+    assert_eq!(locked.status, StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(retry_after(&locked), LOCKOUT_SECS);
+
+    app.clock.advance(TimeDelta::seconds(LOCKOUT_SECS as i64));
+    login(&app, PASSWORD).await.assert_status(StatusCode::OK);
+}
+
+#[tokio::test]
+async fn successful_login_forgets_failures() {
+    let app = TestApp::spawn_with(|config| {
+        config.rate_limit.lockout_failures = 3;
+    })
+    .await;
+    app.register("alice").await;
+
+    for _ in 0..2 {
+        login(&app, "wrong password").await;
+    }
+    login(&app, PASSWORD).await.assert_status(StatusCode::OK);
+    for _ in 0..2 {
+        login(&app, "wrong password")
+            .await
+            .assert_error(ErrorCode::InvalidCredentials);
+    }
+    login(&app, PASSWORD).await.assert_status(StatusCode::OK);
+}
+
+#[tokio::test]
+async fn requests_are_limited_per_address() {
+    let app = TestApp::spawn_with(|config| {
+        config.rate_limit.trust_forwarded_for = true;
+        config.rate_limit.register.per_ip = Some(Limit {
+            requests: 2,
+            window_secs: 3600,
+        });
+    })
+    .await;
+
+    let register = |username: &'static str, ip: &'static str| {
+        app.post("/auth/register")
+            .header("X-Forwarded-For", ip)
+            .json(json!({ "username": username, "password": PASSWORD }))
+            .send()
+    };
+    register("alice", "203.0.113.7")
+        .await
+        .assert_status(StatusCode::CREATED);
+    register("bob", "203.0.113.7, 10.0.0.1")
+        .await
+        .assert_status(StatusCode::CREATED);
+    let throttled = register("carol", "203.0.113.7")
+        .await
+        .assert_error(ErrorCode::TooManyRequests);
+    assert_eq!(retry_after(&throttled), 3600);
+
+    register("carol", "198.51.100.1")
+        .await
+        .assert_status(StatusCode::CREATED);
+
+    app.clock.advance(TimeDelta::hours(1));
+    register("dave", "203.0.113.7")
+        .await
+        .assert_status(StatusCode::CREATED);
+}
+
+#[tokio::test]
+async fn postgres_backend_keeps_counters_in_the_database() {
+    let app = TestApp::spawn_with(|config| {
+        config.rate_limit.backend = RateLimitBackend::Postgres;
+        config.rate_limit.lockout_failures = 2;
+        config.rate_limit.lockout_secs = LOCKOUT_SECS;
+    })
+    .await;
+    app.register("alice").await;
+
+    for _ in 0..2 {
+        login(&app, "wrong password")
+            .await
+            .assert_error(ErrorCode::InvalidCredentials);
+    }
+    let locked = login(&app, PASSWORD)
+        .await
+        .assert_error(ErrorCode::AccountLocked);
+    assert_eq!(retry_after(&locked), LOCKOUT_SECS);
+
+    let row = app
+        .db
+        .query_one(Statement::from_string(
+            DbBackend::Postgres,
+            "SELECT count FROM rate_limits WHERE key = 'lockout:alice'",
+        ))
+        .await
+        .unwrap()
+        .expect("lockout row");
+    assert_eq!(row.try_get::<i32>("", "count").unwrap(), 2);
+
+    app.clock.advance(TimeDelta::seconds(LOCKOUT_SECS as i64));
+    login(&app, PASSWORD).await.assert_status(StatusCode::OK);
+}
@@ -0,0 +1,243 @@
+//! Concurrent requests racing for the same days and events.
+
+mod common;
+
+use std::sync::Arc;
+
+use axum::http::StatusCode;
+use chrono::{Days, TimeDelta};
+use common::{Session, TestApp, date};
+use friends_server::clock::Clock;
+use friends_server::entities::{Busyday, BusydayColumn};
+use friends_server::error::ErrorCode;
+use sea_orm::{ColumnTrait, EntityTrait, PaginatorTrait, QueryFilter};
+use serde_json::json;
+use tokio::task::JoinSet;
+
+const RACERS: usize = 8;
+/// Races repeated per test, a single one can pass by luck.
+const ROUNDS: i64 = 5;
+
+async fn busy_days_of(app: &TestApp, session: &Session) -> u64 {
+    Busyday::find()
+        .filter(BusydayColumn::UserId.eq(session.user_id))
+        .count(&*app.db)
+        .await
+        .unwrap()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn concurrent_accepts_book_a_day_once() {
+    let app = TestApp::spawn().await;
+    let guest = app.register("guest").await;
+    let day = date(5);
+    let mut events = Vec::new();
+    for i in 0..RACERS {
+        let host = app.register(&format!("host{i}")).await;
+        app.befriend(&host, &guest).await;
+        events.push(app.create_event(&host, &day, &[&guest]).await);
+    }
+
+    let app = Arc::new(app);
+    let guest = Arc::new(guest);
+    let mut racers = JoinSet::new();
+    for id in events {
+        let (app, guest) = (app.clone(), guest.clone());
+        racers.spawn(async move {
+            app.post(&format!("/events/{id}/accept"))
+                .auth(&guest)
+                .send()
+                .await
+        });
+    }
+
+    let mut accepted = 0;
+    while let Some(response) = racers.join_next().await {
+        let response = response.unwrap();
+        match response.status {
+            StatusCode::OK => accepted += 1,
+            StatusCode::CONFLICT => assert_eq!(response.body["code"], "date_reserved"),
+            status => panic!("unexpected {status}: {}", response.body),
+        }
+    }
+    assert_eq!(accepted, 1);
+    assert_eq!(busy_days_of(&app, &guest).await, 1);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn concurrent_accepts_confirm_the_event() {
+    let app = TestApp::spawn().await;
+    let host = app.register("host").await;
+    let mut guests = Vec::new();
+    for i in 0..RACERS {
+        let guest = app.register(&format!("guest{i}")).await;
+        app.befriend(&host, &guest).await;
+        guests.push(guest);
+    }
+    let invited: Vec<&Session> = guests.iter().collect();
+    let mut events = Vec::new();
+    for round in 0..ROUNDS {
+        events.push(app.create_event(&host, &date(5 + round), &invited).await);
+    }
+
+    let app = Arc::new(app);
+    let guests: Vec<Arc<Session>> = guests.into_iter().map(Arc::new).collect();
+    for id in events {
+        let mut racers = JoinSet::new();
+        for guest in &guests {
+            let (app, guest) = (app.clone(), guest.clone());
+            racers.spawn(async move {
+                app.post(&format!("/events/{id}/accept"))
+                    .auth(&guest)
+                    .send()
+                    .await
+                    .assert_status(StatusCode::OK);
+            });
+        }
+        while let Some(result) = racers.join_next().await {
+            result.unwrap();
+        }
+
+        // Each accept sees the ones committed before it, so the last confirms.
+        let event = app
+            .get(&format!("/events/{id}"))
+            .auth(&host)
+            .send()
+            .await
+            .assert_status(StatusCode::OK);
+        assert_eq!(event.body["status"], "confirmed");
+        let history = app
+            .get(&format!("/events/{id}/history"))
+            .auth(&host)
+            .send()
+            .await
+            .assert_status(StatusCode::OK);
+        assert_eq!(history.pluck("/reason"), ["created", "confirmed"]);
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn concurrent_reads_settle_a_past_event_once() {
+    let app = TestApp::spawn().await;
+    let host = app.register("host").await;
+    let mut guests = Vec::new();
+    for i in 0..RACERS {
+        let guest = app.register(&format!("guest{i}")).await;
+        app.befriend(&host, &guest).await;
+        guests.push(Arc::new(guest));
+    }
+    let invited: Vec<&Session> = guests.iter().map(Arc::as_ref).collect();
+
+    let app = Arc::new(app);
+    for _ in 0..ROUNDS {
+        let tomorrow = (app.clock.now().date_naive() + Days::new(1)).to_string();
+        let id = app.create_event(&host, &tomorrow, &invited).await;
+        app.clock.advance(TimeDelta::days(2));
+
+        let mut racers = JoinSet::new();
+        for guest in &guests {
+            let (app, guest) = (app.clone(), guest.clone());
+            racers.spawn(async move {
+                app.get("/events/active")
+                    .auth(&guest)
+                    .send()
+                    .await
+                    .assert_status(StatusCode::OK);
+            });
+        }
+        while let Some(result) = racers.join_next().await {
+            result.unwrap();
+        }
+
+        let history = app
+            .get(&format!("/events/{id}/history"))
+            .auth(&host)
+            .send()
+            .await
+            .assert_status(StatusCode::OK);
+        assert_eq!(history.pluck("/reason"), ["created", "expired"]);
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn concurrent_finishes_complete_an_event_once() {
+    let app = TestApp::spawn().await;
+    let host = Arc::new(app.register("host").await);
+    let guest = app.register("guest").await;
+    app.befriend(&host, &guest).await;
+
+    let app = Arc::new(app);
+    for _ in 0..ROUNDS {
+        let today = app.clock.now().date_naive().to_string();
+        let id = app.create_event(&host, &today, &[&guest]).await;
+
+        let mut racers = JoinSet::new();
+        for _ in 0..RACERS {
+            let (app, host) = (app.clone(), host.clone());
+            racers.spawn(async move {
+                app.post(&format!("/events/{id}/finish"))
+                    .auth(&host)
+                    .json(json!({ "memory_image_base64": "aGVsbG8=" }))
+                    .send()
+                    .await
+            });
+        }
+        let mut finished = 0;
+        while let Some(response) = racers.join_next().await {
+            let response = response.unwrap();
+            if response.status == StatusCode::OK {
+                finished += 1;
+            } else {
+                response.assert_error(ErrorCode::EventClosed);
+            }
+        }
+        assert_eq!(finished, 1);
+
+        let history = app
+            .get(&format!("/events/{id}/history"))
+            .auth(&host)
+            .send()
+            .await
+            .assert_status(StatusCode::OK);
+        assert_eq!(history.pluck("/reason"), ["created", "finished"]);
+        app.clock.advance(TimeDelta::days(1));
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn crossed_invitations_do_not_deadlock() {
+    let app = TestApp::spawn().await;
+    let alice = Arc::new(app.register("alice").await);
+    let bob = Arc::new(app.register("bob").await);
+    app.befriend(&alice, &bob).await;
+    let app = Arc::new(app);
+
+    for round in 0..ROUNDS {
+        let day = date(10 + round);
+        let mut racers = JoinSet::new();
+        for (host, guest) in [(alice.clone(), bob.clone()), (bob.clone(), alice.clone())] {
+            let (app, day) = (app.clone(), day.clone());
+            racers.spawn(async move {
+                app.post("/events")
+                    .auth(&host)
+                    .json(json!({
+                        "date": day,
+                        "title": "Dinner",
+                        "invited_friend_ids": [guest.user_id],
+                    }))
+                    .send()
+                    .await
+            });
+        }
+
+        let mut statuses = Vec::new();
+        while let Some(response) = racers.join_next().await {
+            statuses.push(response.unwrap().status);
+        }
+        statuses.sort();
+        assert_eq!(statuses, [StatusCode::CREATED, StatusCode::CONFLICT]);
+    }
+    // Only the winning host of each day has it reserved.
+    let reserved = busy_days_of(&app, &alice).await + busy_days_of(&app, &bob).await;
+    assert_eq!(reserved, ROUNDS as u64);
+}
@@ -0,0 +1,385 @@
+//! Domain rules checked against sea-orm's `MockDatabase`: each test queues the
+//! rows the service is expected to read, in query order.
+
+use std::collections::BTreeMap;
+
+use chrono::{Days, NaiveDate, Utc};
+use friends_server::entities::busyday;
+use friends_server::entities::event::{self, EventStatus};
+use friends_server::entities::event_history::{self, EventTransition};
+use friends_server::entities::friendship::{self, FriendshipStatus};
+use friends_server::entities::user_event::{self, UserEventResponse, UserEventRole};
+use friends_server::error::ErrorCode;
+use friends_server::services::{
+    CalendarService, Calendars, EventService, Events, FriendshipService, Friendships, NewEvent,
+};
+use sea_orm::{DatabaseBackend, DatabaseConnection, MockDatabase, MockExecResult, Value};
+use uuid::Uuid;
+
+fn today() -> NaiveDate {
+    Utc::now().date_naive()
+}
+
+fn event(id: Uuid, creator_id: Uuid, date: NaiveDate, status: EventStatus) -> event::Model {
+    event::Model {
+        id,
+        creator_id,
+        date,
+        title: "Dinner".to_string(),
+        description: None,
+        location: None,
+        status,
+        wish_place_id: None,
+        memory_image_base64: None,
+        created_at: Utc::now().into(),
+    }
+}
+
+fn invitation(
+    event_id: Uuid,
+    user_id: Uuid,
+    response_status: UserEventResponse,
+) -> user_event::Model {
+    user_event::Model {
+        id: Uuid::new_v4(),
+        event_id,
+        user_id,
+        role: UserEventRole::Participant,
+        response_status,
+    }
+}
+
+/// Row returned by the history insert that follows a status change.
+fn history(event_id: Uuid, from: EventStatus, transition: EventTransition) -> event_history::Model {
+    event_history::Model {
+        id: Uuid::new_v4(),
+        event_id,
+        actor_id: None,
+        from_status: Some(from),
+        to_status: transition.target(),
+        reason: transition,
+        created_at: Utc::now().into(),
+    }
+}
+
+fn busy_day(user_id: Uuid, date: NaiveDate, event_id: Option<Uuid>) -> busyday::Model {
+    busyday::Model {
+        id: Uuid::new_v4(),
+        user_id,
+        date,
+        event_id,
+    }
+}
+
+fn friendship(user_id: Uuid, friend_id: Uuid) -> friendship::Model {
+    friendship::Model {
+        id: Uuid::new_v4(),
+        user_id,
+        friend_id,
+        status: FriendshipStatus::Accepted,
+    }
+}
+
+fn count(n: i64) -> BTreeMap<&'static str, Value> {
+    BTreeMap::from([("num_items", Value::BigInt(Some(n)))])
+}
+
+fn deleted(rows: u64) -> MockExecResult {
+    MockExecResult {
+        last_insert_id: 0,
+        rows_affected: rows,
+    }
+}
+
+/// Result of one `pg_advisory_xact_lock` call.
+fn locked() -> MockExecResult {
+    deleted(0)
+}
+
+fn empty() -> DatabaseConnection {
+    MockDatabase::new(DatabaseBackend::Postgres).into_connection()
+}
+
+#[tokio::test]
+async fn last_acceptance_confirms_the_event() {
+    let (id, owner, guest) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+    let date = today() + Days::new(3);
+    let db = MockDatabase::new(DatabaseBackend::Postgres)
+        .append_query_results([[event(id, owner, date, EventStatus::Pending)]])
+        .append_query_results([[invitation(id, guest, UserEventResponse::Pending)]])
+        .append_exec_results([locked()])
+        .append_query_results([Vec::<busyday::Model>::new()])
+        .append_query_results([[invitation(id, guest, UserEventResponse::Accepted)]])
+        .append_query_results([[busy_day(guest, date, Some(id))]])
+        .append_query_results([Vec::<user_event::Model>::new()])
+        .append_query_results([[event(id, owner, date, EventStatus::Confirmed)]])
+        .append_query_results([[history(id, EventStatus::Pending, EventTransition::Confirmed)]])
+        .into_connection();
+
+    let status = Events::new().accept(&db, id, guest).await.unwrap();
+    assert_eq!(status, EventStatus::Confirmed);
+}
+
+#[tokio::test]
+async fn acceptance_waits_for_other_participants() {
+    let (id, owner, guest) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+    let date = today() + Days::new(3);
+    let db = MockDatabase::new(DatabaseBackend::Postgres)
+        .append_query_results([[event(id, owner, date, EventStatus::Pending)]])
+        .append_query_results([[invitation(id, guest, UserEventResponse::Pending)]])
+        .append_exec_results([locked()])
+        .append_query_results([Vec::<busyday::Model>::new()])
+        .append_query_results([[invitation(id, guest, UserEventResponse::Accepted)]])
+        .append_query_results([[busy_day(guest, date, Some(id))]])
+        .append_query_results([[invitation(id, Uuid::new_v4(), UserEventResponse::Pending)]])
+        .into_connection();
+
+    let status = Events::new().accept(&db, id, guest).await.unwrap();
+    assert_eq!(status, EventStatus::Pending);
+}
+
+#[tokio::test]
+async fn acceptance_needs_a_free_day() {
+    let (id, owner, guest) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+    let date = today() + Days::new(3);
+    let db = MockDatabase::new(DatabaseBackend::Postgres)
+        .append_query_results([[event(id, owner, date, EventStatus::Pending)]])
+        .append_query_results([[invitation(id, guest, UserEventResponse::Pending)]])
+        .append_exec_results([locked()])
+        .append_query_results([[busy_day(guest, date, None)]])
+        .into_connection();
+
+    let err = Events::new().accept(&db, id, guest).await.unwrap_err();
+    assert_eq!(err.code(), ErrorCode::DateReserved);
+}
+
+#[tokio::test]
+async fn closed_events_take_no_answers() {
+    let (id, owner, guest) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+    for status in [EventStatus::Canceled, EventStatus::Completed] {
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([[event(id, owner, today(), status.clone())]])
+            .append_query_results([[event(id, owner, today(), status)]])
+            .into_connection();
+
+        let err = Events::new().accept(&db, id, guest).await.unwrap_err();
+        assert_eq!(err.code(), ErrorCode::EventClosed);
+        let err = Events::new().decline(&db, id, guest).await.unwrap_err();
+        assert_eq!(err.code(), ErrorCode::EventClosed);
+    }
+}
+
+#[tokio::test]
+async fn only_participant_declining_cancels_the_event() {
+    let (id, owner, guest) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+    let date = today() + Days::new(3);
+    let db = MockDatabase::new(DatabaseBackend::Postgres)
+        .append_query_results([[event(id, owner, date, EventStatus::Confirmed)]])
+        .append_query_results([[invitation(id, guest, UserEventResponse::Accepted)]])
+        .append_query_results([[invitation(id, guest, UserEventResponse::Declined)]])
+        .append_exec_results([deleted(1)])
+        .append_query_results([[count(1)]])
+        .append_exec_results([deleted(1)])
+        .append_query_results([[event(id, owner, date, EventStatus::Canceled)]])
+        .append_query_results([[history(id, EventStatus::Confirmed, EventTransition::Declined)]])
+        .into_connection();
+
+    let status = Events::new().decline(&db, id, guest).await.unwrap();
+    assert_eq!(status, EventStatus::Canceled);
+}
+
+#[tokio::test]
+async fn declining_reopens_a_confirmed_event() {
+    let (id, owner, guest) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+    let date = today() + Days::new(3);
+    let db = MockDatabase::new(DatabaseBackend::Postgres)
+        .append_query_results([[event(id, owner, date, EventStatus::Confirmed)]])
+        .append_query_results([[invitation(id, guest, UserEventResponse::Accepted)]])
+        .append_query_results([[invitation(id, guest, UserEventResponse::Declined)]])
+        .append_exec_results([deleted(1)])
+        .append_query_results([[count(2)]])
+        .append_query_results([[event(id, owner, date, EventStatus::Pending)]])
+        .append_query_results([[history(id, EventStatus::Confirmed, EventTransition::Reopened)]])
+        .into_connection();
+
+    let status = Events::new().decline(&db, id, guest).await.unwrap();
+    assert_eq!(status, EventStatus::Pending);
+}
+
+#[tokio::test]
+async fn events_finish_on_or_after_their_date() {
+    let (id, owner) = (Uuid::new_v4(), Uuid::new_v4());
+    let tomorrow = today() + Days::new(1);
+    let db = MockDatabase::new(DatabaseBackend::Postgres)
+        .append_query_results([[event(id, owner, tomorrow, EventStatus::Confirmed)]])
+        .into_connection();
+
+    let err = Events::new()
+        .finish(&db, id, owner, "aGVsbG8=".to_string(), today())
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), ErrorCode::EventNotStarted);
+}
+
+#[tokio::test]
+async fn create_validates_before_touching_the_database() {
+    let err = Events::new()
+        .create(
+            &empty(),
+            Uuid::new_v4(),
+            NewEvent {
+                date: today(),
+                title: "  ".to_string(),
+                description: None,
+                location: None,
+                wish_place_id: None,
+                participant_ids: Vec::new(),
+            },
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), ErrorCode::TitleRequired);
+}
+
+#[tokio::test]
+async fn create_invites_only_free_friends() {
+    let (owner, friend, stranger) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+    let new_event = |participant_ids| NewEvent {
+        date: today(),
+        title: "Dinner".to_string(),
+        description: None,
+        location: None,
+        wish_place_id: None,
+        participant_ids,
+    };
+
+    let db = MockDatabase::new(DatabaseBackend::Postgres)
+        .append_query_results([Vec::<friendship::Model>::new()])
+        .into_connection();
+    let err = Events::new()
+        .create(&db, owner, new_event(vec![stranger]))
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), ErrorCode::InviteFriendsOnly);
+
+    let db = MockDatabase::new(DatabaseBackend::Postgres)
+        .append_query_results([[friendship(owner, friend)]])
+        .append_exec_results([locked(), locked()])
+        .append_query_results([Vec::<busyday::Model>::new()])
+        .append_query_results([[busy_day(friend, today(), None)]])
+        .into_connection();
+    let err = Events::new()
+        .create(&db, owner, new_event(vec![friend, owner]))
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), ErrorCode::ParticipantsBusy);
+}
+
+#[tokio::test]
+async fn past_events_are_settled() {
+    let (me, other) = (Uuid::new_v4(), Uuid::new_v4());
+    let (done, missed) = (Uuid::new_v4(), Uuid::new_v4());
+    let yesterday = today() - Days::new(1);
+    let db = MockDatabase::new(DatabaseBackend::Postgres)
+        .append_query_results([[
+            event(done, me, yesterday, EventStatus::Confirmed),
+            event(missed, me, yesterday, EventStatus::Pending),
+        ]])
+        .append_query_results([Vec::<user_event::Model>::new()])
+        .append_query_results([[event(done, me, yesterday, EventStatus::Completed)]])
+        .append_query_results([[history(done, EventStatus::Confirmed, EventTransition::Elapsed)]])
+        .append_query_results([[invitation(missed, other, UserEventResponse::Pending)]])
+        .append_query_results([[event(missed, me, yesterday, EventStatus::Canceled)]])
+        .append_query_results([[history(missed, EventStatus::Pending, EventTransition::Expired)]])
+        .into_connection();
+
+    let canceled = Events::new().settle_past(&db, me, today()).await.unwrap();
+    assert_eq!(canceled, 1);
+}
+
+#[test]
+fn lifecycle_allows_only_listed_transitions() {
+    let (pending, confirmed) = (EventStatus::Pending, EventStatus::Confirmed);
+    let (canceled, completed) = (EventStatus::Canceled, EventStatus::Completed);
+
+    assert!(EventTransition::Created.check(None).is_ok());
+    assert!(EventTransition::Confirmed.check(Some(&pending)).is_ok());
+    assert!(EventTransition::Reopened.check(Some(&confirmed)).is_ok());
+    for transition in [
+        EventTransition::Declined,
+        EventTransition::CanceledByOwner,
+        EventTransition::Expired,
+        EventTransition::Finished,
+        EventTransition::Elapsed,
+    ] {
+        assert!(transition.check(Some(&pending)).is_ok());
+        assert!(transition.check(Some(&confirmed)).is_ok());
+        assert!(transition.check(Some(&canceled)).is_err());
+        assert!(transition.check(Some(&completed)).is_err());
+    }
+
+    let code = |transition: EventTransition, from: Option<&EventStatus>| {
+        transition.check(from).unwrap_err().code()
+    };
+    let not_allowed = ErrorCode::EventTransitionNotAllowed;
+    assert_eq!(code(EventTransition::Reopened, Some(&pending)), not_allowed);
+    assert_eq!(code(EventTransition::Created, Some(&pending)), not_allowed);
+    assert_eq!(code(EventTransition::Confirmed, None), not_allowed);
+    assert_eq!(
+        code(EventTransition::Confirmed, Some(&canceled)),
+        ErrorCode::EventClosed
+    );
+    assert_eq!(
+        code(EventTransition::CanceledByOwner, Some(&completed)),
+        ErrorCode::EventCompleted
+    );
+}
+
+#[tokio::test]
+async fn only_friends_can_view_each_other() {
+    let (me, other) = (Uuid::new_v4(), Uuid::new_v4());
+    Friendships.ensure_can_view(&empty(), me, me).await.unwrap();
+
+    let db = MockDatabase::new(DatabaseBackend::Postgres)
+        .append_query_results([Vec::<friendship::Model>::new()])
+        .append_query_results([[friendship(other, me)]])
+        .into_connection();
+    let err = Friendships
+        .ensure_can_view(&db, me, other)
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), ErrorCode::FriendsOnly);
+    Friendships.ensure_can_view(&db, me, other).await.unwrap();
+}
+
+#[tokio::test]
+async fn friend_requests_need_someone_else() {
+    let me = Uuid::new_v4();
+    let db = empty();
+
+    let err = Friendships.send_request(&db, me, me).await.unwrap_err();
+    assert_eq!(err.code(), ErrorCode::CannotBefriendSelf);
+    let err = Friendships.accept_request(&db, me, me).await.unwrap_err();
+    assert_eq!(err.code(), ErrorCode::OwnFriendRequest);
+    let err = Friendships.remove(&db, me, me).await.unwrap_err();
+    assert_eq!(err.code(), ErrorCode::CannotRemoveSelf);
+}
+
+#[tokio::test]
+async fn friend_ids_cover_both_directions() {
+    let (me, a, b) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+    let db = MockDatabase::new(DatabaseBackend::Postgres)
+        .append_query_results([[friendship(me, a), friendship(b, me)]])
+        .into_connection();
+
+    assert_eq!(Friendships.friend_ids(&db, me).await.unwrap(), vec![a, b]);
+}
+
+#[tokio::test]
+async fn busy_users_skips_the_query_for_nobody() {
+    let busy = Calendars
+        .busy_users(&empty(), Vec::new(), today())
+        .await
+        .unwrap();
+    assert!(busy.is_empty());
+}
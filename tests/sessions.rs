@@ -0,0 +1,200 @@
+mod common;
+
+use axum::http::{StatusCode, header};
+use chrono::{DateTime, TimeDelta, Utc};
+use common::{PASSWORD, Session, TestApp};
+use friends_server::clock::Clock;
+use friends_server::error::ErrorCode;
+use serde_json::json;
+
+async fn login_on(app: &TestApp, username: &str, device_name: &str) -> Session {
+    let response = app
+        .post("/auth/login")
+        .header(header::USER_AGENT.as_str(), "FriendsApp/2.1 (Android 14)")
+        .header("X-Forwarded-For", "203.0.113.7")
+        .json(json!({
+            "username": username,
+            "password": PASSWORD,
+            "device_name": device_name,
+            "platform": "android",
+        }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    Session {
+        user_id: response.uuid("/user/id"),
+        username: username.to_string(),
+        access: response.str("/access_token").to_string(),
+        refresh: response.str("/refresh_token").to_string(),
+    }
+}
+
+#[tokio::test]
+async fn sessions_are_listed_with_device_details() {
+    let app = TestApp::spawn_with(|config| config.rate_limit.trust_forwarded_for = true).await;
+    app.register("alice").await;
+    let pixel = login_on(&app, "alice", "Pixel 8").await;
+
+    // A refresh continues the session instead of starting another.
+    app.clock.advance(TimeDelta::minutes(1));
+    let refreshed = app
+        .post("/auth/refresh")
+        .json(json!({ "refresh_token": pixel.refresh }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+
+    let sessions = app
+        .get("/users/me/sessions")
+        .bearer(refreshed.str("/access_token"))
+        .send()
+        .await
+        .assert_status(StatusCode::OK)
+        .body;
+    let sessions = sessions.as_array().unwrap();
+    assert_eq!(sessions.len(), 2);
+    assert_eq!(sessions[0]["device_name"], "Pixel 8");
+    assert_eq!(sessions[0]["platform"], "android");
+    assert_eq!(sessions[0]["ip"], "203.0.113.7");
+    assert_eq!(sessions[0]["user_agent"], "FriendsApp/2.1 (Android 14)");
+    assert_eq!(sessions[0]["current"], true);
+    assert_eq!(sessions[1]["device_name"], json!(null));
+    assert_eq!(sessions[1]["current"], false);
+}
+
+#[tokio::test]
+async fn session_times_follow_the_app_clock() {
+    let app = TestApp::spawn().await;
+    let alice = app.register("alice").await;
+
+    app.clock.advance(TimeDelta::hours(5));
+    let refreshed = app
+        .post("/auth/refresh")
+        .json(json!({ "refresh_token": alice.refresh }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    let access = refreshed.str("/access_token").to_string();
+    let sessions = app
+        .get("/users/me/sessions")
+        .bearer(&access)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    let last_used_at: DateTime<Utc> = sessions.str("/0/last_used_at").parse().unwrap();
+    assert!((last_used_at - app.clock.now()).abs() < TimeDelta::milliseconds(1));
+
+    // Past the refresh token lifetime the session is no longer listed.
+    app.clock.advance(TimeDelta::days(31));
+    let sessions = app
+        .get("/users/me/sessions")
+        .bearer(&access)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    assert_eq!(sessions.body, json!([]));
+}
+
+#[tokio::test]
+async fn revoking_a_session_ends_its_tokens() {
+    let app = TestApp::spawn().await;
+    let phone = app.register("alice").await;
+    app.clock.advance(TimeDelta::minutes(1));
+    let laptop = login_on(&app, "alice", "Laptop").await;
+    let bob = app.register("bob").await;
+
+    // Warm the session check so the revocation has to invalidate it.
+    app.get("/users/me")
+        .auth(&laptop)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+
+    let sessions = app.get("/users/me/sessions").auth(&phone).send().await;
+    let laptop_id = sessions.str("/0/id").to_string();
+    assert_eq!(sessions.body[0]["device_name"], "Laptop");
+
+    app.delete(&format!("/users/me/sessions/{laptop_id}"))
+        .auth(&bob)
+        .send()
+        .await
+        .assert_error(ErrorCode::SessionNotFound);
+    app.delete(&format!("/users/me/sessions/{laptop_id}"))
+        .auth(&phone)
+        .send()
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    app.delete(&format!("/users/me/sessions/{laptop_id}"))
+        .auth(&phone)
+        .send()
+        .await
+        .assert_error(ErrorCode::SessionNotFound);
+
+    app.get("/users/me")
+        .auth(&laptop)
+        .send()
+        .await
+        .assert_error(ErrorCode::SessionExpired);
+    app.post("/auth/refresh")
+        .json(json!({ "refresh_token": laptop.refresh }))
+        .send()
+        .await
+        .assert_error(ErrorCode::SessionExpired);
+    app.get("/users/me")
+        .auth(&phone)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+}
+
+#[tokio::test]
+async fn logging_out_everywhere_else_keeps_the_current_session() {
+    let app = TestApp::spawn().await;
+    let phone = app.register("alice").await;
+    app.clock.advance(TimeDelta::minutes(1));
+    let laptop = login_on(&app, "alice", "Laptop").await;
+    let tablet = login_on(&app, "alice", "Tablet").await;
+    let bob = app.register("bob").await;
+
+    let response = app
+        .post("/users/me/sessions/revoke-others")
+        .auth(&phone)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    assert_eq!(response.body["revoked"], 2);
+
+    for session in [&laptop, &tablet] {
+        app.get("/users/me")
+            .auth(session)
+            .send()
+            .await
+            .assert_error(ErrorCode::SessionExpired);
+    }
+    let sessions = app.get("/users/me/sessions").auth(&phone).send().await;
+    assert_eq!(sessions.body.as_array().unwrap().len(), 1);
+    assert_eq!(sessions.body[0]["current"], true);
+
+    app.get("/users/me")
+        .auth(&bob)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+}
+
+#[tokio::test]
+async fn logout_ends_the_session() {
+    let app = TestApp::spawn().await;
+    let alice = app.register("alice").await;
+
+    app.post("/auth/logout")
+        .json(json!({ "refresh_token": alice.refresh }))
+        .send()
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    app.get("/users/me")
+        .auth(&alice)
+        .send()
+        .await
+        .assert_error(ErrorCode::SessionExpired);
+}
@@ -0,0 +1,131 @@
+mod common;
+
+use std::path::PathBuf;
+
+use axum::http::StatusCode;
+use chrono::{TimeDelta, Utc};
+use common::TestApp;
+use friends_server::auth::keys;
+use friends_server::config::JwtConfig;
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{Algorithm, DecodingKey, Validation, decode, decode_header};
+use serde_json::Value;
+use uuid::Uuid;
+
+/// A key directory removed when dropped.
+struct KeyDir(PathBuf);
+
+impl KeyDir {
+    fn new() -> Self {
+        KeyDir(std::env::temp_dir().join(format!("friends-keys-{}", Uuid::new_v4().simple())))
+    }
+
+    fn generate(&self, age: TimeDelta) -> String {
+        keys::generate(&self.0, Utc::now() - age).unwrap()
+    }
+}
+
+impl Drop for KeyDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+fn kid(token: &str) -> String {
+    let header = decode_header(token).unwrap();
+    assert_eq!(header.alg, Algorithm::EdDSA);
+    header.kid.unwrap()
+}
+
+#[tokio::test]
+async fn tokens_verify_with_the_published_keys() {
+    let dir = KeyDir::new();
+    let signing = dir.generate(TimeDelta::hours(1));
+    let path = dir.0.clone();
+    let app = TestApp::spawn_with(|config| config.jwt.key_dir = Some(path)).await;
+    let alice = app.register("alice").await;
+    assert_eq!(kid(&alice.access), signing);
+    assert_eq!(kid(&alice.refresh), signing);
+
+    let jwks = app
+        .get("/.well-known/jwks.json")
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    let jwks: JwkSet = serde_json::from_value(jwks.body).unwrap();
+    let jwk = jwks.find(&signing).expect("signing key is published");
+
+    // What another service holding only the JWKS would do.
+    let mut validation = Validation::new(Algorithm::EdDSA);
+    validation.set_audience(&["friends-api"]);
+    validation.set_issuer(&["friends-server"]);
+    let claims = decode::<Value>(
+        &alice.access,
+        &DecodingKey::from_jwk(jwk).unwrap(),
+        &validation,
+    )
+    .unwrap()
+    .claims;
+    assert_eq!(claims["sub"], alice.user_id.to_string());
+}
+
+#[tokio::test]
+async fn rotated_keys_sign_once_active() {
+    let dir = KeyDir::new();
+    let first = dir.generate(TimeDelta::hours(3));
+    let path = dir.0.clone();
+    let app = TestApp::spawn_with(|config| {
+        config.jwt.key_dir = Some(path);
+        config.jwt.key_activation_secs = 300;
+    })
+    .await;
+    let before = app.register("alice").await;
+    assert_eq!(kid(&before.access), first);
+
+    let second = dir.generate(TimeDelta::hours(1));
+    app.jwt_keys.reload().unwrap();
+    let after = app.login("alice").await;
+    assert_eq!(kid(&after.access), second);
+
+    // Tokens of the previous key stay valid until they expire.
+    app.get("/users/me")
+        .auth(&before)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+
+    // A fresh key is published first and signs only after the delay.
+    let pending = dir.generate(TimeDelta::zero());
+    app.jwt_keys.reload().unwrap();
+    assert_eq!(kid(&app.login("alice").await.access), second);
+    let jwks = app.get("/.well-known/jwks.json").send().await;
+    let kids: Vec<&str> = jwks.body["keys"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|key| key["kid"].as_str().unwrap())
+        .collect();
+    assert_eq!(kids, [first.as_str(), second.as_str(), pending.as_str()]);
+}
+
+#[test]
+fn prune_keeps_keys_tokens_may_still_need() {
+    let dir = KeyDir::new();
+    let config = JwtConfig {
+        key_dir: Some(dir.0.clone()),
+        refresh_ttl_secs: TimeDelta::days(30).num_seconds(),
+        ..JwtConfig::default()
+    };
+    let oldest = dir.generate(TimeDelta::days(40));
+    let replaced = dir.generate(TimeDelta::days(35));
+    let newest = dir.generate(TimeDelta::days(1));
+
+    let pruned = keys::prune(&dir.0, &config, Utc::now()).unwrap();
+    assert_eq!(pruned, [oldest]);
+    let left: Vec<String> = keys::read_dir(&dir.0)
+        .unwrap()
+        .into_iter()
+        .map(|key| key.kid)
+        .collect();
+    assert_eq!(left, [replaced, newest]);
+}
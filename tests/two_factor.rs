@@ -0,0 +1,298 @@
+mod common;
+
+use axum::http::StatusCode;
+use chrono::{DateTime, TimeDelta, Utc};
+use common::{PASSWORD, Session, TestApp, TestResponse};
+use friends_server::auth::totp;
+use friends_server::clock::Clock;
+use friends_server::error::ErrorCode;
+use serde_json::{Value, json};
+
+/// Turns TOTP on for `session` and returns the secret and recovery codes.
+async fn enable(app: &TestApp, session: &Session) -> (String, Vec<String>) {
+    let response = app
+        .post("/users/me/2fa/totp")
+        .auth(session)
+        .json(json!({ "password": PASSWORD }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    let secret = response.str("/secret").to_string();
+    let response = app
+        .post("/users/me/2fa/totp/confirm")
+        .auth(session)
+        .json(json!({ "code": code(app, &secret) }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    let codes = recovery_codes(&response);
+    // Each code works once, so later codes come from the next time step.
+    app.clock.advance(TimeDelta::seconds(totp::STEP_SECS));
+    (secret, codes)
+}
+
+fn recovery_codes(response: &TestResponse) -> Vec<String> {
+    response.body["recovery_codes"]
+        .as_array()
+        .expect("recovery codes")
+        .iter()
+        .map(|code| code.as_str().unwrap().to_string())
+        .collect()
+}
+
+fn code(app: &TestApp, secret: &str) -> String {
+    code_at(secret, app.clock.now())
+}
+
+fn code_at(secret: &str, now: DateTime<Utc>) -> String {
+    let secret = totp::base32_decode(secret).unwrap();
+    totp::code(&secret, totp::step_at(now))
+}
+
+async fn status(app: &TestApp, session: &Session) -> Value {
+    app.get("/users/me/2fa")
+        .auth(session)
+        .send()
+        .await
+        .assert_status(StatusCode::OK)
+        .body
+}
+
+async fn challenge(app: &TestApp) -> String {
+    let response = app
+        .post("/auth/login")
+        .json(json!({ "username": "alice", "password": PASSWORD }))
+        .send()
+        .await
+        .assert_status(StatusCode::ACCEPTED);
+    assert!(response.body.get("access_token").is_none());
+    response.str("/challenge_token").to_string()
+}
+
+async fn finish(app: &TestApp, challenge_token: &str, code: &str) -> TestResponse {
+    app.post("/auth/login/2fa")
+        .json(json!({ "challenge_token": challenge_token, "code": code }))
+        .send()
+        .await
+}
+
+#[test]
+fn codes_match_the_rfc_6238_test_vectors() {
+    let secret = b"12345678901234567890";
+    for (time, expected) in [
+        (59, "287082"),
+        (1_111_111_109, "081804"),
+        (1_234_567_890, "005924"),
+        (2_000_000_000, "279037"),
+    ] {
+        let now = DateTime::from_timestamp(time, 0).unwrap();
+        assert_eq!(totp::code(secret, totp::step_at(now)), expected);
+    }
+
+    let encoded = totp::base32_encode(secret);
+    assert_eq!(encoded, "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ");
+    assert_eq!(totp::base32_decode(&encoded).unwrap(), secret);
+
+    let now = DateTime::from_timestamp(59, 0).unwrap();
+    let step = totp::verify(&encoded, "287082", now, None);
+    assert_eq!(step, Some(1));
+    assert_eq!(totp::verify(&encoded, "287082", now, step), None);
+    let later = now + TimeDelta::seconds(5 * totp::STEP_SECS);
+    assert_eq!(totp::verify(&encoded, "287082", later, None), None);
+}
+
+#[tokio::test]
+async fn login_needs_a_code_once_totp_is_confirmed() {
+    let app = TestApp::spawn().await;
+    let alice = app.register("alice").await;
+
+    app.post("/users/me/2fa/totp/confirm")
+        .auth(&alice)
+        .json(json!({ "code": "123456" }))
+        .send()
+        .await
+        .assert_error(ErrorCode::TwoFactorNotEnrolled);
+    app.post("/users/me/2fa/totp")
+        .auth(&alice)
+        .json(json!({ "password": "wrong" }))
+        .send()
+        .await
+        .assert_error(ErrorCode::CurrentPasswordIncorrect);
+    let response = app
+        .post("/users/me/2fa/totp")
+        .auth(&alice)
+        .json(json!({ "password": PASSWORD }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    let secret = response.str("/secret");
+    let uri = response.str("/otpauth_uri");
+    assert!(uri.starts_with("otpauth://totp/Friends:alice?"));
+    assert!(uri.contains(&format!("secret={secret}")));
+
+    // Until the first code is confirmed, the password alone still logs in.
+    app.login("alice").await;
+    let wrong = if code(&app, secret) == "000000" {
+        "111111"
+    } else {
+        "000000"
+    };
+    app.post("/users/me/2fa/totp/confirm")
+        .auth(&alice)
+        .json(json!({ "code": wrong }))
+        .send()
+        .await
+        .assert_error(ErrorCode::InvalidTwoFactorCode);
+
+    let (secret, codes) = enable(&app, &alice).await;
+    assert_eq!(codes.len(), 10);
+    assert_eq!(
+        status(&app, &alice).await,
+        json!({ "enabled": true, "recovery_codes_left": 10 })
+    );
+    app.post("/users/me/2fa/totp")
+        .auth(&alice)
+        .json(json!({ "password": PASSWORD }))
+        .send()
+        .await
+        .assert_error(ErrorCode::TwoFactorAlreadyEnabled);
+
+    let challenge_token = challenge(&app).await;
+    finish(&app, &challenge_token, "000000x")
+        .await
+        .assert_error(ErrorCode::InvalidTwoFactorCode);
+    finish(&app, &alice.access, &code(&app, &secret))
+        .await
+        .assert_error(ErrorCode::TwoFactorChallengeExpired);
+
+    let current = code(&app, &secret);
+    let response = finish(&app, &challenge_token, &current)
+        .await
+        .assert_status(StatusCode::OK);
+    assert_eq!(response.str("/user/username"), "alice");
+    app.get("/users/me")
+        .bearer(response.str("/access_token"))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+
+    // The same code does not work a second time.
+    finish(&app, &challenge(&app).await, &current)
+        .await
+        .assert_error(ErrorCode::InvalidTwoFactorCode);
+}
+
+#[tokio::test]
+async fn recovery_codes_work_once_and_can_be_replaced() {
+    let app = TestApp::spawn().await;
+    let alice = app.register("alice").await;
+    let (secret, codes) = enable(&app, &alice).await;
+
+    let challenge_token = challenge(&app).await;
+    finish(&app, &challenge_token, &codes[0].to_uppercase())
+        .await
+        .assert_status(StatusCode::OK);
+    finish(&app, &challenge_token, &codes[0])
+        .await
+        .assert_error(ErrorCode::InvalidTwoFactorCode);
+    assert_eq!(
+        status(&app, &alice).await,
+        json!({ "enabled": true, "recovery_codes_left": 9 })
+    );
+
+    let response = app
+        .post("/users/me/2fa/recovery-codes")
+        .auth(&alice)
+        .json(json!({ "code": code(&app, &secret) }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    let fresh = recovery_codes(&response);
+    assert_eq!(fresh.len(), 10);
+    assert!(!fresh.contains(&codes[1]));
+
+    finish(&app, &challenge_token, &codes[1])
+        .await
+        .assert_error(ErrorCode::InvalidTwoFactorCode);
+    finish(&app, &challenge_token, &fresh[0])
+        .await
+        .assert_status(StatusCode::OK);
+}
+
+#[tokio::test]
+async fn disabling_needs_password_and_code() {
+    let app = TestApp::spawn().await;
+    let alice = app.register("alice").await;
+    app.delete("/users/me/2fa/totp")
+        .auth(&alice)
+        .json(json!({ "password": PASSWORD, "code": "123456" }))
+        .send()
+        .await
+        .assert_error(ErrorCode::TwoFactorNotEnabled);
+
+    let (secret, codes) = enable(&app, &alice).await;
+    let challenge_token = challenge(&app).await;
+
+    app.delete("/users/me/2fa/totp")
+        .auth(&alice)
+        .json(json!({ "password": "wrong", "code": code(&app, &secret) }))
+        .send()
+        .await
+        .assert_error(ErrorCode::CurrentPasswordIncorrect);
+    app.delete("/users/me/2fa/totp")
+        .auth(&alice)
+        .json(json!({ "password": PASSWORD, "code": "abcde-fghjk" }))
+        .send()
+        .await
+        .assert_error(ErrorCode::InvalidTwoFactorCode);
+    app.delete("/users/me/2fa/totp")
+        .auth(&alice)
+        .json(json!({ "password": PASSWORD, "code": codes[0] }))
+        .send()
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+
+    assert_eq!(
+        status(&app, &alice).await,
+        json!({ "enabled": false, "recovery_codes_left": 0 })
+    );
+    app.login("alice").await;
+    finish(&app, &challenge_token, &code(&app, &secret))
+        .await
+        .assert_error(ErrorCode::TwoFactorChallengeExpired);
+}
+
+#[tokio::test]
+async fn wrong_codes_count_towards_the_lockout() {
+    let app = TestApp::spawn_with(|config| config.rate_limit.lockout_failures = 2).await;
+    let alice = app.register("alice").await;
+    let secret = app
+        .post("/users/me/2fa/totp")
+        .auth(&alice)
+        .json(json!({ "password": PASSWORD }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK)
+        .str("/secret")
+        .to_string();
+
+    for _ in 0..2 {
+        app.post("/users/me/2fa/totp/confirm")
+            .auth(&alice)
+            .json(json!({ "code": "000000" }))
+            .send()
+            .await
+            .assert_error(ErrorCode::InvalidTwoFactorCode);
+    }
+    app.post("/users/me/2fa/totp/confirm")
+        .auth(&alice)
+        .json(json!({ "code": code(&app, &secret) }))
+        .send()
+        .await
+        .assert_error(ErrorCode::AccountLocked);
+    app.post("/auth/login")
+        .json(json!({ "username": "alice", "password": PASSWORD }))
+        .send()
+        .await
+        .assert_error(ErrorCode::AccountLocked);
+}
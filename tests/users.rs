@@ -0,0 +1,178 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::TestApp;
+use friends_server::error::ErrorCode;
+use serde_json::json;
+use uuid::Uuid;
+
+#[tokio::test]
+async fn get_me_shows_the_locale() {
+    let app = TestApp::spawn().await;
+    let alice = app.register("alice").await;
+
+    let response = app
+        .get("/users/me")
+        .auth(&alice)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    assert_eq!(response.uuid("/id"), alice.user_id);
+    assert_eq!(response.body["username"], "alice");
+}
+
+#[tokio::test]
+async fn update_me_changes_the_profile() {
+    let app = TestApp::spawn().await;
+    let alice = app.register("alice").await;
+
+    let response = app
+        .patch("/users/me")
+        .auth(&alice)
+        .json(json!({
+            "username": "alicia",
+            "bio": "new bio",
+            "locale": "ru",
+            "timezone": "Asia/Vladivostok",
+            "email": " alice@example.com ",
+        }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    assert_eq!(response.body["username"], "alicia");
+    assert_eq!(response.body["bio"], "new bio");
+    assert_eq!(response.body["locale"], "ru");
+    assert_eq!(response.body["timezone"], "Asia/Vladivostok");
+    assert_eq!(response.body["email"], "alice@example.com");
+
+    let other = app
+        .get(&format!("/users/{}", alice.user_id))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    assert_eq!(other.body["username"], "alicia");
+    assert!(other.body.get("locale").is_none());
+    assert!(other.body.get("timezone").is_none());
+    assert!(other.body.get("email").is_none());
+}
+
+#[tokio::test]
+async fn update_me_rejects_bad_input() {
+    let app = TestApp::spawn().await;
+    let alice = app.register("alice").await;
+    app.register("bob").await;
+
+    app.patch("/users/me")
+        .auth(&alice)
+        .json(json!({ "locale": "xx" }))
+        .send()
+        .await
+        .assert_error(ErrorCode::UnsupportedLocale);
+    app.patch("/users/me")
+        .auth(&alice)
+        .json(json!({ "timezone": "Mars/Olympus_Mons" }))
+        .send()
+        .await
+        .assert_error(ErrorCode::UnsupportedTimezone);
+    app.patch("/users/me")
+        .auth(&alice)
+        .json(json!({ "email": "alice at example" }))
+        .send()
+        .await
+        .assert_error(ErrorCode::InvalidEmail);
+    app.patch("/users/me")
+        .auth(&alice)
+        .json(json!({ "username": "bob" }))
+        .send()
+        .await
+        .assert_error(ErrorCode::UsernameTaken);
+    app.patch("/users/me")
+        .json(json!({ "bio": "anonymous" }))
+        .send()
+        .await
+        .assert_error(ErrorCode::SessionExpired);
+}
+
+#[tokio::test]
+async fn get_user_by_id_reports_unknown_ids() {
+    let app = TestApp::spawn().await;
+
+    app.get(&format!("/users/{}", Uuid::new_v4()))
+        .send()
+        .await
+        .assert_error(ErrorCode::UserNotFound);
+}
+
+#[tokio::test]
+async fn search_pages_through_matching_users() {
+    let app = TestApp::spawn().await;
+    for name in ["anna", "andrew", "angela", "bob"] {
+        app.register(name).await;
+    }
+
+    let first = app
+        .get("/users/search?username=an&limit=2")
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    assert_eq!(
+        first.pluck("/username"),
+        vec![json!("andrew"), json!("angela")]
+    );
+    let cursor = first.str("/next_cursor").to_string();
+
+    let second = app
+        .get(&format!(
+            "/users/search?username=an&limit=2&cursor={cursor}"
+        ))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    assert_eq!(second.pluck("/username"), vec![json!("anna")]);
+    assert!(second.body["next_cursor"].is_null());
+}
+
+#[tokio::test]
+async fn search_rejects_bad_queries() {
+    let app = TestApp::spawn().await;
+
+    app.get("/users/search")
+        .send()
+        .await
+        .assert_error(ErrorCode::SearchQueryRequired);
+    app.get("/users/search?username=a&limit=0")
+        .send()
+        .await
+        .assert_error(ErrorCode::InvalidPagination);
+    app.get("/users/search?username=a&cursor=garbage")
+        .send()
+        .await
+        .assert_error(ErrorCode::InvalidPagination);
+}
+
+#[tokio::test]
+async fn a_new_locale_applies_to_the_current_token() {
+    let app = TestApp::spawn().await;
+    let alice = app.register("alice").await;
+    let bad_timezone = || {
+        app.patch("/users/me")
+            .auth(&alice)
+            .json(json!({ "timezone": "Mars/Olympus_Mons" }))
+    };
+
+    let en = bad_timezone()
+        .send()
+        .await
+        .assert_error(ErrorCode::UnsupportedTimezone);
+    app.patch("/users/me")
+        .auth(&alice)
+        .json(json!({ "locale": "ru" }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    let ru = bad_timezone()
+        .send()
+        .await
+        .assert_error(ErrorCode::UnsupportedTimezone);
+    assert_ne!(en.body["message"], ru.body["message"]);
+}
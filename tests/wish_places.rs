@@ -0,0 +1,175 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::{TestApp, date};
+use friends_server::error::ErrorCode;
+use serde_json::json;
+use uuid::Uuid;
+
+#[tokio::test]
+async fn create_list_update_and_archive() {
+    let app = TestApp::spawn().await;
+    let alice = app.register("alice").await;
+
+    let created = app
+        .post("/wish-places")
+        .auth(&alice)
+        .json(json!({ "title": "Ramen bar", "location": "Downtown" }))
+        .send()
+        .await
+        .assert_status(StatusCode::CREATED);
+    assert_eq!(created.body["status"], "active");
+    let id = created.uuid("/id");
+
+    let updated = app
+        .patch(&format!("/wish-places/{id}"))
+        .auth(&alice)
+        .json(json!({ "title": "Udon bar" }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    assert_eq!(updated.body["title"], "Udon bar");
+
+    let list = app
+        .get(&format!("/wish-places?user_id={}", alice.user_id))
+        .auth(&alice)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    assert_eq!(list.pluck("/title"), vec![json!("Udon bar")]);
+
+    app.delete(&format!("/wish-places/{id}"))
+        .auth(&alice)
+        .send()
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    let list = app
+        .get(&format!("/wish-places?user_id={}", alice.user_id))
+        .auth(&alice)
+        .send()
+        .await;
+    assert_eq!(list.pluck("/status"), vec![json!("archived")]);
+}
+
+#[tokio::test]
+async fn validation_and_ownership() {
+    let app = TestApp::spawn().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+
+    app.post("/wish-places")
+        .auth(&alice)
+        .json(json!({ "title": "  " }))
+        .send()
+        .await
+        .assert_error(ErrorCode::TitleRequired);
+    let id = app
+        .post("/wish-places")
+        .auth(&alice)
+        .json(json!({ "title": "Museum" }))
+        .send()
+        .await
+        .uuid("/id");
+
+    let path = format!("/wish-places/{id}");
+    app.patch(&path)
+        .auth(&alice)
+        .json(json!({}))
+        .send()
+        .await
+        .assert_error(ErrorCode::NothingToUpdate);
+    app.patch(&path)
+        .auth(&alice)
+        .json(json!({ "title": "" }))
+        .send()
+        .await
+        .assert_error(ErrorCode::TitleRequired);
+    app.patch(&path)
+        .auth(&bob)
+        .json(json!({ "title": "Mine now" }))
+        .send()
+        .await
+        .assert_error(ErrorCode::WishPlaceNotFound);
+    app.delete(&path)
+        .auth(&bob)
+        .send()
+        .await
+        .assert_error(ErrorCode::WishPlaceNotFound);
+    app.delete(&format!("/wish-places/{}", Uuid::new_v4()))
+        .auth(&alice)
+        .send()
+        .await
+        .assert_error(ErrorCode::WishPlaceNotFound);
+}
+
+#[tokio::test]
+async fn list_is_friends_only() {
+    let app = TestApp::spawn().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+    let carol = app.register("carol").await;
+    app.befriend(&alice, &bob).await;
+
+    let path = format!("/wish-places?user_id={}", alice.user_id);
+    app.get(&path)
+        .auth(&bob)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    app.get(&path)
+        .auth(&carol)
+        .send()
+        .await
+        .assert_error(ErrorCode::FriendsOnly);
+    app.get(&path)
+        .send()
+        .await
+        .assert_error(ErrorCode::SessionExpired);
+}
+
+#[tokio::test]
+async fn visit_links_an_event_of_the_owner() {
+    let app = TestApp::spawn().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+    app.befriend(&alice, &bob).await;
+    let alice_event = app.create_event(&alice, &date(1), &[&bob]).await;
+    let bob_event = app.create_event(&bob, &date(2), &[&alice]).await;
+    let id = app
+        .post("/wish-places")
+        .auth(&alice)
+        .json(json!({ "title": "Bowling" }))
+        .send()
+        .await
+        .uuid("/id");
+
+    let path = format!("/wish-places/{id}/visit");
+    app.post(&path)
+        .auth(&alice)
+        .json(json!({ "event_id": Uuid::new_v4() }))
+        .send()
+        .await
+        .assert_error(ErrorCode::EventNotFound);
+    app.post(&path)
+        .auth(&alice)
+        .json(json!({ "event_id": bob_event }))
+        .send()
+        .await
+        .assert_error(ErrorCode::NotEventCreator);
+    app.post(&path)
+        .auth(&bob)
+        .json(json!({ "event_id": bob_event }))
+        .send()
+        .await
+        .assert_error(ErrorCode::WishPlaceNotFound);
+
+    let visited = app
+        .post(&path)
+        .auth(&alice)
+        .json(json!({ "event_id": alice_event }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    assert_eq!(visited.body["status"], "visited");
+    assert_eq!(visited.body["visited_event_id"], json!(alice_event));
+}